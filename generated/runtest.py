@@ -17,33 +17,110 @@ def delete_if_exists(target_path):
     if path.exists(target_path):
         os.system(f"rm -rf {target_path}")
 
-def generate(seed):
+def generate(seed, flags=""):
     mkdir_if_not_exist("./src/bin/")
-    os.system("cargo run --release -- -s " + str(seed))
+    # Left over from an earlier run with --check
+    os.system(f"rm -f ./src/bin/seed_{seed}.errors ./src/bin/seed_{seed}_*.errors")
+    os.system("cargo run --release -- -s " + str(seed) + flags)
 
 def compile(seed):
-    name = "seed_" + str(seed)
+    compile_bin("seed_" + str(seed))
+
+def compile_bin(name):
     mkdir_if_not_exist("executables")
     for opt_level in opt_levels:
         mkdir_if_not_exist(f"executables/{opt_level}")
-        os.system(f"CARGO_PROFILE_RELEASE_OPT_LEVEL={opt_level}")
-        os.system(f"cargo build --bin {name} --release --target-dir executables/{opt_level}")
-
-    os.system("unset CARGO_PROFILE_RELEASE_OPT_LEVEL")
+        os.system(f"CARGO_PROFILE_RELEASE_OPT_LEVEL={opt_level} cargo build --bin {name} --release --target-dir executables/{opt_level}")
 
 def run(seed):
     def checksum(result_dict):
         return sum(x for x in result_dict.values())
+    # Each const check is a pair of the compile time and runtime result of the same call
+    def const_mismatch(const_checks):
+        return any(const_result != runtime_result for const_result, runtime_result in const_checks)
     filename = "seed_" + str(seed)
     checksums = []
+    const_mismatches = []
+    function_checksums = {}
+    # Written by --expect, unless the interpreter gave up on the program
+    expected = None
+    if path.exists(f"./src/bin/{filename}.expected"):
+        with open(f"./src/bin/{filename}.expected") as f:
+            expected = [json.loads(line) for line in f.read().splitlines()]
+    interpreter_mismatches = []
     for opt_level in opt_levels:
         result = subprocess.Popen(f"timeout 5s ./executables/{opt_level}/release/{filename}", shell=True, stdout=subprocess.PIPE)
         result_str = result.stdout.read()
-        if result_str:
-            result_dict = json.loads(result_str)
-            checksums.append((opt_level, checksum(result_dict)))
+        global_checksum = None
+        local_checksum = None
+        outputs = []
+        for line in result_str.splitlines():
+            result_dict = json.loads(line)
+            if "const_checks" in result_dict:
+                if const_mismatch(result_dict["const_checks"]):
+                    const_mismatches.append(opt_level)
+            elif "checksum" in result_dict:
+                # Checksum of the local variables, with a breakdown per function
+                local_checksum = result_dict["checksum"]
+                function_checksums[opt_level] = result_dict["functions"]
+                outputs.append(result_dict)
+            else:
+                global_checksum = checksum(result_dict)
+                outputs.append(result_dict)
+        if global_checksum is not None:
+            checksums.append((opt_level, (global_checksum, local_checksum)))
+            if expected is not None and outputs != expected:
+                interpreter_mismatches.append(opt_level)
     
-    return checksums
+    return checksums, const_mismatches, function_checksums, interpreter_mismatches
+
+# Functions whose local checksum is not the same at every optimisation level
+def differing_functions(function_checksums):
+    function_names = set()
+    for functions in function_checksums.values():
+        function_names.update(functions.keys())
+    return sorted(name for name in function_names if len(set(functions.get(name) for functions in function_checksums.values())) > 1)
+
+# Runs a program generated with --trace at every optimisation level, and reports the first statement
+# whose trace differs, as its id and the trace line of each optimisation level
+def first_divergence(seed):
+    filename = "seed_" + str(seed)
+    traces = {}
+    for opt_level in opt_levels:
+        result = subprocess.Popen(f"timeout 5s ./executables/{opt_level}/release/{filename}", shell=True, stdout=subprocess.DEVNULL, stderr=subprocess.PIPE)
+        traces[opt_level] = result.stderr.read().splitlines()
+
+    for i in range(max(len(trace) for trace in traces.values())):
+        lines = {opt_level: (trace[i] if i < len(trace) else None) for opt_level, trace in traces.items()}
+        if len(set(lines.values())) > 1:
+            stmt_ids = [json.loads(line)["stmt"] for line in lines.values() if line is not None]
+            return min(stmt_ids), lines
+
+    return None
+
+# Source of the statement that the trace with this id follows, looked up in the AST written with --ast
+def traced_stmt(seed, stmt_id):
+    result = subprocess.run(f"cargo run --release -q -- --render ./src/bin/seed_{seed}.json --stmt {stmt_id}", shell=True, stdout=subprocess.PIPE)
+    return result.stdout.decode().splitlines()
+
+def trace(seed):
+    generate(seed, " --trace --ast")
+    compile(seed)
+    divergence = first_divergence(seed)
+    if divergence is None:
+        print(f"Traces of seed {seed} are the same at every optimisation level")
+    else:
+        stmt_id, lines = divergence
+        print(f"Traces of seed {seed} first diverge at statement {stmt_id}:")
+        for source_line in traced_stmt(seed, stmt_id):
+            print(f"    {source_line}")
+        for opt_level, line in lines.items():
+            print(f"    {opt_level}: {line.decode() if line is not None else 'no trace'}")
+        print(f"To reduce the program while keeping the statement, pass --reduce ./src/bin/seed_{seed}.json --stmt {stmt_id} with a --test")
+
+# Written by --check instead of the source of a program the checker rejected
+def generator_bug(name):
+    return path.exists(f"./src/bin/{name}.errors")
 
 def delete_bin_seed(seed):
     filename = "seed_" + str(seed)
@@ -53,22 +130,33 @@ def delete_bin_seed(seed):
 
 def test(count, start=0):
     differentials = []
+    const_differentials = []
+    interpreter_differentials = []
+    generator_bugs = []
     timeout_info = dict.fromkeys(opt_levels, count)
     for i in range(start, start + count):
         print(f"Testing for seed {i}")
-        generate(i)
+        generate(i, " --expect --check")
+        if generator_bug("seed_" + str(i)):
+            generator_bugs.append(i)
+            timeout_info = {opt_level: timeouts - 1 for opt_level, timeouts in timeout_info.items()}
+            continue
         compile(i)
-        result = run(i)
+        result, const_mismatches, function_checksums, interpreter_mismatches = run(i)
         if len(result) >= 2:
             if min(result, key=lambda x: x[1]) != max(result, key=lambda x: x[1]):
-                differentials.append(i)
+                differentials.append((i, differing_functions(function_checksums)))
+        if len(const_mismatches) > 0:
+            const_differentials.append((i, const_mismatches))
+        if len(interpreter_mismatches) > 0:
+            interpreter_differentials.append((i, interpreter_mismatches))
         for opt_level, _ in result:
             timeout_info[opt_level] -= 1
         delete_bin_seed(i)
         print("\n\n\n\n\n\n")
         
     
-    if len(differentials) > 0:
+    if len(differentials) > 0 or len(const_differentials) > 0 or len(interpreter_differentials) > 0:
         print(f"Found differentials")
     else:
         print("No differentials found")
@@ -76,14 +164,64 @@ def test(count, start=0):
     with open("results", "w") as f:
         f.write(f"Total runs: {count} | Starting seed: {start}\n")
         f.write(f"Timeouts: {timeout_info}\n")
-        f.write(f"Differentials: {differentials}")
+        f.write(f"Differentials: {differentials}\n")
+        f.write(f"Const evaluation differentials: {const_differentials}\n")
+        f.write(f"Interpreter differentials: {interpreter_differentials}\n")
+        f.write(f"Generator bugs: {generator_bugs}")
+
+# Output of a binary at every optimisation level, None where it timed out
+def outputs(name):
+    result = {}
+    for opt_level in opt_levels:
+        process = subprocess.run(f"timeout 5s ./executables/{opt_level}/release/{name}", shell=True, stdout=subprocess.PIPE)
+        result[opt_level] = process.stdout.splitlines() if process.returncode == 0 else None
+    return result
+
+# Every variant has to print the same as the program it was derived from, at every optimisation level
+# EMI variants come from --emi and metamorphic variants from --metamorph
+def variants(kind, count, start=0, variant_count=4):
+    flag, label = {"emi": ("--emi", "EMI"), "meta": ("--metamorph", "Metamorphic")}[kind]
+    differentials = []
+    generator_bugs = []
+    for i in range(start, start + count):
+        print(f"Testing {label} variants of seed {i}")
+        generate(i, f" {flag} {variant_count} --check")
+        names = ["seed_" + str(i)] + [f"seed_{i}_{kind}_{v}" for v in range(1, variant_count + 1)]
+        generator_bugs.extend(name for name in names if generator_bug(name))
+        if generator_bug(names[0]):
+            continue
+        names = [name for name in names if not generator_bug(name)]
+        results = {}
+        for name in names:
+            compile_bin(name)
+            for opt_level, output in outputs(name).items():
+                if output is not None:
+                    results[(name, opt_level)] = output
+        # Compared against the program at the lowest optimisation level that did not time out,
+        # or the first variant that did not if the program always timed out
+        if len(set(tuple(output) for output in results.values())) > 1:
+            reference = next(iter(results.values()))
+            differentials.append((i, sorted(key for key, output in results.items() if output != reference)))
+        delete_bin_seed(i)
+        os.system(f"rm -rf ./src/bin/seed_{i}_{kind}_*.rs")
+        print("\n\n\n\n\n\n")
+
+    if len(differentials) > 0:
+        print(f"Found {label} differentials")
+    else:
+        print(f"No {label} differentials found")
+
+    with open("results", "w") as f:
+        f.write(f"Total runs: {count} | Starting seed: {start} | Variants: {variant_count}\n")
+        f.write(f"{label} differentials: {differentials}\n")
+        f.write(f"Generator bugs: {generator_bugs}")
 
 def clean():
     delete_if_exists("./executables")
     delete_if_exists("./results")
-    os.system("rm -rf ./src/bin/*.rs")
+    os.system("rm -rf ./src/bin/*.rs ./src/bin/*.expected ./src/bin/*.errors")
 
-commands = ["compile [seed (int)]", "generate [seed (int)]", "run [seed (int)]", "test [seed (int)]", "clean", "format", "help"]
+commands = ["compile [seed (int)]", "generate [seed (int)]", "run [seed (int)]", "test [seed (int)]", "trace [seed (int)]", "emi [count (int)] [seed (int)]", "meta [count (int)] [seed (int)]", "clean", "format", "help"]
 
 def main():
     args = sys.argv
@@ -119,6 +257,15 @@ def main():
         else:
             test(int(args[2]))
 
+    elif args[1] == "trace":
+        trace(int(args[2]))
+
+    elif args[1] in ["emi", "meta"]:
+        if len(args) == 4:
+            variants(args[1], int(args[2]), int(args[3]))
+        else:
+            variants(args[1], int(args[2]))
+
     elif args[1] == "clean":
         clean()
 
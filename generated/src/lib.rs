@@ -1,9 +1,9 @@
 #![allow(warnings)]
 
-use smith::generate_from_seed;
+use smith::{generate_from_seed, Features, GeneratorConfig};
 pub fn generate(count: u64) -> std::io::Result<()> {
     for i in 0..count {
-        let code = generate_from_seed(i);
+        let code = generate_from_seed(i, Features::default(), &GeneratorConfig::default());
         let path = "../bin/";
         std::fs::write(path, code)?;
     }
@@ -1,22 +1,316 @@
 use clap::{App, Arg, ArgMatches};
 use indicatif::ProgressBar;
-use std::fs;
+use smith::{
+    program::program::Program, Checker, EmiGenerator, Features, GenerationStats, Generator,
+    GeneratorConfig, Interpreter, MetamorphicTransformer, OutputMode, Reducer,
+};
+use std::{fs, path::Path, process, process::Command};
+
+// Generated workspaces depend on the runtime crate through an absolute path
+const RUNTIME_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../runtime");
+
 fn main() {
     let config = RunConfig::new_from_args(get_args());
 
+    if let Some(ast_path) = config.render() {
+        match config.stmt() {
+            Some(stmt_id) => print_traced_stmt(ast_path, stmt_id),
+            None => render_ast(ast_path, config.features()),
+        }
+        return;
+    }
+
+    if let Some(ast_path) = config.reduce() {
+        reduce_ast(
+            ast_path,
+            config.test().unwrap(),
+            config.features(),
+            config.stmt(),
+        );
+        return;
+    }
+
+    if config.stmt().is_some() {
+        exit_with_error(String::from(
+            "--stmt can only be used with --render or --reduce",
+        ));
+    }
+
+    if config.stats() {
+        print_stats(&config);
+        return;
+    }
+
     let progress_bar = ProgressBar::new(config.count());
 
     for seed in config.seed()..config.seed() + config.count() {
-        let code = smith::generate_from_seed(seed);
+        let output_mode = if config.workspace() {
+            OutputMode::Workspace(runtime_path())
+        } else {
+            OutputMode::File
+        };
 
-        let file_path = format!("./src/bin/{}.rs", RunConfig::as_file_name(seed));
+        let generated = Generator::new(seed)
+            .with_config(config.generator_config().clone())
+            .with_features(config.features())
+            .with_output_mode(output_mode)
+            .generate();
 
-        match fs::write(file_path, code.as_str()) {
-            Ok(_) => (),
-            Err(err) => panic!("Failed to generate, {}", err),
+        let output_dir = if config.workspace() {
+            format!("./workspaces/{}", RunConfig::as_file_name(seed))
+        } else {
+            String::from("./src/bin")
         };
 
+        // A program the generator got wrong is not worth compiling, its errors are written instead
+        if config.check() {
+            if let Some(errors_file) =
+                check_program(generated.get_program(), RunConfig::as_file_name(seed))
+            {
+                progress_bar.println(format!(
+                    "Generator bug in {}, see {}",
+                    RunConfig::as_file_name(seed),
+                    errors_file.0
+                ));
+                write_files(&output_dir, &[errors_file]);
+                progress_bar.inc(1);
+                continue;
+            }
+        }
+        write_files(&output_dir, generated.get_files());
+
+        if config.ast() {
+            let ast_file = (
+                format!("{}.json", RunConfig::as_file_name(seed)),
+                generated.get_program().to_json(),
+            );
+            write_files(&output_dir, &[ast_file]);
+        }
+
+        // A program the interpreter gives up on is still compared across optimisation levels
+        if config.expect() {
+            match Interpreter::new(generated.get_program()).run() {
+                Ok(expected) => {
+                    let expected_file = (
+                        format!("{}.expected", RunConfig::as_file_name(seed)),
+                        expected.to_string(),
+                    );
+                    write_files(&output_dir, &[expected_file]);
+                }
+                Err(err) => progress_bar.println(format!(
+                    "No expected output for {}, {}",
+                    RunConfig::as_file_name(seed),
+                    err
+                )),
+            }
+        }
+
+        // Every variant has to print what the program prints, so they share its expected output
+        if let Some(emi_count) = config.emi() {
+            let variants = EmiGenerator::new(generated.get_program(), seed)
+                .with_features(config.features())
+                .with_config(config.generator_config().clone())
+                .variants(emi_count);
+            let variant_files: Vec<(String, String)> = variants
+                .iter()
+                .enumerate()
+                .map(|(i, variant)| {
+                    let name = format!("{}_emi_{}", RunConfig::as_file_name(seed), i + 1);
+                    variant_file(variant, name, &config)
+                })
+                .collect();
+            write_files(&output_dir, &variant_files);
+        }
+
+        if let Some(metamorph_count) = config.metamorph() {
+            let variants = MetamorphicTransformer::new(generated.get_program(), seed)
+                .variants(metamorph_count);
+            let variant_files: Vec<(String, String)> = variants
+                .iter()
+                .enumerate()
+                .map(|(i, variant)| {
+                    let name = format!("{}_meta_{}", RunConfig::as_file_name(seed), i + 1);
+                    variant_file(variant, name, &config)
+                })
+                .collect();
+            write_files(&output_dir, &variant_files);
+        }
+
+        progress_bar.inc(1);
+    }
+}
+
+// Generates every seed in the range without writing it, and prints what the generator did as JSON
+fn print_stats(config: &RunConfig) {
+    let progress_bar = ProgressBar::new(config.count());
+    let mut stats = GenerationStats::default();
+
+    for seed in config.seed()..config.seed() + config.count() {
+        let generated = Generator::new(seed)
+            .with_config(config.generator_config().clone())
+            .with_features(config.features())
+            .generate();
+        stats.merge(generated.get_stats());
+
+        progress_bar.inc(1);
+    }
+    progress_bar.finish_and_clear();
+
+    println!("{}", stats.to_json());
+}
+
+// The errors of a program the checker rejects, as a file named after it
+fn check_program(program: &Program, name: String) -> Option<(String, String)> {
+    match Checker::new(program).check() {
+        Ok(()) => None,
+        Err(errors) => {
+            let lines: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+            Some((format!("{}.errors", name), lines.join("\n")))
+        }
+    }
+}
+
+// A variant's source, or with --check its errors if the rewrites broke it
+fn variant_file(variant: &Program, name: String, config: &RunConfig) -> (String, String) {
+    if config.check() {
+        if let Some(errors_file) = check_program(variant, name.clone()) {
+            return errors_file;
+        }
+    }
+
+    (
+        format!("{}.rs", name),
+        smith::render_program(variant, config.features()),
+    )
+}
+
+// Renders an AST dumped with --ast, possibly edited since, into ./src/bin
+fn render_ast(ast_path: &str, features: Features) {
+    let program = read_ast(ast_path);
+
+    write_files(
+        "./src/bin",
+        &[(
+            format!("{}.rs", ast_name(ast_path)),
+            smith::render_program(&program, features),
+        )],
+    );
+}
+
+// Prints the source of the statement that the trace with this id follows, such as where traces
+// first diverged
+fn print_traced_stmt(ast_path: &str, stmt_id: u32) {
+    match read_ast(ast_path).traced_stmt(stmt_id) {
+        Some(stmt) => println!("{}", stmt.to_string()),
+        None => exit_with_error(format!(
+            "No statement is traced with id {} in {}",
+            stmt_id, ast_path
+        )),
+    }
+}
+
+// Reduces an AST dumped with --ast for as long as the test still succeeds on it
+// The test is run with the path of each rendered candidate, which is also where the result ends up
+// The statement with the kept trace id stays as it is in every candidate
+fn reduce_ast(ast_path: &str, test_path: &str, features: Features, kept_stmt: Option<u32>) {
+    let program = read_ast(ast_path);
+    if let Some(stmt_id) = kept_stmt {
+        if program.traced_stmt(stmt_id).is_none() {
+            exit_with_error(format!(
+                "No statement is traced with id {} in {}",
+                stmt_id, ast_path
+            ));
+        }
+    }
+    let reduced_name = format!("{}_reduced", ast_name(ast_path));
+    let source_name = format!("{}.rs", reduced_name);
+    let source_path = Path::new("./src/bin").join(&source_name);
+
+    let progress_bar = ProgressBar::new_spinner();
+    let mut reducer = Reducer::new(|candidate: &Program| {
         progress_bar.inc(1);
+        write_files(
+            "./src/bin",
+            &[(
+                source_name.clone(),
+                smith::render_program(candidate, features),
+            )],
+        );
+
+        match Command::new(test_path).arg(&source_path).status() {
+            Ok(status) => status.success(),
+            Err(err) => panic!("Failed to run test, {}", err),
+        }
+    });
+
+    if let Some(stmt_id) = kept_stmt {
+        reducer = reducer.with_kept_stmt(stmt_id);
+    }
+
+    let reduced = match reducer.reduce(program) {
+        Some(reduced) => reduced,
+        None => panic!(
+            "Failed to reduce, the test does not succeed on {}",
+            ast_path
+        ),
+    };
+    progress_bar.finish_and_clear();
+    println!("Reduced after {} tests", reducer.get_test_count());
+
+    write_files(
+        "./src/bin",
+        &[
+            (source_name, smith::render_program(&reduced, features)),
+            (format!("{}.json", reduced_name), reduced.to_json()),
+        ],
+    );
+}
+
+fn read_ast(ast_path: &str) -> Program {
+    let json = match fs::read_to_string(ast_path) {
+        Ok(json) => json,
+        Err(err) => panic!("Failed to read AST, {}", err),
+    };
+    match Program::from_json(&json) {
+        Ok(program) => program,
+        Err(err) => panic!("Failed to parse AST, {}", err),
+    }
+}
+
+fn ast_name(ast_path: &str) -> String {
+    match Path::new(ast_path).file_stem() {
+        Some(stem) => stem.to_string_lossy().to_string(),
+        None => panic!("Failed to name rendered file for {}", ast_path),
+    }
+}
+
+// Mistakes in what was passed are reported without a panic
+fn exit_with_error(message: String) -> ! {
+    eprintln!("{}", message);
+    process::exit(1)
+}
+
+fn runtime_path() -> String {
+    match fs::canonicalize(RUNTIME_PATH) {
+        Ok(path) => path.to_string_lossy().to_string(),
+        Err(err) => panic!("Failed to find runtime crate, {}", err),
+    }
+}
+
+fn write_files(output_dir: &str, files: &[(String, String)]) {
+    for (relative_path, contents) in files {
+        let file_path = Path::new(output_dir).join(relative_path);
+
+        if let Some(parent) = file_path.parent() {
+            if let Err(err) = fs::create_dir_all(parent) {
+                panic!("Failed to generate, {}", err);
+            }
+        }
+
+        match fs::write(file_path, contents.as_str()) {
+            Ok(_) => (),
+            Err(err) => panic!("Failed to generate, {}", err),
+        };
     }
 }
 
@@ -46,6 +340,112 @@ pub fn get_args() -> ArgMatches<'static> {
                 .takes_value(true)
                 .help("Number of random programs to generate"),
         )
+        .arg(
+            Arg::with_name("workspace")
+                .short("w")
+                .long("workspace")
+                .help(
+                "Generate each program as a Cargo workspace with a library crate in ./workspaces",
+            ),
+        )
+        .arg(
+            Arg::with_name("threads")
+                .long("threads")
+                .help("Allow function calls to run on scoped threads"),
+        )
+        .arg(
+            Arg::with_name("unsafe")
+                .long("unsafe")
+                .help("Allow unsafe blocks with raw pointers and MaybeUninit"),
+        )
+        .arg(
+            Arg::with_name("standalone").long("standalone").help(
+                "Inline printing and runtime helpers so that programs compile with a bare rustc",
+            ),
+        )
+        .arg(
+            Arg::with_name("trace")
+                .long("trace")
+                .conflicts_with("threads")
+                .help("Print the id and assigned values of every statement to stderr as it runs"),
+        )
+        .arg(
+            Arg::with_name("swarm").long("swarm").help(
+                "Disable a random subset of productions and perturb the depth limits for each seed",
+            ),
+        )
+        .arg(
+            Arg::with_name("ast")
+                .long("ast")
+                .help("Also write the AST of each program as JSON next to its source"),
+        )
+        .arg(
+            Arg::with_name("expect")
+                .long("expect")
+                .help("Also write the output of the reference interpreter, which every build should print"),
+        )
+        .arg(
+            Arg::with_name("check")
+                .long("check")
+                .help("Check each program for type and borrow errors first, writing the errors of a program that has any instead of its source"),
+        )
+        .arg(
+            Arg::with_name("stats")
+                .long("stats")
+                .conflicts_with_all(&["workspace", "ast", "expect", "check", "emi", "metamorph"])
+                .help("Print how often the generator picked, retried and gave up on each production across the seeds as JSON, instead of writing them"),
+        )
+        .arg(
+            Arg::with_name("emi")
+                .long("emi")
+                .takes_value(true)
+                .conflicts_with("workspace")
+                .help("Also write this many EMI variants of each program, which have to print the same"),
+        )
+        .arg(
+            Arg::with_name("metamorph")
+                .long("metamorph")
+                .takes_value(true)
+                .conflicts_with("workspace")
+                .help("Also write this many metamorphic variants of each program, rewritten to print the same"),
+        )
+        .arg(
+            Arg::with_name("render")
+                .long("render")
+                .takes_value(true)
+                .help("Render an AST written with --ast into ./src/bin instead of generating"),
+        )
+        .arg(
+            Arg::with_name("reduce")
+                .long("reduce")
+                .takes_value(true)
+                .requires("test")
+                .help("Reduce an AST written with --ast for as long as --test succeeds on it"),
+        )
+        .arg(
+            Arg::with_name("test")
+                .long("test")
+                .takes_value(true)
+                .help("Executable run with the path of each candidate source, which succeeds if it is still interesting"),
+        )
+        .arg(
+            Arg::with_name("stmt")
+                .long("stmt")
+                .takes_value(true)
+                .help("Trace id of a statement, whose source --render prints instead of the program, and which --reduce keeps as it is"),
+        )
+        .arg(
+            Arg::with_name("config")
+                .long("config")
+                .takes_value(true)
+                .help("JSON file with generator constants and production weights, such as a profile in ./profiles"),
+        )
+        .arg(
+            Arg::with_name("size")
+                .long("size")
+                .takes_value(true)
+                .help("Statements and expressions that each program is generated close to, overriding target_size of --config"),
+        )
         .get_matches()
 }
 
@@ -53,18 +453,92 @@ pub fn get_args() -> ArgMatches<'static> {
 pub struct RunConfig {
     seed: u64,
     count: u64,
+    workspace: bool,
+    threads: bool,
+    unsafe_code: bool,
+    standalone: bool,
+    trace: bool,
+    swarm: bool,
+    ast: bool,
+    expect: bool,
+    check: bool,
+    stats: bool,
+    emi: Option<usize>,
+    metamorph: Option<usize>,
+    render: Option<String>,
+    reduce: Option<String>,
+    test: Option<String>,
+    stmt: Option<u32>,
+    generator_config: GeneratorConfig,
 }
 
 impl RunConfig {
     pub fn new(seed: u64, count: u64) -> Self {
-        RunConfig { seed, count }
+        RunConfig {
+            seed,
+            count,
+            workspace: false,
+            threads: false,
+            unsafe_code: false,
+            standalone: false,
+            trace: false,
+            swarm: false,
+            ast: false,
+            expect: false,
+            check: false,
+            stats: false,
+            emi: None,
+            metamorph: None,
+            render: None,
+            reduce: None,
+            test: None,
+            stmt: None,
+            generator_config: GeneratorConfig::default(),
+        }
     }
 
     pub fn new_from_args(args: ArgMatches) -> Self {
         let seed: u64 = RunConfig::parse_seed(&args);
         let count: u64 = RunConfig::parse_count(&args);
+        let workspace = args.is_present("workspace");
+        let threads = args.is_present("threads");
+        let unsafe_code = args.is_present("unsafe");
+        let standalone = args.is_present("standalone");
+        let trace = args.is_present("trace");
+        let swarm = args.is_present("swarm");
+        let ast = args.is_present("ast");
+        let expect = args.is_present("expect");
+        let check = args.is_present("check");
+        let stats = args.is_present("stats");
+        let emi = RunConfig::parse_emi(&args);
+        let metamorph = RunConfig::parse_metamorph(&args);
+        let render = args.value_of("render").map(String::from);
+        let reduce = args.value_of("reduce").map(String::from);
+        let test = args.value_of("test").map(String::from);
+        let stmt = RunConfig::parse_stmt(&args);
+        let generator_config = RunConfig::parse_generator_config(&args);
 
-        RunConfig { seed, count }
+        RunConfig {
+            seed,
+            count,
+            workspace,
+            threads,
+            unsafe_code,
+            standalone,
+            trace,
+            swarm,
+            ast,
+            expect,
+            check,
+            stats,
+            emi,
+            metamorph,
+            render,
+            reduce,
+            test,
+            stmt,
+            generator_config,
+        }
     }
 
     pub fn seed(&self) -> u64 {
@@ -79,6 +553,89 @@ impl RunConfig {
         self.count
     }
 
+    pub fn workspace(&self) -> bool {
+        self.workspace
+    }
+
+    pub fn features(&self) -> Features {
+        Features {
+            threads: self.threads,
+            unsafe_code: self.unsafe_code,
+            standalone: self.standalone,
+            trace: self.trace,
+            swarm: self.swarm,
+        }
+    }
+
+    pub fn ast(&self) -> bool {
+        self.ast
+    }
+
+    pub fn expect(&self) -> bool {
+        self.expect
+    }
+
+    pub fn check(&self) -> bool {
+        self.check
+    }
+
+    pub fn stats(&self) -> bool {
+        self.stats
+    }
+
+    pub fn emi(&self) -> Option<usize> {
+        self.emi
+    }
+
+    pub fn metamorph(&self) -> Option<usize> {
+        self.metamorph
+    }
+
+    pub fn render(&self) -> Option<&str> {
+        self.render.as_deref()
+    }
+
+    pub fn reduce(&self) -> Option<&str> {
+        self.reduce.as_deref()
+    }
+
+    pub fn test(&self) -> Option<&str> {
+        self.test.as_deref()
+    }
+
+    pub fn stmt(&self) -> Option<u32> {
+        self.stmt
+    }
+
+    pub fn generator_config(&self) -> &GeneratorConfig {
+        &self.generator_config
+    }
+
+    fn parse_generator_config(args: &ArgMatches) -> GeneratorConfig {
+        let mut generator_config = match args.value_of("config") {
+            None => GeneratorConfig::default(),
+            Some(path) => {
+                let json = match fs::read_to_string(path) {
+                    Ok(json) => json,
+                    Err(err) => exit_with_error(format!("Failed to read config, {}", err)),
+                };
+                match GeneratorConfig::from_json(&json) {
+                    Ok(generator_config) => generator_config,
+                    Err(err) => exit_with_error(format!("Invalid config, {}", err)),
+                }
+            }
+        };
+
+        if let Some(value) = args.value_of("size") {
+            match value.parse::<u32>() {
+                Ok(target_size) => generator_config.target_size = Some(target_size),
+                Err(_) => panic!("Failed to parse target size, {}", value),
+            }
+        }
+
+        generator_config
+    }
+
     fn parse_seed(args: &ArgMatches) -> u64 {
         match args.value_of("seed") {
             None => Default::default(),
@@ -98,4 +655,28 @@ impl RunConfig {
             Some(value) => value.parse::<u64>().unwrap_or(1),
         }
     }
+
+    fn parse_emi(args: &ArgMatches) -> Option<usize> {
+        args.value_of("emi")
+            .map(|value| match value.parse::<usize>() {
+                Ok(count) => count,
+                Err(_) => panic!("Failed to parse EMI variant count, {}", value),
+            })
+    }
+
+    fn parse_stmt(args: &ArgMatches) -> Option<u32> {
+        args.value_of("stmt")
+            .map(|value| match value.parse::<u32>() {
+                Ok(stmt_id) => stmt_id,
+                Err(_) => exit_with_error(format!("Failed to parse statement id, {}", value)),
+            })
+    }
+
+    fn parse_metamorph(args: &ArgMatches) -> Option<usize> {
+        args.value_of("metamorph")
+            .map(|value| match value.parse::<usize>() {
+                Ok(count) => count,
+                Err(_) => panic!("Failed to parse metamorphic variant count, {}", value),
+            })
+    }
 }
@@ -0,0 +1,109 @@
+// A deterministic checksum over the local variables of generated programs
+// Each block folds its variables in before they go out of scope, so that their values are observed
+use std::{collections::BTreeMap, sync::Mutex};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+// Sum of the hashes of every fold, per function
+// Sums do not depend on the order of folds, so functions on scoped threads still agree
+static CHECKSUMS: Mutex<BTreeMap<&'static str, u64>> = Mutex::new(BTreeMap::new());
+
+// Takes the value, so that fields of packed structs are copied out instead of borrowed
+pub trait ToChecksum {
+    fn to_checksum(self) -> u128;
+}
+
+macro_rules! impl_to_checksum {
+    (for $($t:ty),+) => {
+        $(impl ToChecksum for $t {
+    #[inline]
+    fn to_checksum(self) -> u128 {
+        self as u128
+    }
+        })*
+    };
+}
+
+impl_to_checksum!(for u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, bool);
+
+// FNV-1a
+fn hash_bytes(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+
+    hash
+}
+
+fn fold_hash(function_name: &str, values: &[u128]) -> u64 {
+    let mut hash = hash_bytes(FNV_OFFSET_BASIS, function_name.as_bytes());
+    for value in values {
+        hash = hash_bytes(hash, &value.to_le_bytes());
+    }
+
+    hash
+}
+
+pub fn fold(function_name: &'static str, values: &[u128]) {
+    let hash = fold_hash(function_name, values);
+
+    let mut checksums = CHECKSUMS.lock().unwrap();
+    let checksum = checksums.entry(function_name).or_insert(0);
+    *checksum = checksum.wrapping_add(hash);
+}
+
+pub fn total() -> u64 {
+    CHECKSUMS
+        .lock()
+        .unwrap()
+        .values()
+        .fold(0, |total, checksum| total.wrapping_add(*checksum))
+}
+
+// The total and the checksum of each function as a JSON line, the breakdown localises a differential
+pub fn to_json() -> String {
+    let functions: Vec<String> = CHECKSUMS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(function_name, checksum)| format!("\"{}\":{}", function_name, checksum))
+        .collect();
+
+    format!(
+        "{{\"checksum\":{},\"functions\":{{{}}}}}",
+        total(),
+        functions.join(",")
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fold_hash_depends_on_values_and_function() {
+        let values = [1u8.to_checksum(), (-1i32).to_checksum()];
+        let swapped = [(-1i32).to_checksum(), 1u8.to_checksum()];
+
+        assert_ne!(
+            fold_hash("function_1", &values),
+            fold_hash("function_1", &swapped)
+        );
+        assert_ne!(
+            fold_hash("function_1", &values),
+            fold_hash("function_2", &values)
+        );
+    }
+
+    #[test]
+    fn json_has_total_and_functions() {
+        fold("function_1", &[true.to_checksum()]);
+
+        let json = to_json();
+        assert!(json.starts_with("{\"checksum\":"));
+        assert!(json.contains("\"functions\":{"));
+        assert!(json.contains("\"function_1\":"));
+    }
+}
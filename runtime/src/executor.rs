@@ -0,0 +1,74 @@
+// A minimal single threaded executor for driving generated async functions
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+
+// Futures are polled in a loop, so waking does nothing
+fn noop_raw_waker() -> RawWaker {
+    fn clone(_: *const ()) -> RawWaker {
+        noop_raw_waker()
+    }
+    fn noop(_: *const ()) {}
+
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    RawWaker::new(std::ptr::null(), &VTABLE)
+}
+
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = Box::pin(future);
+
+    let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+    let mut context = Context::from_waker(&waker);
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+// Suspends the current async function once, creating an await point
+pub struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            Poll::Ready(())
+        } else {
+            self.yielded = true;
+            context.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+pub fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    async fn add_after_yield(a: &mut u32, b: u32) -> u32 {
+        yield_now().await;
+        *a += b;
+        yield_now().await;
+        *a
+    }
+
+    #[test]
+    fn drives_future_across_yields() {
+        let mut a = 1;
+
+        assert_eq!(block_on(add_after_yield(&mut a, 2)), 3);
+        assert_eq!(a, 3);
+    }
+}
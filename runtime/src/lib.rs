@@ -1,5 +1,8 @@
+pub mod checksum;
+pub mod executor;
 pub mod ops;
 pub mod safe_ops;
+pub mod trace;
 
 #[cfg(test)]
 mod test {
@@ -0,0 +1,36 @@
+// Traces of generated programs, printed after each statement when generated in trace mode
+// Traces go to stderr so that the output on stdout is unchanged
+use std::io::Write;
+
+// The values are those of the places the statement just assigned, converted with ToChecksum
+fn to_json(stmt_id: u32, values: &[(&str, u128)]) -> String {
+    let values: Vec<String> = values
+        .iter()
+        .map(|(place, value)| format!("\"{}\":{}", place, value))
+        .collect();
+
+    format!(
+        "{{\"stmt\":{},\"values\":{{{}}}}}",
+        stmt_id,
+        values.join(",")
+    )
+}
+
+pub fn record(stmt_id: u32, values: &[(&str, u128)]) {
+    let mut stderr = std::io::stderr().lock();
+    let _ = writeln!(stderr, "{}", to_json(stmt_id, values));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn json_has_stmt_and_values() {
+        assert_eq!(
+            to_json(3, &[("var_1", 5), ("var_2.field_1", 1)]),
+            "{\"stmt\":3,\"values\":{\"var_1\":5,\"var_2.field_1\":1}}"
+        );
+        assert_eq!(to_json(4, &[]), "{\"stmt\":4,\"values\":{}}");
+    }
+}
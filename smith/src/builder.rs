@@ -0,0 +1,240 @@
+/// Library entry point for tools that embed the generator
+/// Besides the rendered source, the result keeps the AST for reduction and mutation
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+
+use crate::{
+    generator::{config::GeneratorConfig, features::Features, main_gen, stats::GenerationStats},
+    program::{program::Program, visitor::passes::NodeCount},
+    render_program, runtime_dependency, workspace_files,
+};
+
+// A single file, or a Cargo workspace depending on the runtime crate at the given path
+#[derive(Clone, Debug, PartialEq)]
+pub enum OutputMode {
+    File,
+    Workspace(String),
+}
+
+pub struct Generator {
+    seed: u64,
+    config: GeneratorConfig,
+    features: Features,
+    output_mode: OutputMode,
+}
+
+impl Generator {
+    pub fn new(seed: u64) -> Self {
+        Generator {
+            seed,
+            config: GeneratorConfig::default(),
+            features: Features::default(),
+            output_mode: OutputMode::File,
+        }
+    }
+
+    pub fn with_config(mut self, config: GeneratorConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_features(mut self, features: Features) -> Self {
+        self.features = features;
+        self
+    }
+
+    pub fn with_output_mode(mut self, output_mode: OutputMode) -> Self {
+        self.output_mode = output_mode;
+        self
+    }
+
+    pub fn generate(&self) -> GeneratedProgram {
+        // Named explicitly rather than StdRng, whose algorithm may change between rand releases
+        let mut rng = ChaCha12Rng::seed_from_u64(self.seed);
+
+        // A workspace needs at least one module to put into its library crate
+        let min_modules = match self.output_mode {
+            OutputMode::File => 0,
+            OutputMode::Workspace(_) => 1,
+        };
+        let (program, stats) =
+            main_gen::gen_program_with_stats(&mut rng, min_modules, self.features, &self.config);
+
+        let files = match &self.output_mode {
+            OutputMode::File => vec![(
+                format!("seed_{}.rs", self.seed),
+                render_program(&program, self.features),
+            )],
+            OutputMode::Workspace(runtime_path) => {
+                workspace_files(&program, self.seed, runtime_path, self.features)
+            }
+        };
+
+        let metadata = GenerationMetadata::new(self.seed, &program);
+        let hash = match &self.output_mode {
+            OutputMode::File => content_hash(&files),
+            OutputMode::Workspace(runtime_path) => {
+                content_hash(&without_runtime_path(&files, runtime_path))
+            }
+        };
+
+        GeneratedProgram {
+            program,
+            files,
+            metadata,
+            stats,
+            hash,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenerationMetadata {
+    pub seed: u64,
+    pub function_count: usize,
+    pub struct_count: usize, // Includes the global struct
+    pub stmt_count: usize,   // Includes statements in nested blocks
+    pub node_count: usize, // Statements and expressions, comparable to GeneratorConfig::target_size
+}
+
+impl GenerationMetadata {
+    fn new(seed: u64, program: &Program) -> Self {
+        let count = NodeCount::of_program(program);
+
+        GenerationMetadata {
+            seed,
+            function_count: count.functions,
+            struct_count: program.struct_count(),
+            stmt_count: count.stmts,
+            node_count: count.size(),
+        }
+    }
+}
+
+pub struct GeneratedProgram {
+    program: Program,
+    files: Vec<(String, String)>, // (relative path, contents) pairs
+    metadata: GenerationMetadata,
+    stats: GenerationStats,
+    hash: u64,
+}
+
+impl GeneratedProgram {
+    pub fn get_program(&self) -> &Program {
+        &self.program
+    }
+
+    pub fn into_program(self) -> Program {
+        self.program
+    }
+
+    // The file holding main
+    pub fn get_source(&self) -> &str {
+        let (_, source) = self
+            .files
+            .iter()
+            .find(|(path, _)| path.ends_with(".rs") && !path.ends_with("lib.rs"))
+            .unwrap();
+
+        source
+    }
+
+    pub fn get_files(&self) -> &[(String, String)] {
+        &self.files
+    }
+
+    pub fn get_metadata(&self) -> &GenerationMetadata {
+        &self.metadata
+    }
+
+    // What the generator picked and retried along the way
+    pub fn get_stats(&self) -> &GenerationStats {
+        &self.stats
+    }
+
+    // Only depends on the generated files, so it stays the same across platforms and releases
+    // The path to the runtime crate is left out, as it depends on where a workspace is written
+    pub fn get_hash(&self) -> u64 {
+        self.hash
+    }
+}
+
+fn without_runtime_path(files: &[(String, String)], runtime_path: &str) -> Vec<(String, String)> {
+    let dependency = runtime_dependency(runtime_path);
+    let normalised = runtime_dependency("");
+
+    files
+        .iter()
+        .map(|(path, contents)| (path.clone(), contents.replace(&dependency, &normalised)))
+        .collect()
+}
+
+// 64 bit FNV-1a over every path and its contents
+fn content_hash(files: &[(String, String)]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    files
+        .iter()
+        .flat_map(|(path, contents)| path.bytes().chain(contents.bytes()))
+        .fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(PRIME)
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn metadata_matches_program() {
+        let generated = Generator::small(3).generate();
+        let metadata = generated.get_metadata();
+
+        assert_eq!(metadata.seed, 3);
+        assert_eq!(
+            metadata.function_count,
+            generated.get_source().matches("fn function_").count() + 1
+        );
+        assert!(metadata.struct_count >= 1);
+        assert!(metadata.stmt_count > 0);
+    }
+
+    #[test]
+    fn hash_depends_on_contents() {
+        let first = Generator::small(0).generate();
+        let second = Generator::small(0).generate();
+        let other = Generator::small(1).generate();
+
+        assert_eq!(first.get_hash(), second.get_hash());
+        assert_ne!(first.get_hash(), other.get_hash());
+    }
+
+    #[test]
+    fn workspace_source_is_the_binary_crate() {
+        let generated = Generator::small(0)
+            .with_output_mode(OutputMode::Workspace(String::from("../runtime")))
+            .generate();
+
+        assert_eq!(generated.get_files().len(), 5);
+        assert!(generated.get_source().contains("fn main()"));
+    }
+
+    #[test]
+    fn hash_does_not_depend_on_runtime_path() {
+        let workspace = |runtime_path: &str| {
+            Generator::small(0)
+                .with_output_mode(OutputMode::Workspace(String::from(runtime_path)))
+                .generate()
+        };
+        let relative = workspace("../runtime");
+        let absolute = workspace("/home/user/rustsmith/runtime");
+
+        assert_ne!(relative.get_files(), absolute.get_files());
+        assert_eq!(relative.get_hash(), absolute.get_hash());
+        assert_ne!(
+            relative.get_hash(),
+            Generator::small(0).generate().get_hash()
+        );
+    }
+}
@@ -0,0 +1,1299 @@
+/// Static checker for generated programs, which catches generator bugs before rustc does
+/// It checks that the types recorded across the AST agree with each other, and that the program
+/// keeps to the move and borrow rules the generator tracks in its scopes
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+};
+
+use crate::program::{
+    expr::{
+        arithmetic_expr::{BinaryExpr, IntExpr},
+        bool_expr::ComparisonExpr,
+        borrow_expr::BorrowExpr,
+        expr::Expr,
+        func_call_expr::FunctionCallExpr,
+        macro_call_expr::MacroCallExpr,
+        struct_expr::{StructExpr, StructLiteral},
+        tuple_expr::TupleExpr,
+    },
+    function::{Function, FunctionTemplate},
+    pattern::Pattern,
+    program::Program,
+    stmt::{
+        assign_stmt::AssignStmt, await_stmt::AwaitStmt, block_stmt::BlockStmt,
+        checksum_stmt::ChecksumStmt, conditional_stmt::ConditionalStmt, expr_stmt::ExprStmt,
+        for_loop_stmt::ForLoopStmt, if_let_stmt::IfLetStmt, let_pattern_stmt::LetPatternStmt,
+        let_stmt::LetStmt, op_assign_stmt::OpAssignStmt, return_stmt::ReturnStmt, stmt::Stmt,
+        trace_stmt::TraceStmt, while_stmt::WhileStmt,
+    },
+    struct_template::StructTemplate,
+    types::{BorrowTypeID, TypeID},
+    var::Var,
+    visitor::visit::Visit,
+};
+
+// Longest rendering of a node in an error message
+const MAX_NODE_LENGTH: usize = 120;
+
+#[derive(Debug, PartialEq)]
+pub enum CheckErrorKind {
+    // The type that was expected, then the type found
+    TypeMismatch(TypeID, TypeID),
+    BorrowMismatch(BorrowTypeID, BorrowTypeID),
+    NotAnInteger(TypeID),
+    UnknownStruct(String),
+    UnknownField(String, String), // Struct and field name
+    UnknownFunction(String),
+    ArgumentCount(usize, usize),
+    UseAfterMove(String),
+    MoveOutOfBorrow(String),
+    WriteToImmutable(String),
+    MutBorrowOfImmutable(String),
+    // A place used while a mutable borrow of it is alive, or changed while it is borrowed
+    BorrowConflict(String),
+}
+
+impl fmt::Display for CheckErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TypeMismatch(expected, found) => write!(
+                f,
+                "expected type {}, found {}",
+                type_name(expected),
+                type_name(found)
+            ),
+            Self::BorrowMismatch(expected, found) => {
+                write!(f, "expected borrow {:?}, found {:?}", expected, found)
+            }
+            Self::NotAnInteger(type_id) => {
+                write!(f, "arithmetic on non-integer type {}", type_name(type_id))
+            }
+            Self::UnknownStruct(name) => write!(f, "unknown struct {}", name),
+            Self::UnknownField(struct_name, field_name) => {
+                write!(f, "{} has no field {}", struct_name, field_name)
+            }
+            Self::UnknownFunction(name) => write!(f, "unknown function {}", name),
+            Self::ArgumentCount(expected, found) => {
+                write!(f, "expected {} arguments, found {}", expected, found)
+            }
+            Self::UseAfterMove(place) => write!(f, "use of {} after it was moved", place),
+            Self::MoveOutOfBorrow(place) => write!(f, "move of {} out of a borrow", place),
+            Self::WriteToImmutable(place) => write!(f, "write to immutable {}", place),
+            Self::MutBorrowOfImmutable(place) => {
+                write!(f, "mutable borrow of immutable {}", place)
+            }
+            Self::BorrowConflict(place) => write!(f, "use of {} conflicts with a borrow", place),
+        }
+    }
+}
+
+// The unit type has no name of its own in generated code
+fn type_name(type_id: &TypeID) -> String {
+    match type_id {
+        TypeID::NullType => String::from("()"),
+        _ => type_id.to_string(),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CheckError {
+    item: String, // The function or const the node is in
+    node: String,
+    kind: CheckErrorKind,
+}
+
+impl CheckError {
+    pub fn get_item(&self) -> &str {
+        &self.item
+    }
+
+    pub fn get_node(&self) -> &str {
+        &self.node
+    }
+
+    pub fn get_kind(&self) -> &CheckErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut node: String = self.node.chars().take(MAX_NODE_LENGTH).collect();
+        if node.len() < self.node.len() {
+            node.push_str("...");
+        }
+        write!(f, "{}: {} in `{}`", self.item, self.kind, node)
+    }
+}
+
+pub struct Checker<'a> {
+    program: &'a Program,
+    structs: HashMap<String, &'a StructTemplate>,
+    functions: HashMap<String, FunctionTemplate>,
+}
+
+impl<'a> Checker<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        let structs = program
+            .structs_iter()
+            .chain(
+                program
+                    .modules_iter()
+                    .flat_map(|module| module.structs_iter()),
+            )
+            .map(|struct_template| (struct_template.get_name(), struct_template))
+            .collect();
+        let functions = Self::all_functions(program)
+            .map(|function| (function.get_name(), function.get_template()))
+            .collect();
+
+        Checker {
+            program,
+            structs,
+            functions,
+        }
+    }
+
+    // Every error found, in the order of the items and nodes they are in
+    pub fn check(&self) -> Result<(), Vec<CheckError>> {
+        let mut errors = Vec::new();
+
+        for const_stmt in self.program.consts_iter() {
+            let mut check = ItemCheck::new(self, const_stmt.get_name());
+            check.visit_expr(const_stmt.get_expr());
+            if let Some((found, _)) = check.type_of(const_stmt.get_expr()) {
+                check.expect_type(&const_stmt.get_type(), &found, || const_stmt.to_string());
+            }
+            errors.extend(check.errors);
+        }
+
+        for function in Self::all_functions(self.program) {
+            let mut check = ItemCheck::new(self, function.get_name());
+            check.visit_function(function);
+            errors.extend(check.errors);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn all_functions(program: &'a Program) -> impl Iterator<Item = &'a Function> {
+        program.functions_iter().chain(
+            program
+                .modules_iter()
+                .flat_map(|module| module.functions_iter()),
+        )
+    }
+}
+
+// How a statement or expression touches a place
+#[derive(Clone, Copy, PartialEq)]
+enum Access {
+    Read,
+    Move,
+    Write,
+    Update, // Read and written in place, such as by a compound assignment
+    Borrow(BorrowTypeID),
+}
+
+impl Access {
+    fn conflicts_with(self, borrow_type: BorrowTypeID) -> bool {
+        match borrow_type {
+            BorrowTypeID::MutRef => true,
+            BorrowTypeID::Ref => matches!(
+                self,
+                Access::Move
+                    | Access::Write
+                    | Access::Update
+                    | Access::Borrow(BorrowTypeID::MutRef)
+            ),
+            BorrowTypeID::None => false,
+        }
+    }
+}
+
+fn root_of(place: &str) -> &str {
+    place.split('.').next().unwrap_or(place)
+}
+
+// One place contains the other, like var_1 and var_1.field_2
+fn overlaps(place: &str, other: &str) -> bool {
+    let (shorter, longer) = if place.len() <= other.len() {
+        (place, other)
+    } else {
+        (other, place)
+    };
+
+    longer == shorter || (longer.starts_with(shorter) && longer[shorter.len()..].starts_with('.'))
+}
+
+// The places a node touches, with how it touches them, in evaluation order
+// A place whose variable the node binds again is another place from then on, and is left out
+#[derive(Default)]
+struct Accesses {
+    places: Vec<(String, Access)>,
+    bound: BTreeSet<String>,
+}
+
+impl Accesses {
+    fn of_stmt(stmt: &Stmt) -> Self {
+        let mut accesses = Accesses::default();
+        accesses.visit_stmt(stmt);
+        accesses
+    }
+
+    fn of_expr(expr: &Expr) -> Vec<(String, Access)> {
+        let mut accesses = Accesses::default();
+        accesses.visit_expr(expr);
+        accesses.places
+    }
+
+    fn touch(&mut self, place: &str, access: Access) {
+        if !self.bound.contains(root_of(place)) {
+            self.places.push((place.to_string(), access));
+        }
+    }
+}
+
+impl Visit for Accesses {
+    fn visit_var(&mut self, node: &Var) {
+        self.touch(node.get_name_str(), Access::Read);
+    }
+
+    fn visit_binding(&mut self, node: &Var) {
+        self.bound.insert(node.get_name());
+    }
+
+    // The bindings of these statements are made after their expressions are evaluated
+    fn visit_let_stmt(&mut self, node: &LetStmt) {
+        self.visit_expr(node.get_expr());
+        self.visit_binding(node.get_var());
+    }
+
+    fn visit_let_pattern_stmt(&mut self, node: &LetPatternStmt) {
+        self.visit_expr(node.get_expr());
+        if let Some(else_body) = node.get_else_body() {
+            self.visit_block_stmt(else_body);
+        }
+        self.visit_pattern(node.get_pattern());
+    }
+
+    fn visit_if_let_stmt(&mut self, node: &IfLetStmt) {
+        self.visit_expr(node.get_expr());
+        if let Some(else_body) = node.get_else_body() {
+            self.visit_block_stmt(else_body);
+        }
+        self.visit_pattern(node.get_pattern());
+        self.visit_block_stmt(node.get_body());
+    }
+
+    fn visit_for_loop_stmt(&mut self, node: &ForLoopStmt) {
+        self.visit_iter_expr(node.get_iterable());
+        self.visit_binding(node.get_iter_var());
+        self.visit_block_stmt(node.get_block_stmt());
+    }
+
+    fn visit_assign_stmt(&mut self, node: &AssignStmt) {
+        self.visit_expr(node.get_expr());
+        self.touch(node.get_var().get_name_str(), Access::Write);
+    }
+
+    fn visit_op_assign_stmt(&mut self, node: &OpAssignStmt) {
+        self.visit_arithmetic_expr(node.get_expr());
+        self.touch(node.get_var().get_name_str(), Access::Update);
+    }
+
+    fn visit_borrow_expr(&mut self, node: &BorrowExpr) {
+        match (node.is_explicit(), node.get_expr()) {
+            (true, Expr::Variable(var)) => {
+                self.touch(var.get_name_str(), Access::Borrow(node.get_borrow_type()))
+            }
+            _ => node.walk(self),
+        }
+    }
+
+    fn visit_checksum_stmt(&mut self, node: &ChecksumStmt) {
+        for place in node.places_iter() {
+            self.touch(place, Access::Read);
+        }
+    }
+
+    fn visit_trace_stmt(&mut self, node: &TraceStmt) {
+        for place in node.places_iter() {
+            self.touch(place, Access::Read);
+        }
+    }
+
+    fn visit_await_stmt(&mut self, node: &AwaitStmt) {
+        for place in node.held_borrows_iter() {
+            self.touch(place, Access::Read);
+        }
+    }
+}
+
+// The place a value borrows for as long as the value is in use, such as the argument &mut var_1
+fn borrowed_place(expr: &Expr) -> Option<(String, BorrowTypeID)> {
+    match expr {
+        Expr::Borrow(borrow_expr) => match borrow_expr.get_expr() {
+            Expr::Variable(var) => Some((var.get_name(), borrow_expr.get_borrow_type())),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Checks a single function or const, keeping track of the bindings in scope and the places moved
+struct ItemCheck<'c, 'a> {
+    checker: &'c Checker<'a>,
+    item: String,
+    scopes: Vec<HashMap<String, Var>>,
+    return_types: Vec<Option<TypeID>>, // Unknown in the body of an inlined call
+    moved: BTreeSet<String>,
+    diverged: bool, // The rest of the block is never reached
+    errors: Vec<CheckError>,
+}
+
+impl<'c, 'a> ItemCheck<'c, 'a> {
+    fn new(checker: &'c Checker<'a>, item: String) -> Self {
+        ItemCheck {
+            checker,
+            item,
+            scopes: vec![HashMap::new()],
+            return_types: Vec::new(),
+            moved: BTreeSet::new(),
+            diverged: false,
+            errors: Vec::new(),
+        }
+    }
+
+    fn report(&mut self, node: String, kind: CheckErrorKind) {
+        let error = CheckError {
+            item: self.item.clone(),
+            node,
+            kind,
+        };
+        // Loop bodies are checked twice
+        if !self.errors.contains(&error) {
+            self.errors.push(error);
+        }
+    }
+
+    fn expect_type<F: Fn() -> String>(&mut self, expected: &TypeID, found: &TypeID, node: F) {
+        if expected != found {
+            self.report(
+                node(),
+                CheckErrorKind::TypeMismatch(expected.clone(), found.clone()),
+            );
+        }
+    }
+
+    fn lookup(&self, place: &str) -> Option<&Var> {
+        let root = root_of(place);
+        self.scopes.iter().rev().find_map(|scope| scope.get(root))
+    }
+
+    fn bind(&mut self, var: &Var) {
+        let name = var.get_name();
+        self.moved.retain(|place| root_of(place) != name);
+        self.scopes.last_mut().unwrap().insert(name, var.clone());
+    }
+
+    fn in_scope<F: FnOnce(&mut Self)>(&mut self, f: F) {
+        self.scopes.push(HashMap::new());
+        f(self);
+        let scope = self.scopes.pop().unwrap();
+        self.moved
+            .retain(|place| !scope.contains_key(root_of(place)));
+    }
+
+    fn struct_template(
+        &mut self,
+        name: &str,
+        node: &dyn Fn() -> String,
+    ) -> Option<&'a StructTemplate> {
+        let struct_template = self.checker.structs.get(name).copied();
+        if struct_template.is_none() {
+            self.report(node(), CheckErrorKind::UnknownStruct(name.to_string()));
+        }
+        struct_template
+    }
+
+    fn field_type(
+        &mut self,
+        type_id: &TypeID,
+        field_name: &str,
+        node: &dyn Fn() -> String,
+    ) -> Option<TypeID> {
+        let struct_name = match type_id {
+            TypeID::StructType(struct_name) => struct_name,
+            _ => {
+                self.report(
+                    node(),
+                    CheckErrorKind::UnknownField(type_name(type_id), field_name.to_string()),
+                );
+                return None;
+            }
+        };
+        let struct_template = self.struct_template(struct_name, node)?;
+        let field_type = struct_template
+            .fields_iter()
+            .find(|(name, _)| name == field_name)
+            .map(|(_, field_type)| field_type.clone());
+        if field_type.is_none() {
+            self.report(
+                node(),
+                CheckErrorKind::UnknownField(struct_name.clone(), field_name.to_string()),
+            );
+        }
+        field_type
+    }
+
+    // The type of a place follows from its variable and the fields of the structs on its path
+    // Variables that are not bound in the function, such as consts, are left alone
+    fn check_var(&mut self, var: &Var) {
+        let name = var.get_name();
+        let mut type_id = match self.lookup(&name) {
+            Some(binding) => binding.get_type(),
+            None => return,
+        };
+        for field_name in name.split('.').skip(1) {
+            type_id = match self.field_type(&type_id, field_name, &|| name.clone()) {
+                Some(field_type) => field_type,
+                None => return,
+            };
+        }
+
+        self.expect_type(&type_id, &var.get_type(), || name.clone());
+    }
+
+    // A struct value moves, a reference that is passed on is copied or reborrowed
+    fn moves(&self, var: &Var) -> bool {
+        let name = var.get_name_str();
+        let is_reference = match self.lookup(name) {
+            Some(binding) => binding.get_borrow_type() != BorrowTypeID::None && !name.contains('.'),
+            None => false,
+        };
+
+        !is_reference
+            && var.get_borrow_type() == BorrowTypeID::None
+            && matches!(var.get_type(), TypeID::StructType(_))
+    }
+
+    fn access(&mut self, place: &str, access: Access) {
+        if !self.diverged {
+            self.check_moved(place, access);
+        }
+
+        let binding = self.lookup(place).cloned();
+        let is_mut_ref = |binding: &Var| binding.get_borrow_type() == BorrowTypeID::MutRef;
+        let is_mutable = |binding: &Var| {
+            is_mut_ref(binding)
+                || (binding.get_borrow_type() == BorrowTypeID::None && binding.is_mut())
+        };
+        match (access, binding) {
+            (Access::Write | Access::Update, Some(binding)) if !is_mutable(&binding) => self
+                .report(
+                    place.to_string(),
+                    CheckErrorKind::WriteToImmutable(place.to_string()),
+                ),
+            (Access::Borrow(BorrowTypeID::MutRef), Some(binding)) if !is_mutable(&binding) => self
+                .report(
+                    place.to_string(),
+                    CheckErrorKind::MutBorrowOfImmutable(place.to_string()),
+                ),
+            (Access::Move, Some(binding)) if binding.get_borrow_type() != BorrowTypeID::None => {
+                self.report(
+                    place.to_string(),
+                    CheckErrorKind::MoveOutOfBorrow(place.to_string()),
+                )
+            }
+            _ => (),
+        }
+
+        match access {
+            Access::Move => {
+                self.moved.insert(place.to_string());
+            }
+            // A new value makes the place and everything in it usable again
+            Access::Write => self
+                .moved
+                .retain(|moved| !overlaps(moved, place) || moved.len() < place.len()),
+            _ => (),
+        }
+    }
+
+    fn check_moved(&mut self, place: &str, access: Access) {
+        let moved = self.moved.iter().find(|moved| match access {
+            // Only a write to a part of a moved place is not allowed
+            Access::Write => moved.len() < place.len() && overlaps(moved, place),
+            _ => overlaps(moved, place),
+        });
+        if let Some(moved) = moved.cloned() {
+            self.report(place.to_string(), CheckErrorKind::UseAfterMove(moved));
+        }
+    }
+
+    fn use_var(&mut self, var: &Var, access: Access) {
+        self.check_var(var);
+        self.access(var.get_name_str(), access);
+    }
+
+    // The type of an expression, with how it is borrowed
+    // Raw expressions, tuples and inlined bodies have no type the AST records
+    fn type_of(&self, expr: &Expr) -> Option<(TypeID, BorrowTypeID)> {
+        let var_borrow = |var: &Var| match self.lookup(var.get_name_str()) {
+            Some(binding) if !var.get_name_str().contains('.') => binding.get_borrow_type(),
+            _ => var.get_borrow_type(),
+        };
+
+        match expr {
+            Expr::Arithmetic(arith_expr) => Some((arith_expr.get_type(), BorrowTypeID::None)),
+            Expr::Boolean(_) => Some((TypeID::BoolType, BorrowTypeID::None)),
+            Expr::Struct(StructExpr::Literal(literal)) => Some((
+                TypeID::StructType(literal.get_struct_name()),
+                BorrowTypeID::None,
+            )),
+            Expr::Struct(StructExpr::Var(var)) | Expr::Variable(var) => {
+                Some((var.get_type(), var_borrow(var)))
+            }
+            Expr::Struct(StructExpr::Func(call)) | Expr::Func(call) => {
+                Some((call.get_type(), BorrowTypeID::None))
+            }
+            Expr::Borrow(borrow_expr) => self
+                .type_of(borrow_expr.get_expr())
+                .map(|(type_id, _)| (type_id, borrow_expr.get_borrow_type())),
+            Expr::Macro(call) => Some((call.get_type(), BorrowTypeID::None)),
+            Expr::Closure(closure) => Some((closure.get_type(), BorrowTypeID::None)),
+            Expr::Raw(_) | Expr::Tuple(_) | Expr::Block(_) => None,
+        }
+    }
+
+    fn expect_expr(&mut self, type_id: &TypeID, borrow_type: BorrowTypeID, expr: &Expr) {
+        if let Some((found_type, found_borrow)) = self.type_of(expr) {
+            self.expect_type(type_id, &found_type, || expr.to_string());
+            if borrow_type != found_borrow {
+                self.report(
+                    expr.to_string(),
+                    CheckErrorKind::BorrowMismatch(borrow_type, found_borrow),
+                );
+            }
+        }
+    }
+
+    fn check_pattern(&mut self, pattern: &Pattern, expr: Option<&Expr>) {
+        match (pattern, expr) {
+            (Pattern::Tuple(patterns), Some(Expr::Tuple(tuple_expr))) => {
+                self.check_tuple_pattern(patterns, tuple_expr)
+            }
+            _ => {
+                let type_id = expr
+                    .and_then(|expr| self.type_of(expr))
+                    .map(|(type_id, _)| type_id);
+                self.check_pattern_type(pattern, type_id.as_ref());
+            }
+        }
+    }
+
+    fn check_tuple_pattern(&mut self, patterns: &[Pattern], tuple_expr: &TupleExpr) {
+        if patterns.len() != tuple_expr.len() {
+            self.report(
+                tuple_expr.to_string(),
+                CheckErrorKind::ArgumentCount(patterns.len(), tuple_expr.len()),
+            );
+            return;
+        }
+        for (pattern, element) in patterns.iter().zip(tuple_expr.elements_iter()) {
+            self.check_pattern(pattern, Some(element));
+        }
+    }
+
+    fn check_pattern_type(&mut self, pattern: &Pattern, type_id: Option<&TypeID>) {
+        let node = || pattern.to_string();
+        match pattern {
+            Pattern::Wildcard | Pattern::Tuple(_) => (),
+            Pattern::Binding(var) => {
+                if let Some(type_id) = type_id {
+                    self.expect_type(type_id, &var.get_type(), || var.get_name());
+                }
+            }
+            Pattern::At(var, pattern) => {
+                if let Some(type_id) = type_id {
+                    self.expect_type(type_id, &var.get_type(), || var.get_name());
+                }
+                self.check_pattern_type(pattern, type_id);
+            }
+            Pattern::Range(start, end) => {
+                let start_type = start.get_type();
+                self.expect_type(&start_type, &end.get_type(), node);
+                if let Some(type_id) = type_id {
+                    self.expect_type(type_id, &start_type, node);
+                }
+            }
+            Pattern::Bool(_) => {
+                if let Some(type_id) = type_id {
+                    self.expect_type(type_id, &TypeID::BoolType, node);
+                }
+            }
+            Pattern::Struct(name, fields, _) => {
+                let struct_type = TypeID::StructType(name.clone());
+                if let Some(type_id) = type_id {
+                    self.expect_type(type_id, &struct_type, node);
+                }
+                for (field_name, field_pattern) in fields.iter() {
+                    if let Some(field_type) = self.field_type(&struct_type, field_name, &node) {
+                        self.check_pattern_type(field_pattern, Some(&field_type));
+                    }
+                }
+            }
+        }
+    }
+
+    // A place borrowed by one argument stays borrowed until the call, so the arguments after it
+    // may not touch the place, and no argument before it may hold a borrow of the place either
+    fn check_operands<'e, I: Iterator<Item = &'e Expr>>(&mut self, operands: I) {
+        let operands: Vec<&Expr> = operands.collect();
+        for (i, operand) in operands.iter().enumerate() {
+            let (place, borrow_type) = match borrowed_place(operand) {
+                Some(borrowed) => borrowed,
+                None => continue,
+            };
+
+            for later in operands[i + 1..].iter() {
+                for (other, access) in Accesses::of_expr(later) {
+                    if overlaps(&place, &other) && access.conflicts_with(borrow_type) {
+                        self.report(later.to_string(), CheckErrorKind::BorrowConflict(other));
+                    }
+                }
+            }
+            if borrow_type == BorrowTypeID::MutRef {
+                for earlier in operands[..i].iter() {
+                    match borrowed_place(earlier) {
+                        Some((other, _)) if overlaps(&place, &other) => self.report(
+                            operand.to_string(),
+                            CheckErrorKind::BorrowConflict(place.clone()),
+                        ),
+                        _ => (),
+                    }
+                }
+            }
+        }
+    }
+
+    // A reference bound by a let statement keeps its place borrowed up to the last statement of
+    // the block that uses it, or a reference copied from it, and the statements in between may
+    // not touch the place in a way that conflicts with the borrow
+    fn check_loans(&mut self, node: &BlockStmt) {
+        let stmts: Vec<&Stmt> = node.stmts_iter().collect();
+        let mut accesses: Option<Vec<Accesses>> = None;
+
+        for (i, stmt) in stmts.iter().enumerate() {
+            let (holder, place, borrow_type) = match stmt {
+                Stmt::LetStatement(let_stmt) => match borrowed_place(let_stmt.get_expr()) {
+                    Some((place, borrow_type)) => (let_stmt.get_name(), place, borrow_type),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            let accesses = accesses
+                .get_or_insert_with(|| stmts.iter().map(|stmt| Accesses::of_stmt(stmt)).collect());
+
+            // Until the variable of the place is bound again by a later statement
+            let mut holders = BTreeSet::new();
+            holders.insert(holder);
+            let mut last_use = i;
+            let mut end = stmts.len();
+            for (j, stmt) in stmts.iter().enumerate().skip(i + 1) {
+                let uses_holder = accesses[j]
+                    .places
+                    .iter()
+                    .any(|(other, _)| holders.contains(root_of(other)));
+                holders.retain(|holder| !accesses[j].bound.contains(holder));
+                if uses_holder {
+                    last_use = j;
+                    if let Stmt::LetStatement(let_stmt) = stmt {
+                        if let_stmt.get_var().get_borrow_type() != BorrowTypeID::None {
+                            holders.insert(let_stmt.get_name());
+                        }
+                    }
+                }
+                if accesses[j].bound.contains(root_of(&place)) {
+                    end = j + 1;
+                    break;
+                }
+            }
+
+            for j in i + 1..last_use.min(end) {
+                for (other, access) in accesses[j].places.iter() {
+                    if overlaps(&place, other) && access.conflicts_with(borrow_type) {
+                        let kind = CheckErrorKind::BorrowConflict(other.clone());
+                        self.report(stmts[j].to_string(), kind);
+                    }
+                }
+            }
+        }
+    }
+
+    // Each branch starts from the same places moved, and afterwards a place is moved
+    // if it was moved in any branch that does not diverge
+    fn branches<F: FnMut(&mut Self, usize) -> bool>(&mut self, count: usize, mut branch: F) {
+        let (moved, diverged) = (self.moved.clone(), self.diverged);
+        let mut ends: Vec<BTreeSet<String>> = Vec::new();
+
+        for i in 0..count {
+            self.moved = moved.clone();
+            self.diverged = diverged;
+            if branch(self, i) && !self.diverged {
+                ends.push(self.moved.clone());
+            }
+        }
+
+        self.diverged = diverged || ends.is_empty();
+        self.moved = ends.into_iter().flatten().collect();
+        if self.diverged {
+            self.moved = moved;
+        }
+    }
+
+    // The body runs once more with what its first run moved, which finds places moved in one
+    // iteration and used in the next
+    fn loop_body<F: Fn(&mut Self)>(&mut self, body: F) {
+        let (before, diverged) = (self.moved.clone(), self.diverged);
+        body(self);
+        let body_diverged = self.diverged;
+        self.diverged = diverged;
+
+        if !body_diverged && self.moved != before {
+            let first_run = self.moved.clone();
+            body(self);
+            self.diverged = diverged;
+            self.moved.extend(first_run);
+        }
+        self.moved.extend(before);
+    }
+}
+
+impl Visit for ItemCheck<'_, '_> {
+    fn visit_function(&mut self, node: &Function) {
+        for param in node.params_iter() {
+            self.bind(&Var::from_param(param));
+        }
+        self.return_types.push(Some(node.get_return_type()));
+        node.walk(self);
+        self.return_types.pop();
+    }
+
+    fn visit_block_stmt(&mut self, node: &BlockStmt) {
+        self.check_loans(node);
+        self.in_scope(|check| node.walk(check));
+    }
+
+    fn visit_let_stmt(&mut self, node: &LetStmt) {
+        let var = node.get_var();
+        self.visit_expr(node.get_expr());
+        self.expect_expr(&var.get_type(), var.get_borrow_type(), node.get_expr());
+        self.bind(var);
+    }
+
+    fn visit_assign_stmt(&mut self, node: &AssignStmt) {
+        self.visit_expr(node.get_expr());
+
+        let var = node.get_var();
+        self.check_var(var);
+        if node.is_deref() {
+            // Through a reference, which has to be a mutable one
+            match self.lookup(var.get_name_str()) {
+                Some(binding) if binding.get_borrow_type() != BorrowTypeID::MutRef => self.report(
+                    node.to_string(),
+                    CheckErrorKind::WriteToImmutable(var.get_name()),
+                ),
+                _ => (),
+            }
+            if let Some((found, _)) = self.type_of(node.get_expr()) {
+                self.expect_type(&var.get_type(), &found, || node.to_string());
+            }
+        } else {
+            self.access(var.get_name_str(), Access::Write);
+            if let Some((found, _)) = self.type_of(node.get_expr()) {
+                self.expect_type(&var.get_type(), &found, || node.to_string());
+            }
+        }
+    }
+
+    fn visit_op_assign_stmt(&mut self, node: &OpAssignStmt) {
+        self.visit_arithmetic_expr(node.get_expr());
+
+        let var = node.get_var();
+        self.use_var(var, Access::Update);
+        let type_id = var.get_type();
+        if !matches!(type_id, TypeID::IntType(_)) {
+            self.report(
+                node.to_string(),
+                CheckErrorKind::NotAnInteger(type_id.clone()),
+            );
+        }
+        self.expect_type(&type_id, &node.get_expr().get_type(), || node.to_string());
+    }
+
+    fn visit_return_stmt(&mut self, node: &ReturnStmt) {
+        self.visit_expr(node.get_expr());
+
+        let return_type = node.get_type();
+        if let Some(Some(expected)) = self.return_types.last().cloned() {
+            self.expect_type(&expected, &return_type, || node.to_string());
+        }
+        if return_type != TypeID::NullType {
+            if let Some((found, _)) = self.type_of(node.get_expr()) {
+                self.expect_type(&return_type, &found, || node.to_string());
+            }
+        }
+
+        if node.is_explicit() {
+            self.diverged = true;
+        }
+    }
+
+    fn visit_expr_stmt(&mut self, node: &ExprStmt) {
+        node.walk(self);
+
+        if let Expr::Raw(raw_expr) = node.get_expr() {
+            let raw = raw_expr.get_expr_string().trim();
+            if raw.starts_with("return") || raw.starts_with("break") || raw.starts_with("continue")
+            {
+                self.diverged = true;
+            }
+        }
+    }
+
+    fn visit_conditional_stmt(&mut self, node: &ConditionalStmt) {
+        // A branch runs after its own condition and the ones before it
+        let branches: Vec<_> = node.branches_iter().collect();
+        self.branches(branches.len() + 1, |check, i| {
+            for (condition, _) in branches.iter().take(i + 1) {
+                check.visit_bool_expr(condition);
+            }
+            match branches.get(i) {
+                Some((_, block_stmt)) => check.visit_block_stmt(block_stmt),
+                None => {
+                    if let Some(else_body) = node.get_else_body() {
+                        check.visit_block_stmt(else_body);
+                    }
+                }
+            }
+            true
+        });
+    }
+
+    fn visit_if_let_stmt(&mut self, node: &IfLetStmt) {
+        self.visit_scrutinee(node.get_expr());
+        self.check_pattern(node.get_pattern(), Some(node.get_expr()));
+
+        self.branches(2, |check, i| {
+            if i == 0 {
+                check.in_scope(|check| {
+                    check.visit_pattern(node.get_pattern());
+                    check.visit_block_stmt(node.get_body());
+                });
+            } else if let Some(else_body) = node.get_else_body() {
+                check.visit_block_stmt(else_body);
+            }
+            true
+        });
+    }
+
+    fn visit_let_pattern_stmt(&mut self, node: &LetPatternStmt) {
+        self.visit_scrutinee(node.get_expr());
+        self.check_pattern(node.get_pattern(), Some(node.get_expr()));
+
+        // The else body always diverges, and the pattern binds nothing in it
+        if let Some(else_body) = node.get_else_body() {
+            let (moved, diverged) = (self.moved.clone(), self.diverged);
+            self.visit_block_stmt(else_body);
+            self.moved = moved;
+            self.diverged = diverged;
+        }
+        self.visit_pattern(node.get_pattern());
+    }
+
+    fn visit_for_loop_stmt(&mut self, node: &ForLoopStmt) {
+        self.visit_iter_expr(node.get_iterable());
+        self.in_scope(|check| {
+            check.bind(node.get_iter_var());
+            check.loop_body(|check| check.visit_block_stmt(node.get_block_stmt()));
+        });
+    }
+
+    fn visit_while_stmt(&mut self, node: &WhileStmt) {
+        self.visit_bool_expr(node.get_condition());
+        self.loop_body(|check| check.visit_block_stmt(node.get_block_stmt()));
+    }
+
+    fn visit_checksum_stmt(&mut self, node: &ChecksumStmt) {
+        for place in node.places_iter() {
+            self.access(place, Access::Read);
+        }
+    }
+
+    fn visit_trace_stmt(&mut self, node: &TraceStmt) {
+        for place in node.places_iter() {
+            self.access(place, Access::Read);
+        }
+    }
+
+    fn visit_expr(&mut self, node: &Expr) {
+        match node {
+            Expr::Variable(var) | Expr::Struct(StructExpr::Var(var)) => {
+                let access = if self.moves(var) {
+                    Access::Move
+                } else {
+                    Access::Read
+                };
+                self.use_var(var, access);
+            }
+            // A return in the body of an inlined call leaves only the body
+            Expr::Block(block_stmt) => {
+                let diverged = self.diverged;
+                self.return_types.push(None);
+                self.visit_block_stmt(block_stmt);
+                self.return_types.pop();
+                self.diverged = diverged;
+            }
+            Expr::Closure(closure) => {
+                let diverged = self.diverged;
+                self.return_types.push(Some(closure.get_type()));
+                self.visit_block_stmt(closure.get_block_stmt());
+                self.return_types.pop();
+                self.diverged = diverged;
+            }
+            _ => node.walk(self),
+        }
+    }
+
+    fn visit_borrow_expr(&mut self, node: &BorrowExpr) {
+        match (node.is_explicit(), node.get_expr()) {
+            (true, Expr::Variable(var)) => {
+                self.use_var(var, Access::Borrow(node.get_borrow_type()))
+            }
+            // Already a reference, which is passed on as it is
+            (false, Expr::Variable(var)) => self.use_var(var, Access::Read),
+            _ => node.walk(self),
+        }
+    }
+
+    fn visit_var(&mut self, node: &Var) {
+        self.use_var(node, Access::Read);
+    }
+
+    fn visit_binding(&mut self, node: &Var) {
+        self.bind(node);
+    }
+
+    fn visit_binary_expr(&mut self, node: &BinaryExpr) {
+        node.walk(self);
+
+        let left_type = node.get_left().get_type();
+        if !matches!(left_type, TypeID::IntType(_)) {
+            self.report(
+                node.to_string(),
+                CheckErrorKind::NotAnInteger(left_type.clone()),
+            );
+        }
+        self.expect_type(&left_type, &node.get_right().get_type(), || {
+            node.to_string()
+        });
+    }
+
+    fn visit_comparison_expr(&mut self, node: &ComparisonExpr) {
+        node.walk(self);
+
+        let left_type = node.get_left().get_type();
+        self.expect_type(&left_type, &node.get_right().get_type(), || {
+            node.to_string()
+        });
+    }
+
+    fn visit_func_call_expr(&mut self, node: &FunctionCallExpr) {
+        node.walk(self);
+        self.check_operands(node.arguments_iter());
+
+        let name = node.get_name();
+        let template = match self.checker.functions.get(&name) {
+            Some(template) => template,
+            None => {
+                self.report(node.to_string(), CheckErrorKind::UnknownFunction(name));
+                return;
+            }
+        };
+        self.expect_type(&template.get_type(), &node.get_type(), || node.to_string());
+
+        let params: Vec<_> = template.params_iter().cloned().collect();
+        let arguments: Vec<&Expr> = node.arguments_iter().collect();
+        if params.len() != arguments.len() {
+            self.report(
+                node.to_string(),
+                CheckErrorKind::ArgumentCount(params.len(), arguments.len()),
+            );
+            return;
+        }
+        for (param, argument) in params.iter().zip(arguments) {
+            self.expect_expr(&param.get_type(), param.get_borrow_type(), argument);
+        }
+    }
+
+    fn visit_macro_call_expr(&mut self, node: &MacroCallExpr) {
+        node.walk(self);
+        self.check_operands(node.arguments_iter());
+    }
+
+    fn visit_tuple_expr(&mut self, node: &TupleExpr) {
+        node.walk(self);
+        self.check_operands(node.elements_iter());
+    }
+
+    // The struct of the literal has to be the one the program declares, field for field
+    fn visit_struct_literal(&mut self, node: &StructLiteral) {
+        node.walk(self);
+        self.check_operands(node.fields_iter().map(|(_, value)| value));
+
+        let name = node.get_struct_name();
+        let declared = match self.struct_template(&name, &|| node.to_string()) {
+            Some(declared) => declared,
+            None => return,
+        };
+        let literal_fields: Vec<(String, TypeID)> =
+            node.get_template().fields_iter().cloned().collect();
+        let declared_fields: Vec<(String, TypeID)> = declared.fields_iter().cloned().collect();
+        if literal_fields.len() != declared_fields.len() {
+            self.report(
+                node.to_string(),
+                CheckErrorKind::ArgumentCount(declared_fields.len(), literal_fields.len()),
+            );
+        }
+
+        for (field_name, value) in node.fields_iter() {
+            let field_type = match declared_fields
+                .iter()
+                .find(|(declared, _)| declared == field_name)
+            {
+                Some((_, field_type)) => field_type.clone(),
+                None => {
+                    self.report(
+                        node.to_string(),
+                        CheckErrorKind::UnknownField(name.clone(), field_name.clone()),
+                    );
+                    continue;
+                }
+            };
+            if let Some((found, _)) = self.type_of(value) {
+                self.expect_type(&field_type, &found, || {
+                    format!("{}: {}", field_name, value.to_string())
+                });
+            }
+        }
+    }
+
+    fn visit_int_expr(&mut self, _node: &IntExpr) {}
+}
+
+impl ItemCheck<'_, '_> {
+    // Matching a variable only reads it, unlike a tuple built from it
+    fn visit_scrutinee(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Variable(var) => self.use_var(var, Access::Read),
+            _ => self.visit_expr(expr),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        program::{
+            expr::{
+                arithmetic_expr::{ArithmeticExpr, BinaryOp},
+                bool_expr::BoolValue,
+            },
+            types::IntTypeID,
+        },
+        Features, Generator,
+    };
+
+    fn struct_template() -> StructTemplate {
+        StructTemplate::new_from_fields(
+            String::from("Struct1"),
+            vec![
+                (String::from("field_1"), IntTypeID::I8.as_type()),
+                (String::from("field_2"), TypeID::BoolType),
+            ],
+        )
+    }
+
+    fn literal(template: StructTemplate, field_1: Expr) -> Expr {
+        StructLiteral::new(
+            template,
+            vec![field_1, BoolValue::new(true).as_bool_expr().as_expr()],
+        )
+        .as_struct_expr()
+        .as_expr()
+    }
+
+    fn program_with(return_type: TypeID, stmts: Vec<Stmt>) -> Program {
+        let mut program = Program::new();
+        program.push_struct_template(struct_template());
+        program.push_function(Function::new(
+            String::from("function_1"),
+            Vec::new(),
+            return_type,
+            BlockStmt::new_from_vec(stmts),
+        ));
+        program
+    }
+
+    fn kinds(program: &Program) -> Vec<CheckErrorKind> {
+        match Checker::new(program).check() {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.into_iter().map(|error| error.kind).collect(),
+        }
+    }
+
+    #[test]
+    fn reports_binary_operands_of_different_types() {
+        let sum = BinaryExpr::new(
+            IntExpr::new_i8(1).as_arith_expr(),
+            IntExpr::new_u32(2).as_arith_expr(),
+            BinaryOp::ADD,
+        );
+        let program = program_with(
+            IntTypeID::I8.as_type(),
+            vec![ReturnStmt::new(
+                IntTypeID::I8.as_type(),
+                ArithmeticExpr::new_from_bin_expr(sum).as_expr(),
+            )
+            .as_stmt()],
+        );
+
+        assert_eq!(
+            kinds(&program),
+            vec![CheckErrorKind::TypeMismatch(
+                IntTypeID::I8.as_type(),
+                IntTypeID::U32.as_type()
+            )]
+        );
+    }
+
+    #[test]
+    fn reports_return_type_other_than_the_function_returns() {
+        let program = program_with(
+            IntTypeID::U32.as_type(),
+            vec![ReturnStmt::new(IntTypeID::I8.as_type(), IntExpr::new_i8(1).as_expr()).as_stmt()],
+        );
+
+        let errors = Checker::new(&program).check().unwrap_err();
+
+        assert_eq!(
+            errors[0].get_kind(),
+            &CheckErrorKind::TypeMismatch(IntTypeID::U32.as_type(), IntTypeID::I8.as_type())
+        );
+        assert_eq!(errors[0].get_item(), "function_1");
+        assert_eq!(errors[0].get_node(), "return 1i8;");
+    }
+
+    #[test]
+    fn reports_struct_field_of_wrong_type() {
+        let var = Var::new(
+            TypeID::StructType(String::from("Struct1")),
+            String::from("var_1"),
+            false,
+        );
+        let program = program_with(
+            TypeID::NullType,
+            vec![LetStmt::new(
+                var,
+                literal(struct_template(), IntExpr::new_u8(1).as_expr()),
+            )
+            .as_stmt()],
+        );
+
+        assert_eq!(
+            kinds(&program),
+            vec![CheckErrorKind::TypeMismatch(
+                IntTypeID::I8.as_type(),
+                IntTypeID::U8.as_type()
+            )]
+        );
+    }
+
+    #[test]
+    fn reports_use_after_move() {
+        let struct_type = TypeID::StructType(String::from("Struct1"));
+        let var = |name: &str| Var::new(struct_type.clone(), String::from(name), false);
+        let program = program_with(
+            TypeID::NullType,
+            vec![
+                LetStmt::new(
+                    var("var_1"),
+                    literal(struct_template(), IntExpr::new_i8(1).as_expr()),
+                )
+                .as_stmt(),
+                LetStmt::new(var("var_2"), var("var_1").as_expr()).as_stmt(),
+                LetStmt::new(var("var_3"), var("var_1").as_expr()).as_stmt(),
+            ],
+        );
+
+        assert_eq!(
+            kinds(&program),
+            vec![CheckErrorKind::UseAfterMove(String::from("var_1"))]
+        );
+    }
+
+    #[test]
+    fn reports_mutable_borrow_of_immutable_var() {
+        let program = program_with(
+            TypeID::NullType,
+            vec![
+                LetStmt::new(
+                    Var::new(TypeID::BoolType, String::from("var_1"), false),
+                    BoolValue::new(true).as_bool_expr().as_expr(),
+                )
+                .as_stmt(),
+                LetStmt::new(
+                    Var::new_mut_ref(TypeID::BoolType, String::from("var_2"), false),
+                    BorrowExpr::new(
+                        BorrowTypeID::MutRef,
+                        Var::new(TypeID::BoolType, String::from("var_1"), false).as_expr(),
+                        true,
+                    )
+                    .as_expr(),
+                )
+                .as_stmt(),
+            ],
+        );
+
+        assert_eq!(
+            kinds(&program),
+            vec![CheckErrorKind::MutBorrowOfImmutable(String::from("var_1"))]
+        );
+    }
+
+    #[test]
+    fn generated_programs_pass() {
+        let features = Features {
+            threads: true,
+            unsafe_code: true,
+            ..Default::default()
+        };
+
+        for seed in 0..8 {
+            for features in [Features::default(), features].iter() {
+                let generated = Generator::small(seed).with_features(*features).generate();
+                let result = Checker::new(generated.get_program()).check();
+
+                assert!(result.is_ok(), "seed {}: {:?}", seed, result);
+            }
+        }
+    }
+}
@@ -0,0 +1,269 @@
+/// Passes that change a program without changing what it prints
+/// Blocks are counted in traversal order, the same way on the original program and on its copy
+use crate::program::{
+    function::Function,
+    program::Module,
+    stmt::{
+        block_stmt::BlockStmt, conditional_stmt::ConditionalStmt, if_let_stmt::IfLetStmt,
+        stmt::Stmt,
+    },
+    visitor::{visit::Visit, visit_mut::VisitMut},
+};
+
+use crate::interpret::profile::Profile;
+
+// Blocks that a dead block can go into, per top level function other than const functions
+// Const functions are left alone since a dead block may do what is not allowed at compile time
+#[derive(Default)]
+pub struct DeadBlockSites {
+    functions: Vec<(String, usize)>, // Function name and the number of blocks in it
+}
+
+impl DeadBlockSites {
+    pub fn total(&self) -> usize {
+        self.functions.iter().map(|(_, count)| count).sum()
+    }
+
+    // Name of the function that contains the site with the given index
+    pub fn function_of(&self, mut index: usize) -> Option<&str> {
+        for (name, count) in self.functions.iter() {
+            if index < *count {
+                return Some(name);
+            }
+            index -= count;
+        }
+
+        None
+    }
+}
+
+impl Visit for DeadBlockSites {
+    fn visit_module(&mut self, _node: &Module) {}
+
+    fn visit_function(&mut self, node: &Function) {
+        if !node.get_template().is_const() {
+            self.functions.push((node.get_name(), 0));
+            node.walk(self);
+        }
+    }
+
+    fn visit_block_stmt(&mut self, node: &BlockStmt) {
+        if let Some((_, count)) = self.functions.last_mut() {
+            *count += 1;
+        }
+        node.walk(self);
+    }
+}
+
+// Inserts a statement into the site with the given index, before its last statement
+// so that a block which evaluates to its final statement still does
+pub struct InsertDeadBlock {
+    index: usize,
+    position: usize, // Reduced modulo the number of places in the block
+    seen: usize,
+    stmt: Option<Stmt>,
+}
+
+impl InsertDeadBlock {
+    pub fn new(index: usize, position: usize, stmt: Stmt) -> Self {
+        InsertDeadBlock {
+            index,
+            position,
+            seen: 0,
+            stmt: Some(stmt),
+        }
+    }
+
+    pub fn is_applied(&self) -> bool {
+        self.stmt.is_none()
+    }
+}
+
+impl VisitMut for InsertDeadBlock {
+    fn visit_module_mut(&mut self, _node: &mut Module) {}
+
+    fn visit_function_mut(&mut self, node: &mut Function) {
+        if !node.get_template().is_const() && !self.is_applied() {
+            node.walk_mut(self);
+        }
+    }
+
+    fn visit_block_stmt_mut(&mut self, node: &mut BlockStmt) {
+        if self.seen == self.index {
+            if let Some(stmt) = self.stmt.take() {
+                let position = self.position % node.len().max(1);
+                node.insert(position, stmt);
+            }
+        }
+        self.seen += 1;
+
+        node.walk_mut(self);
+    }
+}
+
+// Branches of conditionals and if lets, in every function, that the profile shows never ran
+// Emptying one of them cannot change what the program prints
+pub struct UnexecutedBranches<'a> {
+    profile: &'a Profile,
+    seen: usize,
+    unexecuted: Vec<usize>,
+}
+
+impl<'a> UnexecutedBranches<'a> {
+    pub fn new(profile: &'a Profile) -> Self {
+        UnexecutedBranches {
+            profile,
+            seen: 0,
+            unexecuted: Vec::new(),
+        }
+    }
+
+    pub fn into_indices(self) -> Vec<usize> {
+        self.unexecuted
+    }
+
+    fn branch(&mut self, node: &BlockStmt) {
+        if !self.profile.is_executed(node) {
+            self.unexecuted.push(self.seen);
+        }
+        self.seen += 1;
+
+        self.visit_block_stmt(node);
+    }
+}
+
+impl<'a> Visit for UnexecutedBranches<'a> {
+    fn visit_conditional_stmt(&mut self, node: &ConditionalStmt) {
+        for (condition, block_stmt) in node.branches_iter() {
+            self.visit_bool_expr(condition);
+            self.branch(block_stmt);
+        }
+        if let Some(else_body) = node.get_else_body() {
+            self.branch(else_body);
+        }
+    }
+
+    fn visit_if_let_stmt(&mut self, node: &IfLetStmt) {
+        self.visit_expr(node.get_expr());
+        self.branch(node.get_body());
+        if let Some(else_body) = node.get_else_body() {
+            self.branch(else_body);
+        }
+    }
+}
+
+// Empties the branches with the given indices, counted the same way as UnexecutedBranches
+// A branch is only emptied after its nested branches have been counted
+pub struct PruneBranches {
+    indices: Vec<usize>, // Sorted
+    seen: usize,
+}
+
+impl PruneBranches {
+    pub fn new(mut indices: Vec<usize>) -> Self {
+        indices.sort_unstable();
+        PruneBranches { indices, seen: 0 }
+    }
+
+    fn branch(&mut self, node: &mut BlockStmt) {
+        let index = self.seen;
+        self.seen += 1;
+
+        self.visit_block_stmt_mut(node);
+        if self.indices.binary_search(&index).is_ok() {
+            *node = BlockStmt::new();
+        }
+    }
+}
+
+impl VisitMut for PruneBranches {
+    fn visit_conditional_stmt_mut(&mut self, node: &mut ConditionalStmt) {
+        for (condition, block_stmt) in node.branches_iter_mut() {
+            self.visit_bool_expr_mut(condition);
+            self.branch(block_stmt);
+        }
+        if let Some(else_body) = node.get_else_body_mut() {
+            self.branch(else_body);
+        }
+    }
+
+    fn visit_if_let_stmt_mut(&mut self, node: &mut IfLetStmt) {
+        self.visit_expr_mut(node.get_expr_mut());
+        self.branch(node.get_body_mut());
+        if let Some(else_body) = node.get_else_body_mut() {
+            self.branch(else_body);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        interpret::interpreter::Interpreter,
+        program::{
+            expr::expr::RawExpr, program::Program, stmt::expr_stmt::ExprStmt,
+            visitor::passes::NodeCount,
+        },
+        Generator,
+    };
+
+    #[test]
+    fn pruning_unexecuted_branches_keeps_output() {
+        let mut pruned_any = false;
+
+        for seed in 0..10 {
+            let program = Generator::small(seed).generate().into_program();
+            let (expected, profile) = match Interpreter::new(&program)
+                .with_max_steps(100_000)
+                .run_with_profile()
+            {
+                Ok(result) => result,
+                Err(_) => continue,
+            };
+
+            let mut branches = UnexecutedBranches::new(&profile);
+            branches.visit_program(&program);
+            let mut pruned = program.clone();
+            PruneBranches::new(branches.into_indices()).visit_program_mut(&mut pruned);
+
+            pruned_any |=
+                NodeCount::of_program(&pruned).stmts < NodeCount::of_program(&program).stmts;
+            assert_eq!(
+                Interpreter::new(&pruned).run(),
+                Ok(expected),
+                "seed {}",
+                seed
+            );
+        }
+
+        assert!(pruned_any);
+    }
+
+    #[test]
+    fn dead_block_goes_before_last_stmt() {
+        let mut program = Generator::small(2).generate().into_program();
+        let body = |program: &Program| -> Vec<String> {
+            // The first site is the body of the first function that is not const
+            let function = program
+                .functions_iter()
+                .find(|function| !function.get_template().is_const())
+                .unwrap();
+            function
+                .get_block_stmt()
+                .stmts_iter()
+                .map(|stmt| stmt.to_string())
+                .collect()
+        };
+        let before = body(&program);
+
+        let stmt = ExprStmt::new(RawExpr::new(String::from("dead()")).as_expr()).as_stmt();
+        let mut insert = InsertDeadBlock::new(0, usize::MAX, stmt);
+        insert.visit_program_mut(&mut program);
+        let after = body(&program);
+
+        assert!(insert.is_applied());
+        assert_eq!(after.len(), before.len() + 1);
+        assert_eq!(after.last(), before.last());
+    }
+}
@@ -0,0 +1,158 @@
+/// Equivalence modulo inputs: variants of a program that have to print exactly what it prints
+/// Each variant empties some of the branches that the interpreter saw never run,
+/// and inserts dead blocks guarded by opaque predicates, built by the generator itself
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+
+use crate::{
+    generator::{config::GeneratorConfig, dead_block_gen::DeadBlockGenerator, features::Features},
+    interpret::interpreter::{Interpreter, DEFAULT_MAX_STEPS},
+    program::{
+        program::Program,
+        visitor::{visit::Visit, visit_mut::VisitMut},
+    },
+};
+
+use super::mutations::{DeadBlockSites, InsertDeadBlock, PruneBranches, UnexecutedBranches};
+
+pub struct EmiGenerator<'a> {
+    program: &'a Program,
+    seed: u64,
+    features: Features,
+    config: GeneratorConfig,
+    max_steps: u64,
+}
+
+impl<'a> EmiGenerator<'a> {
+    pub fn new(program: &'a Program, seed: u64) -> Self {
+        EmiGenerator {
+            program,
+            seed,
+            features: Features::default(),
+            config: GeneratorConfig::default(),
+            max_steps: DEFAULT_MAX_STEPS,
+        }
+    }
+
+    // Should be the features the program was generated with, which dead blocks are generated with too
+    pub fn with_features(mut self, features: Features) -> Self {
+        self.features = features;
+        self
+    }
+
+    pub fn with_config(mut self, config: GeneratorConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    // Of the interpreter run that finds the branches to prune
+    pub fn with_max_steps(mut self, max_steps: u64) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    pub fn variants(&self, count: usize) -> Vec<Program> {
+        let mut rng = ChaCha12Rng::seed_from_u64(self.seed);
+
+        // Without a profile, for example after the step limit, variants only get dead blocks
+        let unexecuted = match Interpreter::new(self.program)
+            .with_max_steps(self.max_steps)
+            .run_with_profile()
+        {
+            Ok((_, profile)) => {
+                let mut branches = UnexecutedBranches::new(&profile);
+                branches.visit_program(self.program);
+                branches.into_indices()
+            }
+            Err(_) => Vec::new(),
+        };
+
+        (0..count)
+            .map(|_| self.variant(&unexecuted, &mut rng))
+            .collect()
+    }
+
+    fn variant<R: Rng>(&self, unexecuted: &[usize], rng: &mut R) -> Program {
+        let mut variant = self.program.clone();
+
+        // Pruned first, since inserting dead blocks changes how branches are counted
+        let pruned: Vec<usize> = unexecuted
+            .iter()
+            .copied()
+            .filter(|_| rng.gen_bool(self.config.prob_prune_unexecuted))
+            .collect();
+        PruneBranches::new(pruned).visit_program_mut(&mut variant);
+
+        for _ in 0..rng.gen_range(1..=self.config.max_dead_blocks.max(1)) {
+            let mut sites = DeadBlockSites::default();
+            sites.visit_program(&variant);
+            if sites.total() == 0 {
+                break;
+            }
+
+            let index = rng.gen_range(0..sites.total());
+            let function_name = sites.function_of(index).unwrap().to_string();
+            let dead_block =
+                DeadBlockGenerator::new(&variant, &function_name, self.features, &self.config)
+                    .dead_block(rng);
+
+            let mut insert = InsertDeadBlock::new(index, rng.gen(), dead_block);
+            insert.visit_program_mut(&mut variant);
+        }
+
+        variant
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{render_program, Generator};
+
+    #[test]
+    fn variants_print_the_same() {
+        let mut compared = 0;
+
+        for seed in 0..8 {
+            let program = Generator::small(seed).generate().into_program();
+            let expected = match Interpreter::new(&program).with_max_steps(100_000).run() {
+                Ok(expected) => expected,
+                Err(_) => continue,
+            };
+
+            let emi = EmiGenerator::new(&program, seed).with_max_steps(100_000);
+            for variant in emi.variants(3) {
+                let rendered = render_program(&variant, Features::default());
+                assert!(rendered.contains("std::hint::black_box("));
+                assert_eq!(
+                    Interpreter::new(&variant)
+                        .with_max_steps(1_000_000)
+                        .run()
+                        .as_ref(),
+                    Ok(&expected),
+                    "seed {}",
+                    seed
+                );
+                compared += 1;
+            }
+        }
+
+        assert!(compared > 0);
+    }
+
+    #[test]
+    fn same_seed_gives_same_variants() {
+        let program = Generator::small(5).generate().into_program();
+
+        let first = EmiGenerator::new(&program, 1)
+            .with_max_steps(100_000)
+            .variants(2);
+        let second = EmiGenerator::new(&program, 1)
+            .with_max_steps(100_000)
+            .variants(2);
+
+        for (a, b) in first.iter().zip(second.iter()) {
+            assert_eq!(a.to_json(), b.to_json());
+        }
+    }
+}
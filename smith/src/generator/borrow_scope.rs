@@ -1,13 +1,13 @@
 /// Maintains the borrow information of a single entry
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 
 use crate::program::types::BorrowStatus;
 
 #[derive(Clone, Debug)]
 pub struct BorrowContext {
     borrow_source: Option<String>,
-    borrows: HashSet<String>,
-    mut_borrows: HashSet<String>,
+    borrows: BTreeSet<String>,
+    mut_borrows: BTreeSet<String>,
     func_mut_borrow: bool,
 }
 
@@ -15,8 +15,8 @@ impl BorrowContext {
     pub fn new(source: Option<String>) -> Self {
         BorrowContext {
             borrow_source: source,
-            borrows: HashSet::new(),
-            mut_borrows: HashSet::new(),
+            borrows: BTreeSet::new(),
+            mut_borrows: BTreeSet::new(),
             func_mut_borrow: false,
         }
     }
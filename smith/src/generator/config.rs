@@ -0,0 +1,287 @@
+/// All constants and production weights the generator samples from
+/// Fields missing from a loaded config keep their default values
+use serde::{Deserialize, Serialize};
+
+use super::weights::{
+    attribute::{
+        variants::{FunctionAttributeVariants, StructAttributeVariants},
+        weights::{FunctionAttributeWeights, StructAttributeWeights},
+    },
+    expr::{
+        variants::{ArithmeticExprVariants, BoolExprVariants, StructExprVariants},
+        weights::{ArithmeticExprWeights, BoolExprWeights, StructExprWeights},
+    },
+    stmt::{variants::StmtVariants, weights::StmtWeights},
+    EnumWeights,
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GeneratorConfig {
+    pub max_expr_depth: u32,
+    pub max_arith_expr_depth: u32,
+    pub max_bool_expr_depth: u32,
+    pub max_structs: u32,
+    pub max_struct_fields: u32,
+    pub num_global_struct_fields: u32,
+    pub max_funcs: u32,
+    pub max_const_funcs: u32,
+    pub max_func_params: u32,
+    pub max_macros: u32,
+    pub max_const_generics: u32,
+    pub max_modules: u32,
+    pub max_threads: u32,
+    pub max_unsafe_array_len: u32,
+    pub max_unsafe_array_writes: u32,
+    pub max_pattern_depth: u32,
+    pub max_tuple_pattern_len: u32,
+    pub max_func_attributes: u32,
+    pub max_struct_attributes: u32,
+
+    pub max_stmts_in_block: u32,
+    // Statements and expressions that the program is generated close to
+    // When set, blocks add statements until their share of it is spent instead of having
+    // max_stmts_in_block statements, and it is split into max_funcs functions of about the same size
+    pub target_size: Option<u32>,
+    pub max_conditional_branches: u32,
+    pub max_conditional_depth: u32, // Only refers to conditional statements
+    pub max_loop_depth: u32,
+
+    pub max_for_loop_iters: u32,
+    pub prob_max_for_loop_iters: f32,
+
+    // Probability that the next function is still placed in a module
+    pub prob_func_in_module: f64,
+
+    // Lengths that const generic items are instantiated at
+    pub const_generic_lengths: Vec<u32>,
+
+    // Alignments in bytes that #[repr(align(N))] is generated with
+    pub repr_alignments: Vec<u32>,
+
+    // Probability that a function other than main is async
+    pub prob_async_func: f64,
+
+    // Probability that scoped threads also add to a shared atomic counter
+    pub prob_thread_counter: f64,
+
+    // Probability that a pattern statement destructures a struct variable instead of a new value
+    pub prob_pattern_place: f64,
+
+    // Probability that a pattern statement matches a tuple of new values
+    pub prob_tuple_pattern: f64,
+
+    // Dead blocks inserted into each EMI variant, at least one
+    pub max_dead_blocks: u32,
+
+    // Probability that an EMI variant empties a branch the interpreter saw never run
+    pub prob_prune_unexecuted: f64,
+
+    pub stmt_weights: StmtWeights,
+    pub arith_expr_weights: ArithmeticExprWeights,
+    pub bool_expr_weights: BoolExprWeights,
+    pub struct_expr_weights: StructExprWeights,
+    pub func_attribute_weights: FunctionAttributeWeights,
+    pub struct_attribute_weights: StructAttributeWeights,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        GeneratorConfig {
+            max_expr_depth: 12,
+            max_arith_expr_depth: 10,
+            max_bool_expr_depth: 10,
+            max_structs: 2,
+            max_struct_fields: 10,
+            num_global_struct_fields: 20,
+            max_funcs: 5,
+            max_const_funcs: 3,
+            max_func_params: 6,
+            max_macros: 4,
+            max_const_generics: 3,
+            max_modules: 3,
+            max_threads: 4,
+            max_unsafe_array_len: 8,
+            max_unsafe_array_writes: 3,
+            max_pattern_depth: 3,
+            max_tuple_pattern_len: 3,
+            max_func_attributes: 2,
+            max_struct_attributes: 2,
+            max_stmts_in_block: 8,
+            target_size: None,
+            max_conditional_branches: 4,
+            max_conditional_depth: 2,
+            max_loop_depth: 2,
+            max_for_loop_iters: 1000,
+            prob_max_for_loop_iters: 0.2,
+            prob_func_in_module: 0.6,
+            const_generic_lengths: vec![1, 2, 3, 4, 7, 8, 16],
+            repr_alignments: vec![2, 4, 8, 16, 32],
+            prob_async_func: 0.3,
+            prob_thread_counter: 0.5,
+            prob_pattern_place: 0.5,
+            prob_tuple_pattern: 0.3,
+            max_dead_blocks: 3,
+            prob_prune_unexecuted: 0.5,
+            stmt_weights: StmtWeights::default(),
+            arith_expr_weights: ArithmeticExprWeights::default(),
+            bool_expr_weights: BoolExprWeights::default(),
+            struct_expr_weights: StructExprWeights::default(),
+            func_attribute_weights: FunctionAttributeWeights::default(),
+            struct_attribute_weights: StructAttributeWeights::default(),
+        }
+    }
+}
+
+impl GeneratorConfig {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let config: GeneratorConfig = serde_json::from_str(json).map_err(|err| err.to_string())?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    // Rejects values that generation would panic on, or that would not compile
+    pub fn validate(&self) -> Result<(), String> {
+        // Fewest items that are ever generated, scoped threads spawn at least two
+        let min_bounds = [
+            ("max_threads", self.max_threads, 2),
+            ("max_unsafe_array_len", self.max_unsafe_array_len, 1),
+            ("max_unsafe_array_writes", self.max_unsafe_array_writes, 1),
+            ("max_tuple_pattern_len", self.max_tuple_pattern_len, 2),
+        ];
+        for (name, max, min) in min_bounds.iter() {
+            if max < min {
+                return Err(format!("{} is {}, but can not be below {}", name, max, min));
+            }
+        }
+
+        let choices = [
+            ("const_generic_lengths", &self.const_generic_lengths),
+            ("repr_alignments", &self.repr_alignments),
+        ];
+        for (name, values) in choices.iter() {
+            if values.is_empty() {
+                return Err(format!("{} can not be empty", name));
+            }
+        }
+        if let Some(alignment) = self
+            .repr_alignments
+            .iter()
+            .find(|alignment| !alignment.is_power_of_two())
+        {
+            return Err(format!(
+                "repr_alignments has {}, but alignments must be powers of two",
+                alignment
+            ));
+        }
+
+        let probabilities = [
+            (
+                "prob_max_for_loop_iters",
+                self.prob_max_for_loop_iters as f64,
+            ),
+            ("prob_func_in_module", self.prob_func_in_module),
+            ("prob_async_func", self.prob_async_func),
+            ("prob_thread_counter", self.prob_thread_counter),
+            ("prob_pattern_place", self.prob_pattern_place),
+            ("prob_tuple_pattern", self.prob_tuple_pattern),
+            ("prob_prune_unexecuted", self.prob_prune_unexecuted),
+        ];
+        for (name, prob) in probabilities.iter() {
+            if !(0.0..=1.0).contains(prob) {
+                return Err(format!("{} is {}, but must be between 0 and 1", name, prob));
+            }
+        }
+
+        let zero_weights = [
+            ("stmt_weights", StmtVariants::all_zero(&self.stmt_weights)),
+            (
+                "arith_expr_weights",
+                ArithmeticExprVariants::all_zero(&self.arith_expr_weights),
+            ),
+            (
+                "bool_expr_weights",
+                BoolExprVariants::all_zero(&self.bool_expr_weights),
+            ),
+            (
+                "struct_expr_weights",
+                StructExprVariants::all_zero(&self.struct_expr_weights),
+            ),
+            (
+                "func_attribute_weights",
+                FunctionAttributeVariants::all_zero(&self.func_attribute_weights),
+            ),
+            (
+                "struct_attribute_weights",
+                StructAttributeVariants::all_zero(&self.struct_attribute_weights),
+            ),
+        ];
+        for (name, all_zero) in zero_weights.iter() {
+            if *all_zero {
+                return Err(format!("{} can not all be zero", name));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn missing_fields_keep_defaults() {
+        let config = GeneratorConfig::from_json(
+            r#"{"max_funcs": 9, "stmt_weights": {"loop_statement": 0}}"#,
+        )
+        .unwrap();
+        let default = GeneratorConfig::default();
+
+        assert_eq!(config.max_funcs, 9);
+        assert_eq!(config.stmt_weights.loop_statement, 0);
+        assert_eq!(
+            config.stmt_weights.let_statement,
+            default.stmt_weights.let_statement
+        );
+        assert_eq!(config.max_expr_depth, default.max_expr_depth);
+        assert_eq!(config.const_generic_lengths, default.const_generic_lengths);
+    }
+
+    #[test]
+    fn profiles_parse() {
+        let profiles = [
+            include_str!("../../../generated/profiles/arith-heavy.json"),
+            include_str!("../../../generated/profiles/borrow-heavy.json"),
+        ];
+
+        for profile in profiles.iter() {
+            assert!(GeneratorConfig::from_json(profile).is_ok());
+        }
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        assert!(GeneratorConfig::from_json(r#"{"max_funcz": 9}"#).is_err());
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        let invalid = [
+            r#"{"max_threads": 1}"#,
+            r#"{"max_tuple_pattern_len": 0}"#,
+            r#"{"const_generic_lengths": []}"#,
+            r#"{"repr_alignments": [3]}"#,
+            r#"{"prob_async_func": 1.5}"#,
+            r#"{"prob_max_for_loop_iters": -0.1}"#,
+            r#"{"bool_expr_weights": {"bool": 0, "binary": 0, "comparison": 0, "negation": 0,
+                "var": 0, "func": 0, "macro": 0, "matches": 0}}"#,
+        ];
+
+        assert!(GeneratorConfig::default().validate().is_ok());
+        for json in invalid.iter() {
+            assert!(GeneratorConfig::from_json(json).is_err(), "{}", json);
+        }
+    }
+}
@@ -0,0 +1,81 @@
+/// Generates functions and structs parameterised by a const length
+use std::{cell::RefCell, rc::Rc};
+
+use rand::Rng;
+use strum::IntoEnumIterator;
+
+use crate::program::{
+    const_generic::{BodyVar, ConstGeneric, ConstGenericKind, ConstGenericTemplate},
+    types::BorrowTypeID,
+    var::Var,
+};
+
+use super::{
+    context::Context, expr_gen::ExprGenerator, name_gen::NameGenerator, struct_gen::StructTable,
+};
+
+pub struct ConstGenericGenerator<'a> {
+    struct_table: &'a StructTable,
+    func_name_gen: NameGenerator,
+    struct_name_gen: NameGenerator,
+}
+
+impl<'a> ConstGenericGenerator<'a> {
+    pub fn new(struct_table: &'a StructTable) -> Self {
+        ConstGenericGenerator {
+            struct_table,
+            func_name_gen: NameGenerator::new(String::from("const_generic_")),
+            struct_name_gen: NameGenerator::new(String::from("ConstGeneric")),
+        }
+    }
+
+    pub fn gen_const_generic<R: Rng>(
+        &mut self,
+        context: Rc<RefCell<Context>>,
+        rng: &mut R,
+    ) -> ConstGeneric {
+        let elem_type = self.struct_table.rand_int_type(rng);
+        let type_id = elem_type.as_type();
+
+        let template = if rng.gen::<bool>() {
+            ConstGenericTemplate::new(
+                self.func_name_gen.next().unwrap(),
+                ConstGenericKind::Function,
+                elem_type,
+            )
+        } else {
+            ConstGenericTemplate::new(
+                self.struct_name_gen.next().unwrap(),
+                ConstGenericKind::Struct,
+                elem_type,
+            )
+        };
+
+        context.borrow_mut().enter_scope();
+
+        // Elements from both ends, the index itself and the accumulator are all usable in the body
+        // The accumulator is immutable here so it is never mutably borrowed by the update
+        for body_var in BodyVar::iter() {
+            let var = Var::new(type_id.clone(), body_var.get_name().to_string(), false);
+            context
+                .borrow()
+                .scope
+                .borrow_mut()
+                .insert(&var.get_name(), var.as_scope_entry());
+        }
+
+        let expr_generator = ExprGenerator::new(
+            self.struct_table,
+            Rc::clone(&context),
+            type_id,
+            BorrowTypeID::None,
+        );
+
+        context.borrow_mut().reset_expr_depth();
+        let update_expr = expr_generator.expr(rng);
+
+        context.borrow_mut().leave_scope();
+
+        ConstGeneric::new(template, update_expr)
+    }
+}
@@ -1,9 +1,14 @@
 /// The Context data structure is passed throughout the generator
 /// It holds important information such as the scope and helps determine
 /// what productions can be used and when
-use std::{cell::RefCell, collections::HashSet, rc::Rc};
+use std::{cell::RefCell, collections::BTreeSet, rc::Rc};
 
-use super::scope::Scope;
+use crate::program::types::TypeID;
+
+use super::{
+    config::GeneratorConfig, features::Features, scope::Scope, size_budget::SizeBudget,
+    stats::GenerationStats, swarm::Swarm,
+};
 pub struct Context {
     pub scope: Rc<RefCell<Scope>>,
     pub loop_depth: u32,
@@ -11,7 +16,18 @@ pub struct Context {
     pub arith_expr_depth: u32,
     pub bool_expr_depth: u32,
     pub if_depth: u32,
-    pub func_call_mut_borrows: HashSet<String>,
+    pub func_call_mut_borrows: BTreeSet<String>,
+    pub in_const_fn: bool, // Restricts generation to what can be evaluated at compile time
+    pub in_async_fn: bool, // Async functions are awaited rather than driven by the executor
+    pub in_main: bool,     // Statements outside of loops in main run exactly once
+    pub return_type: TypeID, // Of the function being generated, for early returns
+    pub function_name: String, // Of the function being generated, which its checksum is kept under
+    pub traced_stmt_count: u32, // Across the whole program, so that trace ids are unique
+    pub features: Features,
+    pub config: GeneratorConfig,
+    pub swarm: Swarm,
+    pub stats: GenerationStats, // Of everything generated under this context
+    pub size_budget: Option<SizeBudget>, // Only while a program with a target size is generated
 }
 
 impl Context {
@@ -20,6 +36,23 @@ impl Context {
             ..Default::default()
         }
     }
+
+    pub fn new_with_features(features: Features) -> Self {
+        Context {
+            features,
+            ..Default::default()
+        }
+    }
+
+    pub fn new_with_config(features: Features, config: GeneratorConfig, swarm: Swarm) -> Self {
+        Context {
+            features,
+            config,
+            swarm,
+            ..Default::default()
+        }
+    }
+
     pub fn enter_scope(&mut self) {
         let new_scope = Rc::new(RefCell::new(Scope::new_from_parent(Rc::clone(&self.scope))));
 
@@ -51,7 +84,18 @@ impl Default for Context {
             if_depth: 0,
             arith_expr_depth: 0,
             bool_expr_depth: 0,
-            func_call_mut_borrows: HashSet::new(),
+            func_call_mut_borrows: BTreeSet::new(),
+            in_const_fn: false,
+            in_async_fn: false,
+            in_main: false,
+            return_type: TypeID::NullType,
+            function_name: String::new(),
+            traced_stmt_count: 0,
+            features: Features::default(),
+            config: GeneratorConfig::default(),
+            swarm: Swarm::default(),
+            stats: GenerationStats::new(),
+            size_budget: None,
         }
     }
 }
@@ -0,0 +1,166 @@
+/// Generates dead blocks to insert into an existing program, for its EMI variants
+/// The scope is rebuilt from the program's items as they were when the enclosing function was generated,
+/// without any of its variables, so that a dead block never borrows or moves what the live code uses
+use std::{cell::RefCell, rc::Rc};
+
+use rand::Rng;
+
+use crate::program::{
+    expr::bool_expr::{BinBoolExpr, BoolExpr, BoolOp, NegationExpr, OpaqueBool},
+    function::Function,
+    program::Program,
+    stmt::{conditional_stmt::ConditionalStmt, stmt::Stmt},
+    types::{BorrowTypeID, TypeID},
+};
+
+use super::{
+    config::GeneratorConfig,
+    context::Context,
+    expr_gen::ExprGenerator,
+    features::Features,
+    scope_entry::{ConstGenericScopeEntry, FuncScopeEntry, MacroScopeEntry},
+    stmt_gen::StmtGenerator,
+    struct_gen::{StructTable, GLOBAL_STRUCT_NAME},
+    swarm::Swarm,
+};
+
+pub struct DeadBlockGenerator {
+    struct_table: StructTable,
+    context: Rc<RefCell<Context>>,
+}
+
+impl DeadBlockGenerator {
+    // The function has to be a top level one, whose body can name every item of the program
+    pub fn new(
+        program: &Program,
+        function_name: &str,
+        features: Features,
+        config: &GeneratorConfig,
+    ) -> Self {
+        let mut struct_table = StructTable::new().with_config(config.clone());
+        let struct_templates = program.structs_iter().chain(
+            program
+                .modules_iter()
+                .flat_map(|module| module.structs_iter()),
+        );
+        for struct_template in struct_templates {
+            if struct_template.get_name() == GLOBAL_STRUCT_NAME {
+                struct_table.insert_global_struct(struct_template.clone());
+            } else {
+                struct_table.insert_struct(struct_template.clone());
+            }
+        }
+
+        // Trace ids are only unique within the statements the generator numbered itself
+        let features = Features {
+            trace: false,
+            ..features
+        };
+        let context = Rc::new(RefCell::new(Context::new_with_config(
+            features,
+            config.clone(),
+            Swarm::from_config(config),
+        )));
+
+        for macro_template in program.macros_iter() {
+            context.borrow().scope.borrow_mut().insert(
+                &macro_template.get_name(),
+                MacroScopeEntry::new(macro_template.clone()).as_scope_entry(),
+            );
+        }
+
+        let const_generics = program.const_generics_iter().chain(
+            program
+                .modules_iter()
+                .flat_map(|module| module.const_generics_iter()),
+        );
+        for const_generic in const_generics {
+            let template = const_generic.get_template();
+            context.borrow().scope.borrow_mut().insert(
+                &template.get_name(),
+                ConstGenericScopeEntry::new(template).as_scope_entry(),
+            );
+        }
+
+        // Functions in modules are all generated before the top level ones, which are in order,
+        // so only functions that cannot call the enclosing one are in scope
+        let earlier_functions = program
+            .modules_iter()
+            .flat_map(|module| module.functions_iter())
+            .chain(
+                program
+                    .functions_iter()
+                    .take_while(|function| function.get_name() != function_name),
+            );
+        for function in earlier_functions {
+            context.borrow().scope.borrow_mut().insert(
+                &function.get_name(),
+                FuncScopeEntry::new(function.get_return_type(), function.get_template())
+                    .as_scope_entry(),
+            );
+        }
+
+        if let Some(function) = program
+            .functions_iter()
+            .find(|function| function.get_name() == function_name)
+        {
+            Self::enter_function(&mut context.borrow_mut(), function);
+        }
+
+        DeadBlockGenerator {
+            struct_table,
+            context,
+        }
+    }
+
+    // Mirrors the context the function's own body was generated in, except for its parameters
+    fn enter_function(context: &mut Context, function: &Function) {
+        let template = function.get_template();
+
+        context.enter_scope();
+        context.in_main = function.get_name() == "main";
+        context.in_async_fn = template.is_async();
+        context.return_type = function.get_return_type();
+        context.function_name = function.get_name();
+    }
+
+    // A conditional whose only branch never runs, although the compiler cannot tell
+    pub fn dead_block<R: Rng>(&self, rng: &mut R) -> Stmt {
+        let guard = self.opaque_predicate(rng);
+
+        // Counts towards the conditional depth like any other conditional
+        self.context.borrow_mut().if_depth += 1;
+        let block_stmt =
+            StmtGenerator::new(&self.struct_table).block_stmt(Rc::clone(&self.context), rng);
+        self.context.borrow_mut().if_depth -= 1;
+
+        ConditionalStmt::new_from_vec(vec![(guard, block_stmt)], None).as_stmt()
+    }
+
+    // Always false, optionally followed by a generated condition that is then never evaluated
+    fn opaque_predicate<R: Rng>(&self, rng: &mut R) -> BoolExpr {
+        let opaque_false = if rng.gen::<bool>() {
+            OpaqueBool::new(false).as_bool_expr()
+        } else {
+            NegationExpr::new(OpaqueBool::new(true).as_bool_expr()).as_bool_expr()
+        };
+
+        if rng.gen::<bool>() {
+            return opaque_false;
+        }
+
+        self.context.borrow_mut().enter_scope();
+        self.context.borrow_mut().reset_expr_depth();
+        let condition: BoolExpr = ExprGenerator::new(
+            &self.struct_table,
+            Rc::clone(&self.context),
+            TypeID::BoolType,
+            BorrowTypeID::None,
+        )
+        .expr(rng)
+        .into();
+        self.context.borrow_mut().leave_scope();
+
+        BinBoolExpr::new(opaque_false, condition, BoolOp::AND).as_bool_expr()
+    }
+}
@@ -3,25 +3,33 @@ use std::{cell::RefCell, rc::Rc};
 
 use crate::program::{
     expr::{
-        arithmetic_expr::{ArithmeticExpr, BinaryExpr, BinaryOp, IntExpr, IntValue},
+        arithmetic_expr::{ArithmeticExpr, BinaryExpr, IntExpr, IntValue},
+        array_expr::ArrayExpr,
         bool_expr::{
-            BinBoolExpr, BoolExpr, BoolOp, BoolValue, ComparisonExpr, ComparisonOp, NegationExpr,
+            BinBoolExpr, BoolExpr, BoolOp, BoolValue, ComparisonExpr, ComparisonOp, MatchesExpr,
+            NegationExpr,
         },
         borrow_expr::BorrowExpr,
+        const_generic_call_expr::ConstGenericCallExpr,
         expr::Expr,
         func_call_expr::FunctionCallExpr,
+        macro_call_expr::MacroCallExpr,
         struct_expr::{StructExpr, StructLiteral},
     },
     function::FunctionTemplate,
     struct_template::StructTemplate,
-    types::{BorrowStatus, BorrowTypeID, IntTypeID, TypeID},
+    types::{BorrowStatus, BorrowTypeID, TypeID},
     var::Var,
 };
 use rand::{seq::SliceRandom, Rng};
 
 use super::{
-    consts, context::Context, filters::*, scope_entry::ScopeEntry, struct_gen::StructTable,
-    weights::expr::variants::*,
+    context::Context,
+    filters::*,
+    pattern_gen::PatternGenerator,
+    scope_entry::ScopeEntry,
+    struct_gen::StructTable,
+    weights::{expr::variants::*, EnumWeights},
 };
 
 pub struct ExprGenerator<'table> {
@@ -62,7 +70,7 @@ impl<'table> ExprGenerator<'table> {
 
     pub fn expr<R: Rng>(&self, rng: &mut R) -> Expr {
         self.context.borrow_mut().expr_depth += 1;
-        if self.context.borrow().expr_depth < consts::MAX_EXPR_DEPTH {
+        if self.context.borrow().expr_depth < self.context.borrow().swarm.max_expr_depth {
             match &self.type_id {
                 TypeID::IntType(_) => self.arith_expr(rng).as_expr(),
                 TypeID::StructType(struct_name) => {
@@ -72,10 +80,22 @@ impl<'table> ExprGenerator<'table> {
                 TypeID::NullType => panic!("Tried to construct an expression of null type"),
             }
         } else {
+            self.count_depth_limited();
             self.literal_expr(rng)
         }
     }
 
+    // Under the production that the literal is generated in place of
+    fn count_depth_limited(&self) {
+        let stats = &mut self.context.borrow_mut().stats;
+        match &self.type_id {
+            TypeID::IntType(_) => stats.arith_exprs.depth_limited(),
+            TypeID::StructType(_) => stats.struct_exprs.depth_limited(),
+            TypeID::BoolType => stats.bool_exprs.depth_limited(),
+            TypeID::NullType => (),
+        }
+    }
+
     pub fn literal_expr<R: Rng>(&self, rng: &mut R) -> Expr {
         match &self.type_id {
             TypeID::IntType(_) => self.int_expr(rng).as_expr(),
@@ -181,13 +201,26 @@ impl<'table> ExprGenerator<'table> {
     }
 
     fn struct_expr<R: Rng>(&self, struct_name: String, rng: &mut R) -> StructExpr {
-        let mut expr_choice: StructExprVariants = rng.gen();
-        let loop_limit = 100;
-        for _ in 0..loop_limit {
+        let mut expr_choice =
+            StructExprVariants::sample(&self.context.borrow().config.struct_expr_weights, rng);
+        let loop_limit: u64 = 100;
+        for attempt in 1..=loop_limit {
             if let Some(expr) = self.try_struct_expr(&struct_name, expr_choice, rng) {
+                let depth = self.context.borrow().expr_depth;
+                let stats = &mut self.context.borrow_mut().stats.struct_exprs;
+                stats.generated(expr_choice, depth);
+                stats.retried(attempt, loop_limit);
                 return expr;
             } else {
-                expr_choice = rng.gen();
+                self.context
+                    .borrow_mut()
+                    .stats
+                    .struct_exprs
+                    .failed(expr_choice);
+                expr_choice = StructExprVariants::sample(
+                    &self.context.borrow().config.struct_expr_weights,
+                    rng,
+                );
             }
         }
 
@@ -219,6 +252,10 @@ impl<'table> ExprGenerator<'table> {
         expr_choice: ArithmeticExprVariants,
         rng: &mut R,
     ) -> Option<ArithmeticExpr> {
+        if !self.context.borrow().swarm.allows_arith_expr(expr_choice) {
+            return None;
+        }
+
         match expr_choice {
             ArithmeticExprVariants::Int => Some(self.int_expr(rng).as_arith_expr()),
             ArithmeticExprVariants::Binary => Some(self.binary_int_expr(rng).as_arith_expr()),
@@ -238,36 +275,63 @@ impl<'table> ExprGenerator<'table> {
                 Some(expr) => Some(expr.into()),
                 None => None,
             },
+            ArithmeticExprVariants::Macro => match self.macro_call_expr(rng) {
+                Some(expr) => Some(expr.into()),
+                None => None,
+            },
+            ArithmeticExprVariants::ConstGeneric => match self.const_generic_call_expr(rng) {
+                Some(expr) => Some(expr.into()),
+                None => None,
+            },
         }
     }
 
     fn arith_expr<R: Rng>(&self, rng: &mut R) -> ArithmeticExpr {
         self.context.borrow_mut().arith_expr_depth += 1;
 
-        let mut expr_choice: ArithmeticExprVariants = rng.gen();
+        let mut expr_choice =
+            ArithmeticExprVariants::sample(&self.context.borrow().config.arith_expr_weights, rng);
 
-        if self.context.borrow_mut().arith_expr_depth > consts::MAX_ARITH_EXPR_DEPTH {
+        if self.context.borrow().arith_expr_depth > self.context.borrow().swarm.max_arith_expr_depth
+        {
             expr_choice = ArithmeticExprVariants::Int;
+            self.context.borrow_mut().stats.arith_exprs.depth_limited();
         }
 
-        let loop_limit = 100;
-        for _ in 0..loop_limit {
+        let loop_limit: u64 = 100;
+        for attempt in 1..=loop_limit {
             if let Some(expr) = self.try_arith_expr(expr_choice, rng) {
+                let depth = self.context.borrow().arith_expr_depth;
+                let stats = &mut self.context.borrow_mut().stats.arith_exprs;
+                stats.generated(expr_choice, depth);
+                stats.retried(attempt, loop_limit);
                 return expr;
             } else {
-                expr_choice = rng.gen();
+                self.context
+                    .borrow_mut()
+                    .stats
+                    .arith_exprs
+                    .failed(expr_choice);
+                expr_choice = ArithmeticExprVariants::sample(
+                    &self.context.borrow().config.arith_expr_weights,
+                    rng,
+                );
             }
         }
         panic!("Could not generate arithmetic expr");
     }
 
     fn binary_int_expr<R: Rng>(&self, rng: &mut R) -> BinaryExpr {
-        let op: BinaryOp = rng.gen();
+        let op = self.context.borrow().swarm.rand_binary_op(rng);
 
         let left = self.arith_expr(rng);
         let right = self.arith_expr(rng);
 
-        BinaryExpr::new(left, right, op)
+        if self.context.borrow().in_const_fn {
+            BinaryExpr::new_const(left, right, op)
+        } else {
+            BinaryExpr::new(left, right, op)
+        }
     }
 
     fn int_expr<R: Rng>(&self, rng: &mut R) -> IntExpr {
@@ -306,24 +370,41 @@ impl<'table> ExprGenerator<'table> {
                 }
             }
             BoolExprVariants::Bool => Some(self.bool_literal(rng).as_bool_expr()),
+            BoolExprVariants::Macro => match self.macro_call_expr(rng) {
+                Some(macro_call_expr) => Some(macro_call_expr.into()),
+                None => None,
+            },
+            BoolExprVariants::Matches => Some(self.matches_expr(rng).as_bool_expr()),
         }
     }
 
     fn bool_expr<R: Rng>(&self, rng: &mut R) -> BoolExpr {
         self.context.borrow_mut().bool_expr_depth += 1;
 
-        let mut expr_choice: BoolExprVariants = rng.gen();
+        let mut expr_choice =
+            BoolExprVariants::sample(&self.context.borrow().config.bool_expr_weights, rng);
 
-        if self.context.borrow_mut().bool_expr_depth > consts::MAX_BOOL_EXPR_DEPTH {
+        if self.context.borrow().bool_expr_depth > self.context.borrow().swarm.max_bool_expr_depth {
             expr_choice = BoolExprVariants::Bool;
+            self.context.borrow_mut().stats.bool_exprs.depth_limited();
         }
 
-        let loop_limit = 100;
-        for _ in 0..loop_limit {
+        let loop_limit: u64 = 100;
+        for attempt in 1..=loop_limit {
             if let Some(expr) = self.try_bool_expr(expr_choice, rng) {
+                let depth = self.context.borrow().bool_expr_depth;
+                let stats = &mut self.context.borrow_mut().stats.bool_exprs;
+                stats.generated(expr_choice, depth);
+                stats.retried(attempt, loop_limit);
                 return expr;
             } else {
-                expr_choice = rng.gen();
+                self.context
+                    .borrow_mut()
+                    .stats
+                    .bool_exprs
+                    .failed(expr_choice);
+                expr_choice =
+                    BoolExprVariants::sample(&self.context.borrow().config.bool_expr_weights, rng);
             }
         }
 
@@ -346,7 +427,7 @@ impl<'table> ExprGenerator<'table> {
     fn comparison_expr<R: Rng>(&self, rng: &mut R) -> ComparisonExpr {
         let op: ComparisonOp = rng.gen();
 
-        let int_type: IntTypeID = rng.gen();
+        let int_type = self.struct_table.rand_int_type(rng);
 
         // TODO: Borrow type
         let generator = ExprGenerator::new_sub_expr(self, int_type.as_type(), BorrowTypeID::None);
@@ -357,6 +438,24 @@ impl<'table> ExprGenerator<'table> {
         ComparisonExpr::new(left, right, op)
     }
 
+    // Const functions only match on primitives
+    fn matches_expr<R: Rng>(&self, rng: &mut R) -> MatchesExpr {
+        let type_id = if self.context.borrow().in_const_fn {
+            self.struct_table.rand_primitive_type(rng)
+        } else {
+            self.struct_table.rand_type(rng)
+        };
+
+        let generator = ExprGenerator::new_sub_expr(self, type_id.clone(), BorrowTypeID::None);
+        let expr = generator.expr(rng);
+
+        let max_depth = self.context.borrow().config.max_pattern_depth;
+        let pattern = PatternGenerator::new(self.struct_table, true, max_depth)
+            .pattern_without_bindings(&type_id, rng);
+
+        MatchesExpr::new(expr, pattern)
+    }
+
     fn negation_expr<R: Rng>(&self, rng: &mut R) -> NegationExpr {
         let bool_expr = self.bool_expr(rng);
 
@@ -390,19 +489,30 @@ impl<'table> ExprGenerator<'table> {
                 BorrowTypeID::None => arguments.push(generator.expr(rng)),
                 BorrowTypeID::Ref => arguments.push(generator.borrow_expr(rng).as_expr()),
                 BorrowTypeID::MutRef => {
-                    arguments.push(generator.func_mut_borrow_expr(rng).as_expr())
+                    let borrow_expr = generator.func_mut_borrow_expr(rng);
+                    self.context.borrow_mut().stats.borrowed(&borrow_expr);
+                    arguments.push(borrow_expr.as_expr())
                 }
             }
         }
 
-        FunctionCallExpr::new(function_template, arguments)
+        if self.context.borrow().in_async_fn {
+            FunctionCallExpr::new_in_async(function_template, arguments)
+        } else {
+            FunctionCallExpr::new(function_template, arguments)
+        }
     }
 
     // Assumes that the function with the correct type already exists
     fn func_call_expr<R: Rng>(&self, rng: &mut R) -> Option<FunctionCallExpr> {
-        let filters = Filters::new()
+        let mut filters = Filters::new()
             .with_filters(vec![is_func_filter(), is_type_filter(self.type_id.clone())]);
 
+        // Const functions can only call other const functions
+        if self.context.borrow().in_const_fn {
+            filters.add_filter(is_const_func_filter());
+        }
+
         let func_list: Vec<(String, (Rc<ScopeEntry>, BorrowStatus))> =
             filters.filter(&self.context.borrow().scope);
 
@@ -422,23 +532,111 @@ impl<'table> ExprGenerator<'table> {
         }
     }
 
+    // Macros are generic, so every argument is generated with the type of the expansion
+    fn macro_call_expr<R: Rng>(&self, rng: &mut R) -> Option<MacroCallExpr> {
+        // Expansions call the runtime traits, which are not const
+        if self.context.borrow().in_const_fn {
+            return None;
+        }
+
+        let filters = Filters::new().with_filters(vec![
+            is_macro_filter(),
+            macro_produces_type_filter(self.type_id.clone()),
+        ]);
+
+        let macro_list = filters.filter(&self.context.borrow().scope);
+
+        let (_, (entry_choice, _)) = macro_list.choose(rng)?;
+
+        if let ScopeEntry::Macro(macro_scope_entry) = entry_choice.as_ref() {
+            let macro_template = macro_scope_entry.get_template();
+            let (min_args, max_args) = macro_template.get_kind().arg_bounds();
+
+            let mut arguments: Vec<Expr> = Vec::new();
+            for _ in 0..rng.gen_range(min_args..=max_args) {
+                let generator =
+                    ExprGenerator::new_sub_expr(self, self.type_id.clone(), BorrowTypeID::None);
+                arguments.push(generator.expr(rng));
+            }
+
+            Some(MacroCallExpr::new(
+                macro_template,
+                arguments,
+                self.type_id.clone(),
+            ))
+        } else {
+            None
+        }
+    }
+
+    // Instantiates a const generic item at one of a few lengths
+    fn const_generic_call_expr<R: Rng>(&self, rng: &mut R) -> Option<ConstGenericCallExpr> {
+        if self.context.borrow().in_const_fn {
+            return None;
+        }
+
+        let filters = Filters::new().with_filters(vec![const_generic_produces_type_filter(
+            self.type_id.clone(),
+        )]);
+
+        let const_generic_list = filters.filter(&self.context.borrow().scope);
+
+        let (_, (entry_choice, _)) = const_generic_list.choose(rng)?;
+
+        if let ScopeEntry::ConstGeneric(const_generic_entry) = entry_choice.as_ref() {
+            let length = *self
+                .context
+                .borrow()
+                .config
+                .const_generic_lengths
+                .choose(rng)
+                .unwrap();
+
+            let mut elements: Vec<Expr> = Vec::new();
+            for _ in 0..length {
+                let generator =
+                    ExprGenerator::new_sub_expr(self, self.type_id.clone(), BorrowTypeID::None);
+                elements.push(generator.expr(rng));
+            }
+
+            let init_generator =
+                ExprGenerator::new_sub_expr(self, self.type_id.clone(), BorrowTypeID::None);
+            let init = init_generator.expr(rng);
+
+            Some(ConstGenericCallExpr::new(
+                const_generic_entry.get_template(),
+                ArrayExpr::new(self.type_id.clone(), length, elements),
+                init,
+            ))
+        } else {
+            None
+        }
+    }
+
     // TODO: Force mutable borrow on global struct? Prevent instantiation
     pub fn borrow_expr<R: Rng>(&self, rng: &mut R) -> BorrowExpr {
-        match self.borrow_type_id {
+        let borrow_expr = match self.borrow_type_id {
             BorrowTypeID::Ref => self.immut_borrow_expr(rng),
             BorrowTypeID::MutRef => self.mut_borrow_expr(rng),
             _ => panic!("Expr generator calling borrow expr when borrow type is none"),
-        }
+        };
+        self.context.borrow_mut().stats.borrowed(&borrow_expr);
+
+        borrow_expr
     }
 
     // A mutable borrow in a function constitutes a 'use' of that borrow
     // All previous variable borrows must go out of scope
     fn func_mut_borrow_expr<R: Rng>(&self, rng: &mut R) -> BorrowExpr {
-        let filters = Filters::new().with_filters(vec![
-            is_type_filter(self.type_id.clone()),
-            is_var_struct_filter(),
-            is_mut_or_mut_ref_filter(),
-        ]);
+        let filters = Filters::new()
+            .with_filters(vec![
+                is_type_filter(self.type_id.clone()),
+                is_var_struct_filter(),
+                is_mut_or_mut_ref_filter(),
+            ])
+            .with_full_filters(vec![is_not_packed_field_filter(Rc::clone(
+                &self.context.borrow().scope,
+            ))]);
 
         let entries: Vec<(String, (Rc<ScopeEntry>, BorrowStatus))> = filters
             .filter(&self.context.borrow().scope)
@@ -482,11 +680,15 @@ impl<'table> ExprGenerator<'table> {
     // Mutable borrow expr DOES NOT remove previous borrows
     // This borrow expr is strictly for instantiation
     fn mut_borrow_expr<R: Rng>(&self, rng: &mut R) -> BorrowExpr {
-        let filters = Filters::new().with_filters(vec![
-            is_type_filter(self.type_id.clone()),
-            is_var_struct_filter(),
-            is_mut_or_mut_ref_filter(),
-        ]);
+        let filters = Filters::new()
+            .with_filters(vec![
+                is_type_filter(self.type_id.clone()),
+                is_var_struct_filter(),
+                is_mut_or_mut_ref_filter(),
+            ])
+            .with_full_filters(vec![is_not_packed_field_filter(Rc::clone(
+                &self.context.borrow().scope,
+            ))]);
 
         let entries = filters.filter(&self.context.borrow().scope);
         let choice = entries.choose(rng);
@@ -521,12 +723,16 @@ impl<'table> ExprGenerator<'table> {
     }
 
     fn immut_borrow_expr<R: Rng>(&self, rng: &mut R) -> BorrowExpr {
-        let filters = Filters::new().with_filters(vec![
-            is_type_filter(self.type_id.clone()),
-            is_not_mut_borrowed_filter(),
-            is_var_filter(),
-            is_struct_filter(),
-        ]);
+        let filters = Filters::new()
+            .with_filters(vec![
+                is_type_filter(self.type_id.clone()),
+                is_not_mut_borrowed_filter(),
+                is_var_filter(),
+                is_struct_filter(),
+            ])
+            .with_full_filters(vec![is_not_packed_field_filter(Rc::clone(
+                &self.context.borrow().scope,
+            ))]);
 
         let entries = filters.filter(&self.context.borrow().scope);
         let choice = entries.choose(rng);
@@ -0,0 +1,9 @@
+/// Optional language features that are only generated when enabled
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Features {
+    pub threads: bool,     // Function calls run on scoped threads
+    pub unsafe_code: bool, // Raw pointer and MaybeUninit accesses in unsafe blocks
+    pub standalone: bool,  // No serde or runtime crate, printing and runtime helpers are inlined
+    pub trace: bool,       // Every statement is followed by a trace, calls never run on threads
+    pub swarm: bool,       // Each seed disables some productions and perturbs the depth limits
+}
@@ -12,6 +12,34 @@ pub fn is_func_filter() -> ScopeBorrowClosure {
     Box::new(|scope_entry, _| scope_entry.is_func())
 }
 
+pub fn is_const_func_filter() -> ScopeBorrowClosure {
+    Box::new(|scope_entry, _| match scope_entry.as_ref() {
+        ScopeEntry::Func(func_entry) => func_entry.get_template().is_const(),
+        _ => false,
+    })
+}
+
+pub fn is_macro_filter() -> ScopeBorrowClosure {
+    Box::new(|scope_entry, _| scope_entry.is_macro())
+}
+
+// Macros are generic over their arguments, so we match on what the expansion can produce
+pub fn macro_produces_type_filter(type_id: TypeID) -> ScopeBorrowClosure {
+    Box::new(move |scope_entry, _| match scope_entry.as_ref() {
+        ScopeEntry::Macro(macro_entry) => macro_entry.get_template().can_produce(&type_id),
+        _ => false,
+    })
+}
+
+pub fn const_generic_produces_type_filter(type_id: TypeID) -> ScopeBorrowClosure {
+    Box::new(move |scope_entry, _| match scope_entry.as_ref() {
+        ScopeEntry::ConstGeneric(const_generic_entry) => {
+            const_generic_entry.get_template().get_type() == type_id
+        }
+        _ => false,
+    })
+}
+
 pub fn is_var_filter() -> ScopeBorrowClosure {
     Box::new(|scope_entry, _| scope_entry.is_var())
 }
@@ -69,6 +97,13 @@ pub fn can_move_filter(scope: Rc<RefCell<Scope>>) -> NameScopeBorrowClosure {
     Box::new(move |entry_name: &String, _, _| -> bool { scope.borrow().can_move_entry(entry_name) })
 }
 
+// References are never taken to fields of packed structs, as they might not be aligned
+pub fn is_not_packed_field_filter(scope: Rc<RefCell<Scope>>) -> NameScopeBorrowClosure {
+    Box::new(move |entry_name: &String, _, _| -> bool {
+        !scope.borrow().is_packed_field(entry_name)
+    })
+}
+
 pub struct Filters {
     filters: Vec<ScopeBorrowClosure>,
     full_filters: Vec<NameScopeBorrowClosure>,
@@ -132,7 +167,7 @@ mod test {
             scope_entry::{StructScopeEntry, VarScopeEntry},
             struct_gen::StructTable,
         },
-        program::{struct_template::StructTemplate, types::IntTypeID},
+        program::{attribute::StructAttribute, struct_template::StructTemplate, types::IntTypeID},
     };
 
     use super::*;
@@ -262,4 +297,48 @@ mod test {
             0
         );
     }
+
+    #[test]
+    fn packed_fields_are_filtered_at_any_depth() {
+        let mut scope = Scope::new();
+
+        let struct_c_type = TypeID::StructType("C".to_string());
+        let struct_c_fields = vec![("field1".to_string(), IntTypeID::I32.as_type())];
+        let struct_c_template = StructTemplate::new_from_fields("C".to_string(), struct_c_fields);
+
+        let struct_p_fields = vec![
+            ("field1".to_string(), IntTypeID::U64.as_type()),
+            ("field2".to_string(), struct_c_type),
+        ];
+        let mut struct_p_template =
+            StructTemplate::new_from_fields("P".to_string(), struct_p_fields);
+        struct_p_template.insert_attribute(StructAttribute::ReprPacked);
+
+        let struct_b_fields = vec![("field1".to_string(), TypeID::StructType("P".to_string()))];
+        let struct_b_template = StructTemplate::new_from_fields("B".to_string(), struct_b_fields);
+
+        let mut struct_table = StructTable::new();
+        struct_table.insert_struct(struct_b_template.clone());
+        struct_table.insert_struct(struct_c_template.clone());
+        struct_table.insert_struct(struct_p_template);
+
+        let entry_b =
+            StructScopeEntry::new(BorrowTypeID::None, struct_b_template, &struct_table, true)
+                .as_scope_entry();
+        let entry_c =
+            StructScopeEntry::new(BorrowTypeID::None, struct_c_template, &struct_table, true)
+                .as_scope_entry();
+        scope.insert(&"b".to_string(), entry_b);
+        scope.insert(&"c".to_string(), entry_c);
+
+        let scope = Rc::new(RefCell::new(scope));
+        let names: Vec<String> = scope
+            .borrow()
+            .filter_with_closure_full(is_not_packed_field_filter(scope.clone()))
+            .into_iter()
+            .map(|(entry_name, _)| entry_name)
+            .collect();
+
+        assert_eq!(names, vec!["b", "b.field1", "c", "c.field1"]);
+    }
 }
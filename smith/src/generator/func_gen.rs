@@ -2,10 +2,17 @@
 use std::{cell::RefCell, rc::Rc};
 
 use super::{
-    consts, context::Context, name_gen::NameGenerator, scope_entry::ScopeEntry,
-    scope_entry::StructScopeEntry, stmt_gen::StmtGenerator, struct_gen, struct_gen::StructTable,
+    context::Context,
+    name_gen::NameGenerator,
+    scope_entry::ScopeEntry,
+    scope_entry::StructScopeEntry,
+    stmt_gen::StmtGenerator,
+    struct_gen,
+    struct_gen::StructTable,
+    weights::{attribute::variants::FunctionAttributeVariants, EnumWeights},
 };
 use crate::program::{
+    attribute::FunctionAttribute,
     function::{Function, Param},
     stmt::block_stmt::BlockStmt,
     types::{BorrowTypeID, TypeID},
@@ -52,7 +59,7 @@ impl<'a> FuncGenerator<'a> {
         let mut param_list: Vec<Param> = Vec::new();
 
         for _ in 0..self.max_params {
-            if rng.gen_range(0.0..1.0) < param_list.len() as f32 / consts::MAX_FUNC_PARAMS as f32 {
+            if rng.gen_range(0.0..1.0) < param_list.len() as f32 / self.max_params as f32 {
                 break;
             }
 
@@ -116,6 +123,94 @@ impl<'a> FuncGenerator<'a> {
         param_list
     }
 
+    // Params of a const function are passed by value and are never structs
+    fn gen_const_params<R: Rng>(&self, context: Rc<RefCell<Context>>, rng: &mut R) -> Vec<Param> {
+        let mut param_name_gen = NameGenerator::new(String::from("param_"));
+        let mut param_list: Vec<Param> = Vec::new();
+
+        for _ in 0..self.max_params {
+            if rng.gen_range(0.0..1.0) < param_list.len() as f32 / self.max_params as f32 {
+                break;
+            }
+
+            let param = Param::new(
+                param_name_gen.next().unwrap(),
+                self.struct_table.rand_primitive_type(rng),
+            );
+
+            context
+                .borrow()
+                .scope
+                .borrow_mut()
+                .insert(&param.get_name(), ScopeEntry::Var(Var::from_param(&param)));
+
+            param_list.push(param);
+        }
+
+        param_list
+    }
+
+    // Main and async functions can not track their caller
+    fn gen_attributes<R: Rng>(
+        &self,
+        context: Rc<RefCell<Context>>,
+        can_track_caller: bool,
+        rng: &mut R,
+    ) -> Vec<FunctionAttribute> {
+        let mut attributes: Vec<FunctionAttribute> = Vec::new();
+        let config = &context.borrow().config;
+
+        for _ in 0..rng.gen_range(0..=config.max_func_attributes) {
+            let attribute =
+                match FunctionAttributeVariants::sample(&config.func_attribute_weights, rng) {
+                    FunctionAttributeVariants::Inline => FunctionAttribute::Inline,
+                    FunctionAttributeVariants::InlineAlways => FunctionAttribute::InlineAlways,
+                    FunctionAttributeVariants::InlineNever => FunctionAttribute::InlineNever,
+                    FunctionAttributeVariants::Cold => FunctionAttribute::Cold,
+                    FunctionAttributeVariants::TrackCaller if can_track_caller => {
+                        FunctionAttribute::TrackCaller
+                    }
+                    FunctionAttributeVariants::TrackCaller => continue,
+                };
+
+            if !attributes.iter().any(|x| x.conflicts_with(&attribute)) {
+                attributes.push(attribute);
+            }
+        }
+
+        attributes
+    }
+
+    // Const functions return a primitive so that the result can be compared against a runtime call
+    pub fn gen_const_func<R: Rng>(
+        &mut self,
+        context: Rc<RefCell<Context>>,
+        rng: &mut R,
+    ) -> Function {
+        context.borrow_mut().enter_scope();
+        context.borrow_mut().in_const_fn = true;
+
+        let params = self.gen_const_params(Rc::clone(&context), rng);
+        let func_name = self.name_gen.next().unwrap();
+        let return_type = self.struct_table.rand_primitive_type(rng);
+        context.borrow_mut().return_type = return_type.clone();
+
+        let mut stmt_generator = StmtGenerator::new(self.struct_table);
+
+        let block_stmt =
+            stmt_generator.block_stmt_with_return(Rc::clone(&context), rng, return_type.clone());
+
+        context.borrow_mut().in_const_fn = false;
+        context.borrow_mut().leave_scope();
+
+        let mut function = Function::new_const(func_name, params, return_type, block_stmt);
+        for attribute in self.gen_attributes(Rc::clone(&context), true, rng) {
+            function.insert_attribute(attribute);
+        }
+
+        function
+    }
+
     // Returns the generated function and whether or not it is the main function
     pub fn gen_func<R: Rng>(
         &mut self,
@@ -125,11 +220,15 @@ impl<'a> FuncGenerator<'a> {
     ) -> Function {
         // Function scope, add params
         context.borrow_mut().enter_scope();
+        context.borrow_mut().in_main = is_main;
 
         let params: Vec<Param>;
         let func_name: String;
         let return_type: TypeID;
 
+        let is_async = !is_main && rng.gen_bool(context.borrow().config.prob_async_func);
+        context.borrow_mut().in_async_fn = is_async;
+
         if is_main {
             params = Vec::new();
             func_name = String::from("main");
@@ -139,6 +238,8 @@ impl<'a> FuncGenerator<'a> {
             func_name = self.name_gen.next().unwrap();
             return_type = self.struct_table.rand_type_with_null(rng);
         }
+        context.borrow_mut().return_type = return_type.clone();
+        context.borrow_mut().function_name = func_name.clone();
 
         let mut stmt_generator = StmtGenerator::new(self.struct_table);
 
@@ -163,8 +264,22 @@ impl<'a> FuncGenerator<'a> {
                 stmt_generator.block_stmt_with_return(Rc::clone(&context), rng, return_type.clone())
         }
 
+        context.borrow_mut().in_main = false;
+        context.borrow_mut().in_async_fn = false;
         context.borrow_mut().leave_scope();
 
-        Function::new(func_name, params, return_type, block_stmt)
+        let mut function = if is_async {
+            Function::new_async(func_name, params, return_type, block_stmt)
+        } else {
+            Function::new(func_name, params, return_type, block_stmt)
+        };
+
+        if !is_main {
+            for attribute in self.gen_attributes(Rc::clone(&context), !is_async, rng) {
+                function.insert_attribute(attribute);
+            }
+        }
+
+        function
     }
 }
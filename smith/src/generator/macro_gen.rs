@@ -0,0 +1,22 @@
+/// Generates macro_rules! definitions
+use rand::Rng;
+
+use crate::program::macro_template::MacroTemplate;
+
+use super::name_gen::NameGenerator;
+
+pub struct MacroGenerator {
+    name_gen: NameGenerator,
+}
+
+impl MacroGenerator {
+    pub fn new() -> Self {
+        MacroGenerator {
+            name_gen: NameGenerator::new(String::from("macro_")),
+        }
+    }
+
+    pub fn gen_macro<R: Rng>(&mut self, rng: &mut R) -> MacroTemplate {
+        MacroTemplate::new(self.name_gen.next().unwrap(), rng.gen())
+    }
+}
@@ -2,42 +2,219 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use crate::program::program::Program;
+use crate::program::{
+    expr::{expr::Expr, func_call_expr::FunctionCallExpr},
+    program::{Module, Program},
+    stmt::const_stmt::ConstStmt,
+    struct_template::StructTemplate,
+    types::BorrowTypeID,
+};
 use rand::Rng;
 
-use super::consts;
+use super::config::GeneratorConfig;
+use super::const_generic_gen::ConstGenericGenerator;
 use super::context::Context;
+use super::expr_gen::ExprGenerator;
+use super::features::Features;
 use super::func_gen::FuncGenerator;
-use super::scope_entry::FuncScopeEntry;
+use super::macro_gen::MacroGenerator;
+use super::name_gen::NameGenerator;
+use super::scope_entry::{ConstGenericScopeEntry, FuncScopeEntry, MacroScopeEntry};
+use super::size_budget::SizeBudget;
+use super::stats::GenerationStats;
+use super::stmt_gen::StmtGenerator;
 use super::struct_gen::StructTable;
+use super::swarm::Swarm;
 
-pub fn gen_main<R: Rng>(rng: &mut R) -> String {
-    let mut func_count: u8 = 0;
+pub fn gen_main<R: Rng>(rng: &mut R, features: Features, config: &GeneratorConfig) -> String {
+    let program = gen_program(rng, 0, features, config);
+
+    format!("{}{}", program.get_header_comment(), program.to_string())
+}
+
+// Picks the module an item is placed in, None if there are no modules
+fn rand_module<R: Rng>(program: &Program, rng: &mut R) -> Option<usize> {
+    match program.module_count() {
+        0 => None,
+        count => Some(rng.gen_range(0..count)),
+    }
+}
+
+pub fn gen_program<R: Rng>(
+    rng: &mut R,
+    min_modules: u32,
+    features: Features,
+    config: &GeneratorConfig,
+) -> Program {
+    let (program, _) = gen_program_with_stats(rng, min_modules, features, config);
+
+    program
+}
+
+// Items are placed in modules up until the first function kept at the top level
+// That way items in modules only ever refer to other items in modules
+pub fn gen_program_with_stats<R: Rng>(
+    rng: &mut R,
+    min_modules: u32,
+    features: Features,
+    config: &GeneratorConfig,
+) -> (Program, GenerationStats) {
+    let mut func_count: u32 = 0;
 
     let mut program = Program::new();
 
-    let context = Rc::new(RefCell::new(Context::new()));
+    // Sampled before anything else, so that the rest of the program is generated under it
+    let swarm = if features.swarm {
+        let swarm = Swarm::sample(config, rng);
+        program.set_header_comment(swarm.to_comment());
+        swarm
+    } else {
+        Swarm::from_config(config)
+    };
+
+    let mut module_name_gen = NameGenerator::new(String::from("module_"));
+    for _ in 0..rng.gen_range(min_modules..=config.max_modules.max(min_modules)) {
+        program.push_module(Module::new(module_name_gen.next().unwrap()));
+    }
 
-    let mut struct_table = StructTable::new();
+    let mut struct_table = StructTable::new()
+        .with_config(config.clone())
+        .with_disabled_int_types(swarm.disabled_int_types.clone());
 
-    let static_struct_template = struct_table.gen_global_struct(rng);
+    let context = Rc::new(RefCell::new(Context::new_with_config(
+        features,
+        config.clone(),
+        swarm,
+    )));
+    context.borrow_mut().size_budget = config
+        .target_size
+        .map(|target_size| SizeBudget::new(target_size, config.max_funcs));
 
-    program.push_struct_template(static_struct_template);
+    let mut static_struct_template = struct_table.gen_global_struct(rng);
+    if !features.standalone {
+        static_struct_template.insert_derive_attribute(String::from("Serialize"));
+    }
+
+    push_struct_template(&mut program, static_struct_template, rng);
     loop {
-        if rng.gen_range(0.0..1.0) < struct_table.len() as f32 / consts::MAX_STRUCTS as f32 {
+        if rng.gen_range(0.0..1.0) < struct_table.len() as f32 / config.max_structs as f32 {
             break;
         }
         let struct_template = struct_table.gen_struct(rng);
-        program.push_struct_template(struct_template);
+        push_struct_template(&mut program, struct_template, rng);
+    }
+
+    let mut macro_gen = MacroGenerator::new();
+    let mut macro_count: u32 = 0;
+    loop {
+        if rng.gen_range(0.0..1.0) < macro_count as f32 / config.max_macros as f32 {
+            break;
+        }
+        let macro_template = macro_gen.gen_macro(rng);
+
+        context.borrow().scope.borrow_mut().insert(
+            &macro_template.get_name(),
+            MacroScopeEntry::new(macro_template.clone()).as_scope_entry(),
+        );
+
+        program.push_macro_template(macro_template);
+        macro_count += 1;
     }
 
-    let mut func_gen = FuncGenerator::new(&struct_table, consts::MAX_FUNC_PARAMS);
+    let mut func_gen = FuncGenerator::new(&struct_table, config.max_func_params);
 
+    // Const functions come first so that every other function can call them
+    // Each one is evaluated in a const item and at runtime, main prints both results
+    let mut const_name_gen = NameGenerator::new(String::from("CONST_"));
+    let mut const_checks: Vec<(String, String)> = Vec::new();
+    loop {
+        if rng.gen_range(0.0..1.0) < const_checks.len() as f32 / config.max_const_funcs as f32 {
+            break;
+        }
+        if let Some(size_budget) = context.borrow_mut().size_budget.as_mut() {
+            size_budget.reserve_const_func();
+        }
+        let function = func_gen.gen_const_func(Rc::clone(&context), rng);
+
+        context.borrow().scope.borrow_mut().insert(
+            &function.get_name(),
+            FuncScopeEntry::new(function.get_return_type(), function.get_template())
+                .as_scope_entry(),
+        );
+
+        // Literal arguments are the same in both evaluations
+        let arguments: Vec<Expr> = function
+            .get_params()
+            .iter()
+            .map(|param| {
+                ExprGenerator::new(
+                    &struct_table,
+                    Rc::clone(&context),
+                    param.get_type(),
+                    BorrowTypeID::None,
+                )
+                .literal_expr(rng)
+            })
+            .collect();
+        let call_expr = FunctionCallExpr::new(function.get_template(), arguments);
+
+        let const_name = const_name_gen.next().unwrap();
+        const_checks.push((const_name.clone(), call_expr.to_string()));
+
+        program.push_const_stmt(ConstStmt::new(
+            const_name,
+            function.get_return_type(),
+            call_expr.as_expr(),
+        ));
+        match rand_module(&program, rng) {
+            Some(index) => program.get_module_mut(index).push_function(function),
+            None => program.push_function(function),
+        }
+    }
+
+    // Generated after const functions so that their bodies can call them
+    let mut const_generic_gen = ConstGenericGenerator::new(&struct_table);
+    let mut const_generic_count: u32 = 0;
+    loop {
+        if rng.gen_range(0.0..1.0) < const_generic_count as f32 / config.max_const_generics as f32 {
+            break;
+        }
+        let const_generic = const_generic_gen.gen_const_generic(Rc::clone(&context), rng);
+
+        let template = const_generic.get_template();
+        context.borrow().scope.borrow_mut().insert(
+            &template.get_name(),
+            ConstGenericScopeEntry::new(template).as_scope_entry(),
+        );
+
+        match rand_module(&program, rng) {
+            Some(index) => program
+                .get_module_mut(index)
+                .push_const_generic(const_generic),
+            None => program.push_const_generic(const_generic),
+        }
+        const_generic_count += 1;
+    }
+
+    let mut in_modules = program.module_count() > 0;
     loop {
         // generate main on some probability proportional to number of generated funcs vs max (linear)
-        let is_main = rng.gen_range(0.0..1.0) < func_count as f32 / consts::MAX_FUNCS as f32;
+        // or once the size budget is nearly spent
+        let is_main = match &context.borrow().size_budget {
+            Some(size_budget) => size_budget.wants_main(),
+            None => rng.gen_range(0.0..1.0) < func_count as f32 / config.max_funcs as f32,
+        };
 
-        let function = func_gen.gen_func(Rc::clone(&context), rng, is_main);
+        reserve_func(&context, is_main);
+        let mut function = func_gen.gen_func(Rc::clone(&context), rng, is_main);
+
+        if is_main {
+            function.push_stmt(StmtGenerator::const_checks_stmt(
+                &const_checks,
+                features.standalone,
+            ));
+            function.push_stmt(StmtGenerator::print_checksum_stmt());
+        }
 
         context.borrow().scope.borrow_mut().insert(
             &function.get_name(),
@@ -45,7 +222,11 @@ pub fn gen_main<R: Rng>(rng: &mut R) -> String {
                 .as_scope_entry(),
         );
 
-        program.push_function(function);
+        in_modules = in_modules && !is_main && rng.gen_bool(config.prob_func_in_module);
+        match rand_module(&program, rng) {
+            Some(index) if in_modules => program.get_module_mut(index).push_function(function),
+            _ => program.push_function(function),
+        }
 
         func_count += 1;
 
@@ -54,5 +235,27 @@ pub fn gen_main<R: Rng>(rng: &mut R) -> String {
         }
     }
 
-    program.to_string()
+    let stats = context.borrow().stats.clone();
+
+    (program, stats)
+}
+
+// The body of the next function gets a share of the whole program, not of a block
+fn reserve_func(context: &Rc<RefCell<Context>>, is_main: bool) {
+    if let Some(size_budget) = context.borrow_mut().size_budget.as_mut() {
+        size_budget.reserve_func(is_main);
+    }
+}
+
+fn push_struct_template<R: Rng>(
+    program: &mut Program,
+    struct_template: StructTemplate,
+    rng: &mut R,
+) {
+    match rand_module(program, rng) {
+        Some(index) => program
+            .get_module_mut(index)
+            .push_struct_template(struct_template),
+        None => program.push_struct_template(struct_template),
+    }
 }
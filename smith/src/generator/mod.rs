@@ -1,19 +1,26 @@
 mod borrow_scope;
+pub mod config;
+mod const_generic_gen;
+pub mod dead_block_gen;
 pub mod context;
 mod expr_gen;
+pub mod features;
 pub mod filters;
 mod func_gen;
+mod macro_gen;
 pub mod main_gen;
 mod name_gen;
+mod pattern_gen;
 pub mod scope;
 pub mod scope_entry;
+pub mod size_budget;
 mod static_gen;
+pub mod stats;
 mod stmt_gen;
 pub mod struct_gen;
+pub mod swarm;
 pub mod weights;
 
-mod consts;
-
 #[cfg(test)]
 mod test {
     use std::cell::RefCell;
@@ -0,0 +1,202 @@
+/// Generates patterns against struct templates and integer types
+use rand::Rng;
+
+use crate::program::{
+    expr::arithmetic_expr::{IntExpr, IntValue},
+    pattern::Pattern,
+    types::TypeID,
+    var::Var,
+};
+
+use super::{name_gen::NameGenerator, struct_gen::StructTable};
+
+pub struct PatternGenerator<'a> {
+    struct_table: &'a StructTable,
+    refutable: bool, // Plain let only takes irrefutable patterns
+    max_depth: u32,
+}
+
+impl<'a> PatternGenerator<'a> {
+    pub fn new(struct_table: &'a StructTable, refutable: bool, max_depth: u32) -> Self {
+        PatternGenerator {
+            struct_table,
+            refutable,
+            max_depth,
+        }
+    }
+
+    // For matches!, where bindings could only be used in a guard
+    pub fn pattern_without_bindings<R: Rng>(&self, type_id: &TypeID, rng: &mut R) -> Pattern {
+        self.pattern_r(type_id, String::new(), 0, None, &mut Vec::new(), rng)
+    }
+
+    // Also returns every binding, along with the path of the value it binds relative to the matched value
+    pub fn pattern<R: Rng>(
+        &self,
+        type_id: &TypeID,
+        var_name_gen: &mut NameGenerator,
+        rng: &mut R,
+    ) -> (Pattern, Vec<(String, Var)>) {
+        let mut bindings: Vec<(String, Var)> = Vec::new();
+        let pattern = self.pattern_r(
+            type_id,
+            String::new(),
+            0,
+            Some(var_name_gen),
+            &mut bindings,
+            rng,
+        );
+
+        (pattern, bindings)
+    }
+
+    // Element i of the tuple is at path i
+    pub fn tuple_pattern<R: Rng>(
+        &self,
+        type_ids: &[TypeID],
+        var_name_gen: &mut NameGenerator,
+        rng: &mut R,
+    ) -> (Pattern, Vec<(String, Var)>) {
+        let mut bindings: Vec<(String, Var)> = Vec::new();
+        let patterns: Vec<Pattern> = type_ids
+            .iter()
+            .enumerate()
+            .map(|(i, type_id)| {
+                self.pattern_r(
+                    type_id,
+                    i.to_string(),
+                    0,
+                    Some(&mut *var_name_gen),
+                    &mut bindings,
+                    rng,
+                )
+            })
+            .collect();
+
+        (Pattern::Tuple(patterns), bindings)
+    }
+
+    fn pattern_r<R: Rng>(
+        &self,
+        type_id: &TypeID,
+        path: String,
+        depth: u32,
+        mut names: Option<&mut NameGenerator>,
+        bindings: &mut Vec<(String, Var)>,
+        rng: &mut R,
+    ) -> Pattern {
+        match rng.gen_range(0..4) {
+            0 => Pattern::Wildcard,
+            1 => match names {
+                Some(names) => Pattern::Binding(self.binding(type_id, path, names, bindings, rng)),
+                None => Pattern::Wildcard,
+            },
+            choice => {
+                // The subpattern of an @ binding binds nothing itself
+                let structured = if choice == 3 && names.is_some() {
+                    self.structured_pattern(type_id, &path, depth, None, bindings, rng)
+                } else {
+                    self.structured_pattern(
+                        type_id,
+                        &path,
+                        depth,
+                        names.as_deref_mut(),
+                        bindings,
+                        rng,
+                    )
+                };
+
+                match (structured, names) {
+                    (Some(pattern), Some(names)) if choice == 3 => {
+                        let var = self.binding(type_id, path, names, bindings, rng);
+                        Pattern::new_at(var, pattern)
+                    }
+                    (Some(pattern), _) => pattern,
+                    (None, Some(names)) => {
+                        Pattern::Binding(self.binding(type_id, path, names, bindings, rng))
+                    }
+                    (None, None) => Pattern::Wildcard,
+                }
+            }
+        }
+    }
+
+    // Ranges and literals can only be generated where the pattern may be refutable
+    fn structured_pattern<R: Rng>(
+        &self,
+        type_id: &TypeID,
+        path: &str,
+        depth: u32,
+        mut names: Option<&mut NameGenerator>,
+        bindings: &mut Vec<(String, Var)>,
+        rng: &mut R,
+    ) -> Option<Pattern> {
+        match type_id {
+            TypeID::IntType(int_type_id) if self.refutable => {
+                let start = IntValue::rand_from_type(*int_type_id, rng);
+                let end = IntValue::rand_from_type(*int_type_id, rng);
+
+                if start <= end {
+                    Some(Pattern::Range(IntExpr::new(start), IntExpr::new(end)))
+                } else {
+                    Some(Pattern::Range(IntExpr::new(end), IntExpr::new(start)))
+                }
+            }
+            TypeID::BoolType if self.refutable => Some(Pattern::Bool(rng.gen())),
+            TypeID::StructType(struct_name) if depth < self.max_depth => {
+                let struct_template = self.struct_table.get_struct_template(struct_name).unwrap();
+
+                let mut fields: Vec<(String, Pattern)> = Vec::new();
+                for (field_name, field_type) in struct_template.fields_iter() {
+                    if rng.gen_bool(0.3) {
+                        continue;
+                    }
+
+                    let field_path = if path.is_empty() {
+                        field_name.clone()
+                    } else {
+                        format!("{}.{}", path, field_name)
+                    };
+
+                    // Sometimes bind the field under its own name, which can only happen once per pattern
+                    let pattern = match names.as_deref_mut() {
+                        Some(_)
+                            if rng.gen_bool(0.3)
+                                && !bindings
+                                    .iter()
+                                    .any(|(_, var)| var.get_name() == *field_name) =>
+                        {
+                            let var = Var::new(field_type.clone(), field_name.clone(), false);
+                            bindings.push((field_path, var.clone()));
+                            Pattern::Binding(var)
+                        }
+                        names => {
+                            self.pattern_r(field_type, field_path, depth + 1, names, bindings, rng)
+                        }
+                    };
+
+                    fields.push((field_name.clone(), pattern));
+                }
+
+                let has_rest = fields.len() < struct_template.num_fields() || rng.gen_bool(0.2);
+
+                Some(Pattern::new_struct(struct_name.clone(), fields, has_rest))
+            }
+            _ => None,
+        }
+    }
+
+    fn binding<R: Rng>(
+        &self,
+        type_id: &TypeID,
+        path: String,
+        names: &mut NameGenerator,
+        bindings: &mut Vec<(String, Var)>,
+        rng: &mut R,
+    ) -> Var {
+        let var = Var::new(type_id.clone(), names.next().unwrap(), rng.gen_bool(0.5));
+        bindings.push((path, var.clone()));
+
+        var
+    }
+}
@@ -282,13 +282,74 @@ impl Scope {
     // Corresponds to a move or remove from scope
     pub fn remove_entry(&mut self, entry_name: &String) {
         if entry_name.contains('.') {
-            let parent_struct_var_name = string_before_first_period(entry_name);
+            let parent_struct_var_name = self.owning_entry_name(entry_name);
             self.remove_struct_scope_entry(&parent_struct_var_name);
         } else {
             self.remove_var_scope_entry(entry_name);
         }
     }
 
+    // Moves a field out of a struct, as in let Struct1 { field_1: var_2, .. } = a
+    // The struct can not be used as a whole anymore but its other fields stay in scope,
+    // they are kept under their full names in the current scope and keep their borrow contexts
+    pub fn partial_move(&mut self, field_name: &String) {
+        let owner_name = self.owning_entry_name(field_name);
+
+        if owner_name == *field_name {
+            self.remove_entry(field_name);
+            return;
+        }
+
+        let owner_entry = match self.lookup(&owner_name) {
+            Some((scope_entry, _)) => scope_entry,
+            None => return,
+        };
+
+        match owner_entry.as_ref() {
+            // Flattened fields would no longer be known to be packed, so the rest goes too
+            ScopeEntry::Struct(struct_scope_entry) if struct_scope_entry.is_packed() => {
+                self.remove_entry(&owner_name)
+            }
+            ScopeEntry::Struct(struct_scope_entry) => {
+                self.remove_scope_entry(&owner_name);
+                for (name, scope_entry) in struct_scope_entry.get_fields_map() {
+                    self.entries
+                        .insert(format!("{}.{}", owner_name, name), scope_entry);
+                }
+
+                // The owner of the field is now one level further down
+                self.partial_move(field_name);
+            }
+            _ => self.remove_entry(field_name),
+        }
+    }
+
+    // The entry a (possibly flattened) name is stored under, the struct variable itself
+    // unless some of its fields have been moved out
+    fn owning_entry_name(&self, entry_name: &str) -> String {
+        let separated: Vec<&str> = entry_name.split('.').collect();
+
+        for i in 0..separated.len() {
+            let prefix = separated[..=i].join(".");
+            if self.contains_entry_r(&prefix) {
+                return prefix;
+            }
+        }
+
+        string_before_first_period(entry_name)
+    }
+
+    fn contains_entry_r(&self, entry_name: &str) -> bool {
+        if self.entries.contains_key(entry_name) {
+            true
+        } else {
+            match &self.parent {
+                Some(parent_scope) => parent_scope.borrow().contains_entry_r(entry_name),
+                None => false,
+            }
+        }
+    }
+
     fn get_borrow_source(&self, entry_name: &String) -> Option<String> {
         let result = self.borrows.get(entry_name);
 
@@ -416,6 +477,111 @@ impl Scope {
         result
     }
 
+    // References that have not been invalidated yet, struct fields are covered by their struct
+    pub fn held_borrows(&self) -> Vec<String> {
+        self.get_all_entries()
+            .into_keys()
+            .filter(|entry_name| {
+                !entry_name.contains('.') && self.get_borrow_source(entry_name).is_some()
+            })
+            .collect()
+    }
+
+    // Integer and bool places declared in this scope, which go out of scope with it
+    // Struct variables are flattened into their fields, places that are mutably borrowed can not be read
+    pub fn checksum_places(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .flat_map(|(entry_name, scope_entry)| self.primitive_places(entry_name, scope_entry))
+            .collect()
+    }
+
+    // Integer and bool places that a statement assigning to the entry has just written
+    // A struct entry is flattened into its fields, like the checksum places
+    pub fn trace_places(&self, entry_name: &str) -> Vec<String> {
+        match self.lookup(entry_name) {
+            Some((scope_entry, _)) => self.primitive_places(entry_name, &scope_entry),
+            None => Vec::new(),
+        }
+    }
+
+    fn primitive_places(&self, entry_name: &str, scope_entry: &ScopeEntry) -> Vec<String> {
+        let mut places: Vec<String> = Vec::new();
+
+        match scope_entry {
+            ScopeEntry::Var(var) => {
+                if var.get_type().is_primitive() && self.can_read_place(entry_name) {
+                    places.push(entry_name.to_string());
+                }
+            }
+            ScopeEntry::Struct(struct_scope_entry) => {
+                for (field_name, field_entry) in struct_scope_entry.get_field_entries() {
+                    let place = format!("{}.{}", entry_name, field_name);
+                    if field_entry.get_type().is_primitive() && self.can_read_place(&place) {
+                        places.push(place);
+                    }
+                }
+            }
+            _ => (),
+        }
+
+        places
+    }
+
+    // Neither the place nor any struct it is a field of can be mutably borrowed
+    // Structs that fields have been moved out of are no longer tracked themselves
+    fn can_read_place(&self, place: &str) -> bool {
+        let separated: Vec<&str> = place.split('.').collect();
+
+        (0..separated.len()).all(|i| match self.borrows.get(&separated[..=i].join(".")) {
+            Some(borrow_context) => borrow_context.get_borrow_status() != BorrowStatus::MutBorrowed,
+            None => i + 1 < separated.len(),
+        })
+    }
+
+    // Places a *mut can be taken from and written through
+    // Writes would pop any reference into the place under Stacked Borrows, so there must be none
+    pub fn ptr_write_places(&self) -> Vec<(String, Rc<ScopeEntry>)> {
+        self.ptr_places(true, |borrow_status| borrow_status == BorrowStatus::None)
+    }
+
+    // Places a *const can be taken from and read through, which only pops mutable references
+    pub fn ptr_read_places(&self) -> Vec<(String, Rc<ScopeEntry>)> {
+        self.ptr_places(false, |borrow_status| {
+            borrow_status != BorrowStatus::MutBorrowed
+        })
+    }
+
+    // Only owned variables and their fields are pointed to, the struct has to allow the access too
+    fn ptr_places<T>(&self, needs_mut: bool, allows_access: T) -> Vec<(String, Rc<ScopeEntry>)>
+    where
+        T: Fn(BorrowStatus) -> bool,
+    {
+        let all_entries = self.get_all_entries();
+
+        all_entries
+            .iter()
+            .filter(|(entry_name, (scope_entry, borrow_status))| {
+                match all_entries.get(&string_before_first_period(entry_name)) {
+                    Some((root_entry, root_borrow_status)) => {
+                        matches!(scope_entry.as_ref(), ScopeEntry::Var(_))
+                            && matches!(
+                                root_entry.as_ref(),
+                                ScopeEntry::Var(_) | ScopeEntry::Struct(_)
+                            )
+                            && root_entry.is_borrow_type(BorrowTypeID::None)
+                            && scope_entry.is_borrow_type(BorrowTypeID::None)
+                            && (!needs_mut || root_entry.is_mut())
+                            && allows_access(*borrow_status)
+                            && allows_access(*root_borrow_status)
+                    }
+                    None => false,
+                }
+            })
+            .map(|(entry_name, (scope_entry, _))| (entry_name.clone(), Rc::clone(scope_entry)))
+            .collect()
+    }
+
     pub fn can_move_entry(&self, entry_name: &str) -> bool {
         match self.lookup(entry_name) {
             Some((scope_entry, borrow_status)) => {
@@ -431,6 +597,38 @@ impl Scope {
         }
     }
 
+    // Fields at any depth below a packed struct, nested structs are laid out unaligned as a whole
+    pub fn is_packed_field(&self, entry_name: &str) -> bool {
+        if !entry_name.contains('.') {
+            return false;
+        }
+
+        // Fields moved out of a struct are stored under their full names, see partial_move
+        let owner_name = self.owning_entry_name(entry_name);
+        let mut scope_entry = match self.lookup(&owner_name) {
+            Some((scope_entry, _)) => scope_entry,
+            None => return false,
+        };
+
+        for field_name in entry_name[owner_name.len()..].split('.').skip(1) {
+            let field_entry = match scope_entry.as_ref() {
+                ScopeEntry::Struct(struct_scope_entry) if struct_scope_entry.is_packed() => {
+                    return true
+                }
+                ScopeEntry::Struct(struct_scope_entry) => {
+                    match struct_scope_entry.get_fields_map().get(field_name) {
+                        Some(field_entry) => Rc::clone(field_entry),
+                        None => return false,
+                    }
+                }
+                _ => return false,
+            };
+            scope_entry = field_entry;
+        }
+
+        false
+    }
+
     pub fn rand_mut<R: Rng>(
         &self,
         rng: &mut R,
@@ -520,6 +718,7 @@ mod test {
             struct_gen::StructTable,
         },
         program::{
+            attribute::StructAttribute,
             struct_template::StructTemplate,
             types::{BorrowTypeID, IntTypeID, TypeID},
             var::Var,
@@ -648,6 +847,160 @@ mod test {
         assert_eq!(scope.borrow_count(&a), 0);
     }
 
+    #[test]
+    fn held_borrows_only_contains_live_references() {
+        let mut scope = Scope::new();
+
+        let a = "a".to_string();
+        let b = "b".to_string();
+        let c = "c".to_string();
+
+        let entry_a = Var::new(TypeID::NullType, a.clone(), true).as_scope_entry();
+        let entry_b = Var::new(TypeID::NullType, b.clone(), false).as_scope_entry();
+        let entry_c = Var::new(TypeID::NullType, c.clone(), false).as_scope_entry();
+
+        scope.insert(&a, entry_a);
+        scope.insert_borrow(&b, entry_b, &a);
+        scope.insert_mut_borrow(&c, entry_c, &a);
+
+        assert_eq!(scope.held_borrows(), vec![b.clone(), c.clone()]);
+
+        scope.use_mut_borrow(&c);
+        assert!(!scope.held_borrows().contains(&b));
+    }
+
+    #[test]
+    fn ptr_places_exclude_referenced_entries() {
+        let mut scope = Scope::new();
+
+        let a = "a".to_string();
+        let b = "b".to_string();
+        let c = "c".to_string();
+
+        let entry_a = Var::new(IntTypeID::U8.as_type(), a.clone(), true).as_scope_entry();
+        let entry_b = Var::new(IntTypeID::U8.as_type(), b.clone(), false).as_scope_entry();
+        let entry_c = Var::new_ref(IntTypeID::U8.as_type(), c.clone(), false).as_scope_entry();
+
+        scope.insert(&a, entry_a);
+        scope.insert(&b, entry_b);
+
+        let write_places = |scope: &Scope| -> Vec<String> {
+            scope
+                .ptr_write_places()
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect()
+        };
+        let read_places = |scope: &Scope| -> Vec<String> {
+            scope
+                .ptr_read_places()
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect()
+        };
+
+        assert_eq!(write_places(&scope), vec![a.clone()]);
+        assert_eq!(read_places(&scope), vec![a.clone(), b.clone()]);
+
+        // A shared reference allows reads of a but no writes, and is not a place itself
+        scope.insert_borrow(&c, entry_c, &a);
+
+        assert!(write_places(&scope).is_empty());
+        assert_eq!(read_places(&scope), vec![a, b]);
+    }
+
+    #[test]
+    /*
+        struct B -> {field1: C, field2: i32}
+        struct C -> {field1: i32}
+
+        let B { field1: c, .. } = a;
+        a and a.field1 are moved, a.field2 can still be borrowed and moved
+    */
+    fn partial_move_keeps_other_fields() {
+        let mut scope = Scope::new();
+
+        let struct_c_type = TypeID::StructType("C".to_string());
+        let struct_c_fields = vec![("field1".to_string(), IntTypeID::I32.as_type())];
+        let struct_c_template = StructTemplate::new_from_fields("C".to_string(), struct_c_fields);
+
+        let struct_b_fields = vec![
+            ("field1".to_string(), struct_c_type),
+            ("field2".to_string(), IntTypeID::I32.as_type()),
+        ];
+        let struct_b_template = StructTemplate::new_from_fields("B".to_string(), struct_b_fields);
+
+        let mut struct_table = StructTable::new();
+        struct_table.insert_struct(struct_b_template.clone());
+        struct_table.insert_struct(struct_c_template);
+
+        let entry_a =
+            StructScopeEntry::new(BorrowTypeID::None, struct_b_template, &struct_table, true)
+                .as_scope_entry();
+
+        let a = "a".to_string();
+        let a_field2 = "a.field2".to_string();
+        scope.insert(&a, entry_a);
+
+        scope.partial_move(&"a.field1".to_string());
+
+        let entry_names: Vec<String> = scope.get_all_entries().into_keys().collect();
+        assert_eq!(entry_names, vec![a_field2.clone()]);
+
+        // The remaining field can not be moved on its own since a is not whole anymore
+        assert!(!scope.can_move_entry(&a_field2));
+
+        scope.borrow_entry(&"b".to_string(), &a_field2);
+        assert_eq!(scope.borrow_count(&a_field2), 1);
+
+        scope.remove_entry(&a_field2);
+        assert!(scope.get_all_entries().is_empty());
+    }
+
+    #[test]
+    /*
+        struct B -> {field1: i32, field2: P}
+        #[repr(packed)] struct P -> {field1: i32, field2: i32}
+
+        let B { field1: c, .. } = a;
+        a.field2 is kept under its full name but its fields are still packed
+        let P { field1: d, .. } = a.field2;
+        a.field2.field2 goes too, as it could otherwise be borrowed
+    */
+    fn packed_fields_stay_packed_after_partial_move() {
+        let mut scope = Scope::new();
+
+        let struct_p_fields = vec![
+            ("field1".to_string(), IntTypeID::I32.as_type()),
+            ("field2".to_string(), IntTypeID::I32.as_type()),
+        ];
+        let mut struct_p_template =
+            StructTemplate::new_from_fields("P".to_string(), struct_p_fields);
+        struct_p_template.insert_attribute(StructAttribute::ReprPacked);
+
+        let struct_b_fields = vec![
+            ("field1".to_string(), IntTypeID::I32.as_type()),
+            ("field2".to_string(), TypeID::StructType("P".to_string())),
+        ];
+        let struct_b_template = StructTemplate::new_from_fields("B".to_string(), struct_b_fields);
+
+        let mut struct_table = StructTable::new();
+        struct_table.insert_struct(struct_b_template.clone());
+        struct_table.insert_struct(struct_p_template);
+
+        let entry_a =
+            StructScopeEntry::new(BorrowTypeID::None, struct_b_template, &struct_table, true)
+                .as_scope_entry();
+        scope.insert(&"a".to_string(), entry_a);
+
+        scope.partial_move(&"a.field1".to_string());
+        assert!(!scope.is_packed_field("a.field2"));
+        assert!(scope.is_packed_field("a.field2.field1"));
+
+        scope.partial_move(&"a.field2.field1".to_string());
+        assert!(scope.get_all_entries().is_empty());
+    }
+
     #[test]
     /*  This test reflects the following case:
         struct A -> {field1: B, field2: i32}
@@ -3,7 +3,9 @@
 use std::{collections::BTreeMap, fmt, rc::Rc};
 
 use crate::program::{
+    const_generic::ConstGenericTemplate,
     function::{FunctionTemplate, Param},
+    macro_template::MacroTemplate,
     struct_template::StructTemplate,
     types::{BorrowTypeID, TypeID},
     var::Var,
@@ -16,6 +18,8 @@ pub enum ScopeEntry {
     Var(VarScopeEntry),
     Func(FuncScopeEntry),
     Struct(StructScopeEntry),
+    Macro(MacroScopeEntry),
+    ConstGeneric(ConstGenericScopeEntry),
 }
 
 impl ScopeEntry {
@@ -24,6 +28,8 @@ impl ScopeEntry {
             Self::Var(entry) => entry.get_type(),
             Self::Func(entry) => entry.get_type(),
             Self::Struct(entry) => entry.get_type(),
+            Self::Macro(_) => TypeID::NullType,
+            Self::ConstGeneric(_) => TypeID::NullType,
         }
     }
 
@@ -32,6 +38,8 @@ impl ScopeEntry {
             Self::Var(entry) => entry.get_borrow_type(),
             Self::Func(_) => BorrowTypeID::None,
             Self::Struct(entry) => entry.get_borrow_type(),
+            Self::Macro(_) => BorrowTypeID::None,
+            Self::ConstGeneric(_) => BorrowTypeID::None,
         }
     }
 
@@ -62,6 +70,14 @@ impl ScopeEntry {
     pub fn is_struct(&self) -> bool {
         matches!(self, Self::Struct(_))
     }
+
+    pub fn is_macro(&self) -> bool {
+        matches!(self, Self::Macro(_))
+    }
+
+    pub fn is_const_generic(&self) -> bool {
+        matches!(self, Self::ConstGeneric(_))
+    }
 }
 
 pub type VarScopeEntry = Var;
@@ -98,6 +114,45 @@ impl FuncScopeEntry {
     }
 }
 
+#[derive(Debug)]
+pub struct MacroScopeEntry {
+    macro_template: MacroTemplate,
+}
+
+impl MacroScopeEntry {
+    pub fn new(macro_template: MacroTemplate) -> Self {
+        MacroScopeEntry { macro_template }
+    }
+
+    pub fn get_template(&self) -> MacroTemplate {
+        self.macro_template.clone()
+    }
+
+    pub fn as_scope_entry(self) -> ScopeEntry {
+        ScopeEntry::Macro(self)
+    }
+}
+
+// The type of the entry is the element type of the arrays it folds, not the entry itself
+#[derive(Debug)]
+pub struct ConstGenericScopeEntry {
+    template: ConstGenericTemplate,
+}
+
+impl ConstGenericScopeEntry {
+    pub fn new(template: ConstGenericTemplate) -> Self {
+        ConstGenericScopeEntry { template }
+    }
+
+    pub fn get_template(&self) -> ConstGenericTemplate {
+        self.template.clone()
+    }
+
+    pub fn as_scope_entry(self) -> ScopeEntry {
+        ScopeEntry::ConstGeneric(self)
+    }
+}
+
 pub struct StructScopeEntry {
     type_id: TypeID,
     borrow_type: BorrowTypeID,
@@ -187,6 +242,10 @@ impl StructScopeEntry {
         self.borrow_type
     }
 
+    pub fn is_packed(&self) -> bool {
+        self.struct_template.is_packed()
+    }
+
     pub fn get_field_entries(&self) -> BTreeMap<String, Rc<ScopeEntry>> {
         self.get_field_entries_r()
     }
@@ -207,6 +266,11 @@ impl StructScopeEntry {
         result
     }
 
+    // Direct fields only, nested structs keep their own fields
+    pub fn get_fields_map(&self) -> BTreeMap<String, Rc<ScopeEntry>> {
+        self.fields_map.clone()
+    }
+
     pub fn remove_field(&mut self, field_name: String) {
         self.fields_map.remove(&field_name);
     }
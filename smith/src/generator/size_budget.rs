@@ -0,0 +1,157 @@
+/// Budget of AST nodes that a program is generated close to, see GeneratorConfig::target_size
+/// Every block gets a share of the budget of the block around it, and adds statements
+/// until its share is spent, the budget of a function body is a share of the whole program
+/// Sizes are those of NodeCount::size, statements and expressions
+use crate::program::{stmt::stmt::Stmt, visitor::passes::NodeCount};
+
+// A nested block gets at most this fraction of the budget of the block around it
+const NESTED_BLOCK_DIVISOR: i64 = 4;
+
+// Functions are not given less than this, so that a tiny target is generated as few functions
+const MIN_FUNC_SIZE: i64 = 40;
+
+#[derive(Clone, Debug, PartialEq)]
+struct BlockBudget {
+    budget: i64,
+    spent: i64, // Includes the nodes of blocks nested in it
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SizeBudget {
+    blocks: Vec<BlockBudget>, // Innermost last, the first one is the whole program
+    func_size: i64,
+    reserved: Option<i64>, // Budget of the next block entered, in place of a share
+}
+
+impl SizeBudget {
+    // The program is split into max_funcs functions of about the same size
+    pub fn new(target_size: u32, max_funcs: u32) -> Self {
+        let target_size = target_size as i64;
+
+        SizeBudget {
+            blocks: vec![BlockBudget {
+                budget: target_size,
+                spent: 0,
+            }],
+            func_size: (target_size / max_funcs.max(1) as i64).max(MIN_FUNC_SIZE),
+            reserved: None,
+        }
+    }
+
+    pub fn remaining(&self) -> i64 {
+        let block = self.blocks.last().unwrap();
+
+        block.budget - block.spent
+    }
+
+    // Main is generated once what is left would not fit another function besides it
+    pub fn wants_main(&self) -> bool {
+        self.remaining() < 2 * self.func_size
+    }
+
+    // Main is given everything that is left, at least half of a function
+    pub fn reserve_func(&mut self, is_main: bool) {
+        let budget = if is_main {
+            self.remaining().max(self.func_size / 2)
+        } else {
+            self.func_size
+        };
+
+        self.reserved = Some(budget);
+    }
+
+    // Const functions are a fraction of the size of other functions, as they are without a budget
+    pub fn reserve_const_func(&mut self) {
+        self.reserved = Some(self.func_size / NESTED_BLOCK_DIVISOR);
+    }
+
+    pub fn enter_block(&mut self) {
+        let budget = match self.reserved.take() {
+            Some(budget) => budget,
+            None => {
+                let share = self.blocks.last().unwrap().budget / NESTED_BLOCK_DIVISOR;
+                share.min(self.remaining())
+            }
+        };
+
+        self.blocks.push(BlockBudget { budget, spent: 0 });
+    }
+
+    pub fn leave_block(&mut self) {
+        let block = self.blocks.pop().unwrap();
+
+        self.blocks.last_mut().unwrap().spent += block.spent;
+    }
+
+    // Every block has at least one statement, even one entered with nothing left
+    pub fn wants_stmt(&self, stmt_count: usize) -> bool {
+        stmt_count == 0 || self.remaining() > 0
+    }
+
+    pub fn spent(&self) -> i64 {
+        self.blocks.last().unwrap().spent
+    }
+
+    // Spends a statement of the current block, which was spent as far as `spent` before it
+    // Blocks nested in the statement already spent their nodes, which the statement includes
+    pub fn spend_stmt(&mut self, spent: i64, stmt: &Stmt) {
+        self.blocks.last_mut().unwrap().spent = spent + NodeCount::of_stmt(stmt).size() as i64;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Generator;
+
+    #[test]
+    fn nested_block_gets_a_share_of_what_is_left() {
+        let mut budget = SizeBudget::new(1000, 5);
+        budget.reserve_func(false);
+        budget.enter_block();
+        assert_eq!(budget.remaining(), 200);
+
+        budget.enter_block();
+        assert_eq!(budget.remaining(), 50);
+        budget.blocks.last_mut().unwrap().spent = 60;
+        assert!(!budget.wants_stmt(1));
+        budget.leave_block();
+
+        assert_eq!(budget.remaining(), 140);
+        budget.enter_block();
+        assert_eq!(budget.remaining(), 50);
+        budget.leave_block();
+        budget.leave_block();
+
+        assert_eq!(budget.remaining(), 940);
+        assert!(!budget.wants_main());
+    }
+
+    #[test]
+    fn generated_size_follows_target_size() {
+        let size_of = |target_size: u32, seed: u64| {
+            Generator::sized(seed, target_size)
+                .generate()
+                .get_metadata()
+                .node_count as f64
+        };
+
+        for &target_size in [1000, 10000].iter() {
+            let sizes: Vec<f64> = (0..4).map(|seed| size_of(target_size, seed)).collect();
+            let mean = sizes.iter().sum::<f64>() / sizes.len() as f64;
+
+            assert!(
+                mean > target_size as f64 * 0.8,
+                "{} for {}",
+                mean,
+                target_size
+            );
+            assert!(
+                mean < target_size as f64 * 1.5,
+                "{} for {}",
+                mean,
+                target_size
+            );
+        }
+    }
+}
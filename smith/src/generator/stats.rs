@@ -0,0 +1,180 @@
+/// Counts of what the generator did while it generated a program, such as how often each
+/// production was picked, and how often a pick could not be generated and was resampled
+/// Stats of many programs are merged into one summary
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::program::{
+    expr::{borrow_expr::BorrowExpr, expr::Expr},
+    types::BorrowTypeID,
+};
+
+// A retry loop that needs at least this fraction of its attempts is close to giving up
+const NEAR_LIMIT_DIVISOR: u64 = 2;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct RetryStats {
+    pub loops: u64,
+    pub attempts: u64, // Includes the last attempt, which succeeded
+    pub max_attempts: u64,
+    pub near_limit: u64, // Loops that needed at least half of their limit
+}
+
+impl RetryStats {
+    fn merge(&mut self, other: &RetryStats) {
+        self.loops += other.loops;
+        self.attempts += other.attempts;
+        self.max_attempts = self.max_attempts.max(other.max_attempts);
+        self.near_limit += other.near_limit;
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ProductionStats {
+    pub generated: BTreeMap<String, u64>,
+    // Picked but not possible where they were picked, such as a variable when none is in scope
+    pub failed: BTreeMap<String, u64>,
+    pub retries: RetryStats,
+    pub depths: BTreeMap<u32, u64>, // How deep each production was generated
+    pub depth_limited: u64,         // Literals generated because the depth limit was reached
+}
+
+impl ProductionStats {
+    pub fn generated<T: std::fmt::Debug>(&mut self, production: T, depth: u32) {
+        *self
+            .generated
+            .entry(format!("{:?}", production))
+            .or_insert(0) += 1;
+        *self.depths.entry(depth).or_insert(0) += 1;
+    }
+
+    pub fn failed<T: std::fmt::Debug>(&mut self, production: T) {
+        *self.failed.entry(format!("{:?}", production)).or_insert(0) += 1;
+    }
+
+    pub fn retried(&mut self, attempts: u64, limit: u64) {
+        self.retries.loops += 1;
+        self.retries.attempts += attempts;
+        self.retries.max_attempts = self.retries.max_attempts.max(attempts);
+        if attempts * NEAR_LIMIT_DIVISOR >= limit {
+            self.retries.near_limit += 1;
+        }
+    }
+
+    pub fn depth_limited(&mut self) {
+        self.depth_limited += 1;
+    }
+
+    fn merge(&mut self, other: &ProductionStats) {
+        merge_counts(&mut self.generated, &other.generated);
+        merge_counts(&mut self.failed, &other.failed);
+        self.retries.merge(&other.retries);
+        merge_counts(&mut self.depths, &other.depths);
+        self.depth_limited += other.depth_limited;
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct GenerationStats {
+    pub programs: u64,
+    pub stmts: ProductionStats, // Depth is the number of loops and conditionals around it
+    pub arith_exprs: ProductionStats,
+    pub bool_exprs: ProductionStats,
+    pub struct_exprs: ProductionStats,
+    // Kind of each borrow expression, and whether it borrows a place or a new value
+    pub borrows: BTreeMap<String, u64>,
+}
+
+impl GenerationStats {
+    pub fn new() -> Self {
+        GenerationStats {
+            programs: 1,
+            ..Default::default()
+        }
+    }
+
+    pub fn borrowed(&mut self, borrow_expr: &BorrowExpr) {
+        let kind = match (borrow_expr.get_borrow_type(), borrow_expr.get_expr()) {
+            (BorrowTypeID::Ref, Expr::Variable(_)) => "Ref",
+            (BorrowTypeID::Ref, _) => "Ref of value",
+            (BorrowTypeID::MutRef, Expr::Variable(_)) => "MutRef",
+            (BorrowTypeID::MutRef, _) => "MutRef of value",
+            (BorrowTypeID::None, _) => "None",
+        };
+        *self.borrows.entry(kind.to_string()).or_insert(0) += 1;
+    }
+
+    pub fn merge(&mut self, other: &GenerationStats) {
+        self.programs += other.programs;
+        self.stmts.merge(&other.stmts);
+        self.arith_exprs.merge(&other.arith_exprs);
+        self.bool_exprs.merge(&other.bool_exprs);
+        self.struct_exprs.merge(&other.struct_exprs);
+        merge_counts(&mut self.borrows, &other.borrows);
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+fn merge_counts<K: Ord + Clone>(counts: &mut BTreeMap<K, u64>, other: &BTreeMap<K, u64>) {
+    for (key, count) in other.iter() {
+        *counts.entry(key.clone()).or_insert(0) += count;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{generator::weights::stmt::variants::StmtVariants, Generator};
+
+    #[test]
+    fn merge_adds_counts_and_keeps_max_attempts() {
+        let mut first = GenerationStats::new();
+        first.stmts.generated(StmtVariants::LetStatement, 0);
+        first.stmts.retried(3, 100);
+        let mut second = GenerationStats::new();
+        second.stmts.generated(StmtVariants::LetStatement, 1);
+        second.stmts.failed(StmtVariants::AssignStatement);
+        second.stmts.retried(60, 100);
+
+        first.merge(&second);
+
+        assert_eq!(first.programs, 2);
+        assert_eq!(first.stmts.generated["LetStatement"], 2);
+        assert_eq!(first.stmts.failed["AssignStatement"], 1);
+        assert_eq!(first.stmts.depths.len(), 2);
+        assert_eq!(
+            first.stmts.retries,
+            RetryStats {
+                loops: 2,
+                attempts: 63,
+                max_attempts: 60,
+                near_limit: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn every_retry_loop_generates_one_production() {
+        let stats = Generator::new(3).generate().get_stats().clone();
+
+        for production_stats in [
+            &stats.stmts,
+            &stats.arith_exprs,
+            &stats.bool_exprs,
+            &stats.struct_exprs,
+        ]
+        .iter()
+        {
+            let generated: u64 = production_stats.generated.values().sum();
+            let failed: u64 = production_stats.failed.values().sum();
+
+            assert!(generated > 0);
+            assert_eq!(production_stats.retries.loops, generated);
+            assert_eq!(production_stats.retries.attempts, generated + failed);
+        }
+    }
+}
@@ -8,14 +8,30 @@ use crate::{
     program::{
         expr::{
             arithmetic_expr::{ArithmeticExpr, BinaryOp, IntExpr},
+            array_expr::ArrayExpr,
             bool_expr::{BoolExpr, ComparisonExpr, ComparisonOp},
             expr::{Expr, RawExpr},
             iter_expr::IterRange,
+            macro_call_expr::MacroCallExpr,
+            tuple_expr::TupleExpr,
         },
         stmt::{
-            assign_stmt::AssignStmt, block_stmt::BlockStmt, conditional_stmt::ConditionalStmt,
-            expr_stmt::ExprStmt, for_loop_stmt::ForLoopStmt, let_stmt::LetStmt,
-            op_assign_stmt::OpAssignStmt, return_stmt::ReturnStmt, stmt::Stmt,
+            assign_stmt::AssignStmt,
+            await_stmt::AwaitStmt,
+            block_stmt::BlockStmt,
+            checksum_stmt::ChecksumStmt,
+            conditional_stmt::ConditionalStmt,
+            expr_stmt::ExprStmt,
+            for_loop_stmt::ForLoopStmt,
+            if_let_stmt::IfLetStmt,
+            let_pattern_stmt::LetPatternStmt,
+            let_stmt::LetStmt,
+            op_assign_stmt::OpAssignStmt,
+            return_stmt::ReturnStmt,
+            stmt::Stmt,
+            thread_scope_stmt::{ThreadScopeStmt, ThreadSpawn},
+            trace_stmt::TraceStmt,
+            unsafe_stmt::UnsafeStmt,
         },
         struct_template::StructTemplate,
         types::{BorrowTypeID, IntTypeID, TypeID},
@@ -24,13 +40,14 @@ use crate::{
 };
 
 use super::{
-    consts,
     context::Context,
     expr_gen::ExprGenerator,
     name_gen::NameGenerator,
+    pattern_gen::PatternGenerator,
     scope_entry::{ScopeEntry, StructScopeEntry},
+    size_budget::SizeBudget,
     struct_gen::{self, StructTable},
-    weights::stmt::variants::StmtVariants,
+    weights::{stmt::variants::StmtVariants, EnumWeights},
 };
 
 pub struct StmtGenerator<'a> {
@@ -51,14 +68,10 @@ impl<'a> StmtGenerator<'a> {
 
         let mut stmt_list: Vec<Stmt> = Vec::new();
 
-        for _ in 0..consts::MAX_STMTS_IN_BLOCK {
-            let mut stmt = self.stmt(Rc::clone(&context), rng);
-            if let Stmt::LoopStatement(for_loop_stmt) = &mut stmt {
-                if rng.gen::<f32>() < consts::PROB_MAX_FOR_LOOP_ITERS {
-                    self.inject_loop_stopper(&mut stmt_list, for_loop_stmt);
-                }
-            }
-            stmt_list.push(stmt);
+        self.push_stmts(Rc::clone(&context), &mut stmt_list, rng);
+
+        if let Some(checksum_stmt) = Self::checksum_stmt(Rc::clone(&context)) {
+            stmt_list.push(checksum_stmt);
         }
 
         context.borrow_mut().leave_scope();
@@ -73,29 +86,178 @@ impl<'a> StmtGenerator<'a> {
         rng: &mut R,
     ) -> BlockStmt {
         let mut stmt_list: Vec<Stmt> =
-            vec![self.global_struct_stmt(struct_template, Rc::clone(&context), rng)];
+            vec![self.global_struct_stmt(struct_template.clone(), Rc::clone(&context), rng)];
+
+        self.push_stmts(Rc::clone(&context), &mut stmt_list, rng);
+
+        if let Some(checksum_stmt) = Self::checksum_stmt(Rc::clone(&context)) {
+            stmt_list.push(checksum_stmt);
+        }
+
+        let print_stmt = if context.borrow().features.standalone {
+            Self::print_global_struct_stmt(&struct_template)
+        } else {
+            let print_serialized = RawExpr::new(format!(
+                "println!(\"{{}}\", (serde_json::to_string(&{}).unwrap()))",
+                struct_gen::GLOBAL_STRUCT_VAR_NAME
+            ))
+            .as_expr();
+
+            ExprStmt::new(print_serialized).as_stmt()
+        };
+
+        stmt_list.push(print_stmt);
 
-        for _ in 0..consts::MAX_STMTS_IN_BLOCK {
+        BlockStmt::new_from_vec(stmt_list)
+    }
+
+    // Pushes max_stmts_in_block statements, or with a size budget, as many as the share of it
+    // that the block gets
+    fn push_stmts<R: Rng>(
+        &mut self,
+        context: Rc<RefCell<Context>>,
+        stmt_list: &mut Vec<Stmt>,
+        rng: &mut R,
+    ) {
+        if let Some(size_budget) = context.borrow_mut().size_budget.as_mut() {
+            size_budget.enter_block();
+        }
+
+        let max_stmts = context.borrow().config.max_stmts_in_block as usize;
+        let mut stmt_count = 0;
+        while Self::wants_stmt(Rc::clone(&context), stmt_count, max_stmts) {
+            let spent = context.borrow().size_budget.as_ref().map(SizeBudget::spent);
             let mut stmt = self.stmt(Rc::clone(&context), rng);
+            if let (Some(spent), Some(size_budget)) =
+                (spent, context.borrow_mut().size_budget.as_mut())
+            {
+                size_budget.spend_stmt(spent, &stmt);
+            }
+
             if let Stmt::LoopStatement(for_loop_stmt) = &mut stmt {
-                if rng.gen::<f32>() < consts::PROB_MAX_FOR_LOOP_ITERS {
-                    self.inject_loop_stopper(&mut stmt_list, for_loop_stmt);
+                if rng.gen::<f32>() < context.borrow().config.prob_max_for_loop_iters {
+                    self.inject_loop_stopper(Rc::clone(&context), stmt_list, for_loop_stmt);
                 }
             }
+            let trace_stmt = Self::trace_stmt(Rc::clone(&context), &stmt);
             stmt_list.push(stmt);
+            if let Some(trace_stmt) = trace_stmt {
+                stmt_list.push(trace_stmt);
+            }
+            stmt_count += 1;
+        }
+
+        if let Some(size_budget) = context.borrow_mut().size_budget.as_mut() {
+            size_budget.leave_block();
+        }
+    }
+
+    fn wants_stmt(context: Rc<RefCell<Context>>, stmt_count: usize, max_stmts: usize) -> bool {
+        match &context.borrow().size_budget {
+            Some(size_budget) => size_budget.wants_stmt(stmt_count),
+            None => stmt_count < max_stmts,
+        }
+    }
+
+    // Folds the variables of the current scope into the checksum before they go out of scope,
+    // so that values which never reach the global struct are still observed
+    // Const functions can not call into the runtime
+    fn checksum_stmt(context: Rc<RefCell<Context>>) -> Option<Stmt> {
+        if context.borrow().in_const_fn {
+            return None;
+        }
+
+        let places = context.borrow().scope.borrow().checksum_places();
+        if places.is_empty() {
+            return None;
+        }
+
+        let function_name = context.borrow().function_name.clone();
+
+        Some(ChecksumStmt::new(function_name, places).as_stmt())
+    }
+
+    // Follows a statement in trace mode, recording the values it assigned under a new statement id
+    // The id is that of the statement just before the trace, both in the AST and the printed program,
+    // and Program::traced_stmt looks the statement up by it
+    fn trace_stmt(context: Rc<RefCell<Context>>, stmt: &Stmt) -> Option<Stmt> {
+        if !context.borrow().features.trace || context.borrow().in_const_fn {
+            return None;
         }
 
-        let print_serialized = RawExpr::new(format!(
-            "println!(\"{{}}\", (serde_json::to_string(&{}).unwrap()))",
-            struct_gen::GLOBAL_STRUCT_VAR_NAME
+        let places = match stmt.assigned_name() {
+            Some(name) => context.borrow().scope.borrow().trace_places(&name),
+            None => Vec::new(),
+        };
+
+        context.borrow_mut().traced_stmt_count += 1;
+        let stmt_id = context.borrow().traced_stmt_count;
+
+        Some(TraceStmt::new(stmt_id, places).as_stmt())
+    }
+
+    // Prints the total checksum and the checksum of each function as a JSON line
+    pub fn print_checksum_stmt() -> Stmt {
+        let print_checksum =
+            RawExpr::new(String::from("println!(\"{}\", checksum::to_json())")).as_expr();
+
+        ExprStmt::new(print_checksum).as_stmt()
+    }
+
+    // Prints the global struct in the same JSON format as serde_json, its fields are all integers
+    fn print_global_struct_stmt(struct_template: &StructTemplate) -> Stmt {
+        let (format_fields, args): (Vec<String>, Vec<String>) = struct_template
+            .fields_iter()
+            .map(|(field_name, _)| {
+                (
+                    format!("\\\"{}\\\":{{}}", field_name),
+                    format!("{}.{}", struct_gen::GLOBAL_STRUCT_VAR_NAME, field_name),
+                )
+            })
+            .unzip();
+
+        let print_fields = RawExpr::new(format!(
+            "println!(\"{{{{{}}}}}\", {})",
+            format_fields.join(","),
+            args.join(", ")
         ))
         .as_expr();
 
-        let print_stmt = ExprStmt::new(print_serialized).as_stmt();
+        ExprStmt::new(print_fields).as_stmt()
+    }
 
-        stmt_list.push(print_stmt);
+    // Prints the compile time and runtime results of each const function call as a JSON line
+    // Without serde, the pairs are formatted the same way serde_json formats tuples
+    pub fn const_checks_stmt(const_checks: &[(String, String)], standalone: bool) -> Stmt {
+        if standalone {
+            let format_pairs: Vec<&str> = const_checks.iter().map(|_| "[{},{}]").collect();
+            let args: Vec<String> = const_checks
+                .iter()
+                .map(|(const_name, call)| format!("{}, {}", const_name, call))
+                .collect();
+
+            let print_checks = RawExpr::new(format!(
+                "println!(\"{{{{\\\"const_checks\\\":[{}]}}}}\", {})",
+                format_pairs.join(","),
+                args.join(", ")
+            ))
+            .as_expr();
+
+            return ExprStmt::new(print_checks).as_stmt();
+        }
 
-        BlockStmt::new_from_vec(stmt_list)
+        let pairs: String = const_checks
+            .iter()
+            .map(|(const_name, call)| format!("({}, {}), ", const_name, call))
+            .collect();
+
+        let print_checks = RawExpr::new(format!(
+            "println!(\"{{{{\\\"const_checks\\\":{{}}}}}}\", serde_json::to_string(&({})).unwrap())",
+            pairs
+        ))
+        .as_expr();
+
+        ExprStmt::new(print_checks).as_stmt()
     }
 
     pub fn block_stmt_with_return<R: Rng>(
@@ -135,10 +297,29 @@ impl<'a> StmtGenerator<'a> {
         stmt_type: StmtVariants,
         rng: &mut R,
     ) -> Option<Stmt> {
+        if !context.borrow().swarm.allows_stmt(stmt_type) {
+            return None;
+        }
+
+        // Loops, trait method calls and non const calls are not allowed in a const fn
+        if context.borrow().in_const_fn
+            && matches!(
+                stmt_type,
+                StmtVariants::LoopStatement
+                    | StmtVariants::OpAssignStatement
+                    | StmtVariants::FuncCallStatement
+                    | StmtVariants::MacroStatement
+                    | StmtVariants::ThreadScopeStatement
+                    | StmtVariants::UnsafeStatement
+            )
+        {
+            return None;
+        }
+
         match stmt_type {
             StmtVariants::LetStatement => Some(self.let_stmt(context, rng).as_stmt()),
             StmtVariants::ConditionalStatement => {
-                if context.borrow().if_depth < consts::MAX_CONDITIONAL_DEPTH {
+                if context.borrow().if_depth < context.borrow().swarm.max_conditional_depth {
                     Some(self.conditional_stmt(context, rng).as_stmt())
                 } else {
                     None
@@ -152,8 +333,7 @@ impl<'a> StmtGenerator<'a> {
                 }
             }
             StmtVariants::OpAssignStatement => {
-                let filters = Filters::new()
-                    .with_filters(vec![is_mut_or_mut_ref_filter(), is_int_type_filter()]);
+                let filters = Self::op_assign_place_filters(&context);
                 if !filters.filter(&context.borrow().scope).is_empty() {
                     Some(self.op_assign_stmt(context, rng).as_stmt())
                 } else {
@@ -161,7 +341,7 @@ impl<'a> StmtGenerator<'a> {
                 }
             }
             StmtVariants::LoopStatement => {
-                if context.borrow().loop_depth < consts::MAX_LOOP_DEPTH {
+                if context.borrow().loop_depth < context.borrow().swarm.max_loop_depth {
                     Some(self.for_loop_stmt(context, rng).as_stmt())
                 } else {
                     None
@@ -179,18 +359,70 @@ impl<'a> StmtGenerator<'a> {
                     None
                 }
             }
+            StmtVariants::MacroStatement => {
+                let var_filters = Self::op_assign_place_filters(&context);
+                let macro_filters = Filters::new().with_filters(vec![
+                    is_macro_filter(),
+                    macro_produces_type_filter(TypeID::NullType),
+                ]);
+                if !var_filters.filter(&context.borrow().scope).is_empty()
+                    && !macro_filters.filter(&context.borrow().scope).is_empty()
+                {
+                    Some(self.macro_stmt(context, rng).as_stmt())
+                } else {
+                    None
+                }
+            }
+            StmtVariants::ThreadScopeStatement => {
+                // Threads are only spawned where the statement runs once, which bounds the thread count
+                // Traced functions are never run on threads, whose traces would interleave
+                if context.borrow().features.threads
+                    && !context.borrow().features.trace
+                    && context.borrow().in_main
+                    && context.borrow().loop_depth == 0
+                    && context
+                        .borrow()
+                        .scope
+                        .borrow()
+                        .contains_filter(is_func_filter())
+                {
+                    Some(self.thread_scope_stmt(context, rng).as_stmt())
+                } else {
+                    None
+                }
+            }
+            StmtVariants::AwaitStatement => {
+                if context.borrow().in_async_fn {
+                    Some(self.await_stmt(context).as_stmt())
+                } else {
+                    None
+                }
+            }
+            StmtVariants::UnsafeStatement => {
+                if context.borrow().features.unsafe_code {
+                    Some(self.unsafe_stmt(context, rng).as_stmt())
+                } else {
+                    None
+                }
+            }
+            StmtVariants::PatternStatement => Some(self.pattern_stmt(context, rng)),
         }
     }
 
     pub fn stmt<R: Rng>(&mut self, context: Rc<RefCell<Context>>, rng: &mut R) -> Stmt {
-        let mut stmt_select: StmtVariants = rng.gen();
+        let mut stmt_select = StmtVariants::sample(&context.borrow().config.stmt_weights, rng);
 
-        let loop_limit = 100;
-        for _ in 0..loop_limit {
+        let loop_limit: u64 = 100;
+        for attempt in 1..=loop_limit {
             if let Some(stmt) = self.try_stmt(Rc::clone(&context), stmt_select, rng) {
+                let depth = context.borrow().loop_depth + context.borrow().if_depth;
+                let stats = &mut context.borrow_mut().stats.stmts;
+                stats.generated(stmt_select, depth);
+                stats.retried(attempt, loop_limit);
                 return stmt;
             } else {
-                stmt_select = rng.gen();
+                context.borrow_mut().stats.stmts.failed(stmt_select);
+                stmt_select = StmtVariants::sample(&context.borrow().config.stmt_weights, rng);
             }
         }
 
@@ -198,8 +430,17 @@ impl<'a> StmtGenerator<'a> {
     }
 
     pub fn let_stmt<R: Rng>(&mut self, context: Rc<RefCell<Context>>, rng: &mut R) -> LetStmt {
-        let rand_type_id = self.struct_table.rand_type(rng);
-        let rand_borrow_type_id: BorrowTypeID = rng.gen();
+        let rand_type_id: TypeID;
+        let rand_borrow_type_id: BorrowTypeID;
+
+        // Const functions only deal with owned primitives
+        if context.borrow().in_const_fn {
+            rand_type_id = self.struct_table.rand_primitive_type(rng);
+            rand_borrow_type_id = BorrowTypeID::None;
+        } else {
+            rand_type_id = self.struct_table.rand_type(rng);
+            rand_borrow_type_id = rng.gen();
+        }
 
         let expr_generator = ExprGenerator::new(
             self.struct_table,
@@ -271,7 +512,12 @@ impl<'a> StmtGenerator<'a> {
     // Takes the stmt list being generated, inserts an initialiser variable
     // Insert a conditional statement into loop to check if initialiser variable > max
     // Insert an increment statement into loop at the end
-    fn inject_loop_stopper(&mut self, stmt_list: &mut Vec<Stmt>, loop_stmt: &mut ForLoopStmt) {
+    fn inject_loop_stopper(
+        &mut self,
+        context: Rc<RefCell<Context>>,
+        stmt_list: &mut Vec<Stmt>,
+        loop_stmt: &mut ForLoopStmt,
+    ) {
         let counter_name = self.var_name_gen.next().unwrap();
         let counter_var = Var::new(IntTypeID::U32.as_type(), counter_name, true);
         let counter_val = IntExpr::new_u32(0).as_expr();
@@ -279,7 +525,7 @@ impl<'a> StmtGenerator<'a> {
 
         stmt_list.push(counter_let_stmt);
 
-        let comparison_val = IntExpr::new_u32(consts::MAX_FOR_LOOP_ITERS);
+        let comparison_val = IntExpr::new_u32(context.borrow().config.max_for_loop_iters);
         let comparison_expr = ComparisonExpr::new(
             counter_var.clone().into(),
             comparison_val.as_arith_expr(),
@@ -308,7 +554,7 @@ impl<'a> StmtGenerator<'a> {
         context: Rc<RefCell<Context>>,
         rng: &mut R,
     ) -> ForLoopStmt {
-        let rand_int_type: IntTypeID = rand::random();
+        let rand_int_type = self.struct_table.rand_int_type(rng);
         let rand_type = rand_int_type.as_type();
 
         context.borrow_mut().loop_depth += 1;
@@ -404,10 +650,9 @@ impl<'a> StmtGenerator<'a> {
             BorrowTypeID::None,
         );
 
+        let max_branches = context.borrow().config.max_conditional_branches;
         loop {
-            if rng.gen_range(0.0..1.0)
-                < conditional_blocks.len() as f32 / consts::MAX_CONDITIONAL_BRANCHES as f32
-            {
+            if rng.gen_range(0.0..1.0) < conditional_blocks.len() as f32 / max_branches as f32 {
                 break;
             }
             context.borrow_mut().enter_scope();
@@ -432,13 +677,21 @@ impl<'a> StmtGenerator<'a> {
         ConditionalStmt::new_from_vec(conditional_blocks, else_body)
     }
 
+    // Op assigning takes a mutable reference to the place, see is_not_packed_field_filter
+    fn op_assign_place_filters(context: &Rc<RefCell<Context>>) -> Filters {
+        Filters::new()
+            .with_filters(vec![is_mut_or_mut_ref_filter(), is_int_type_filter()])
+            .with_full_filters(vec![is_not_packed_field_filter(Rc::clone(
+                &context.borrow().scope,
+            ))])
+    }
+
     pub fn op_assign_stmt<R: Rng>(
         &mut self,
         context: Rc<RefCell<Context>>,
         rng: &mut R,
     ) -> OpAssignStmt {
-        let filters =
-            Filters::new().with_filters(vec![is_mut_or_mut_ref_filter(), is_int_type_filter()]);
+        let filters = Self::op_assign_place_filters(&context);
 
         let var_list = filters.filter(&context.borrow().scope);
         let (var_name, (scope_entry, _)) = var_list.choose(rng).unwrap();
@@ -465,12 +718,62 @@ impl<'a> StmtGenerator<'a> {
 
         context.borrow_mut().leave_scope();
 
-        let op = rng.gen();
+        let op = context.borrow().swarm.rand_binary_op(rng);
         let lhs_var = Var::new(type_id, var_name.clone(), false);
 
         OpAssignStmt::new(lhs_var, expr, op)
     }
 
+    // Statement macro which op assigns each of its arguments to the first one
+    pub fn macro_stmt<R: Rng>(&mut self, context: Rc<RefCell<Context>>, rng: &mut R) -> ExprStmt {
+        let var_filters = Self::op_assign_place_filters(&context);
+        let macro_filters = Filters::new().with_filters(vec![
+            is_macro_filter(),
+            macro_produces_type_filter(TypeID::NullType),
+        ]);
+
+        let var_list = var_filters.filter(&context.borrow().scope);
+        let (var_name, (scope_entry, _)) = var_list.choose(rng).unwrap();
+        let type_id = scope_entry.get_type();
+
+        let macro_list = macro_filters.filter(&context.borrow().scope);
+        let macro_template = match macro_list.choose(rng).unwrap() {
+            (_, (entry, _)) => match entry.as_ref() {
+                ScopeEntry::Macro(macro_scope_entry) => macro_scope_entry.get_template(),
+                _ => panic!("Filter did not return macro entry"),
+            },
+        };
+
+        // What happens in the expr, stays in the expr
+        context.borrow_mut().enter_scope();
+
+        context
+            .borrow()
+            .scope
+            .borrow_mut()
+            .func_mut_borrow(var_name);
+
+        let generator = ExprGenerator::new(
+            self.struct_table,
+            Rc::clone(&context),
+            type_id.clone(),
+            BorrowTypeID::None,
+        );
+
+        let lhs_var = Var::new(type_id, var_name.clone(), false);
+        let mut arguments: Vec<Expr> = vec![lhs_var.as_expr()];
+
+        let (min_args, max_args) = macro_template.get_kind().arg_bounds();
+        for _ in 1..rng.gen_range(min_args..=max_args) {
+            context.borrow_mut().reset_expr_depth();
+            arguments.push(generator.expr(rng));
+        }
+
+        context.borrow_mut().leave_scope();
+
+        ExprStmt::new(MacroCallExpr::new(macro_template, arguments, TypeID::NullType).as_expr())
+    }
+
     pub fn global_struct_stmt<R: Rng>(
         &self,
         struct_template: StructTemplate,
@@ -537,4 +840,469 @@ impl<'a> StmtGenerator<'a> {
 
         ExprStmt::new(func_call_expr.as_expr())
     }
+
+    // Arguments for every thread are generated as if they were one long argument list,
+    // so mutable borrows and moves handed to one thread can not be used by another
+    pub fn thread_scope_stmt<R: Rng>(
+        &mut self,
+        context: Rc<RefCell<Context>>,
+        rng: &mut R,
+    ) -> ThreadScopeStmt {
+        let filters = Filters::new().with_filters(vec![is_func_filter()]);
+        let func_list = filters.filter(&context.borrow().scope);
+
+        let expr_generator = ExprGenerator::new(
+            self.struct_table,
+            Rc::clone(&context),
+            TypeID::NullType,
+            BorrowTypeID::None,
+        );
+
+        let mut spawns: Vec<ThreadSpawn> = Vec::new();
+        let mut results: Vec<Var> = Vec::new();
+
+        let max_threads = context.borrow().config.max_threads;
+        context.borrow_mut().enter_scope();
+        for _ in 0..rng.gen_range(2..=max_threads) {
+            let (_, (entry, _)) = func_list.choose(rng).unwrap();
+
+            let func_template = match entry.as_ref() {
+                ScopeEntry::Func(func_entry) => func_entry.get_template(),
+                _ => panic!("Filter did not return func entry"),
+            };
+
+            context.borrow_mut().reset_expr_depth();
+            let call = expr_generator.func_call_expr_from_template(func_template, rng);
+
+            let result = match call.get_type() {
+                TypeID::NullType => None,
+                type_id => {
+                    let var = Var::new(
+                        type_id,
+                        self.var_name_gen.next().unwrap(),
+                        rng.gen_bool(0.5),
+                    );
+                    results.push(var.clone());
+                    Some(var)
+                }
+            };
+
+            spawns.push(ThreadSpawn::new(
+                call,
+                result,
+                rng.gen_range(0..=u8::MAX as u64),
+            ));
+        }
+        context.borrow_mut().leave_scope();
+
+        // Joined values are owned by the spawning scope
+        for var in results.iter() {
+            self.insert_owned_var(Rc::clone(&context), var);
+        }
+
+        let counter = if rng.gen_bool(context.borrow().config.prob_thread_counter) {
+            let var = Var::new(
+                IntTypeID::U64.as_type(),
+                self.var_name_gen.next().unwrap(),
+                false,
+            );
+            context
+                .borrow()
+                .scope
+                .borrow_mut()
+                .insert(&var.get_name(), var.clone().as_scope_entry());
+            Some(var)
+        } else {
+            None
+        };
+
+        ThreadScopeStmt::new(spawns, counter)
+    }
+
+    pub fn await_stmt(&self, context: Rc<RefCell<Context>>) -> AwaitStmt {
+        let held_borrows = context.borrow().scope.borrow().held_borrows();
+
+        AwaitStmt::new(held_borrows)
+    }
+
+    // Inserts a newly bound owned value, structs keep their flattened fields
+    fn insert_owned_var(&self, context: Rc<RefCell<Context>>, var: &Var) {
+        let scope_entry = match var.get_type() {
+            TypeID::StructType(struct_name) => ScopeEntry::Struct(StructScopeEntry::new(
+                BorrowTypeID::None,
+                self.struct_table.get_struct_template(&struct_name).unwrap(),
+                self.struct_table,
+                var.is_mut(),
+            )),
+            _ => ScopeEntry::Var(var.clone()),
+        };
+
+        context
+            .borrow()
+            .scope
+            .borrow_mut()
+            .insert(&var.get_name(), scope_entry);
+    }
+
+    // Pointers are only taken from places that Scope reports as free of conflicting references
+    pub fn unsafe_stmt<R: Rng>(
+        &mut self,
+        context: Rc<RefCell<Context>>,
+        rng: &mut R,
+    ) -> UnsafeStmt {
+        let write_places: Vec<(String, Rc<ScopeEntry>)> = context
+            .borrow()
+            .scope
+            .borrow()
+            .ptr_write_places()
+            .into_iter()
+            .filter(|(_, scope_entry)| matches!(scope_entry.get_type(), TypeID::IntType(_)))
+            .collect();
+        let read_places: Vec<(String, Rc<ScopeEntry>)> = context
+            .borrow()
+            .scope
+            .borrow()
+            .ptr_read_places()
+            .into_iter()
+            .filter(|(_, scope_entry)| {
+                matches!(
+                    scope_entry.get_type(),
+                    TypeID::IntType(_) | TypeID::BoolType
+                )
+            })
+            .collect();
+
+        let unsafe_stmt = match rng.gen_range(0..4) {
+            0 if !write_places.is_empty() => {
+                let (place_name, scope_entry) = write_places.choose(rng).unwrap();
+                self.ptr_update(Rc::clone(&context), place_name, scope_entry.get_type(), rng)
+            }
+            1 if !read_places.is_empty() => {
+                let (place_name, scope_entry) = read_places.choose(rng).unwrap();
+                let place = Var::new(scope_entry.get_type(), place_name.clone(), false);
+                let result = Var::new(
+                    scope_entry.get_type(),
+                    self.var_name_gen.next().unwrap(),
+                    rng.gen_bool(0.5),
+                );
+                UnsafeStmt::new_ptr_read(result, place)
+            }
+            2 => self.array_ptr(Rc::clone(&context), rng),
+            _ => self.maybe_uninit(Rc::clone(&context), rng),
+        };
+
+        if let UnsafeStmt::PtrRead { result, .. }
+        | UnsafeStmt::ArrayPtr { result, .. }
+        | UnsafeStmt::MaybeUninit { result, .. } = &unsafe_stmt
+        {
+            self.insert_owned_var(context, result);
+        }
+
+        unsafe_stmt
+    }
+
+    fn ptr_update<R: Rng>(
+        &mut self,
+        context: Rc<RefCell<Context>>,
+        place_name: &String,
+        type_id: TypeID,
+        rng: &mut R,
+    ) -> UnsafeStmt {
+        // The place can not be moved or borrowed by the value
+        context.borrow_mut().enter_scope();
+        context
+            .borrow()
+            .scope
+            .borrow_mut()
+            .func_mut_borrow(place_name);
+
+        let expr_generator = ExprGenerator::new(
+            self.struct_table,
+            Rc::clone(&context),
+            type_id.clone(),
+            BorrowTypeID::None,
+        );
+
+        context.borrow_mut().reset_expr_depth();
+        let value = expr_generator.expr(rng);
+
+        context.borrow_mut().leave_scope();
+
+        let place = Var::new(type_id, place_name.clone(), true);
+
+        let op = context.borrow().swarm.rand_binary_op(rng);
+
+        UnsafeStmt::new_ptr_update(place, op, value)
+    }
+
+    fn array_ptr<R: Rng>(&mut self, context: Rc<RefCell<Context>>, rng: &mut R) -> UnsafeStmt {
+        let int_type = self.struct_table.rand_int_type(rng);
+        let type_id = int_type.as_type();
+        let length = rng.gen_range(1..=context.borrow().config.max_unsafe_array_len);
+
+        let expr_generator = ExprGenerator::new(
+            self.struct_table,
+            Rc::clone(&context),
+            type_id.clone(),
+            BorrowTypeID::None,
+        );
+
+        context.borrow_mut().enter_scope();
+        let mut elements: Vec<Expr> = Vec::new();
+        for _ in 0..length {
+            context.borrow_mut().reset_expr_depth();
+            elements.push(expr_generator.expr(rng));
+        }
+        context.borrow_mut().leave_scope();
+
+        let max_writes = context.borrow().config.max_unsafe_array_writes;
+        let writes = (0..rng.gen_range(1..=max_writes))
+            .map(|_| {
+                (
+                    rng.gen_range(0..length),
+                    rng.gen_range(0..length),
+                    context.borrow().swarm.rand_binary_op(rng),
+                    rng.gen_range(0..length),
+                )
+            })
+            .collect();
+
+        let result = Var::new(
+            type_id.clone(),
+            self.var_name_gen.next().unwrap(),
+            rng.gen_bool(0.5),
+        );
+
+        UnsafeStmt::new_array_ptr(
+            result,
+            ArrayExpr::new(type_id, length, elements),
+            writes,
+            rng.gen_range(0..length),
+        )
+    }
+
+    fn maybe_uninit<R: Rng>(&mut self, context: Rc<RefCell<Context>>, rng: &mut R) -> UnsafeStmt {
+        let type_id = self.struct_table.rand_type(rng);
+
+        let result = Var::new(
+            type_id.clone(),
+            self.var_name_gen.next().unwrap(),
+            rng.gen_bool(0.5),
+        );
+
+        context.borrow_mut().enter_scope();
+        let unsafe_stmt = match &type_id {
+            TypeID::StructType(struct_name) => {
+                let struct_template = self.struct_table.get_struct_template(struct_name).unwrap();
+
+                let mut fields: Vec<(String, Expr)> = Vec::new();
+                for (field_name, field_type) in struct_template.fields_iter() {
+                    let expr_generator = ExprGenerator::new(
+                        self.struct_table,
+                        Rc::clone(&context),
+                        field_type.clone(),
+                        BorrowTypeID::None,
+                    );
+                    context.borrow_mut().reset_expr_depth();
+                    fields.push((field_name.clone(), expr_generator.expr(rng)));
+                }
+
+                UnsafeStmt::new_maybe_uninit_struct(result, fields)
+            }
+            _ => {
+                let expr_generator = ExprGenerator::new(
+                    self.struct_table,
+                    Rc::clone(&context),
+                    type_id.clone(),
+                    BorrowTypeID::None,
+                );
+                context.borrow_mut().reset_expr_depth();
+                UnsafeStmt::new_maybe_uninit(result, expr_generator.expr(rng))
+            }
+        };
+        context.borrow_mut().leave_scope();
+
+        unsafe_stmt
+    }
+
+    // Destructures a struct variable, a new value or a tuple of new values with let, let else or if let
+    // Structs bound by the pattern are moved out of the variable, whose other fields stay usable
+    pub fn pattern_stmt<R: Rng>(&mut self, context: Rc<RefCell<Context>>, rng: &mut R) -> Stmt {
+        // Main only diverges out of loops, so that its output is always printed
+        let can_diverge = context.borrow().loop_depth > 0 || !context.borrow().in_main;
+        let is_if_let = context.borrow().if_depth < context.borrow().swarm.max_conditional_depth
+            && rng.gen_bool(0.4);
+        let is_let_else = !is_if_let && can_diverge && rng.gen_bool(0.5);
+
+        let pattern_generator = PatternGenerator::new(
+            self.struct_table,
+            is_if_let || is_let_else,
+            context.borrow().config.max_pattern_depth,
+        );
+        let prob_tuple_pattern = context.borrow().config.prob_tuple_pattern;
+        let max_tuple_pattern_len = context.borrow().config.max_tuple_pattern_len;
+
+        // Moving out of a variable inside a loop would move it again on the next iteration
+        let place = if !context.borrow().in_const_fn
+            && context.borrow().loop_depth == 0
+            && rng.gen_bool(context.borrow().config.prob_pattern_place)
+        {
+            self.movable_struct_place(Rc::clone(&context), rng)
+        } else {
+            None
+        };
+
+        let (expr, pattern, bindings) = match place {
+            Some((place_name, type_id)) => {
+                let (pattern, bindings) =
+                    pattern_generator.pattern(&type_id, &mut self.var_name_gen, rng);
+
+                // Primitive fields are copied and leave the variable as it was
+                for (path, var) in bindings.iter() {
+                    if let TypeID::StructType(_) = var.get_type() {
+                        let moved = if path.is_empty() {
+                            place_name.clone()
+                        } else {
+                            format!("{}.{}", place_name, path)
+                        };
+                        context.borrow().scope.borrow_mut().partial_move(&moved);
+                    }
+                }
+
+                (
+                    Var::new(type_id, place_name, false).as_expr(),
+                    pattern,
+                    bindings,
+                )
+            }
+            None if rng.gen_bool(prob_tuple_pattern) => {
+                let type_ids: Vec<TypeID> = (0..rng.gen_range(2..=max_tuple_pattern_len))
+                    .map(|_| self.pattern_type(Rc::clone(&context), rng))
+                    .collect();
+                let elements: Vec<Expr> = type_ids
+                    .iter()
+                    .map(|type_id| self.new_value_expr(Rc::clone(&context), type_id.clone(), rng))
+                    .collect();
+
+                let (pattern, bindings) =
+                    pattern_generator.tuple_pattern(&type_ids, &mut self.var_name_gen, rng);
+
+                (TupleExpr::new(elements).as_expr(), pattern, bindings)
+            }
+            None => {
+                let type_id = self.pattern_type(Rc::clone(&context), rng);
+                let expr = self.new_value_expr(Rc::clone(&context), type_id.clone(), rng);
+
+                let (pattern, bindings) =
+                    pattern_generator.pattern(&type_id, &mut self.var_name_gen, rng);
+
+                (expr, pattern, bindings)
+            }
+        };
+
+        if is_if_let {
+            context.borrow_mut().if_depth += 1;
+
+            // Bindings are only in scope in the body
+            context.borrow_mut().enter_scope();
+            for (_, var) in bindings.iter() {
+                self.insert_owned_var(Rc::clone(&context), var);
+            }
+            let body = self.block_stmt(Rc::clone(&context), rng);
+            context.borrow_mut().leave_scope();
+
+            let else_body = if rng.gen::<bool>() {
+                Some(self.block_stmt(Rc::clone(&context), rng))
+            } else {
+                None
+            };
+
+            context.borrow_mut().if_depth -= 1;
+
+            IfLetStmt::new(pattern, expr, body, else_body).as_stmt()
+        } else {
+            let let_pattern_stmt = if is_let_else {
+                let else_body = self.diverging_block(Rc::clone(&context), rng);
+                LetPatternStmt::new_else(pattern, expr, else_body)
+            } else {
+                LetPatternStmt::new(pattern, expr)
+            };
+
+            for (_, var) in bindings.iter() {
+                self.insert_owned_var(Rc::clone(&context), var);
+            }
+
+            let_pattern_stmt.as_stmt()
+        }
+    }
+
+    // Const functions only deal with primitives
+    fn pattern_type<R: Rng>(&self, context: Rc<RefCell<Context>>, rng: &mut R) -> TypeID {
+        if context.borrow().in_const_fn {
+            self.struct_table.rand_primitive_type(rng)
+        } else {
+            self.struct_table.rand_type(rng)
+        }
+    }
+
+    fn new_value_expr<R: Rng>(
+        &self,
+        context: Rc<RefCell<Context>>,
+        type_id: TypeID,
+        rng: &mut R,
+    ) -> Expr {
+        let expr_generator = ExprGenerator::new(
+            self.struct_table,
+            Rc::clone(&context),
+            type_id,
+            BorrowTypeID::None,
+        );
+
+        context.borrow_mut().enter_scope();
+        context.borrow_mut().reset_expr_depth();
+        let expr = expr_generator.expr(rng);
+        context.borrow_mut().leave_scope();
+
+        expr
+    }
+
+    // Owned struct variables and struct fields of them that can be moved, never the global struct
+    fn movable_struct_place<R: Rng>(
+        &self,
+        context: Rc<RefCell<Context>>,
+        rng: &mut R,
+    ) -> Option<(String, TypeID)> {
+        let global_struct_type = TypeID::StructType(struct_gen::GLOBAL_STRUCT_NAME.to_string());
+
+        let mut filters = Filters::new().with_filters(vec![
+            is_struct_filter(),
+            is_borrow_type_filter(BorrowTypeID::None),
+        ]);
+        filters.add_full_filter(can_move_filter(Rc::clone(&context.borrow().scope)));
+
+        let places: Vec<(String, TypeID)> = filters
+            .filter(&context.borrow().scope)
+            .into_iter()
+            .map(|(entry_name, (scope_entry, _))| (entry_name, scope_entry.get_type()))
+            .filter(|(_, type_id)| *type_id != global_struct_type)
+            .collect();
+
+        places.choose(rng).cloned()
+    }
+
+    // The else body of let else, leaves the enclosing loop or otherwise the function
+    fn diverging_block<R: Rng>(&self, context: Rc<RefCell<Context>>, rng: &mut R) -> BlockStmt {
+        let return_type = context.borrow().return_type.clone();
+
+        let stmt = if context.borrow().loop_depth > 0 {
+            ExprStmt::new(RawExpr::new("break".to_string()).as_expr()).as_stmt()
+        } else if return_type == TypeID::NullType {
+            ExprStmt::new(RawExpr::new("return".to_string()).as_expr()).as_stmt()
+        } else {
+            let expr = self.new_value_expr(Rc::clone(&context), return_type.clone(), rng);
+            ReturnStmt::new(return_type, expr).as_stmt()
+        };
+
+        BlockStmt::new_from_vec(vec![stmt])
+    }
 }
@@ -1,19 +1,21 @@
 /// Generates structs, and global struct
 /// Main entry point in order to randomly select a type
 use crate::program::{
+    attribute::StructAttribute,
     struct_template::StructTemplate,
     types::{IntTypeID, TypeID, TypeIDVariants},
 };
 use rand::{prelude::SliceRandom, Rng};
 use std::{collections::BTreeMap, fmt};
 
-use super::name_gen::NameGenerator;
-
-const MAX_STRUCT_FIELDS: u32 = 10;
+use super::{
+    config::GeneratorConfig,
+    name_gen::NameGenerator,
+    weights::{attribute::variants::StructAttributeVariants, EnumWeights},
+};
 
 pub const GLOBAL_STRUCT_NAME: &str = "StructGlobal";
 pub const GLOBAL_STRUCT_VAR_NAME: &str = "struct_global";
-const NUM_GLOBAL_STRUCT_FIELDS: u32 = 20;
 
 // Each struct is represented by its name and contains a list of types as fields
 pub struct StructTable {
@@ -21,6 +23,8 @@ pub struct StructTable {
     names: NameGenerator,
     has_global: bool,
     global_struct: Option<StructTemplate>,
+    disabled_int_types: Vec<IntTypeID>, // Left out by swarm testing
+    config: GeneratorConfig,
 }
 
 impl StructTable {
@@ -30,6 +34,27 @@ impl StructTable {
             names: NameGenerator::new(String::from("Struct")),
             has_global: false,
             global_struct: None,
+            disabled_int_types: Vec::new(),
+            config: GeneratorConfig::default(),
+        }
+    }
+
+    pub fn with_config(mut self, config: GeneratorConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_disabled_int_types(mut self, disabled_int_types: Vec<IntTypeID>) -> Self {
+        self.disabled_int_types = disabled_int_types;
+        self
+    }
+
+    pub fn rand_int_type<R: Rng>(&self, rng: &mut R) -> IntTypeID {
+        loop {
+            let int_type_id: IntTypeID = rng.gen();
+            if !self.disabled_int_types.contains(&int_type_id) {
+                return int_type_id;
+            }
         }
     }
 
@@ -38,6 +63,7 @@ impl StructTable {
     }
 
     // We don't want this type to be INSTANTIATED, but we want it to be passed around as function arguments
+    // Its fields are all integers so that it can be printed as JSON with or without serde
     pub fn gen_global_struct<R: Rng>(&mut self, rng: &mut R) -> StructTemplate {
         if self.has_global {
             panic!("Attempting to create another 'global' struct");
@@ -47,18 +73,22 @@ impl StructTable {
         let mut struct_template = StructTemplate::new(name);
         let mut field_name_gen = NameGenerator::new(String::from("field_"));
 
-        for _ in 0..NUM_GLOBAL_STRUCT_FIELDS {
-            let rand_int_type: IntTypeID = rng.gen();
+        for _ in 0..self.config.num_global_struct_fields {
+            let rand_int_type = self.rand_int_type(rng);
             struct_template.insert_field(field_name_gen.next().unwrap(), rand_int_type.as_type());
         }
 
-        struct_template.insert_derive_attribute(String::from("Serialize"));
-
         self.global_struct = Some(struct_template.clone());
 
         struct_template
     }
 
+    // For a table rebuilt from the structs of an existing program
+    pub fn insert_global_struct(&mut self, struct_template: StructTemplate) {
+        self.has_global = true;
+        self.global_struct = Some(struct_template);
+    }
+
     pub fn get_global_struct(&self) -> Option<StructTemplate> {
         self.global_struct.clone()
     }
@@ -105,10 +135,19 @@ impl StructTable {
         let mut field_name_gen = NameGenerator::new(String::from("field_"));
         let mut struct_template = StructTemplate::new(name);
 
+        for attribute in self.gen_attributes(rng) {
+            struct_template.insert_attribute(attribute);
+        }
+
         while rng.gen_range(0.0..1.0)
-            > struct_template.num_fields() as f32 / MAX_STRUCT_FIELDS as f32
+            > struct_template.num_fields() as f32 / self.config.max_struct_fields as f32
         {
-            struct_template.insert_field(field_name_gen.next().unwrap(), self.rand_type(rng))
+            let type_id = if struct_template.is_packed() {
+                self.rand_packed_field_type(rng)
+            } else {
+                self.rand_type(rng)
+            };
+            struct_template.insert_field(field_name_gen.next().unwrap(), type_id)
         }
 
         self.insert_struct(struct_template.clone());
@@ -116,6 +155,52 @@ impl StructTable {
         struct_template
     }
 
+    fn gen_attributes<R: Rng>(&self, rng: &mut R) -> Vec<StructAttribute> {
+        let mut attributes: Vec<StructAttribute> = Vec::new();
+
+        for _ in 0..rng.gen_range(0..=self.config.max_struct_attributes) {
+            let attribute =
+                match StructAttributeVariants::sample(&self.config.struct_attribute_weights, rng) {
+                    StructAttributeVariants::ReprC => StructAttribute::ReprC,
+                    StructAttributeVariants::ReprPacked => StructAttribute::ReprPacked,
+                    StructAttributeVariants::ReprAlign => StructAttribute::ReprAlign(
+                        *self.config.repr_alignments.choose(rng).unwrap(),
+                    ),
+                };
+
+            if !attributes.iter().any(|x| x.conflicts_with(&attribute)) {
+                attributes.push(attribute);
+            }
+        }
+
+        attributes
+    }
+
+    // A packed struct can not contain a struct with an align attribute, even through other structs
+    // Its fields may have any alignment, the generator never takes references to them
+    fn can_be_packed_field(&self, type_id: &TypeID) -> bool {
+        match type_id {
+            TypeID::StructType(struct_name) => {
+                let struct_template = self.get_struct_template(struct_name).unwrap();
+
+                !struct_template.has_align()
+                    && struct_template
+                        .fields_iter()
+                        .all(|(_, field_type)| self.can_be_packed_field(field_type))
+            }
+            _ => true,
+        }
+    }
+
+    fn rand_packed_field_type<R: Rng>(&self, rng: &mut R) -> TypeID {
+        loop {
+            let type_id = self.rand_type(rng);
+            if self.can_be_packed_field(&type_id) {
+                return type_id;
+            }
+        }
+    }
+
     // Should only be called if struct table contains at least 1 struct
     pub fn get_random_struct_name<R: Rng>(&self, rng: &mut R) -> String {
         let names: Vec<String> = self.structs.keys().cloned().collect();
@@ -167,7 +252,7 @@ impl StructTable {
 
                 // Inclusive of the NullType
                 TypeIDVariants::IntType => {
-                    let int_type_id: IntTypeID = rng.gen();
+                    let int_type_id = self.rand_int_type(rng);
                     return int_type_id.as_type();
                 }
 
@@ -206,7 +291,7 @@ impl StructTable {
 
                 // Inclusive of the NullType
                 TypeIDVariants::IntType => {
-                    let int_type_id: IntTypeID = rng.gen();
+                    let int_type_id = self.rand_int_type(rng);
                     return int_type_id.as_type();
                 }
 
@@ -220,6 +305,16 @@ impl StructTable {
         }
     }
 
+    // Either an integer or a bool, never a struct
+    pub fn rand_primitive_type<R: Rng>(&self, rng: &mut R) -> TypeID {
+        if rng.gen::<bool>() {
+            let int_type_id = self.rand_int_type(rng);
+            int_type_id.as_type()
+        } else {
+            TypeID::BoolType
+        }
+    }
+
     pub fn rand_type_with_null<R: Rng>(&self, rng: &mut R) -> TypeID {
         let mut loop_limit = 20;
         loop {
@@ -242,7 +337,7 @@ impl StructTable {
                 // No null types in struct
                 TypeIDVariants::NullType => return TypeID::NullType,
                 TypeIDVariants::IntType => {
-                    let int_type_id: IntTypeID = rng.gen();
+                    let int_type_id = self.rand_int_type(rng);
                     return int_type_id.as_type();
                 }
                 TypeIDVariants::BoolType => return TypeID::BoolType,
@@ -263,15 +358,33 @@ impl fmt::Debug for StructTable {
 
 #[cfg(test)]
 mod test {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+
     use super::*;
     #[test]
     fn creates_new_symbol_with_correct_name() {
         let mut table = StructTable::new();
-        table.gen_struct(&mut rand::thread_rng());
+        table.gen_struct(&mut ChaCha12Rng::seed_from_u64(0));
 
         assert_eq!(table.len(), 1);
     }
 
+    #[test]
+    fn packed_structs_never_contain_aligned_structs() {
+        let mut table = StructTable::new();
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+
+        for _ in 0..50 {
+            let struct_template = table.gen_struct(&mut rng);
+            if struct_template.is_packed() {
+                assert!(struct_template
+                    .fields_iter()
+                    .all(|(_, field_type)| table.can_be_packed_field(field_type)));
+            }
+        }
+    }
+
     #[test]
     fn flattens_struct_properly() {
         /* CONSTRUCTING THE STRUCT TABLE AND STRUCT TEMPLATES */
@@ -0,0 +1,175 @@
+/// Swarm testing: each seed generates with a random subset of the productions
+/// Productions that are disabled are resampled wherever they are chosen
+use rand::Rng;
+use strum::IntoEnumIterator;
+
+use crate::program::{expr::arithmetic_expr::BinaryOp, types::IntTypeID};
+
+use super::{
+    config::GeneratorConfig,
+    weights::{expr::variants::ArithmeticExprVariants, stmt::variants::StmtVariants},
+};
+
+const INT_TYPES: [IntTypeID; 10] = [
+    IntTypeID::I8,
+    IntTypeID::I16,
+    IntTypeID::I32,
+    IntTypeID::I64,
+    IntTypeID::I128,
+    IntTypeID::U8,
+    IntTypeID::U16,
+    IntTypeID::U32,
+    IntTypeID::U64,
+    IntTypeID::U128,
+];
+
+// Probability that a production is left out of a seed's configuration
+const PROB_DISABLED: f64 = 0.5;
+
+#[derive(Clone, Debug)]
+pub struct Swarm {
+    pub disabled_stmts: Vec<StmtVariants>,
+    pub disabled_arith_exprs: Vec<ArithmeticExprVariants>,
+    pub disabled_binary_ops: Vec<BinaryOp>,
+    pub disabled_int_types: Vec<IntTypeID>,
+    pub max_expr_depth: u32,
+    pub max_arith_expr_depth: u32,
+    pub max_bool_expr_depth: u32,
+    pub max_conditional_depth: u32,
+    pub max_loop_depth: u32,
+}
+
+impl Swarm {
+    // Let statements and integer literals always remain, since every other production can fail
+    // At least one binary operator and one integer type remain
+    pub fn sample<R: Rng>(config: &GeneratorConfig, rng: &mut R) -> Self {
+        let disabled_stmts = StmtVariants::iter()
+            .filter(|variant| !matches!(variant, StmtVariants::LetStatement))
+            .filter(|_| rng.gen_bool(PROB_DISABLED))
+            .collect();
+        let disabled_arith_exprs = ArithmeticExprVariants::iter()
+            .filter(|variant| !matches!(variant, ArithmeticExprVariants::Int))
+            .filter(|_| rng.gen_bool(PROB_DISABLED))
+            .collect();
+        let disabled_binary_ops = Self::disable_some(BinaryOp::iter().collect(), rng);
+        let disabled_int_types = Self::disable_some(INT_TYPES.to_vec(), rng);
+
+        Swarm {
+            disabled_stmts,
+            disabled_arith_exprs,
+            disabled_binary_ops,
+            disabled_int_types,
+            max_expr_depth: Self::perturb(config.max_expr_depth, rng),
+            max_arith_expr_depth: Self::perturb(config.max_arith_expr_depth, rng),
+            max_bool_expr_depth: Self::perturb(config.max_bool_expr_depth, rng),
+            max_conditional_depth: Self::perturb(config.max_conditional_depth, rng),
+            max_loop_depth: Self::perturb(config.max_loop_depth, rng),
+        }
+    }
+
+    fn disable_some<T: Copy, R: Rng>(variants: Vec<T>, rng: &mut R) -> Vec<T> {
+        let mut disabled: Vec<T> = variants
+            .iter()
+            .copied()
+            .filter(|_| rng.gen_bool(PROB_DISABLED))
+            .collect();
+
+        if disabled.len() == variants.len() {
+            let kept = rng.gen_range(0..disabled.len());
+            disabled.remove(kept);
+        }
+
+        disabled
+    }
+
+    // Somewhere between 1 and the default, limits are never raised since the size of a program
+    // grows exponentially with them
+    fn perturb<R: Rng>(default: u32, rng: &mut R) -> u32 {
+        rng.gen_range(default.min(1)..=default)
+    }
+
+    pub fn allows_stmt(&self, stmt: StmtVariants) -> bool {
+        !self.disabled_stmts.contains(&stmt)
+    }
+
+    pub fn allows_arith_expr(&self, expr: ArithmeticExprVariants) -> bool {
+        !self.disabled_arith_exprs.contains(&expr)
+    }
+
+    pub fn rand_binary_op<R: Rng>(&self, rng: &mut R) -> BinaryOp {
+        loop {
+            let op: BinaryOp = rng.gen();
+            if !self.disabled_binary_ops.contains(&op) {
+                return op;
+            }
+        }
+    }
+
+    // The configuration as comment lines, so that the generated file records what it was generated with
+    pub fn to_comment(&self) -> String {
+        format!(
+            "// Swarm configuration\n\
+            // Disabled statements: {:?}\n\
+            // Disabled arithmetic expressions: {:?}\n\
+            // Disabled binary operators: {:?}\n\
+            // Disabled integer types: {:?}\n\
+            // Max expression depth: {}, arithmetic: {}, bool: {}, conditional: {}, loop: {}\n",
+            self.disabled_stmts,
+            self.disabled_arith_exprs,
+            self.disabled_binary_ops,
+            self.disabled_int_types,
+            self.max_expr_depth,
+            self.max_arith_expr_depth,
+            self.max_bool_expr_depth,
+            self.max_conditional_depth,
+            self.max_loop_depth,
+        )
+    }
+
+    // Every production enabled, with the configured depth limits
+    pub fn from_config(config: &GeneratorConfig) -> Self {
+        Swarm {
+            disabled_stmts: Vec::new(),
+            disabled_arith_exprs: Vec::new(),
+            disabled_binary_ops: Vec::new(),
+            disabled_int_types: Vec::new(),
+            max_expr_depth: config.max_expr_depth,
+            max_arith_expr_depth: config.max_arith_expr_depth,
+            max_bool_expr_depth: config.max_bool_expr_depth,
+            max_conditional_depth: config.max_conditional_depth,
+            max_loop_depth: config.max_loop_depth,
+        }
+    }
+}
+
+impl Default for Swarm {
+    fn default() -> Self {
+        Swarm::from_config(&GeneratorConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+
+    use super::*;
+
+    #[test]
+    fn sample_keeps_a_production_of_each_kind() {
+        let config = GeneratorConfig::default();
+        for seed in 0..100 {
+            let swarm = Swarm::sample(&config, &mut ChaCha12Rng::seed_from_u64(seed));
+
+            assert!(swarm.allows_stmt(StmtVariants::LetStatement));
+            assert!(swarm.allows_arith_expr(ArithmeticExprVariants::Int));
+            assert!(swarm.disabled_binary_ops.len() < BinaryOp::iter().count());
+            assert!(swarm.disabled_int_types.len() < INT_TYPES.len());
+            assert!(swarm.max_loop_depth >= 1 && swarm.max_loop_depth <= config.max_loop_depth);
+            assert!(swarm.max_expr_depth <= config.max_expr_depth);
+            assert!(swarm.max_arith_expr_depth <= config.max_arith_expr_depth);
+            assert!(swarm.max_bool_expr_depth <= config.max_bool_expr_depth);
+            assert!(swarm.max_conditional_depth <= config.max_conditional_depth);
+        }
+    }
+}
@@ -0,0 +1,2 @@
+pub mod variants;
+pub mod weights;
@@ -0,0 +1,17 @@
+use strum_macros::{EnumCount, EnumIter};
+
+#[derive(EnumCount, EnumIter, Clone, Copy)]
+pub enum FunctionAttributeVariants {
+    Inline,
+    InlineAlways,
+    InlineNever,
+    Cold,
+    TrackCaller,
+}
+
+#[derive(EnumCount, EnumIter, Clone, Copy)]
+pub enum StructAttributeVariants {
+    ReprC,
+    ReprPacked,
+    ReprAlign,
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+use crate::generator::weights::EnumWeights;
+
+use super::variants::{FunctionAttributeVariants, StructAttributeVariants};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FunctionAttributeWeights {
+    pub inline: u32,
+    pub inline_always: u32,
+    pub inline_never: u32,
+    pub cold: u32,
+    pub track_caller: u32,
+}
+
+impl Default for FunctionAttributeWeights {
+    fn default() -> Self {
+        FunctionAttributeWeights {
+            inline: 2,
+            inline_always: 2,
+            inline_never: 2,
+            cold: 1,
+            track_caller: 1,
+        }
+    }
+}
+
+impl EnumWeights for FunctionAttributeVariants {
+    type Weights = FunctionAttributeWeights;
+
+    fn weight(&self, weights: &FunctionAttributeWeights) -> u32 {
+        match self {
+            FunctionAttributeVariants::Inline => weights.inline,
+            FunctionAttributeVariants::InlineAlways => weights.inline_always,
+            FunctionAttributeVariants::InlineNever => weights.inline_never,
+            FunctionAttributeVariants::Cold => weights.cold,
+            FunctionAttributeVariants::TrackCaller => weights.track_caller,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StructAttributeWeights {
+    pub repr_c: u32,
+    pub repr_packed: u32,
+    pub repr_align: u32,
+}
+
+impl Default for StructAttributeWeights {
+    fn default() -> Self {
+        StructAttributeWeights {
+            repr_c: 2,
+            repr_packed: 1,
+            repr_align: 1,
+        }
+    }
+}
+
+impl EnumWeights for StructAttributeVariants {
+    type Weights = StructAttributeWeights;
+
+    fn weight(&self, weights: &StructAttributeWeights) -> u32 {
+        match self {
+            StructAttributeVariants::ReprC => weights.repr_c,
+            StructAttributeVariants::ReprPacked => weights.repr_packed,
+            StructAttributeVariants::ReprAlign => weights.repr_align,
+        }
+    }
+}
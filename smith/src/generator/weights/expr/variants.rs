@@ -1,21 +1,16 @@
-use rand::{
-    distributions::{Distribution, Standard, WeightedIndex},
-    Rng,
-};
-use strum::IntoEnumIterator;
 use strum_macros::{EnumCount, EnumIter};
 
-use super::super::EnumWeights;
-
-#[derive(EnumCount, EnumIter, Clone, Copy)]
+#[derive(EnumCount, EnumIter, Clone, Copy, Debug, PartialEq)]
 pub enum ArithmeticExprVariants {
     Int,
     Binary,
     Var,
     Func,
+    Macro,
+    ConstGeneric,
 }
 
-#[derive(EnumCount, EnumIter, Clone, Copy)]
+#[derive(EnumCount, EnumIter, Clone, Copy, Debug)]
 pub enum BoolExprVariants {
     Bool,
     Binary,
@@ -23,44 +18,13 @@ pub enum BoolExprVariants {
     Negation,
     Var,
     Func,
+    Macro,
+    Matches,
 }
 
-#[derive(EnumCount, EnumIter, Clone, Copy)]
+#[derive(EnumCount, EnumIter, Clone, Copy, Debug)]
 pub enum StructExprVariants {
     Literal,
     Var,
     Func,
 }
-
-impl Distribution<ArithmeticExprVariants> for Standard {
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> ArithmeticExprVariants {
-        let choices: Vec<ArithmeticExprVariants> = ArithmeticExprVariants::iter().collect();
-
-        let dist = WeightedIndex::new(ArithmeticExprVariants::weights()).unwrap();
-        let idx = dist.sample(rng);
-
-        choices[idx]
-    }
-}
-
-impl Distribution<BoolExprVariants> for Standard {
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> BoolExprVariants {
-        let choices: Vec<BoolExprVariants> = BoolExprVariants::iter().collect();
-
-        let dist = WeightedIndex::new(BoolExprVariants::weights()).unwrap();
-        let idx = dist.sample(rng);
-
-        choices[idx]
-    }
-}
-
-impl Distribution<StructExprVariants> for Standard {
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> StructExprVariants {
-        let choices: Vec<StructExprVariants> = StructExprVariants::iter().collect();
-
-        let dist = WeightedIndex::new(StructExprVariants::weights()).unwrap();
-        let idx = dist.sample(rng);
-
-        choices[idx]
-    }
-}
@@ -1,37 +1,121 @@
+use serde::{Deserialize, Serialize};
+
 use crate::generator::weights::EnumWeights;
 
 use super::variants::{ArithmeticExprVariants, BoolExprVariants, StructExprVariants};
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ArithmeticExprWeights {
+    pub int: u32,
+    pub binary: u32,
+    pub var: u32,
+    pub func: u32,
+    #[serde(rename = "macro")]
+    pub macro_call: u32,
+    pub const_generic: u32,
+}
+
+impl Default for ArithmeticExprWeights {
+    fn default() -> Self {
+        ArithmeticExprWeights {
+            int: 2,
+            binary: 4,
+            var: 2,
+            func: 2,
+            macro_call: 1,
+            const_generic: 1,
+        }
+    }
+}
+
 impl EnumWeights for ArithmeticExprVariants {
-    fn weight(&self) -> u32 {
+    type Weights = ArithmeticExprWeights;
+
+    fn weight(&self, weights: &ArithmeticExprWeights) -> u32 {
         match self {
-            ArithmeticExprVariants::Int => 2,
-            ArithmeticExprVariants::Binary => 4,
-            ArithmeticExprVariants::Var => 2,
-            ArithmeticExprVariants::Func => 2,
+            ArithmeticExprVariants::Int => weights.int,
+            ArithmeticExprVariants::Binary => weights.binary,
+            ArithmeticExprVariants::Var => weights.var,
+            ArithmeticExprVariants::Func => weights.func,
+            ArithmeticExprVariants::Macro => weights.macro_call,
+            ArithmeticExprVariants::ConstGeneric => weights.const_generic,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BoolExprWeights {
+    pub bool: u32,
+    pub binary: u32,
+    pub comparison: u32,
+    pub negation: u32,
+    pub var: u32,
+    pub func: u32,
+    #[serde(rename = "macro")]
+    pub macro_call: u32,
+    pub matches: u32,
+}
+
+impl Default for BoolExprWeights {
+    fn default() -> Self {
+        BoolExprWeights {
+            bool: 1,
+            binary: 4,
+            comparison: 3,
+            negation: 3,
+            var: 2,
+            func: 2,
+            macro_call: 1,
+            matches: 1,
         }
     }
 }
 
 impl EnumWeights for BoolExprVariants {
-    fn weight(&self) -> u32 {
+    type Weights = BoolExprWeights;
+
+    fn weight(&self, weights: &BoolExprWeights) -> u32 {
         match self {
-            BoolExprVariants::Bool => 1,
-            BoolExprVariants::Binary => 4,
-            BoolExprVariants::Comparison => 3,
-            BoolExprVariants::Negation => 3,
-            BoolExprVariants::Var => 2,
-            BoolExprVariants::Func => 2,
+            BoolExprVariants::Bool => weights.bool,
+            BoolExprVariants::Binary => weights.binary,
+            BoolExprVariants::Comparison => weights.comparison,
+            BoolExprVariants::Negation => weights.negation,
+            BoolExprVariants::Var => weights.var,
+            BoolExprVariants::Func => weights.func,
+            BoolExprVariants::Macro => weights.macro_call,
+            BoolExprVariants::Matches => weights.matches,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StructExprWeights {
+    pub literal: u32,
+    pub var: u32,
+    pub func: u32,
+}
+
+impl Default for StructExprWeights {
+    fn default() -> Self {
+        StructExprWeights {
+            literal: 1,
+            var: 2,
+            func: 1,
         }
     }
 }
 
 impl EnumWeights for StructExprVariants {
-    fn weight(&self) -> u32 {
+    type Weights = StructExprWeights;
+
+    fn weight(&self, weights: &StructExprWeights) -> u32 {
         match self {
-            StructExprVariants::Literal => 1,
-            StructExprVariants::Var => 2,
-            StructExprVariants::Func => 1,
+            StructExprVariants::Literal => weights.literal,
+            StructExprVariants::Var => weights.var,
+            StructExprVariants::Func => weights.func,
         }
     }
 }
@@ -1,13 +1,31 @@
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng,
+};
 use strum::IntoEnumIterator;
 
+pub mod attribute;
 pub mod expr;
 pub mod stmt;
 pub mod type_weights;
 
-trait EnumWeights: IntoEnumIterator {
-    fn weights() -> Vec<u32> {
-        Self::iter().map(|x| x.weight()).collect::<Vec<u32>>()
+// The weights of each variant are read from the generator config, see config.rs
+pub trait EnumWeights: IntoEnumIterator + Copy {
+    type Weights;
+
+    fn weight(&self, weights: &Self::Weights) -> u32;
+
+    fn all_zero(weights: &Self::Weights) -> bool {
+        Self::iter().all(|choice| choice.weight(weights) == 0)
     }
 
-    fn weight(&self) -> u32;
+    fn sample<R: Rng + ?Sized>(weights: &Self::Weights, rng: &mut R) -> Self {
+        let choices: Vec<Self> = Self::iter().collect();
+
+        let dist = WeightedIndex::new(choices.iter().map(|choice| choice.weight(weights)))
+            .expect("Production weights can not all be zero");
+        let idx = dist.sample(rng);
+
+        choices[idx]
+    }
 }
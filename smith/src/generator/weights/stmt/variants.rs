@@ -1,13 +1,6 @@
-use rand::{
-    distributions::{Distribution, Standard, WeightedIndex},
-    Rng,
-};
-use strum::IntoEnumIterator;
 use strum_macros::{EnumCount, EnumIter};
 
-use crate::generator::weights::EnumWeights;
-
-#[derive(EnumCount, EnumIter, Clone, Copy)]
+#[derive(EnumCount, EnumIter, Clone, Copy, Debug, PartialEq)]
 pub enum StmtVariants {
     LetStatement,
     ConditionalStatement,
@@ -15,15 +8,9 @@ pub enum StmtVariants {
     LoopStatement,
     OpAssignStatement,
     FuncCallStatement,
-}
-
-impl Distribution<StmtVariants> for Standard {
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> StmtVariants {
-        let choices: Vec<StmtVariants> = StmtVariants::iter().collect();
-
-        let dist = WeightedIndex::new(StmtVariants::weights()).unwrap();
-        let idx = dist.sample(rng);
-
-        choices[idx]
-    }
+    MacroStatement,
+    ThreadScopeStatement,
+    AwaitStatement,
+    UnsafeStatement,
+    PatternStatement,
 }
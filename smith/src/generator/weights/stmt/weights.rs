@@ -1,16 +1,59 @@
+use serde::{Deserialize, Serialize};
+
 use crate::generator::weights::EnumWeights;
 
 use super::variants::StmtVariants;
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StmtWeights {
+    pub let_statement: u32,
+    pub conditional_statement: u32,
+    pub assign_statement: u32,
+    pub loop_statement: u32,
+    pub op_assign_statement: u32,
+    pub func_call_statement: u32,
+    pub macro_statement: u32,
+    pub thread_scope_statement: u32,
+    pub await_statement: u32,
+    pub unsafe_statement: u32,
+    pub pattern_statement: u32,
+}
+
+impl Default for StmtWeights {
+    fn default() -> Self {
+        StmtWeights {
+            let_statement: 2,
+            conditional_statement: 1,
+            assign_statement: 3,
+            loop_statement: 1,
+            op_assign_statement: 2,
+            func_call_statement: 2,
+            macro_statement: 1,
+            thread_scope_statement: 1,
+            await_statement: 1,
+            unsafe_statement: 1,
+            pattern_statement: 1,
+        }
+    }
+}
+
 impl EnumWeights for StmtVariants {
-    fn weight(&self) -> u32 {
+    type Weights = StmtWeights;
+
+    fn weight(&self, weights: &StmtWeights) -> u32 {
         match self {
-            StmtVariants::LetStatement => 2,
-            StmtVariants::ConditionalStatement => 1,
-            StmtVariants::AssignStatement => 3,
-            StmtVariants::LoopStatement => 1,
-            StmtVariants::OpAssignStatement => 2,
-            StmtVariants::FuncCallStatement => 2,
+            StmtVariants::LetStatement => weights.let_statement,
+            StmtVariants::ConditionalStatement => weights.conditional_statement,
+            StmtVariants::AssignStatement => weights.assign_statement,
+            StmtVariants::LoopStatement => weights.loop_statement,
+            StmtVariants::OpAssignStatement => weights.op_assign_statement,
+            StmtVariants::FuncCallStatement => weights.func_call_statement,
+            StmtVariants::MacroStatement => weights.macro_statement,
+            StmtVariants::ThreadScopeStatement => weights.thread_scope_statement,
+            StmtVariants::AwaitStatement => weights.await_statement,
+            StmtVariants::UnsafeStatement => weights.unsafe_statement,
+            StmtVariants::PatternStatement => weights.pattern_statement,
         }
     }
 }
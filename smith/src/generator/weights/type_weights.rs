@@ -1,6 +1,7 @@
 use crate::program::expr::arithmetic_expr::BinaryOp;
 use crate::program::expr::arithmetic_expr::IntValue;
 use crate::program::expr::bool_expr::{BoolOp, ComparisonOp};
+use crate::program::macro_template::MacroKind;
 use crate::program::types::{BorrowTypeID, IntTypeID, TypeIDVariants};
 
 use rand::prelude::SliceRandom;
@@ -125,3 +126,14 @@ impl Distribution<BoolOp> for Standard {
         }
     }
 }
+
+impl Distribution<MacroKind> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> MacroKind {
+        match rng.gen_range(0..=3) {
+            0 => MacroKind::ArithFold(rng.gen()),
+            1 => MacroKind::ArithShadow(rng.gen()),
+            2 => MacroKind::BoolFold(rng.gen()),
+            _ => MacroKind::OpAssignEach(rng.gen()),
+        }
+    }
+}
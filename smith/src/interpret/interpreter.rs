@@ -0,0 +1,1243 @@
+/// Reference interpreter for generated programs, an oracle that does not go through rustc at all
+/// It computes what a correct compilation prints for the global struct and the checksum,
+/// so that bugs shared by every optimisation level still show up as a differential
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    rc::Rc,
+};
+use strum::IntoEnumIterator;
+
+use crate::{
+    generator::struct_gen::GLOBAL_STRUCT_VAR_NAME,
+    program::{
+        const_generic::{BodyVar, ConstGeneric},
+        expr::{
+            arithmetic_expr::{ArithmeticExpr, BinaryOp, IntValue},
+            bool_expr::{BoolExpr, BoolOp, ComparisonOp},
+            borrow_expr::BorrowExpr,
+            const_generic_call_expr::ConstGenericCallExpr,
+            expr::Expr,
+            func_call_expr::FunctionCallExpr,
+            iter_expr::IterExpr,
+            macro_call_expr::MacroCallExpr,
+            struct_expr::StructExpr,
+        },
+        function::Function,
+        macro_template::MacroKind,
+        pattern::Pattern,
+        program::Program,
+        stmt::{
+            block_stmt::BlockStmt, stmt::Stmt, thread_scope_stmt::ThreadScopeStmt,
+            unsafe_stmt::UnsafeStmt,
+        },
+        types::{BorrowTypeID, IntTypeID, TypeID},
+        var::Var,
+    },
+};
+
+use super::{
+    precedence::Grouped,
+    profile::Profile,
+    value::{apply_binary_op, cast_usize, successor, wrapping_neg, Place, StructShape, Value},
+};
+
+// A few seconds of interpretation, programs that run longer are left to the differential alone
+pub const DEFAULT_MAX_STEPS: u64 = 10_000_000;
+
+#[derive(Debug, PartialEq)]
+pub enum InterpretError {
+    // A construct that the interpreter does not model, such as an unknown raw expression
+    Unsupported(String),
+    StepLimit,
+}
+
+impl InterpretError {
+    pub fn type_mismatch(expected: &str, value: &Value) -> Self {
+        InterpretError::Unsupported(format!("expected {}, found {:?}", expected, value))
+    }
+}
+
+impl fmt::Display for InterpretError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InterpretError::Unsupported(reason) => write!(f, "unsupported: {}", reason),
+            InterpretError::StepLimit => write!(f, "step limit reached"),
+        }
+    }
+}
+
+// The lines a correct compilation prints for the global struct and the checksum
+#[derive(Debug, PartialEq)]
+pub struct ExpectedOutput {
+    struct_global: String,
+    checksum: String,
+}
+
+impl ExpectedOutput {
+    pub fn get_struct_global(&self) -> &str {
+        &self.struct_global
+    }
+
+    pub fn get_checksum(&self) -> &str {
+        &self.checksum
+    }
+}
+
+impl fmt::Display for ExpectedOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.struct_global)?;
+        writeln!(f, "{}", self.checksum)
+    }
+}
+
+pub struct Interpreter<'a> {
+    program: &'a Program,
+    functions: HashMap<String, &'a Function>,
+    const_generics: HashMap<String, &'a ConstGeneric>,
+    shapes: HashMap<String, Rc<StructShape>>,
+    max_steps: u64,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        let functions = program
+            .functions_iter()
+            .chain(
+                program
+                    .modules_iter()
+                    .flat_map(|module| module.functions_iter()),
+            )
+            .map(|function| (function.get_name(), function))
+            .collect();
+        let const_generics = program
+            .const_generics_iter()
+            .chain(
+                program
+                    .modules_iter()
+                    .flat_map(|module| module.const_generics_iter()),
+            )
+            .map(|const_generic| (const_generic.get_template().get_name(), const_generic))
+            .collect();
+        let shapes = program
+            .structs_iter()
+            .chain(
+                program
+                    .modules_iter()
+                    .flat_map(|module| module.structs_iter()),
+            )
+            .map(|template| {
+                let field_names = template.fields_iter().map(|(name, _)| name.clone());
+                let shape = StructShape::new(template.get_name(), field_names.collect());
+                (template.get_name(), Rc::new(shape))
+            })
+            .collect();
+
+        Interpreter {
+            program,
+            functions,
+            const_generics,
+            shapes,
+            max_steps: DEFAULT_MAX_STEPS,
+        }
+    }
+
+    // Statements and loop iterations executed before giving up
+    pub fn with_max_steps(mut self, max_steps: u64) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    pub fn run(&self) -> Result<ExpectedOutput, InterpretError> {
+        Execution::new(self).run()
+    }
+
+    // Also records which blocks ran, so that EMI can prune the ones that did not
+    pub fn run_with_profile(&self) -> Result<(ExpectedOutput, Profile), InterpretError> {
+        let mut execution = Execution::new(self);
+        execution.executed = Some(HashSet::new());
+
+        let expected = execution.run()?;
+        Ok((
+            expected,
+            Profile::new(execution.executed.unwrap_or_default()),
+        ))
+    }
+}
+
+enum Flow {
+    Next,
+    Break,
+    Return(Value), // Also the value of a block expression
+}
+
+// Each function call sees only its parameters and the global items
+struct Frame<'a> {
+    names: Vec<(&'a str, usize)>, // Innermost last, so that shadowing finds the latest binding
+    scopes: Vec<(usize, usize)>,  // Length of names and of the heap at the start of each block
+    heap_base: usize,
+}
+
+impl<'a> Frame<'a> {
+    fn new(heap_base: usize) -> Self {
+        Frame {
+            names: Vec::new(),
+            scopes: Vec::new(),
+            heap_base,
+        }
+    }
+}
+
+struct Execution<'a> {
+    interpreter: &'a Interpreter<'a>,
+    heap: Vec<Value>, // One slot per variable or borrowed temporary, freed when its block ends
+    globals: Vec<(&'a str, usize)>,
+    frames: Vec<Frame<'a>>,
+    checksums: BTreeMap<&'a str, u64>,
+    struct_global: Option<String>,
+    checksum: Option<String>,
+    grouped: HashMap<*const ArithmeticExpr, Rc<Grouped<'a>>>, // Regrouped once per expression
+    executed: Option<HashSet<*const BlockStmt>>,              // Only recorded when profiling
+    steps: u64,
+}
+
+impl<'a> Execution<'a> {
+    fn new(interpreter: &'a Interpreter<'a>) -> Self {
+        Execution {
+            interpreter,
+            heap: Vec::new(),
+            globals: Vec::new(),
+            frames: Vec::new(),
+            checksums: BTreeMap::new(),
+            struct_global: None,
+            checksum: None,
+            grouped: HashMap::new(),
+            executed: None,
+            steps: 0,
+        }
+    }
+
+    fn run(&mut self) -> Result<ExpectedOutput, InterpretError> {
+        // Const items only call const functions, which have no side effects
+        // Each is evaluated in a frame of its own, as a metamorphic variant may inline the call
+        for const_stmt in self.interpreter.program.consts_iter() {
+            self.frames.push(Frame::new(self.heap.len()));
+            let value = self.eval_expr(const_stmt.get_expr())?;
+            let value = self.load(value)?;
+            let frame = self.frames.pop().unwrap();
+            self.heap.truncate(frame.heap_base);
+
+            let slot = self.alloc(value);
+            self.globals.push((const_stmt.get_name_str(), slot));
+        }
+
+        self.invoke("main", Vec::new())?;
+
+        match (self.struct_global.take(), self.checksum.take()) {
+            (Some(struct_global), Some(checksum)) => Ok(ExpectedOutput {
+                struct_global,
+                checksum,
+            }),
+            _ => Err(InterpretError::Unsupported(String::from(
+                "main does not print both the global struct and the checksum",
+            ))),
+        }
+    }
+
+    fn step(&mut self) -> Result<(), InterpretError> {
+        self.steps += 1;
+        if self.steps > self.interpreter.max_steps {
+            return Err(InterpretError::StepLimit);
+        }
+        Ok(())
+    }
+
+    fn frame(&mut self) -> &mut Frame<'a> {
+        self.frames.last_mut().unwrap()
+    }
+
+    fn alloc(&mut self, value: Value) -> usize {
+        self.heap.push(value);
+        self.heap.len() - 1
+    }
+
+    fn bind(&mut self, name: &'a str, value: Value) {
+        let slot = self.alloc(value);
+        self.frame().names.push((name, slot));
+    }
+
+    fn enter_scope(&mut self) {
+        let heap_len = self.heap.len();
+        let frame = self.frame();
+        frame.scopes.push((frame.names.len(), heap_len));
+    }
+
+    fn leave_scope(&mut self) {
+        let (names_len, heap_len) = self.frame().scopes.pop().unwrap();
+        self.frame().names.truncate(names_len);
+        self.heap.truncate(heap_len);
+    }
+
+    fn lookup(&self, name: &str) -> Result<usize, InterpretError> {
+        let local = self
+            .frames
+            .last()
+            .and_then(|frame| frame.names.iter().rev().find(|(n, _)| *n == name));
+
+        local
+            .or_else(|| self.globals.iter().find(|(n, _)| *n == name))
+            .map(|(_, slot)| *slot)
+            .ok_or_else(|| InterpretError::Unsupported(format!("{} is not in scope", name)))
+    }
+
+    // Fields are accessed through any number of references, like auto deref does
+    fn place_of(&self, name: &str) -> Result<Place, InterpretError> {
+        let mut parts = name.split('.');
+        let mut place = Place::new(self.lookup(parts.next().unwrap())?);
+        for field in parts {
+            place = self.follow_refs(place)?;
+            let index = self
+                .get(&place)?
+                .field_index(field)
+                .ok_or_else(|| InterpretError::Unsupported(format!("no field {}", field)))?;
+            place.push_field(index);
+        }
+
+        Ok(place)
+    }
+
+    fn get(&self, place: &Place) -> Result<&Value, InterpretError> {
+        let mut value = self
+            .heap
+            .get(place.get_slot())
+            .ok_or_else(|| InterpretError::Unsupported(String::from("dangling reference")))?;
+        for index in place.fields_iter() {
+            value = value
+                .get_field(*index)
+                .ok_or_else(|| InterpretError::type_mismatch("a struct", value))?;
+        }
+
+        Ok(value)
+    }
+
+    fn set(&mut self, place: &Place, new_value: Value) -> Result<(), InterpretError> {
+        let mut value = self
+            .heap
+            .get_mut(place.get_slot())
+            .ok_or_else(|| InterpretError::Unsupported(String::from("dangling reference")))?;
+        for index in place.fields_iter() {
+            value = value
+                .get_field_mut(*index)
+                .ok_or_else(|| InterpretError::Unsupported(format!("no field {}", index)))?;
+        }
+
+        *value = new_value;
+        Ok(())
+    }
+
+    fn follow_refs(&self, mut place: Place) -> Result<Place, InterpretError> {
+        while let Value::Ref(target) = self.get(&place)? {
+            place = target.clone();
+        }
+
+        Ok(place)
+    }
+
+    // The value behind any number of references
+    fn load(&self, value: Value) -> Result<Value, InterpretError> {
+        match value {
+            Value::Ref(place) => {
+                let place = self.follow_refs(place)?;
+                Ok(self.get(&place)?.clone())
+            }
+            value => Ok(value),
+        }
+    }
+
+    fn load_var(&self, name: &str) -> Result<Value, InterpretError> {
+        let place = self.follow_refs(self.place_of(name)?)?;
+        Ok(self.get(&place)?.clone())
+    }
+
+    fn invoke(&mut self, name: &str, args: Vec<Value>) -> Result<Value, InterpretError> {
+        let interpreter = self.interpreter;
+        let function = *interpreter
+            .functions
+            .get(name)
+            .ok_or_else(|| InterpretError::Unsupported(format!("no function {}", name)))?;
+
+        self.frames.push(Frame::new(self.heap.len()));
+        for (param, arg) in function.params_iter().zip(args) {
+            let value = match param.get_borrow_type() {
+                BorrowTypeID::None => self.load(arg)?,
+                _ => arg,
+            };
+            self.bind(param.get_name_str(), value);
+        }
+
+        let flow = self.exec_block(function.get_block_stmt())?;
+        let frame = self.frames.pop().unwrap();
+        self.heap.truncate(frame.heap_base);
+
+        match flow {
+            Flow::Return(value) => Ok(value),
+            _ => Ok(Value::Unit),
+        }
+    }
+
+    fn exec_block(&mut self, block: &'a BlockStmt) -> Result<Flow, InterpretError> {
+        if let Some(executed) = &mut self.executed {
+            executed.insert(block);
+        }
+        self.enter_scope();
+
+        let mut flow = Flow::Next;
+        for stmt in block.stmts_iter() {
+            flow = self.exec_stmt(stmt)?;
+            if !matches!(flow, Flow::Next) {
+                break;
+            }
+        }
+        // Loaded before the block's variables go away
+        if let Flow::Return(value) = flow {
+            flow = Flow::Return(self.load(value)?);
+        }
+
+        self.leave_scope();
+        Ok(flow)
+    }
+
+    fn exec_stmt(&mut self, stmt: &'a Stmt) -> Result<Flow, InterpretError> {
+        self.step()?;
+
+        match stmt {
+            Stmt::LetStatement(let_stmt) => {
+                let value = self.eval_expr(let_stmt.get_expr())?;
+                let value = match let_stmt.get_var().get_borrow_type() {
+                    BorrowTypeID::None => self.load(value)?,
+                    _ => value,
+                };
+                self.bind(let_stmt.get_var().get_name_str(), value);
+            }
+            Stmt::StaticStatement(static_stmt) => {
+                let value = self.eval_expr(static_stmt.get_expr())?;
+                let value = self.load(value)?;
+                self.bind(static_stmt.get_name_str(), value);
+            }
+            Stmt::ConstStatement(const_stmt) => {
+                let value = self.eval_expr(const_stmt.get_expr())?;
+                let value = self.load(value)?;
+                self.bind(const_stmt.get_name_str(), value);
+            }
+            Stmt::ConditionalStatement(conditional_stmt) => {
+                for (condition, block) in conditional_stmt.branches_iter() {
+                    if self.eval_bool(condition)? {
+                        return self.exec_block(block);
+                    }
+                }
+                if let Some(else_body) = conditional_stmt.get_else_body() {
+                    return self.exec_block(else_body);
+                }
+            }
+            Stmt::AssignStatement(assign_stmt) => {
+                let value = self.eval_expr(assign_stmt.get_expr())?;
+                let mut place = self.place_of(assign_stmt.get_var().get_name_str())?;
+                if assign_stmt.is_deref() {
+                    place = self.follow_refs(place)?;
+                }
+                // A reference assigned to a reference variable rebinds it
+                let value = match (&value, self.get(&place)?) {
+                    (Value::Ref(_), Value::Ref(_)) => value,
+                    _ => self.load(value)?,
+                };
+                self.set(&place, value)?;
+            }
+            Stmt::OpAssignStatement(op_assign_stmt) => {
+                let right = self.eval_arith(op_assign_stmt.get_expr())?;
+                let place =
+                    self.follow_refs(self.place_of(op_assign_stmt.get_var().get_name_str())?)?;
+                self.op_assign(&place, op_assign_stmt.get_op(), right)?;
+            }
+            Stmt::ReturnStatement(return_stmt) => {
+                let value = self.eval_expr(return_stmt.get_expr())?;
+                return Ok(Flow::Return(value));
+            }
+            Stmt::ExprStatement(expr_stmt) => match expr_stmt.get_expr() {
+                Expr::Raw(raw) if raw.get_expr_string() == "break" => return Ok(Flow::Break),
+                Expr::Raw(raw) if raw.get_expr_string() == "return" => {
+                    return Ok(Flow::Return(Value::Unit))
+                }
+                expr => {
+                    self.eval_expr(expr)?;
+                }
+            },
+            Stmt::LoopStatement(for_loop_stmt) => {
+                let IterExpr::Range(range) = for_loop_stmt.get_iterable();
+                let mut index = self.eval_arith(range.get_left())?;
+                let end = self.eval_arith(range.get_right())?;
+
+                while index < end {
+                    self.step()?;
+                    self.enter_scope();
+                    self.bind(
+                        for_loop_stmt.get_iter_var().get_name_str(),
+                        Value::Int(index),
+                    );
+                    let flow = self.exec_block(for_loop_stmt.get_block_stmt())?;
+                    self.leave_scope();
+
+                    match flow {
+                        Flow::Next => index = successor(index),
+                        Flow::Break => break,
+                        Flow::Return(_) => return Ok(flow),
+                    }
+                }
+            }
+            Stmt::WhileStatement(while_stmt) => {
+                while self.eval_bool(while_stmt.get_condition())? {
+                    self.step()?;
+                    match self.exec_block(while_stmt.get_block_stmt())? {
+                        Flow::Next => (),
+                        Flow::Break => break,
+                        flow => return Ok(flow),
+                    }
+                }
+            }
+            Stmt::ThreadScopeStatement(thread_scope_stmt) => {
+                self.exec_thread_scope(thread_scope_stmt)?
+            }
+            // Futures are polled to completion where they are created, so suspending changes nothing
+            Stmt::AwaitStatement(_) => (),
+            Stmt::UnsafeStatement(unsafe_stmt) => self.exec_unsafe(unsafe_stmt)?,
+            Stmt::LetPatternStatement(let_pattern_stmt) => {
+                let value = self.eval_expr(let_pattern_stmt.get_expr())?;
+                let value = self.load(value)?;
+
+                let mut bindings = Vec::new();
+                if Self::matches(let_pattern_stmt.get_pattern(), &value, &mut bindings)? {
+                    for (name, value) in bindings {
+                        self.bind(name, value);
+                    }
+                } else if let Some(else_body) = let_pattern_stmt.get_else_body() {
+                    return match self.exec_block(else_body)? {
+                        Flow::Next => Err(InterpretError::Unsupported(String::from(
+                            "let else body does not diverge",
+                        ))),
+                        flow => Ok(flow),
+                    };
+                } else {
+                    return Err(InterpretError::Unsupported(String::from(
+                        "irrefutable pattern does not match",
+                    )));
+                }
+            }
+            Stmt::IfLetStatement(if_let_stmt) => {
+                let value = self.eval_expr(if_let_stmt.get_expr())?;
+                let value = self.load(value)?;
+
+                let mut bindings = Vec::new();
+                if Self::matches(if_let_stmt.get_pattern(), &value, &mut bindings)? {
+                    self.enter_scope();
+                    for (name, value) in bindings {
+                        self.bind(name, value);
+                    }
+                    let flow = self.exec_block(if_let_stmt.get_body())?;
+                    self.leave_scope();
+                    return Ok(flow);
+                } else if let Some(else_body) = if_let_stmt.get_else_body() {
+                    return self.exec_block(else_body);
+                }
+            }
+            Stmt::ChecksumStatement(checksum_stmt) => {
+                let mut values = Vec::new();
+                for place in checksum_stmt.places_iter() {
+                    values.push(self.load_var(place)?.to_checksum()?);
+                }
+
+                let hash = fold_hash(checksum_stmt.get_function_name(), &values);
+                let checksum = self
+                    .checksums
+                    .entry(checksum_stmt.get_function_name())
+                    .or_insert(0);
+                *checksum = checksum.wrapping_add(hash);
+            }
+            // Traces go to stderr, which is not compared
+            Stmt::TraceStatement(_) => (),
+        }
+
+        Ok(Flow::Next)
+    }
+
+    fn op_assign(
+        &mut self,
+        place: &Place,
+        op: BinaryOp,
+        right: IntValue,
+    ) -> Result<(), InterpretError> {
+        let left = self.get(place)?.as_int()?;
+        let result = apply_binary_op(op, left, right)?;
+        self.set(place, Value::Int(result))
+    }
+
+    // Every argument is evaluated before any thread starts, and the threads share nothing mutable,
+    // so running the calls one after another in spawn order gives the same results
+    fn exec_thread_scope(&mut self, stmt: &'a ThreadScopeStmt) -> Result<(), InterpretError> {
+        let mut spawn_args = Vec::new();
+        for spawn in stmt.spawns_iter() {
+            let mut args = Vec::new();
+            for arg in spawn.get_call().arguments_iter() {
+                args.push(self.eval_expr(arg)?);
+            }
+            spawn_args.push(args);
+        }
+
+        let mut results = Vec::new();
+        for (spawn, args) in stmt.spawns_iter().zip(spawn_args) {
+            results.push(self.invoke(spawn.get_call().get_name_str(), args)?);
+        }
+
+        for (spawn, result) in stmt.spawns_iter().zip(results) {
+            if let Some(var) = spawn.get_result() {
+                self.bind(var.get_name_str(), result);
+            }
+        }
+        if let Some(counter) = stmt.get_counter() {
+            let total = stmt.spawns_iter().fold(0u64, |total, spawn| {
+                total.wrapping_add(spawn.get_increment())
+            });
+            self.bind(counter.get_name_str(), Value::Int(IntValue::U64(total)));
+        }
+
+        Ok(())
+    }
+
+    fn exec_unsafe(&mut self, stmt: &'a UnsafeStmt) -> Result<(), InterpretError> {
+        match stmt {
+            UnsafeStmt::PtrUpdate { place, op, value } => {
+                let value = self.eval_expr(value)?;
+                let right = self.load(value)?.as_int()?;
+                let place = self.follow_refs(self.place_of(place.get_name_str())?)?;
+                self.op_assign(&place, *op, right)?;
+            }
+            UnsafeStmt::PtrRead { result, place } => {
+                let value = self.load_var(place.get_name_str())?;
+                self.bind(result.get_name_str(), value);
+            }
+            UnsafeStmt::ArrayPtr {
+                result,
+                elements,
+                writes,
+                read,
+            } => {
+                let mut array = Vec::new();
+                for element in elements.elements_iter() {
+                    let value = self.eval_expr(element)?;
+                    array.push(self.load(value)?.as_int()?);
+                }
+                for (dest, left, op, right) in writes {
+                    array[*dest as usize] =
+                        apply_binary_op(*op, array[*left as usize], array[*right as usize])?;
+                }
+                self.bind(result.get_name_str(), Value::Int(array[*read as usize]));
+            }
+            UnsafeStmt::MaybeUninit {
+                result,
+                fields,
+                value,
+            } => {
+                let value = match value {
+                    Some(value) => {
+                        let value = self.eval_expr(value)?;
+                        self.load(value)?
+                    }
+                    None => {
+                        let mut written = Vec::new();
+                        for (name, expr) in fields {
+                            let value = self.eval_expr(expr)?;
+                            written.push((name.as_str(), self.load(value)?));
+                        }
+                        self.struct_value(&result.get_type(), written)?
+                    }
+                };
+                self.bind(result.get_name_str(), value);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Fields in declaration order, whatever order they were written in
+    fn struct_value(
+        &self,
+        type_id: &TypeID,
+        written: Vec<(&str, Value)>,
+    ) -> Result<Value, InterpretError> {
+        let struct_name = match type_id {
+            TypeID::StructType(name) => name,
+            _ => return Err(InterpretError::Unsupported(format!("{:?}", type_id))),
+        };
+        let shape = self
+            .interpreter
+            .shapes
+            .get(struct_name)
+            .ok_or_else(|| InterpretError::Unsupported(format!("no struct {}", struct_name)))?;
+
+        let mut written: Vec<(&str, Option<Value>)> = written
+            .into_iter()
+            .map(|(name, value)| (name, Some(value)))
+            .collect();
+        let mut fields = Vec::new();
+        for field_name in shape.field_names_iter() {
+            let value = written
+                .iter_mut()
+                .find(|(name, _)| name == field_name)
+                .and_then(|(_, value)| value.take())
+                .ok_or_else(|| InterpretError::Unsupported(format!("{} unset", field_name)))?;
+            fields.push(value);
+        }
+
+        Ok(Value::Struct(shape.clone(), fields))
+    }
+
+    fn eval_expr(&mut self, expr: &'a Expr) -> Result<Value, InterpretError> {
+        match expr {
+            Expr::Arithmetic(arith_expr) => Ok(Value::Int(self.eval_arith(arith_expr)?)),
+            Expr::Boolean(bool_expr) => Ok(Value::Bool(self.eval_bool(bool_expr)?)),
+            Expr::Struct(struct_expr) => self.eval_struct(struct_expr),
+            // Not loaded, a reference variable may be passed on as it is
+            Expr::Variable(var) => Ok(self.get(&self.place_of(var.get_name_str())?)?.clone()),
+            Expr::Borrow(borrow_expr) => self.eval_borrow(borrow_expr),
+            Expr::Raw(raw) => self.eval_raw(raw.get_expr_string()),
+            Expr::Func(call) => self.call(call),
+            Expr::Macro(call) => self.eval_macro(call),
+            Expr::Tuple(tuple) => {
+                let mut values = Vec::new();
+                for element in tuple.elements_iter() {
+                    let value = self.eval_expr(element)?;
+                    values.push(self.load(value)?);
+                }
+                Ok(Value::Tuple(values))
+            }
+            Expr::Block(block) => match self.exec_block(block)? {
+                Flow::Return(value) => Ok(value),
+                Flow::Next => Ok(Value::Unit),
+                Flow::Break => Err(InterpretError::Unsupported(String::from(
+                    "break out of a block expression",
+                ))),
+            },
+            // A return leaves only the closure, like the block above
+            Expr::Closure(closure) => match self.exec_block(closure.get_block_stmt())? {
+                Flow::Return(value) => Ok(value),
+                Flow::Next => Ok(Value::Unit),
+                Flow::Break => Err(InterpretError::Unsupported(String::from(
+                    "break out of a closure",
+                ))),
+            },
+        }
+    }
+
+    fn eval_borrow(&mut self, borrow_expr: &'a BorrowExpr) -> Result<Value, InterpretError> {
+        // Without an explicit borrow the expression is already a reference
+        if !borrow_expr.is_explicit() {
+            return self.eval_expr(borrow_expr.get_expr());
+        }
+
+        match place_var(borrow_expr.get_expr()) {
+            Some(var) => Ok(Value::Ref(self.place_of(var.get_name_str())?)),
+            // A borrowed temporary lives until the end of the enclosing block
+            None => {
+                let value = self.eval_expr(borrow_expr.get_expr())?;
+                let value = self.load(value)?;
+                Ok(Value::Ref(Place::new(self.alloc(value))))
+            }
+        }
+    }
+
+    // Only the prints of main are raw expressions besides break and return
+    fn eval_raw(&mut self, raw: &str) -> Result<Value, InterpretError> {
+        if raw.contains("const_checks") {
+            // Const functions fold no checksums, so the line has no effect on the others
+        } else if raw.contains("checksum::to_json()") {
+            self.checksum = Some(self.checksum_json());
+        } else if raw.contains(GLOBAL_STRUCT_VAR_NAME) {
+            self.struct_global = Some(self.load_var(GLOBAL_STRUCT_VAR_NAME)?.to_json());
+        } else {
+            return Err(InterpretError::Unsupported(format!(
+                "raw expression {}",
+                raw
+            )));
+        }
+
+        Ok(Value::Unit)
+    }
+
+    fn checksum_json(&self) -> String {
+        let functions: Vec<String> = self
+            .checksums
+            .iter()
+            .map(|(function_name, checksum)| format!("\"{}\":{}", function_name, checksum))
+            .collect();
+        let total = self
+            .checksums
+            .values()
+            .fold(0u64, |total, checksum| total.wrapping_add(*checksum));
+
+        format!(
+            "{{\"checksum\":{},\"functions\":{{{}}}}}",
+            total,
+            functions.join(",")
+        )
+    }
+
+    fn call(&mut self, call: &'a FunctionCallExpr) -> Result<Value, InterpretError> {
+        let mut args = Vec::new();
+        for arg in call.arguments_iter() {
+            args.push(self.eval_expr(arg)?);
+        }
+
+        self.invoke(call.get_name_str(), args)
+    }
+
+    fn eval_struct(&mut self, struct_expr: &'a StructExpr) -> Result<Value, InterpretError> {
+        match struct_expr {
+            StructExpr::Literal(literal) => {
+                let mut written = Vec::new();
+                for (name, expr) in literal.fields_iter() {
+                    let value = self.eval_expr(expr)?;
+                    written.push((name.as_str(), self.load(value)?));
+                }
+                let type_id = TypeID::StructType(literal.get_struct_name());
+                self.struct_value(&type_id, written)
+            }
+            StructExpr::Var(var) => self.load_var(var.get_name_str()),
+            StructExpr::Func(call) => self.call(call),
+        }
+    }
+
+    fn eval_arith(&mut self, expr: &'a ArithmeticExpr) -> Result<IntValue, InterpretError> {
+        match expr {
+            ArithmeticExpr::Binary(_) => {
+                let grouped = self
+                    .grouped
+                    .entry(expr)
+                    .or_insert_with(|| Rc::new(Grouped::of(expr)))
+                    .clone();
+                self.eval_grouped(&grouped)
+            }
+            _ => self.eval_operand(expr),
+        }
+    }
+
+    fn eval_grouped(&mut self, grouped: &Grouped<'a>) -> Result<IntValue, InterpretError> {
+        match grouped {
+            Grouped::Literal(value) => Ok(*value),
+            Grouped::Expr(expr) => self.eval_operand(expr),
+            Grouped::Neg(operand) => Ok(wrapping_neg(self.eval_grouped(operand)?)),
+            Grouped::Method(receiver, op, argument) => {
+                let left = self.eval_grouped(receiver)?;
+                let right = self.eval_arith(argument)?;
+                apply_binary_op(*op, left, right)
+            }
+            Grouped::Infix(left, op, right) => {
+                let left = self.eval_grouped(left)?;
+                let right = self.eval_grouped(right)?;
+                apply_binary_op(*op, left, right)
+            }
+        }
+    }
+
+    // An arithmetic expression that is parsed the same wherever it is written
+    fn eval_operand(&mut self, expr: &'a ArithmeticExpr) -> Result<IntValue, InterpretError> {
+        match expr {
+            ArithmeticExpr::Int(int_expr) => Ok(int_expr.get_value()),
+            // Only const binary expressions are self contained, they are written as a block
+            ArithmeticExpr::Binary(binary_expr) => {
+                let left = self.eval_arith(binary_expr.get_left())?;
+                let right = self.eval_arith(binary_expr.get_right())?;
+                apply_binary_op(binary_expr.get_op(), left, right)
+            }
+            ArithmeticExpr::Var(var) => self.load_var(var.get_name_str())?.as_int(),
+            ArithmeticExpr::Func(call) => self.call(call)?.as_int(),
+            ArithmeticExpr::Macro(call) => self.eval_macro(call)?.as_int(),
+            ArithmeticExpr::ConstGeneric(call) => self.eval_const_generic(call),
+        }
+    }
+
+    fn eval_bool(&mut self, expr: &'a BoolExpr) -> Result<bool, InterpretError> {
+        match expr {
+            BoolExpr::Bool(value) => Ok(value.get_value()),
+            BoolExpr::Binary(binary_expr) => {
+                let left = self.eval_bool(binary_expr.get_left())?;
+                match (binary_expr.get_op(), left) {
+                    (BoolOp::AND, false) => Ok(false),
+                    (BoolOp::OR, true) => Ok(true),
+                    _ => self.eval_bool(binary_expr.get_right()),
+                }
+            }
+            BoolExpr::Comparison(comparison_expr) => {
+                let left = self.eval_arith(comparison_expr.get_left())?;
+                let right = self.eval_arith(comparison_expr.get_right())?;
+                if left.get_type() != right.get_type() {
+                    return Err(InterpretError::Unsupported(format!(
+                        "comparison of {:?} with {:?}",
+                        left, right
+                    )));
+                }
+
+                Ok(match comparison_expr.get_op() {
+                    ComparisonOp::Greater => left > right,
+                    ComparisonOp::Less => left < right,
+                    ComparisonOp::GreaterEq => left >= right,
+                    ComparisonOp::LessEq => left <= right,
+                    ComparisonOp::Equal => left == right,
+                    ComparisonOp::NotEqual => left != right,
+                })
+            }
+            BoolExpr::Negation(negation_expr) => Ok(!self.eval_bool(negation_expr.get_expr())?),
+            BoolExpr::Var(var) => self.load_var(var.get_name_str())?.as_bool(),
+            BoolExpr::Func(call) => self.call(call)?.as_bool(),
+            BoolExpr::Macro(call) => self.eval_macro(call)?.as_bool(),
+            BoolExpr::Matches(matches_expr) => {
+                let value = self.eval_expr(matches_expr.get_expr())?;
+                let value = self.load(value)?;
+                Self::matches(matches_expr.get_pattern(), &value, &mut Vec::new())
+            }
+            BoolExpr::Opaque(opaque) => Ok(opaque.get_value()),
+        }
+    }
+
+    fn eval_macro(&mut self, call: &'a MacroCallExpr) -> Result<Value, InterpretError> {
+        match call.get_kind() {
+            MacroKind::ArithFold(op) => {
+                let values = self.eval_int_args(call.arguments_iter())?;
+                // x op fold(rest), so the fold starts from the last argument
+                let mut values = values.into_iter().rev();
+                let mut result = values.next().ok_or_else(|| {
+                    InterpretError::Unsupported(String::from("fold without arguments"))
+                })?;
+                for value in values {
+                    result = apply_binary_op(op, value, result)?;
+                }
+                Ok(Value::Int(result))
+            }
+            MacroKind::ArithShadow(op) => {
+                let values = self.eval_int_args(call.arguments_iter())?;
+                match values[..] {
+                    [x] => Ok(Value::Int(apply_binary_op(op, x, x)?)),
+                    [x, y] => Ok(Value::Int(apply_binary_op(op, x, y)?)),
+                    _ => Err(InterpretError::Unsupported(format!(
+                        "{} arguments to a shadowing macro",
+                        values.len()
+                    ))),
+                }
+            }
+            MacroKind::BoolFold(op) => {
+                for arg in call.arguments_iter() {
+                    let value = self.eval_expr(arg)?;
+                    let value = self.load(value)?.as_bool()?;
+                    match (op, value) {
+                        (BoolOp::AND, false) => return Ok(Value::Bool(false)),
+                        (BoolOp::OR, true) => return Ok(Value::Bool(true)),
+                        _ => (),
+                    }
+                }
+                Ok(Value::Bool(matches!(op, BoolOp::AND)))
+            }
+            // The target is named again for every argument, and each argument is evaluated first
+            MacroKind::OpAssignEach(op) => {
+                let mut args = call.arguments_iter();
+                let target = args
+                    .next()
+                    .and_then(place_var)
+                    .ok_or_else(|| InterpretError::Unsupported(String::from("macro target")))?;
+
+                for arg in args {
+                    let value = self.eval_expr(arg)?;
+                    let right = self.load(value)?.as_int()?;
+                    let place = self.follow_refs(self.place_of(target.get_name_str())?)?;
+                    self.op_assign(&place, op, right)?;
+                }
+                Ok(Value::Unit)
+            }
+        }
+    }
+
+    fn eval_int_args(
+        &mut self,
+        args: impl Iterator<Item = &'a Expr>,
+    ) -> Result<Vec<IntValue>, InterpretError> {
+        let mut values = Vec::new();
+        for arg in args {
+            let value = self.eval_expr(arg)?;
+            values.push(self.load(value)?.as_int()?);
+        }
+
+        Ok(values)
+    }
+
+    // acc = init, then acc = update for each index, with the body's names bound to their values
+    fn eval_const_generic(
+        &mut self,
+        call: &'a ConstGenericCallExpr,
+    ) -> Result<IntValue, InterpretError> {
+        let elements = self.eval_int_args(call.get_elements().elements_iter())?;
+        let init = self.eval_expr(call.get_init())?;
+        let mut acc = self.load(init)?;
+
+        let template = call.get_template();
+        let interpreter = self.interpreter;
+        let const_generic = interpreter
+            .const_generics
+            .get(&template.get_name())
+            .ok_or_else(|| {
+                InterpretError::Unsupported(format!("no const generic {}", template.get_name()))
+            })?;
+        let elem_type: IntTypeID = template.get_elem_type();
+
+        self.frames.push(Frame::new(self.heap.len()));
+        self.enter_scope();
+        let len = elements.len();
+        for idx in 0..len {
+            self.step()?;
+            self.enter_scope();
+            for body_var in BodyVar::iter() {
+                let value = match body_var {
+                    BodyVar::Front => Value::Int(elements[idx]),
+                    BodyVar::Back => Value::Int(elements[len - 1 - idx]),
+                    BodyVar::Index => Value::Int(cast_usize(idx, elem_type)),
+                    BodyVar::Acc => acc.clone(),
+                };
+                self.bind(body_var.get_name(), value);
+            }
+            let update = self.eval_expr(const_generic.get_update_expr())?;
+            acc = self.load(update)?;
+            self.leave_scope();
+        }
+        self.leave_scope();
+        let frame = self.frames.pop().unwrap();
+        self.heap.truncate(frame.heap_base);
+
+        acc.as_int()
+    }
+
+    // Bindings are only collected, the caller decides which scope they go into
+    fn matches(
+        pattern: &'a Pattern,
+        value: &Value,
+        bindings: &mut Vec<(&'a str, Value)>,
+    ) -> Result<bool, InterpretError> {
+        match pattern {
+            Pattern::Wildcard => Ok(true),
+            Pattern::Binding(var) => {
+                bindings.push((var.get_name_str(), value.clone()));
+                Ok(true)
+            }
+            Pattern::At(var, pattern) => {
+                if !Self::matches(pattern, value, bindings)? {
+                    return Ok(false);
+                }
+                bindings.push((var.get_name_str(), value.clone()));
+                Ok(true)
+            }
+            Pattern::Range(start, end) => {
+                let value = value.as_int()?;
+                Ok(start.get_value() <= value && value <= end.get_value())
+            }
+            Pattern::Bool(expected) => Ok(value.as_bool()? == *expected),
+            Pattern::Tuple(patterns) => match value {
+                Value::Tuple(values) if values.len() == patterns.len() => {
+                    for (pattern, value) in patterns.iter().zip(values) {
+                        if !Self::matches(pattern, value, bindings)? {
+                            return Ok(false);
+                        }
+                    }
+                    Ok(true)
+                }
+                _ => Err(InterpretError::type_mismatch("a tuple", value)),
+            },
+            Pattern::Struct(_, fields, _) => {
+                for (name, pattern) in fields {
+                    let field = value
+                        .field_index(name)
+                        .and_then(|index| value.get_field(index))
+                        .ok_or_else(|| InterpretError::type_mismatch(name, value))?;
+                    if !Self::matches(pattern, field, bindings)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+        }
+    }
+}
+
+// The variable an expression names, if it is a place rather than a temporary
+fn place_var(expr: &Expr) -> Option<&Var> {
+    match expr {
+        Expr::Variable(var)
+        | Expr::Arithmetic(ArithmeticExpr::Var(var))
+        | Expr::Boolean(BoolExpr::Var(var))
+        | Expr::Struct(StructExpr::Var(var)) => Some(var),
+        Expr::Borrow(borrow_expr) if !borrow_expr.is_explicit() => {
+            place_var(borrow_expr.get_expr())
+        }
+        _ => None,
+    }
+}
+
+// Same as the runtime checksum, FNV-1a over the function name and each value's bytes
+fn fold_hash(function_name: &str, values: &[u128]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    function_name
+        .bytes()
+        .chain(values.iter().flat_map(|value| value.to_le_bytes()))
+        .fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(PRIME)
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        program::{
+            expr::{
+                arithmetic_expr::IntExpr, expr::RawExpr, iter_expr::IterRange,
+                struct_expr::StructLiteral,
+            },
+            stmt::{
+                checksum_stmt::ChecksumStmt, expr_stmt::ExprStmt, for_loop_stmt::ForLoopStmt,
+                let_stmt::LetStmt, op_assign_stmt::OpAssignStmt,
+            },
+            struct_template::StructTemplate,
+        },
+        Features, Generator,
+    };
+
+    // struct_global.field_1 += 100i8 overflows and is kept, struct_global.field_2 sums the loop
+    fn loop_program() -> Program {
+        let u32_type = IntTypeID::U32.as_type();
+        let template = StructTemplate::new_from_fields(
+            String::from("Struct1"),
+            vec![
+                (String::from("field_1"), IntTypeID::I8.as_type()),
+                (String::from("field_2"), u32_type.clone()),
+            ],
+        );
+        let struct_type = TypeID::StructType(template.get_name());
+        let literal = StructLiteral::new(
+            template.clone(),
+            vec![
+                IntExpr::new_i8(100).as_arith_expr().as_expr(),
+                IntExpr::new_u32(7).as_arith_expr().as_expr(),
+            ],
+        );
+        let field = |name: &str, type_id: TypeID| {
+            Var::new(
+                type_id,
+                format!("{}.{}", GLOBAL_STRUCT_VAR_NAME, name),
+                true,
+            )
+        };
+        let iter_var = Var::new(u32_type.clone(), String::from("var_1"), false);
+
+        let loop_body = BlockStmt::new_from_vec(vec![
+            OpAssignStmt::new(
+                field("field_1", IntTypeID::I8.as_type()),
+                IntExpr::new_i8(100).as_arith_expr(),
+                BinaryOp::ADD,
+            )
+            .as_stmt(),
+            OpAssignStmt::new(
+                field("field_2", u32_type.clone()),
+                ArithmeticExpr::Var(iter_var.clone()),
+                BinaryOp::ADD,
+            )
+            .as_stmt(),
+        ]);
+        let range = IterRange::new(
+            IntTypeID::U32,
+            IntExpr::new_u32(0).as_arith_expr(),
+            IntExpr::new_u32(5).as_arith_expr(),
+        );
+        let print = |raw: &str| ExprStmt::new(RawExpr::new(raw.to_string()).as_expr()).as_stmt();
+
+        let main = Function::new(
+            String::from("main"),
+            Vec::new(),
+            TypeID::NullType,
+            BlockStmt::new_from_vec(vec![
+                LetStmt::new(
+                    Var::new(struct_type, GLOBAL_STRUCT_VAR_NAME.to_string(), true),
+                    literal.as_struct_expr().as_expr(),
+                )
+                .as_stmt(),
+                ForLoopStmt::new(u32_type, iter_var, range.as_iter_expr(), loop_body).as_stmt(),
+                ChecksumStmt::new(
+                    String::from("main"),
+                    vec![format!("{}.field_2", GLOBAL_STRUCT_VAR_NAME)],
+                )
+                .as_stmt(),
+                print("println!(\"{}\", serde_json::to_string(&struct_global).unwrap())"),
+                print("println!(\"{}\", checksum::to_json())"),
+            ]),
+        );
+
+        let mut program = Program::new();
+        program.push_struct_template(template);
+        program.push_function(main);
+        program
+    }
+
+    #[test]
+    fn runs_loops_over_struct_fields() {
+        let expected = Interpreter::new(&loop_program()).run().unwrap();
+
+        assert_eq!(
+            expected.get_struct_global(),
+            "{\"field_1\":100,\"field_2\":17}"
+        );
+        let hash = fold_hash("main", &[17]);
+        assert_eq!(
+            expected.get_checksum(),
+            format!(
+                "{{\"checksum\":{},\"functions\":{{\"main\":{}}}}}",
+                hash, hash
+            )
+        );
+    }
+
+    #[test]
+    fn gives_up_at_step_limit() {
+        let program = loop_program();
+
+        let result = Interpreter::new(&program).with_max_steps(3).run();
+
+        assert_eq!(result, Err(InterpretError::StepLimit));
+    }
+
+    #[test]
+    fn models_every_generated_construct() {
+        let features = Features {
+            threads: true,
+            unsafe_code: true,
+            ..Default::default()
+        };
+
+        for seed in 0..4 {
+            let generated = Generator::new(seed).with_features(features).generate();
+            let result = Interpreter::new(generated.get_program())
+                .with_max_steps(20_000)
+                .run();
+
+            assert!(
+                matches!(result, Ok(_) | Err(InterpretError::StepLimit)),
+                "seed {}: {:?}",
+                seed,
+                result
+            );
+        }
+    }
+}
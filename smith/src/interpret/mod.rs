@@ -0,0 +1,4 @@
+pub mod interpreter;
+pub mod precedence;
+pub mod profile;
+pub mod value;
@@ -0,0 +1,183 @@
+/// Arithmetic expressions grouped the way rustc parses their rendering, rather than as built
+/// Bit operations are written infix without parentheses, so they group by operator precedence,
+/// and a negative literal is written with a minus that applies after any method call on it
+use crate::program::expr::arithmetic_expr::{ArithmeticExpr, BinaryOp, IntValue};
+
+use super::value::{is_negative, wrapping_neg};
+
+pub enum Grouped<'a> {
+    Literal(IntValue),        // Without the minus sign of a negative literal
+    Expr(&'a ArithmeticExpr), // Parses the same on its own, e.g. a call or a const block
+    Neg(Box<Grouped<'a>>),
+    Method(Box<Grouped<'a>>, BinaryOp, &'a ArithmeticExpr), // The argument is parenthesised
+    Infix(Box<Grouped<'a>>, BinaryOp, Box<Grouped<'a>>),
+}
+
+enum Token<'a> {
+    Minus,
+    Operand(Grouped<'a>),
+    Method(BinaryOp, &'a ArithmeticExpr),
+    Infix(BinaryOp),
+}
+
+impl<'a> Grouped<'a> {
+    pub fn of(expr: &'a ArithmeticExpr) -> Self {
+        let mut tokens = Vec::new();
+        tokenize(expr, &mut tokens);
+
+        let mut tokens = tokens.into_iter().peekable();
+        let grouped = parse_infix(&mut tokens, 0);
+        debug_assert!(tokens.next().is_none());
+
+        grouped
+    }
+}
+
+// The tokens of the rendered text, where a token stands for a self contained piece of it
+fn tokenize<'a>(expr: &'a ArithmeticExpr, tokens: &mut Vec<Token<'a>>) {
+    match expr {
+        ArithmeticExpr::Int(int_expr) if is_negative(int_expr.get_value()) => {
+            tokens.push(Token::Minus);
+            tokens.push(Token::Operand(Grouped::Literal(wrapping_neg(
+                int_expr.get_value(),
+            ))));
+        }
+        ArithmeticExpr::Int(int_expr) => {
+            tokens.push(Token::Operand(Grouped::Literal(int_expr.get_value())))
+        }
+        ArithmeticExpr::Binary(binary_expr) => match binary_expr.get_op() {
+            BinaryOp::BITAND | BinaryOp::BITOR | BinaryOp::BITXOR => {
+                tokenize(binary_expr.get_left(), tokens);
+                tokens.push(Token::Infix(binary_expr.get_op()));
+                tokenize(binary_expr.get_right(), tokens);
+            }
+            _ if binary_expr.is_const() => tokens.push(Token::Operand(Grouped::Expr(expr))),
+            op => {
+                tokenize(binary_expr.get_left(), tokens);
+                tokens.push(Token::Method(op, binary_expr.get_right()));
+            }
+        },
+        _ => tokens.push(Token::Operand(Grouped::Expr(expr))),
+    }
+}
+
+type Tokens<'a> = std::iter::Peekable<std::vec::IntoIter<Token<'a>>>;
+
+// Loosest first, and all of them bind looser than unary minus and method calls
+const INFIX_PRECEDENCE: [BinaryOp; 3] = [BinaryOp::BITOR, BinaryOp::BITXOR, BinaryOp::BITAND];
+
+// Left associative
+fn parse_infix<'a>(tokens: &mut Tokens<'a>, level: usize) -> Grouped<'a> {
+    if level == INFIX_PRECEDENCE.len() {
+        return parse_unary(tokens);
+    }
+
+    let mut left = parse_infix(tokens, level + 1);
+    while let Some(Token::Infix(op)) = tokens.peek() {
+        let op = *op;
+        if op != INFIX_PRECEDENCE[level] {
+            break;
+        }
+        tokens.next();
+
+        let right = parse_infix(tokens, level + 1);
+        left = Grouped::Infix(Box::new(left), op, Box::new(right));
+    }
+
+    left
+}
+
+fn parse_unary<'a>(tokens: &mut Tokens<'a>) -> Grouped<'a> {
+    if let Some(Token::Minus) = tokens.peek() {
+        tokens.next();
+        return Grouped::Neg(Box::new(parse_unary(tokens)));
+    }
+
+    let mut operand = match tokens.next() {
+        Some(Token::Operand(operand)) => operand,
+        _ => unreachable!("Every rendered operator has an operand on both sides"),
+    };
+    while let Some(Token::Method(op, argument)) = tokens.peek() {
+        operand = Grouped::Method(Box::new(operand), *op, argument);
+        tokens.next();
+    }
+
+    operand
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::expr::arithmetic_expr::{BinaryExpr, IntExpr};
+
+    fn binary(left: ArithmeticExpr, op: BinaryOp, right: ArithmeticExpr) -> ArithmeticExpr {
+        BinaryExpr::new(left, right, op).as_arith_expr()
+    }
+
+    fn describe(grouped: &Grouped) -> String {
+        match grouped {
+            Grouped::Literal(value) => value.to_string(),
+            Grouped::Expr(expr) => expr.to_string(),
+            Grouped::Neg(operand) => format!("-({})", describe(operand)),
+            Grouped::Method(receiver, op, argument) => format!(
+                "({}).{}({})",
+                describe(receiver),
+                op.to_string_safe(),
+                argument.to_string()
+            ),
+            Grouped::Infix(left, op, right) => format!(
+                "({} {} {})",
+                describe(left),
+                op.to_string(),
+                describe(right)
+            ),
+        }
+    }
+
+    #[test]
+    fn method_binds_to_nearest_operand() {
+        // Built as (1 | 2).safe_add(3), written as 1i8 | 2i8.safe_add(3i8)
+        let expr = binary(
+            binary(
+                IntExpr::new_i8(1).as_arith_expr(),
+                BinaryOp::BITOR,
+                IntExpr::new_i8(2).as_arith_expr(),
+            ),
+            BinaryOp::ADD,
+            IntExpr::new_i8(3).as_arith_expr(),
+        );
+
+        assert_eq!(describe(&Grouped::of(&expr)), "(1 | (2).safe_add(3i8))");
+    }
+
+    #[test]
+    fn bit_operators_group_by_precedence() {
+        // Built as (1 & 2) | 3 ^ 4 from the right, written as 1i8 & 2i8 | 3i8 ^ 4i8
+        let expr = binary(
+            binary(
+                IntExpr::new_i8(1).as_arith_expr(),
+                BinaryOp::BITAND,
+                IntExpr::new_i8(2).as_arith_expr(),
+            ),
+            BinaryOp::BITOR,
+            binary(
+                IntExpr::new_i8(3).as_arith_expr(),
+                BinaryOp::BITXOR,
+                IntExpr::new_i8(4).as_arith_expr(),
+            ),
+        );
+
+        assert_eq!(describe(&Grouped::of(&expr)), "((1 & 2) | (3 ^ 4))");
+    }
+
+    #[test]
+    fn minus_applies_after_method() {
+        let expr = binary(
+            IntExpr::new_i8(-5).as_arith_expr(),
+            BinaryOp::MUL,
+            IntExpr::new_i8(2).as_arith_expr(),
+        );
+
+        assert_eq!(describe(&Grouped::of(&expr)), "-((5).safe_mul(2i8))");
+    }
+}
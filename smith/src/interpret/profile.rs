@@ -0,0 +1,24 @@
+/// Which blocks of a program ran when it was interpreted
+/// Blocks are told apart by address, so a profile only applies to the program it was recorded on
+use std::collections::HashSet;
+
+use crate::program::stmt::block_stmt::BlockStmt;
+
+pub struct Profile {
+    executed: HashSet<*const BlockStmt>,
+}
+
+impl Profile {
+    pub fn new(executed: HashSet<*const BlockStmt>) -> Self {
+        Profile { executed }
+    }
+
+    pub fn is_executed(&self, block: &BlockStmt) -> bool {
+        self.executed.contains(&(block as *const BlockStmt))
+    }
+
+    // Number of distinct blocks that ran at least once
+    pub fn executed_count(&self) -> usize {
+        self.executed.len()
+    }
+}
@@ -0,0 +1,289 @@
+/// Values of the interpreter and the integer semantics of the runtime crate
+/// Arithmetic is that of SafeArithmetic and BitArithmetic, the left operand is kept when the checked op fails
+use std::rc::Rc;
+
+use crate::program::{
+    expr::arithmetic_expr::{BinaryOp, IntValue},
+    types::IntTypeID,
+};
+
+use super::interpreter::InterpretError;
+
+// Where a value lives, a variable slot and a path of field indices into it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Place {
+    slot: usize,
+    fields: Vec<usize>,
+}
+
+impl Place {
+    pub fn new(slot: usize) -> Self {
+        Place {
+            slot,
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn get_slot(&self) -> usize {
+        self.slot
+    }
+
+    pub fn fields_iter(&self) -> std::slice::Iter<'_, usize> {
+        self.fields.iter()
+    }
+
+    pub fn push_field(&mut self, index: usize) {
+        self.fields.push(index);
+    }
+}
+
+// The name and field names of a struct, shared by all of its values
+#[derive(Debug, PartialEq)]
+pub struct StructShape {
+    name: String,
+    field_names: Vec<String>,
+}
+
+impl StructShape {
+    pub fn new(name: String, field_names: Vec<String>) -> Self {
+        StructShape { name, field_names }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn field_names_iter(&self) -> std::slice::Iter<'_, String> {
+        self.field_names.iter()
+    }
+
+    pub fn field_index(&self, name: &str) -> Option<usize> {
+        self.field_names
+            .iter()
+            .position(|field_name| field_name == name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Int(IntValue),
+    Bool(bool),
+    Struct(Rc<StructShape>, Vec<Value>), // Fields in declaration order
+    Tuple(Vec<Value>),
+    Array(Vec<Value>),
+    Ref(Place), // Shared and mutable borrows alike
+    Unit,
+}
+
+impl Value {
+    pub fn as_int(&self) -> Result<IntValue, InterpretError> {
+        match self {
+            Value::Int(value) => Ok(*value),
+            _ => Err(InterpretError::type_mismatch("an integer", self)),
+        }
+    }
+
+    pub fn as_bool(&self) -> Result<bool, InterpretError> {
+        match self {
+            Value::Bool(value) => Ok(*value),
+            _ => Err(InterpretError::type_mismatch("a bool", self)),
+        }
+    }
+
+    pub fn field_index(&self, name: &str) -> Option<usize> {
+        match self {
+            Value::Struct(shape, _) => shape.field_index(name),
+            _ => None,
+        }
+    }
+
+    pub fn get_field(&self, index: usize) -> Option<&Value> {
+        match self {
+            Value::Struct(_, fields) => fields.get(index),
+            _ => None,
+        }
+    }
+
+    pub fn get_field_mut(&mut self, index: usize) -> Option<&mut Value> {
+        match self {
+            Value::Struct(_, fields) => fields.get_mut(index),
+            _ => None,
+        }
+    }
+
+    // What ToChecksum gives for the value
+    pub fn to_checksum(&self) -> Result<u128, InterpretError> {
+        match self {
+            Value::Int(value) => Ok(int_to_checksum(*value)),
+            Value::Bool(value) => Ok(*value as u128),
+            _ => Err(InterpretError::type_mismatch("an integer or a bool", self)),
+        }
+    }
+
+    // Formatted the way serde_json formats it, references are loaded before they get here
+    pub fn to_json(&self) -> String {
+        let join = |values: &[Value]| {
+            values
+                .iter()
+                .map(|value| value.to_json())
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+
+        match self {
+            Value::Int(value) => value.to_string(),
+            Value::Bool(value) => value.to_string(),
+            Value::Struct(shape, fields) => {
+                let fields: Vec<String> = shape
+                    .field_names_iter()
+                    .zip(fields)
+                    .map(|(name, value)| format!("\"{}\":{}", name, value.to_json()))
+                    .collect();
+                format!("{{{}}}", fields.join(","))
+            }
+            Value::Tuple(values) | Value::Array(values) => format!("[{}]", join(values)),
+            Value::Ref(_) => unreachable!("References are loaded before serializing"),
+            Value::Unit => String::from("null"),
+        }
+    }
+}
+
+// Applies the same expression to operands of the same integer type
+macro_rules! int_binary {
+    ($left:expr, $right:expr, |$l:ident, $r:ident| $result:expr) => {
+        match ($left, $right) {
+            (IntValue::I8($l), IntValue::I8($r)) => Ok(IntValue::I8($result)),
+            (IntValue::I16($l), IntValue::I16($r)) => Ok(IntValue::I16($result)),
+            (IntValue::I32($l), IntValue::I32($r)) => Ok(IntValue::I32($result)),
+            (IntValue::I64($l), IntValue::I64($r)) => Ok(IntValue::I64($result)),
+            (IntValue::I128($l), IntValue::I128($r)) => Ok(IntValue::I128($result)),
+            (IntValue::U8($l), IntValue::U8($r)) => Ok(IntValue::U8($result)),
+            (IntValue::U16($l), IntValue::U16($r)) => Ok(IntValue::U16($result)),
+            (IntValue::U32($l), IntValue::U32($r)) => Ok(IntValue::U32($result)),
+            (IntValue::U64($l), IntValue::U64($r)) => Ok(IntValue::U64($result)),
+            (IntValue::U128($l), IntValue::U128($r)) => Ok(IntValue::U128($result)),
+            (left, right) => Err(InterpretError::Unsupported(format!(
+                "operands of different types {:?} and {:?}",
+                left, right
+            ))),
+        }
+    };
+}
+
+macro_rules! int_unary {
+    ($value:expr, |$v:ident| $result:expr) => {
+        match $value {
+            IntValue::I8($v) => IntValue::I8($result),
+            IntValue::I16($v) => IntValue::I16($result),
+            IntValue::I32($v) => IntValue::I32($result),
+            IntValue::I64($v) => IntValue::I64($result),
+            IntValue::I128($v) => IntValue::I128($result),
+            IntValue::U8($v) => IntValue::U8($result),
+            IntValue::U16($v) => IntValue::U16($result),
+            IntValue::U32($v) => IntValue::U32($result),
+            IntValue::U64($v) => IntValue::U64($result),
+            IntValue::U128($v) => IntValue::U128($result),
+        }
+    };
+}
+
+pub fn apply_binary_op(
+    op: BinaryOp,
+    left: IntValue,
+    right: IntValue,
+) -> Result<IntValue, InterpretError> {
+    match op {
+        BinaryOp::ADD => int_binary!(left, right, |l, r| l.checked_add(r).unwrap_or(l)),
+        BinaryOp::SUB => int_binary!(left, right, |l, r| l.checked_sub(r).unwrap_or(l)),
+        BinaryOp::MUL => int_binary!(left, right, |l, r| l.checked_mul(r).unwrap_or(l)),
+        BinaryOp::DIV => int_binary!(left, right, |l, r| l.checked_div(r).unwrap_or(l)),
+        BinaryOp::MOD => int_binary!(left, right, |l, r| l.checked_rem(r).unwrap_or(l)),
+        BinaryOp::BITAND => int_binary!(left, right, |l, r| l & r),
+        BinaryOp::BITOR => int_binary!(left, right, |l, r| l | r),
+        BinaryOp::BITXOR => int_binary!(left, right, |l, r| l ^ r),
+    }
+}
+
+// Release builds do not check for overflow, so negating the minimum gives it back
+pub fn wrapping_neg(value: IntValue) -> IntValue {
+    int_unary!(value, |v| v.wrapping_neg())
+}
+
+pub fn successor(value: IntValue) -> IntValue {
+    int_unary!(value, |v| v.wrapping_add(1))
+}
+
+pub fn is_negative(value: IntValue) -> bool {
+    int_to_checksum(value) >> 127 == 1 && !is_unsigned(value)
+}
+
+fn is_unsigned(value: IntValue) -> bool {
+    matches!(
+        value,
+        IntValue::U8(_)
+            | IntValue::U16(_)
+            | IntValue::U32(_)
+            | IntValue::U64(_)
+            | IntValue::U128(_)
+    )
+}
+
+// Signed values are sign extended, like an as cast
+fn int_to_checksum(value: IntValue) -> u128 {
+    match value {
+        IntValue::I8(v) => v as u128,
+        IntValue::I16(v) => v as u128,
+        IntValue::I32(v) => v as u128,
+        IntValue::I64(v) => v as u128,
+        IntValue::I128(v) => v as u128,
+        IntValue::U8(v) => v as u128,
+        IntValue::U16(v) => v as u128,
+        IntValue::U32(v) => v as u128,
+        IntValue::U64(v) => v as u128,
+        IntValue::U128(v) => v,
+    }
+}
+
+// An as cast from usize, which truncates
+pub fn cast_usize(value: usize, int_type_id: IntTypeID) -> IntValue {
+    match int_type_id {
+        IntTypeID::I8 => IntValue::I8(value as i8),
+        IntTypeID::I16 => IntValue::I16(value as i16),
+        IntTypeID::I32 => IntValue::I32(value as i32),
+        IntTypeID::I64 => IntValue::I64(value as i64),
+        IntTypeID::I128 => IntValue::I128(value as i128),
+        IntTypeID::U8 => IntValue::U8(value as u8),
+        IntTypeID::U16 => IntValue::U16(value as u16),
+        IntTypeID::U32 => IntValue::U32(value as u32),
+        IntTypeID::U64 => IntValue::U64(value as u64),
+        IntTypeID::U128 => IntValue::U128(value as u128),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn safe_arithmetic_keeps_left_operand() {
+        let add = apply_binary_op(BinaryOp::ADD, IntValue::I8(100), IntValue::I8(100));
+        let div = apply_binary_op(BinaryOp::DIV, IntValue::U32(7), IntValue::U32(0));
+        let rem = apply_binary_op(BinaryOp::MOD, IntValue::I16(i16::MIN), IntValue::I16(-1));
+
+        assert_eq!(add.unwrap(), IntValue::I8(100));
+        assert_eq!(div.unwrap(), IntValue::U32(7));
+        assert_eq!(rem.unwrap(), IntValue::I16(i16::MIN));
+        assert!(apply_binary_op(BinaryOp::BITAND, IntValue::I8(1), IntValue::U8(1)).is_err());
+    }
+
+    #[test]
+    fn checksum_sign_extends() {
+        assert_eq!(
+            Value::Int(IntValue::I8(-1)).to_checksum().unwrap(),
+            u128::MAX
+        );
+        assert_eq!(Value::Bool(true).to_checksum().unwrap(), 1);
+        assert!(is_negative(IntValue::I64(-3)));
+        assert!(!is_negative(IntValue::U128(u128::MAX)));
+    }
+}
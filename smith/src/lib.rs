@@ -1,38 +1,329 @@
-use rand::{prelude::StdRng, SeedableRng};
+use crate::program::program::Program;
 
-use crate::generator::main_gen;
+pub use crate::builder::{GeneratedProgram, GenerationMetadata, Generator, OutputMode};
+pub use crate::check::checker::{CheckError, Checker};
+pub use crate::emi::variants::EmiGenerator;
+pub use crate::generator::config::GeneratorConfig;
+pub use crate::generator::features::Features;
+pub use crate::generator::stats::GenerationStats;
+pub use crate::interpret::interpreter::{ExpectedOutput, InterpretError, Interpreter};
+pub use crate::metamorph::transformer::MetamorphicTransformer;
+pub use crate::reduce::reducer::Reducer;
 
+pub mod builder;
+pub mod check;
+pub mod emi;
 pub mod generator;
+pub mod interpret;
+pub mod metamorph;
 pub mod program;
+pub mod reduce;
+#[cfg(test)]
+mod test_util;
+
+// Name of the library crate in workspace output
+const LIB_CRATE_NAME: &str = "generated_lib";
 
-pub fn generate_from_seed(seed: u64) -> String {
-    let mut rng = StdRng::seed_from_u64(seed);
+// Sources of the runtime crate, inlined into standalone programs
+const RUNTIME_MODULES: [(&str, &str); 5] = [
+    ("checksum", include_str!("../../runtime/src/checksum.rs")),
+    ("executor", include_str!("../../runtime/src/executor.rs")),
+    ("ops", include_str!("../../runtime/src/ops.rs")),
+    ("safe_ops", include_str!("../../runtime/src/safe_ops.rs")),
+    ("trace", include_str!("../../runtime/src/trace.rs")),
+];
 
+fn header(features: Features) -> String {
     let warning_macro = String::from("#![allow(warnings)]\n");
-    let imports = String::from(
-        "use serde::Serialize;\nuse serde_json::Serializer;\nuse runtime::{ops::BitArithmetic, safe_ops::SafeArithmetic};\n",
-    );
-    let main = main_gen::gen_main(&mut rng);
+    let runtime_imports = runtime_imports();
+
+    if features.standalone {
+        return format!("{}{}{}", warning_macro, runtime_imports, inlined_runtime());
+    }
+
+    let serde_imports = String::from("use serde::Serialize;\nuse serde_json::Serializer;\n");
+
+    format!("{}{}{}", warning_macro, serde_imports, runtime_imports)
+}
+
+fn runtime_imports() -> String {
+    String::from(
+        "use runtime::{checksum::{self, ToChecksum}, executor::{block_on, yield_now}, ops::BitArithmetic, safe_ops::SafeArithmetic, trace};\n",
+    )
+}
+
+// A standalone binary shares the runtime inlined into the library, and with it the checksum
+fn workspace_bin_header(features: Features) -> String {
+    if features.standalone {
+        format!(
+            "#![allow(warnings)]\nuse {}::runtime;\n{}",
+            LIB_CRATE_NAME,
+            runtime_imports()
+        )
+    } else {
+        header(features)
+    }
+}
+
+// The runtime crate as a module, so that the program compiles with a bare rustc
+fn inlined_runtime() -> String {
+    let modules: String = RUNTIME_MODULES
+        .iter()
+        .map(|(name, source)| format!("pub mod {} {{\n{}}}\n", name, source))
+        .collect();
+
+    format!("pub mod runtime {{\n{}}}\n", modules)
+}
+
+pub fn generate_from_seed(seed: u64, features: Features, config: &GeneratorConfig) -> String {
+    Generator::new(seed)
+        .with_features(features)
+        .with_config(config.clone())
+        .generate()
+        .get_source()
+        .to_string()
+}
 
-    let code = format!("{}{}{}", warning_macro, imports, main);
+// Generates a Cargo workspace with a library crate holding the program's modules
+// and a binary crate holding the rest, returned as (relative path, contents) pairs
+pub fn generate_workspace_from_seed(
+    seed: u64,
+    runtime_path: &str,
+    features: Features,
+    config: &GeneratorConfig,
+) -> Vec<(String, String)> {
+    Generator::new(seed)
+        .with_features(features)
+        .with_config(config.clone())
+        .with_output_mode(OutputMode::Workspace(runtime_path.to_string()))
+        .generate()
+        .get_files()
+        .to_vec()
+}
 
-    code
+// Renders a generated or reloaded program as a single file
+pub fn render_program(program: &Program, features: Features) -> String {
+    format!(
+        "{}{}{}",
+        program.get_header_comment(),
+        header(features),
+        program.to_string()
+    )
+}
+
+// The line of a manifest that depends on the runtime crate
+fn runtime_dependency(runtime_path: &str) -> String {
+    format!("runtime = {{ path = \"{}\" }}", runtime_path)
+}
+
+fn workspace_files(
+    program: &Program,
+    seed: u64,
+    runtime_path: &str,
+    features: Features,
+) -> Vec<(String, String)> {
+    let bin_name = format!("seed_{}", seed);
+
+    let workspace_manifest = String::from(
+        "[workspace]\nmembers = [\"lib\", \"bin\"]\n\n[profile.release]\nlto = true\ndebug = true\n",
+    );
+    // Standalone crates inline the runtime themselves
+    let dependencies = if features.standalone {
+        String::from("[dependencies]\n")
+    } else {
+        format!(
+            "[dependencies]\n{}\nserde = {{ version = \"1.0\", features = [\"derive\"] }}\nserde_json = \"1.0\"\n",
+            runtime_dependency(runtime_path)
+        )
+    };
+    let lib_manifest = format!(
+        "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2018\"\n\n{}",
+        LIB_CRATE_NAME, dependencies
+    );
+    let bin_manifest = format!(
+        "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2018\"\n\n{}{} = {{ path = \"../lib\" }}\n",
+        bin_name, dependencies, LIB_CRATE_NAME
+    );
+
+    vec![
+        (String::from("Cargo.toml"), workspace_manifest),
+        (String::from("lib/Cargo.toml"), lib_manifest),
+        (
+            String::from("lib/src/lib.rs"),
+            format!("{}{}", header(features), program.lib_to_string()),
+        ),
+        (String::from("bin/Cargo.toml"), bin_manifest),
+        (
+            String::from("bin/src/main.rs"),
+            format!(
+                "{}{}{}",
+                program.get_header_comment(),
+                workspace_bin_header(features),
+                program.bin_to_string(LIB_CRATE_NAME)
+            ),
+        ),
+    ]
 }
 
 #[cfg(test)]
 mod test {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+
+    use crate::{generator::main_gen, test_util::small_config};
+
     use super::*;
 
     #[test]
     fn same_seed_generates_same_program() {
         for i in 0..50 {
-            let mut rng1 = StdRng::seed_from_u64(i);
-            let mut rng2 = StdRng::seed_from_u64(i);
+            let mut rng1 = ChaCha12Rng::seed_from_u64(i);
+            let mut rng2 = ChaCha12Rng::seed_from_u64(i);
 
-            let main1 = main_gen::gen_main(&mut rng1);
-            let main2 = main_gen::gen_main(&mut rng2);
+            let main1 =
+                main_gen::gen_main(&mut rng1, Features::default(), &GeneratorConfig::default());
+            let main2 =
+                main_gen::gen_main(&mut rng2, Features::default(), &GeneratorConfig::default());
 
             assert_eq!(main1, main2);
         }
     }
+
+    #[test]
+    fn threads_only_generated_when_enabled() {
+        let threads = Features {
+            threads: true,
+            ..Default::default()
+        };
+
+        let with_threads = (0..8)
+            .filter(|seed| {
+                generate_from_seed(*seed, threads, &small_config()).contains("std::thread::scope")
+            })
+            .count();
+        let without_threads = (0..8)
+            .filter(|seed| {
+                generate_from_seed(*seed, Features::default(), &small_config())
+                    .contains("std::thread::scope")
+            })
+            .count();
+
+        assert!(with_threads > 0);
+        assert_eq!(without_threads, 0);
+    }
+
+    #[test]
+    fn standalone_program_has_no_dependencies() {
+        let standalone = Features {
+            standalone: true,
+            ..Default::default()
+        };
+
+        for seed in 0..4 {
+            let code = generate_from_seed(seed, standalone, &small_config());
+
+            assert!(!code.contains("serde"));
+            assert!(code.contains("pub mod runtime {"));
+            assert!(code.contains("pub trait SafeArithmetic"));
+        }
+    }
+
+    #[test]
+    fn trace_only_generated_when_enabled() {
+        let trace = Features {
+            trace: true,
+            ..Default::default()
+        };
+
+        for seed in 0..4 {
+            assert!(
+                generate_from_seed(seed, trace, &small_config()).contains("trace::record(1, &[")
+            );
+            assert!(
+                !generate_from_seed(seed, Features::default(), &small_config())
+                    .contains("trace::record(")
+            );
+        }
+    }
+
+    #[test]
+    fn traced_programs_never_spawn_threads() {
+        let features = Features {
+            threads: true,
+            trace: true,
+            ..Default::default()
+        };
+
+        // Without traces, some of these seeds spawn threads
+        for seed in 0..8 {
+            assert!(
+                !generate_from_seed(seed, features, &small_config()).contains("std::thread::scope")
+            );
+        }
+    }
+
+    #[test]
+    fn swarm_configuration_is_recorded_in_header() {
+        let swarm = Features {
+            swarm: true,
+            ..Default::default()
+        };
+
+        for seed in 0..4 {
+            assert!(generate_from_seed(seed, swarm, &small_config())
+                .starts_with("// Swarm configuration\n"));
+            assert!(
+                !generate_from_seed(seed, Features::default(), &small_config())
+                    .contains("// Swarm")
+            );
+        }
+    }
+
+    #[test]
+    fn program_renders_the_same_after_json_round_trip() {
+        // Threads are never generated together with traces
+        let threads = Features {
+            threads: true,
+            unsafe_code: true,
+            swarm: true,
+            ..Default::default()
+        };
+        let trace = Features {
+            threads: false,
+            trace: true,
+            ..threads
+        };
+
+        for &features in [threads, trace].iter() {
+            for seed in 0..3 {
+                let generated = Generator::new(seed).with_features(features).generate();
+                let reloaded = Program::from_json(&generated.get_program().to_json()).unwrap();
+
+                assert_eq!(render_program(&reloaded, features), generated.get_source());
+            }
+        }
+    }
+
+    #[test]
+    fn workspace_binary_uses_library() {
+        let files = generate_workspace_from_seed(
+            0,
+            "../runtime",
+            Features::default(),
+            &GeneratorConfig::default(),
+        );
+
+        let (_, main_rs) = files
+            .iter()
+            .find(|(path, _)| path == "bin/src/main.rs")
+            .unwrap();
+        let (_, lib_rs) = files
+            .iter()
+            .find(|(path, _)| path == "lib/src/lib.rs")
+            .unwrap();
+
+        assert!(main_rs.contains("use generated_lib::*;"));
+        assert!(main_rs.contains("fn main()"));
+        assert!(lib_rs.contains("pub use module_1::*;"));
+    }
 }
@@ -0,0 +1,2 @@
+pub mod rewrites;
+pub mod transformer;
@@ -0,0 +1,1168 @@
+/// Rewrites that change the shape of a program but not what it prints
+/// Like reducer edits, a rewrite has a number of candidates in a program, counted in traversal order
+use std::collections::{BTreeMap, BTreeSet};
+use strum_macros::EnumIter;
+
+use crate::generator::struct_gen::GLOBAL_STRUCT_NAME;
+use crate::program::{
+    expr::{
+        arithmetic_expr::{ArithmeticExpr, BinaryExpr, BinaryOp, IntExpr, IntValue},
+        bool_expr::{BoolExpr, ComparisonExpr, ComparisonOp},
+        borrow_expr::BorrowExpr,
+        closure_expr::ClosureExpr,
+        expr::{Expr, RawExpr},
+        func_call_expr::FunctionCallExpr,
+        iter_expr::IterExpr,
+        macro_call_expr::MacroCallExpr,
+        struct_expr::StructLiteral,
+    },
+    function::{Function, Param},
+    macro_template::MacroKind,
+    pattern::Pattern,
+    program::{Module, Program},
+    stmt::{
+        assign_stmt::AssignStmt, await_stmt::AwaitStmt, block_stmt::BlockStmt,
+        checksum_stmt::ChecksumStmt, expr_stmt::ExprStmt, let_stmt::LetStmt,
+        op_assign_stmt::OpAssignStmt, return_stmt::ReturnStmt, static_stmt::StaticStmt, stmt::Stmt,
+        thread_scope_stmt::ThreadScopeStmt, trace_stmt::TraceStmt, unsafe_stmt::UnsafeStmt,
+        while_stmt::WhileStmt,
+    },
+    struct_template::StructTemplate,
+    types::{BorrowTypeID, IntTypeID, TypeID},
+    var::Var,
+    visitor::{visit::Visit, visit_mut::VisitMut},
+};
+use crate::reduce::edits::{
+    inlined_body, into_call, passes_mut_ref_on, returns_only_at_end, whole_call, Names, Target,
+};
+
+const MAIN_NAME: &str = "main";
+const VAR_PREFIX: &str = "var_";
+const FUNCTION_PREFIX: &str = "function_";
+const STRUCT_PREFIX: &str = "Struct";
+const FIELD_PREFIX: &str = "field_";
+
+#[derive(Clone, Copy, Debug, PartialEq, EnumIter)]
+pub enum Rewrite {
+    SwapOperands,  // The operands of a bitwise and, or or xor change places
+    IntroduceTemp, // The value of a let, assignment or return is bound to a new variable first
+    OpAssign,      // let x = a.safe_add(b) becomes let mut x = a followed by x.safe_self_add(b)
+    ForToWhile,    // A for loop over a range becomes a while loop with a counter of its own
+    InlineCall,    // A call is replaced by a copy of the body of the function it calls
+    OutlineBlock,  // The statements of a nested block move into a new function that it calls
+    SplitStruct,   // The last fields of a struct move into a new struct, nested inside it
+}
+
+impl Rewrite {
+    // Applies the candidate with the given index, or returns false if there are not that many
+    pub fn apply(&self, program: &mut Program, index: usize) -> bool {
+        self.run(program, index).applied
+    }
+
+    // Number of candidates in the program, which is left as it is
+    pub fn count(&self, program: &mut Program) -> usize {
+        self.run(program, usize::MAX).seen
+    }
+
+    fn run(&self, program: &mut Program, index: usize) -> Target {
+        match self {
+            Rewrite::SwapOperands => {
+                let mut rewrite = SwapOperands::new(index);
+                rewrite.visit_program_mut(program);
+                rewrite.target
+            }
+            Rewrite::IntroduceTemp => {
+                let mut rewrite = IntroduceTemp::new(index);
+                rewrite.visit_program_mut(program);
+                rewrite.target
+            }
+            Rewrite::OpAssign => {
+                let mut rewrite = OpAssign::new(index);
+                rewrite.visit_program_mut(program);
+                rewrite.target
+            }
+            Rewrite::ForToWhile => {
+                let mut rewrite = ForToWhile::new(index);
+                rewrite.visit_program_mut(program);
+                rewrite.target
+            }
+            Rewrite::InlineCall => {
+                let mut rewrite = InlineCall::new(program, index);
+                rewrite.visit_program_mut(program);
+                rewrite.target
+            }
+            Rewrite::OutlineBlock => {
+                let mut rewrite = OutlineBlock::new(program, index);
+                rewrite.visit_program_mut(program);
+                if let Some(function) = rewrite.function.take() {
+                    program.push_function(function);
+                }
+                rewrite.target
+            }
+            Rewrite::SplitStruct => split_struct(program, index),
+        }
+    }
+}
+
+// Number at the end of a generated name, such as the 3 of var_3
+fn suffix(prefix: &str, name: &str) -> Option<usize> {
+    name.strip_prefix(prefix)?.parse().ok()
+}
+
+// The name after the last one with the prefix
+fn next_name<'a>(prefix: &str, names: impl Iterator<Item = &'a str>) -> String {
+    let last = names
+        .filter_map(|name| suffix(prefix, name))
+        .max()
+        .unwrap_or(0);
+
+    format!("{}{}", prefix, last + 1)
+}
+
+// Variable that a place such as *var_1 or var_1.field_2 starts from
+fn root_of(place: &str) -> &str {
+    place.trim_start_matches('*').split('.').next().unwrap()
+}
+
+fn is_bit_op(op: BinaryOp) -> bool {
+    matches!(op, BinaryOp::BITAND | BinaryOp::BITOR | BinaryOp::BITXOR)
+}
+
+// Whether the expression is printed with an operator outside of any parentheses,
+// in which case rustc groups it with its neighbours by precedence rather than by the AST
+fn has_infix(expr: &ArithmeticExpr) -> bool {
+    match expr {
+        ArithmeticExpr::Binary(binary) if is_bit_op(binary.get_op()) => true,
+        ArithmeticExpr::Binary(binary) if !binary.is_const() => has_infix(binary.get_left()),
+        _ => false,
+    }
+}
+
+// Calls may have side effects, which have to happen in the same order
+#[derive(Default)]
+struct Calls {
+    found: bool,
+}
+
+impl Visit for Calls {
+    fn visit_func_call_expr(&mut self, _node: &FunctionCallExpr) {
+        self.found = true;
+    }
+
+    fn visit_macro_call_expr(&mut self, _node: &MacroCallExpr) {
+        self.found = true;
+    }
+}
+
+fn has_call(expr: &ArithmeticExpr) -> bool {
+    let mut calls = Calls::default();
+    calls.visit_arithmetic_expr(expr);
+    calls.found
+}
+
+// Hands out variable names after every one that a function mentions
+#[derive(Default)]
+struct FreshVars {
+    last: usize,
+}
+
+impl FreshVars {
+    fn of_function(function: &Function) -> Self {
+        let mut fresh_vars = FreshVars::default();
+        fresh_vars.visit_function(function);
+        fresh_vars
+    }
+
+    fn next(&mut self) -> String {
+        self.last += 1;
+        format!("{}{}", VAR_PREFIX, self.last)
+    }
+
+    fn mention(&mut self, name: &str) {
+        if let Some(last) = suffix(VAR_PREFIX, root_of(name)) {
+            self.last = self.last.max(last);
+        }
+    }
+}
+
+impl Visit for FreshVars {
+    fn visit_var(&mut self, node: &Var) {
+        self.mention(node.get_name_str());
+    }
+
+    fn visit_binding(&mut self, node: &Var) {
+        self.mention(node.get_name_str());
+    }
+}
+
+// Number of declarations of each variable name
+#[derive(Default)]
+struct Bindings {
+    counts: BTreeMap<String, usize>,
+}
+
+impl Bindings {
+    fn of_function(function: &Function) -> Self {
+        let mut bindings = Bindings::default();
+        bindings.visit_function(function);
+        bindings
+    }
+
+    fn count(&self, name: &str) -> usize {
+        self.counts.get(name).copied().unwrap_or(0)
+    }
+}
+
+impl Visit for Bindings {
+    fn visit_binding(&mut self, node: &Var) {
+        *self.counts.entry(node.get_name()).or_insert(0) += 1;
+    }
+}
+
+struct SwapOperands {
+    target: Target,
+    contained: bool, // Whether the expression being visited is printed as a unit
+}
+
+impl SwapOperands {
+    fn new(index: usize) -> Self {
+        SwapOperands {
+            target: Target::new(index),
+            contained: true,
+        }
+    }
+
+    // Neither side may be split up by precedence, or call anything
+    fn can_swap(binary: &BinaryExpr) -> bool {
+        is_bit_op(binary.get_op())
+            && !has_infix(binary.get_left())
+            && !has_infix(binary.get_right())
+            && !has_call(binary.get_left())
+            && !has_call(binary.get_right())
+    }
+}
+
+impl VisitMut for SwapOperands {
+    fn visit_arithmetic_expr_mut(&mut self, node: &mut ArithmeticExpr) {
+        let contained = std::mem::replace(&mut self.contained, true);
+        let binary = match node {
+            ArithmeticExpr::Binary(binary) => binary,
+            _ => {
+                node.walk_mut(self);
+                return;
+            }
+        };
+
+        if contained && Self::can_swap(binary) && self.target.hit() {
+            binary.swap_operands();
+        }
+
+        // The operands of an infix operator, and the receiver of a method, are not printed as a unit
+        // Const arithmetic is written out in a block
+        let (left_contained, right_contained) = if is_bit_op(binary.get_op()) {
+            (false, false)
+        } else if binary.is_const() {
+            (true, true)
+        } else {
+            (false, true)
+        };
+        self.contained = left_contained;
+        self.visit_arithmetic_expr_mut(binary.get_left_mut());
+        self.contained = right_contained;
+        self.visit_arithmetic_expr_mut(binary.get_right_mut());
+        self.contained = true;
+    }
+}
+
+struct IntroduceTemp {
+    target: Target,
+    fresh_vars: FreshVars, // Of the function being visited
+}
+
+impl IntroduceTemp {
+    fn new(index: usize) -> Self {
+        IntroduceTemp {
+            target: Target::new(index),
+            fresh_vars: FreshVars::default(),
+        }
+    }
+
+    // Type of the value that the statement computes, if it is an integer or bool worth a variable
+    fn temp_type(stmt: &Stmt) -> Option<TypeID> {
+        let (expr, type_id) = match stmt {
+            Stmt::LetStatement(s) if s.get_var().get_borrow_type() == BorrowTypeID::None => {
+                (s.get_expr(), s.get_var().get_type())
+            }
+            Stmt::AssignStatement(s) => match s.get_expr() {
+                Expr::Arithmetic(expr) => (s.get_expr(), expr.get_type()),
+                _ => (s.get_expr(), TypeID::BoolType),
+            },
+            Stmt::ReturnStatement(s) => (s.get_expr(), s.get_type()),
+            _ => return None,
+        };
+
+        match expr {
+            Expr::Arithmetic(ArithmeticExpr::Var(_)) | Expr::Boolean(BoolExpr::Var(_)) => None,
+            Expr::Arithmetic(_) if matches!(type_id, TypeID::IntType(_)) => Some(type_id),
+            Expr::Boolean(_) if type_id == TypeID::BoolType => Some(type_id),
+            _ => None,
+        }
+    }
+
+    fn expr_mut(stmt: &mut Stmt) -> &mut Expr {
+        match stmt {
+            Stmt::LetStatement(s) => s.get_expr_mut(),
+            Stmt::AssignStatement(s) => s.get_expr_mut(),
+            Stmt::ReturnStatement(s) => s.get_expr_mut(),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl VisitMut for IntroduceTemp {
+    fn visit_function_mut(&mut self, node: &mut Function) {
+        if !self.target.applied {
+            self.fresh_vars = FreshVars::of_function(node);
+            node.walk_mut(self);
+        }
+    }
+
+    fn visit_block_stmt_mut(&mut self, node: &mut BlockStmt) {
+        if self.target.applied {
+            return;
+        }
+
+        let candidates: Vec<(usize, TypeID)> = node
+            .stmts_iter()
+            .enumerate()
+            .filter_map(|(i, stmt)| Some((i, Self::temp_type(stmt)?)))
+            .collect();
+        for (index, type_id) in candidates {
+            if self.target.hit() {
+                let temp = Var::new(type_id, self.fresh_vars.next(), false);
+                let mut stmt = node.remove(index).unwrap();
+                let expr_mut = Self::expr_mut(&mut stmt);
+                let temp_expr = match expr_mut {
+                    Expr::Arithmetic(_) => ArithmeticExpr::Var(temp.clone()).as_expr(),
+                    _ => BoolExpr::Var(temp.clone()).as_expr(),
+                };
+                let expr = std::mem::replace(expr_mut, temp_expr);
+
+                node.insert(index, stmt);
+                node.insert(index, LetStmt::new(temp, expr).as_stmt());
+                return;
+            }
+        }
+
+        node.walk_mut(self);
+    }
+}
+
+struct OpAssign {
+    target: Target,
+}
+
+impl OpAssign {
+    fn new(index: usize) -> Self {
+        OpAssign {
+            target: Target::new(index),
+        }
+    }
+
+    // The left operand has to be a value that the new variable can hold as it is,
+    // and the right operand is evaluated after the variable exists, so may not mention it
+    fn can_split(stmt: &Stmt) -> bool {
+        let let_stmt = match stmt {
+            Stmt::LetStatement(s) => s,
+            _ => return false,
+        };
+        let var = let_stmt.get_var();
+        let binary = match let_stmt.get_expr() {
+            Expr::Arithmetic(ArithmeticExpr::Binary(binary)) if !binary.is_const() => binary,
+            _ => return false,
+        };
+        if var.get_borrow_type() != BorrowTypeID::None
+            || !matches!(var.get_type(), TypeID::IntType(_))
+            || has_infix(binary.get_left())
+            || (is_bit_op(binary.get_op()) && has_infix(binary.get_right()))
+        {
+            return false;
+        }
+        if let ArithmeticExpr::Var(left) = binary.get_left() {
+            if left.get_borrow_type() != BorrowTypeID::None {
+                return false;
+            }
+        }
+
+        let mut names = Names::default();
+        names.visit_arithmetic_expr(binary.get_right());
+        names.count(var.get_name_str()) == 0
+    }
+}
+
+impl VisitMut for OpAssign {
+    // Trait methods cannot be called in a const fn
+    fn visit_function_mut(&mut self, node: &mut Function) {
+        if !self.target.applied && !node.get_template().is_const() {
+            node.walk_mut(self);
+        }
+    }
+
+    fn visit_block_stmt_mut(&mut self, node: &mut BlockStmt) {
+        if self.target.applied {
+            return;
+        }
+
+        let candidates: Vec<usize> = node
+            .stmts_iter()
+            .enumerate()
+            .filter(|(_, stmt)| Self::can_split(stmt))
+            .map(|(i, _)| i)
+            .collect();
+        for index in candidates {
+            if self.target.hit() {
+                let (var, expr) = match node.remove(index).unwrap() {
+                    Stmt::LetStatement(let_stmt) => let_stmt.into_parts(),
+                    _ => unreachable!(),
+                };
+                let binary = match ArithmeticExpr::from(expr) {
+                    ArithmeticExpr::Binary(binary) => binary,
+                    _ => unreachable!(),
+                };
+                let op = binary.get_op();
+                let (left, right) = binary.into_operands();
+                let var = Var::new(var.get_type(), var.get_name(), true);
+
+                node.insert(index, OpAssignStmt::new(var.clone(), right, op).as_stmt());
+                node.insert(index, LetStmt::new(var, left.as_expr()).as_stmt());
+                return;
+            }
+        }
+
+        node.walk_mut(self);
+    }
+}
+
+struct ForToWhile {
+    target: Target,
+    fresh_vars: FreshVars, // Of the function being visited
+}
+
+impl ForToWhile {
+    fn new(index: usize) -> Self {
+        ForToWhile {
+            target: Target::new(index),
+            fresh_vars: FreshVars::default(),
+        }
+    }
+
+    // for i in lo..hi { body } becomes
+    // let mut c = lo; let e = hi; while (c < e) { let i = c; c.safe_self_add(1); body }
+    // Both bounds are evaluated once and up front, like those of the range, and the counter
+    // moves on before the body runs so that it can never overflow
+    fn while_stmts(&mut self, stmt: Stmt) -> Vec<Stmt> {
+        let (iter_var, iterable, mut block_stmt) = match stmt {
+            Stmt::LoopStatement(for_loop_stmt) => for_loop_stmt.into_parts(),
+            _ => unreachable!(),
+        };
+        let IterExpr::Range(range) = iterable;
+        let type_id = range.get_type();
+        let int_type_id = match type_id {
+            TypeID::IntType(int_type_id) => int_type_id,
+            _ => unreachable!(),
+        };
+        let (start, end) = range.into_bounds();
+
+        let counter = Var::new(type_id.clone(), self.fresh_vars.next(), true);
+        let bound = Var::new(type_id.clone(), self.fresh_vars.next(), false);
+        let condition = ComparisonExpr::new(
+            ArithmeticExpr::Var(counter.clone()),
+            ArithmeticExpr::Var(bound.clone()),
+            ComparisonOp::Less,
+        )
+        .as_bool_expr();
+
+        let iter_var = Var::new(type_id, iter_var.get_name(), iter_var.is_mut());
+        block_stmt.push_front(
+            OpAssignStmt::new(
+                counter.clone(),
+                Self::one(int_type_id).as_arith_expr(),
+                BinaryOp::ADD,
+            )
+            .as_stmt(),
+        );
+        block_stmt.push_front(
+            LetStmt::new(iter_var, ArithmeticExpr::Var(counter.clone()).as_expr()).as_stmt(),
+        );
+
+        vec![
+            LetStmt::new(counter, start.as_expr()).as_stmt(),
+            LetStmt::new(bound, end.as_expr()).as_stmt(),
+            WhileStmt::new(condition, block_stmt).as_stmt(),
+        ]
+    }
+
+    fn one(int_type_id: IntTypeID) -> IntExpr {
+        IntExpr::new(IntValue::one(int_type_id))
+    }
+}
+
+impl VisitMut for ForToWhile {
+    // Trait methods cannot be called in a const fn
+    fn visit_function_mut(&mut self, node: &mut Function) {
+        if !self.target.applied && !node.get_template().is_const() {
+            self.fresh_vars = FreshVars::of_function(node);
+            node.walk_mut(self);
+        }
+    }
+
+    fn visit_block_stmt_mut(&mut self, node: &mut BlockStmt) {
+        if self.target.applied {
+            return;
+        }
+
+        let candidates: Vec<usize> = node
+            .stmts_iter()
+            .enumerate()
+            .filter(|(_, stmt)| matches!(stmt, Stmt::LoopStatement(_)))
+            .map(|(i, _)| i)
+            .collect();
+        for index in candidates {
+            if self.target.hit() {
+                let stmt = node.remove(index).unwrap();
+                for (i, while_stmt) in self.while_stmts(stmt).into_iter().enumerate() {
+                    node.insert(index + i, while_stmt);
+                }
+                return;
+            }
+        }
+
+        node.walk_mut(self);
+    }
+}
+
+// Statics inside a body would be duplicated by inlining it
+#[derive(Default)]
+struct Statics {
+    found: bool,
+}
+
+impl Visit for Statics {
+    fn visit_static_stmt(&mut self, _node: &StaticStmt) {
+        self.found = true;
+    }
+}
+
+// A function with a body inlined into it is not inlined itself, so that bodies do not nest without end
+#[derive(Default)]
+struct InlinedBodies {
+    found: bool,
+}
+
+impl Visit for InlinedBodies {
+    fn visit_expr(&mut self, node: &Expr) {
+        match node {
+            Expr::Block(_) | Expr::Closure(_) => self.found = true,
+            _ => node.walk(self),
+        }
+    }
+}
+
+// Unlike the reducer, which removes a function once its only call is inlined,
+// the function is kept and any one of its calls gets a copy of its body
+// A body that returns before its end is wrapped in a closure, which a const function cannot call
+struct InlineCall {
+    target: Target,
+    callees: BTreeMap<String, (Function, bool)>, // Functions that can be inlined, by name, and if they return early
+    caller: String,
+    caller_is_const: bool,
+}
+
+impl InlineCall {
+    fn new(program: &Program, index: usize) -> Self {
+        let callees = program
+            .functions_iter()
+            .filter(|function| Self::can_inline(function))
+            .map(|function| {
+                let returns_early = Names::of_function(function).count("return") > 0
+                    || !returns_only_at_end(function);
+                (function.get_name(), (function.clone(), returns_early))
+            })
+            .collect();
+
+        InlineCall {
+            target: Target::new(index),
+            callees,
+            caller: String::new(),
+            caller_is_const: false,
+        }
+    }
+
+    // Async functions are left alone, since their calls are awaited or driven by the executor
+    fn can_inline(function: &Function) -> bool {
+        let name = function.get_name();
+        let mut statics = Statics::default();
+        statics.visit_function(function);
+        let mut inlined_bodies = InlinedBodies::default();
+        inlined_bodies.visit_function(function);
+
+        name != MAIN_NAME
+            && !function.get_template().is_async()
+            && Names::of_function(function).count(&name) == 0
+            && !statics.found
+            && !inlined_bodies.found
+    }
+
+    // The arguments are bound outside of the closure, which then only captures the parameters
+    fn inlined(callee: Function, returns_early: bool, arguments: Vec<Expr>) -> Expr {
+        let params = callee.get_params();
+        if !returns_early {
+            return inlined_body(&params, callee.into_block_stmt(), arguments);
+        }
+
+        let return_type = callee.get_return_type();
+        let closure = ClosureExpr::new(return_type.clone(), callee.into_block_stmt());
+        let block_stmt =
+            BlockStmt::new_from_vec(vec![
+                ReturnStmt::new(return_type, closure.as_expr()).as_stmt()
+            ]);
+        inlined_body(&params, block_stmt, arguments)
+    }
+}
+
+impl VisitMut for InlineCall {
+    fn visit_module_mut(&mut self, _node: &mut Module) {}
+
+    fn visit_function_mut(&mut self, node: &mut Function) {
+        let template = node.get_template();
+        if !self.target.applied && !template.is_async() {
+            self.caller = node.get_name();
+            self.caller_is_const = template.is_const();
+            node.walk_mut(self);
+        }
+    }
+
+    fn visit_expr_mut(&mut self, node: &mut Expr) {
+        if self.target.applied {
+            return;
+        }
+
+        // A const function can only take the body of another const function
+        let name = whole_call(node)
+            .filter(|call| !passes_mut_ref_on(call))
+            .map(|call| call.get_name());
+        let can_inline = match name.as_ref().and_then(|name| self.callees.get(name)) {
+            Some((callee, returns_early)) => {
+                callee.get_name() != self.caller
+                    && (!self.caller_is_const
+                        || (callee.get_template().is_const() && !returns_early))
+            }
+            None => false,
+        };
+        if !can_inline || !self.target.hit() {
+            node.walk_mut(self);
+            return;
+        }
+
+        let (callee, returns_early) = &self.callees[&name.unwrap()];
+        let callee = callee.clone();
+        let returns_early = *returns_early;
+        let call = std::mem::replace(node, RawExpr::new(String::new()).as_expr());
+        let arguments = into_call(call).unwrap().into_arguments();
+
+        *node = Self::inlined(callee, returns_early, arguments);
+    }
+}
+
+// What a block reads and writes of the variables around it
+#[derive(Default)]
+struct BlockUses {
+    forbidden: bool, // Statements that only make sense where they are
+    bindings: BTreeMap<String, usize>,
+    uses: Vec<Var>,
+    places: Vec<String>, // Of checksums and traces
+    written: BTreeSet<String>,
+    mut_borrow_depth: usize,
+}
+
+impl Visit for BlockUses {
+    fn visit_stmt(&mut self, node: &Stmt) {
+        match node {
+            Stmt::ReturnStatement(_)
+            | Stmt::AwaitStatement(_)
+            | Stmt::ThreadScopeStatement(_)
+            | Stmt::UnsafeStatement(_)
+            | Stmt::ConstStatement(_)
+            | Stmt::StaticStatement(_) => self.forbidden = true,
+            _ => node.walk(self),
+        }
+    }
+
+    // Breaks and returns
+    fn visit_raw_expr(&mut self, _node: &RawExpr) {
+        self.forbidden = true;
+    }
+
+    fn visit_binding(&mut self, node: &Var) {
+        *self.bindings.entry(node.get_name()).or_insert(0) += 1;
+    }
+
+    fn visit_var(&mut self, node: &Var) {
+        if self.mut_borrow_depth > 0 {
+            self.written
+                .insert(root_of(node.get_name_str()).to_string());
+        }
+        self.uses.push(node.clone());
+    }
+
+    fn visit_assign_stmt(&mut self, node: &AssignStmt) {
+        self.written.insert(root_of(&node.get_name()).to_string());
+        node.walk(self);
+    }
+
+    fn visit_op_assign_stmt(&mut self, node: &OpAssignStmt) {
+        self.written.insert(root_of(&node.get_name()).to_string());
+        node.walk(self);
+    }
+
+    fn visit_borrow_expr(&mut self, node: &BorrowExpr) {
+        let is_mut = node.get_borrow_type() == BorrowTypeID::MutRef;
+        self.mut_borrow_depth += usize::from(is_mut);
+        node.walk(self);
+        self.mut_borrow_depth -= usize::from(is_mut);
+    }
+
+    // Op assigns each of its arguments to the first one
+    fn visit_macro_call_expr(&mut self, node: &MacroCallExpr) {
+        if let (MacroKind::OpAssignEach(_), Some(Expr::Variable(var))) =
+            (node.get_kind(), node.arguments_iter().next())
+        {
+            self.written.insert(root_of(var.get_name_str()).to_string());
+        }
+        node.walk(self);
+    }
+
+    fn visit_checksum_stmt(&mut self, node: &ChecksumStmt) {
+        self.places.extend(node.places_iter().cloned());
+    }
+
+    fn visit_trace_stmt(&mut self, node: &TraceStmt) {
+        self.places.extend(node.places_iter().cloned());
+    }
+}
+
+// Nested blocks of top level functions that are neither async nor const
+// The block must only read integers and bools from around it, which become the parameters
+// of the new function, and the block is left with a call to it
+struct OutlineBlock {
+    target: Target,
+    consts: BTreeSet<String>,
+    function_name: String,      // Of the new function
+    function: Option<Function>, // The new function, once the block is outlined
+    params: BTreeSet<String>,   // Of the function being visited
+    bindings: Bindings,         // Of the function being visited
+}
+
+impl OutlineBlock {
+    fn new(program: &Program, index: usize) -> Self {
+        let function_names: Vec<String> = program
+            .functions_iter()
+            .chain(
+                program
+                    .modules_iter()
+                    .flat_map(|module| module.functions_iter()),
+            )
+            .map(|function| function.get_name())
+            .collect();
+
+        OutlineBlock {
+            target: Target::new(index),
+            consts: program.consts_iter().map(|c| c.get_name()).collect(),
+            function_name: next_name(FUNCTION_PREFIX, function_names.iter().map(String::as_str)),
+            function: None,
+            params: BTreeSet::new(),
+            bindings: Bindings::default(),
+        }
+    }
+
+    // The variables the block reads from around it, in the order they are first read
+    fn free_vars(&self, node: &BlockStmt) -> Option<Vec<(String, TypeID)>> {
+        let mut uses = BlockUses::default();
+        uses.visit_block_stmt(node);
+        if uses.forbidden {
+            return None;
+        }
+
+        // Names declared in the block are declared nowhere else in the function,
+        // so that every use of one of them in the block is of the declaration in the block
+        for (name, count) in uses.bindings.iter() {
+            if self.params.contains(name) || self.bindings.count(name) != *count {
+                return None;
+            }
+        }
+
+        let mut free_vars: Vec<(String, TypeID)> = Vec::new();
+        for var in uses.uses.iter() {
+            let name = var.get_name_str();
+            let root = root_of(name);
+            if uses.bindings.contains_key(root) || self.consts.contains(root) {
+                continue;
+            }
+
+            // Anything but a plain variable, such as a static, stays where it is
+            if name != root
+                || root.starts_with(|c: char| c.is_ascii_uppercase())
+                || var.get_borrow_type() != BorrowTypeID::None
+                || !var.get_type().is_primitive()
+                || uses.written.contains(root)
+            {
+                return None;
+            }
+
+            match free_vars.iter().find(|(free_name, _)| free_name == root) {
+                Some((_, type_id)) if *type_id != var.get_type() => return None,
+                Some(_) => (),
+                None => free_vars.push((root.to_string(), var.get_type())),
+            }
+        }
+
+        let places_known = uses.places.iter().all(|place| {
+            let root = root_of(place);
+            uses.bindings.contains_key(root)
+                || (place == root && free_vars.iter().any(|(name, _)| name == root))
+        });
+
+        places_known.then_some(free_vars)
+    }
+
+    fn outline(&mut self, node: &mut BlockStmt, free_vars: Vec<(String, TypeID)>) {
+        let block_stmt = std::mem::replace(node, BlockStmt::new());
+        let params = free_vars
+            .iter()
+            .map(|(name, type_id)| Param::new(name.clone(), type_id.clone()))
+            .collect();
+        let arguments = free_vars
+            .into_iter()
+            .map(|(name, type_id)| Var::new(type_id, name, false).as_expr())
+            .collect();
+
+        let function = Function::new(
+            self.function_name.clone(),
+            params,
+            TypeID::NullType,
+            block_stmt,
+        );
+        let call = FunctionCallExpr::new(function.get_template(), arguments);
+        node.push(ExprStmt::new(call.as_expr()).as_stmt());
+
+        self.function = Some(function);
+    }
+}
+
+impl VisitMut for OutlineBlock {
+    fn visit_module_mut(&mut self, _node: &mut Module) {}
+
+    fn visit_function_mut(&mut self, node: &mut Function) {
+        let template = node.get_template();
+        if self.target.applied || template.is_async() || template.is_const() {
+            return;
+        }
+
+        self.params = node.params_iter().map(|param| param.get_name()).collect();
+        self.bindings = Bindings::of_function(node);
+
+        // The body itself stays where it is
+        node.get_block_stmt_mut().walk_mut(self);
+    }
+
+    // Inlined bodies evaluate to their last statement
+    fn visit_expr_mut(&mut self, node: &mut Expr) {
+        match node {
+            Expr::Block(block_stmt) => block_stmt.walk_mut(self),
+            _ => node.walk_mut(self),
+        }
+    }
+
+    fn visit_thread_scope_stmt_mut(&mut self, _node: &mut ThreadScopeStmt) {}
+
+    fn visit_unsafe_stmt_mut(&mut self, _node: &mut UnsafeStmt) {}
+
+    fn visit_block_stmt_mut(&mut self, node: &mut BlockStmt) {
+        if self.target.applied {
+            return;
+        }
+
+        if let Some(free_vars) = self.free_vars(node) {
+            if self.target.hit() {
+                self.outline(node, free_vars);
+                return;
+            }
+        }
+
+        node.walk_mut(self);
+    }
+}
+
+// Structs that are written one field at a time through MaybeUninit
+#[derive(Default)]
+struct UninitStructs {
+    names: BTreeSet<String>,
+}
+
+impl Visit for UninitStructs {
+    fn visit_unsafe_stmt(&mut self, node: &UnsafeStmt) {
+        if let UnsafeStmt::MaybeUninit { result, fields, .. } = node {
+            if let TypeID::StructType(name) = result.get_type() {
+                if !fields.is_empty() {
+                    self.names.insert(name);
+                }
+            }
+        }
+        node.walk(self);
+    }
+}
+
+// Each top level struct with the number of fields it keeps, any number but none or all of them
+// The global struct is printed, and the layout of packed and aligned structs is part of the program
+fn split_points(program: &Program) -> Vec<(String, usize)> {
+    let mut uninit_structs = UninitStructs::default();
+    uninit_structs.visit_program(program);
+
+    program
+        .structs_iter()
+        .filter(|template| {
+            template.get_name() != GLOBAL_STRUCT_NAME
+                && !template.is_packed()
+                && !template.has_align()
+                && !uninit_structs.names.contains(&template.get_name())
+        })
+        .flat_map(|template| (1..template.num_fields()).map(move |at| (template.get_name(), at)))
+        .collect()
+}
+
+fn split_struct(program: &mut Program, index: usize) -> Target {
+    let mut target = Target::new(index);
+    for (name, at) in split_points(program) {
+        if target.hit() {
+            target.applied = try_split_struct(program, &name, at);
+        }
+    }
+
+    target
+}
+
+// Changes a copy, which replaces the program only if every place could be followed
+fn try_split_struct(program: &mut Program, name: &str, at: usize) -> bool {
+    let templates: BTreeMap<String, StructTemplate> = program
+        .structs_iter()
+        .map(|template| (template.get_name(), template.clone()))
+        .collect();
+    let struct_names: Vec<String> = program
+        .structs_iter()
+        .chain(
+            program
+                .modules_iter()
+                .flat_map(|module| module.structs_iter()),
+        )
+        .map(|template| template.get_name())
+        .collect();
+
+    let mut outer = templates[name].clone();
+    let field_name = next_name(
+        FIELD_PREFIX,
+        outer.fields_iter().map(|(name, _)| name.as_str()),
+    );
+    let inner = outer.split_off(
+        at,
+        next_name(STRUCT_PREFIX, struct_names.iter().map(String::as_str)),
+    );
+    outer.insert_field(field_name.clone(), inner.get_type());
+
+    let mut variant = program.clone();
+    let mut rewrite = SplitStruct {
+        at,
+        outer,
+        inner: inner.clone(),
+        field_name,
+        templates: &templates,
+        declared: DeclaredTypes::default(),
+        failed: false,
+    };
+    rewrite.visit_program_mut(&mut variant);
+    if rewrite.failed {
+        return false;
+    }
+
+    variant.push_struct_template(inner);
+    *program = variant;
+
+    true
+}
+
+// Type of every variable that a function declares, or None for a name declared with different types
+#[derive(Default)]
+struct DeclaredTypes {
+    types: BTreeMap<String, Option<TypeID>>,
+}
+
+impl DeclaredTypes {
+    fn of_function(function: &Function) -> Self {
+        let mut declared = DeclaredTypes::default();
+        for param in function.params_iter() {
+            declared.insert(param.get_name(), param.get_type());
+        }
+        declared.visit_function(function);
+        declared
+    }
+
+    fn insert(&mut self, name: String, type_id: TypeID) {
+        let declared = self
+            .types
+            .entry(name)
+            .or_insert_with(|| Some(type_id.clone()));
+        if declared.as_ref() != Some(&type_id) {
+            *declared = None;
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<&TypeID> {
+        self.types.get(name)?.as_ref()
+    }
+}
+
+impl Visit for DeclaredTypes {
+    fn visit_binding(&mut self, node: &Var) {
+        self.insert(node.get_name(), node.get_type());
+    }
+}
+
+// Moves the fields of a struct from a position on into a new inner struct,
+// which becomes the last field of the outer one
+// Every literal, pattern and place that names one of the moved fields goes through the new field
+struct SplitStruct<'a> {
+    at: usize,
+    outer: StructTemplate,
+    inner: StructTemplate,
+    field_name: String, // Of the inner struct in the outer one
+    templates: &'a BTreeMap<String, StructTemplate>, // As they were before the split
+    declared: DeclaredTypes, // Of the function being visited
+    failed: bool,
+}
+
+impl<'a> SplitStruct<'a> {
+    fn is_moved(&self, struct_name: &str, field_name: &str) -> bool {
+        struct_name == self.outer.get_name()
+            && self
+                .inner
+                .fields_iter()
+                .any(|(moved_name, _)| moved_name == field_name)
+    }
+
+    // Follows the fields of a place from the type its variable is declared with
+    fn renamed(&self, place: &str) -> Option<String> {
+        let path = place.trim_start_matches('*');
+        let deref = &place[..place.len() - path.len()];
+        let mut fields = path.split('.');
+        let root = fields.next().unwrap();
+        if !path.contains('.') {
+            return Some(place.to_string());
+        }
+
+        let mut type_id = self.declared.get(root)?.clone();
+        let mut renamed = vec![root.to_string()];
+        for field in fields {
+            let struct_name = match &type_id {
+                TypeID::StructType(struct_name) => struct_name.clone(),
+                _ => return None,
+            };
+            if self.is_moved(&struct_name, field) {
+                renamed.push(self.field_name.clone());
+            }
+            renamed.push(field.to_string());
+
+            type_id = self
+                .templates
+                .get(&struct_name)?
+                .fields_iter()
+                .find(|(field_name, _)| field_name == field)?
+                .1
+                .clone();
+        }
+
+        Some(format!("{}{}", deref, renamed.join(".")))
+    }
+
+    fn rename(&mut self, place: &mut String) {
+        match self.renamed(place) {
+            Some(renamed) => *place = renamed,
+            None => self.failed = true,
+        }
+    }
+}
+
+impl<'a> VisitMut for SplitStruct<'a> {
+    // Modules only use the structs declared in them
+    fn visit_module_mut(&mut self, _node: &mut Module) {}
+
+    fn visit_struct_template_mut(&mut self, node: &mut StructTemplate) {
+        if node.get_name() == self.outer.get_name() {
+            *node = self.outer.clone();
+        }
+    }
+
+    fn visit_function_mut(&mut self, node: &mut Function) {
+        self.declared = DeclaredTypes::of_function(node);
+        node.walk_mut(self);
+    }
+
+    fn visit_var_mut(&mut self, node: &mut Var) {
+        let mut name = node.get_name();
+        self.rename(&mut name);
+        node.set_name(name);
+    }
+
+    fn visit_checksum_stmt_mut(&mut self, node: &mut ChecksumStmt) {
+        for place in node.places_iter_mut() {
+            self.rename(place);
+        }
+    }
+
+    fn visit_trace_stmt_mut(&mut self, node: &mut TraceStmt) {
+        for place in node.places_iter_mut() {
+            self.rename(place);
+        }
+    }
+
+    fn visit_await_stmt_mut(&mut self, node: &mut AwaitStmt) {
+        for borrow in node.held_borrows_iter_mut() {
+            self.rename(borrow);
+        }
+    }
+
+    fn visit_struct_literal_mut(&mut self, node: &mut StructLiteral) {
+        node.walk_mut(self);
+        if node.get_struct_name() == self.outer.get_name() {
+            node.split_off(self.at, self.outer.clone(), self.inner.clone());
+        }
+    }
+
+    // The inner pattern skips the rest of its fields unless the outer pattern named all of them
+    fn visit_pattern_mut(&mut self, node: &mut Pattern) {
+        if let Pattern::Struct(name, fields, has_rest) = node {
+            if *name == self.outer.get_name() {
+                let (moved, mut kept): (Vec<_>, Vec<_>) = std::mem::take(fields)
+                    .into_iter()
+                    .partition(|(field_name, _)| self.is_moved(name, field_name));
+                if !moved.is_empty() {
+                    let inner_has_rest = *has_rest || moved.len() < self.inner.num_fields();
+                    let inner_pattern =
+                        Pattern::new_struct(self.inner.get_name(), moved, inner_has_rest);
+                    kept.push((self.field_name.clone(), inner_pattern));
+                }
+                *fields = kept;
+            }
+        }
+
+        node.walk_mut(self);
+    }
+}
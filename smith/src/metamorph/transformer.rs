@@ -0,0 +1,180 @@
+/// Metamorphic variants of a program: each one is the program after a few random rewrites,
+/// and has to compile and print exactly what the program prints
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use strum::IntoEnumIterator;
+
+use crate::program::program::Program;
+
+use super::rewrites::Rewrite;
+
+const DEFAULT_MAX_REWRITES: usize = 8;
+
+pub struct MetamorphicTransformer<'a> {
+    program: &'a Program,
+    seed: u64,
+    rewrites: Vec<Rewrite>,
+    max_rewrites: usize,
+}
+
+impl<'a> MetamorphicTransformer<'a> {
+    pub fn new(program: &'a Program, seed: u64) -> Self {
+        MetamorphicTransformer {
+            program,
+            seed,
+            rewrites: Rewrite::iter().collect(),
+            max_rewrites: DEFAULT_MAX_REWRITES,
+        }
+    }
+
+    // Only these rewrites are chosen from
+    pub fn with_rewrites(mut self, rewrites: Vec<Rewrite>) -> Self {
+        self.rewrites = rewrites;
+        self
+    }
+
+    // Of each variant, which gets between one and this many
+    pub fn with_max_rewrites(mut self, max_rewrites: usize) -> Self {
+        self.max_rewrites = max_rewrites;
+        self
+    }
+
+    pub fn variants(&self, count: usize) -> Vec<Program> {
+        let mut rng = ChaCha12Rng::seed_from_u64(self.seed);
+
+        (0..count).map(|_| self.variant(&mut rng)).collect()
+    }
+
+    // Each rewrite is picked among those that still have a candidate, since earlier ones
+    // create and remove candidates of later ones
+    fn variant<R: Rng>(&self, rng: &mut R) -> Program {
+        let mut variant = self.program.clone();
+
+        for _ in 0..rng.gen_range(1..=self.max_rewrites.max(1)) {
+            let candidates: Vec<(Rewrite, usize)> = self
+                .rewrites
+                .iter()
+                .map(|rewrite| (*rewrite, rewrite.count(&mut variant)))
+                .filter(|(_, count)| *count > 0)
+                .collect();
+            if candidates.is_empty() {
+                break;
+            }
+
+            let (rewrite, count) = candidates[rng.gen_range(0..candidates.len())];
+            rewrite.apply(&mut variant, rng.gen_range(0..count));
+        }
+
+        variant
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        interpret::interpreter::{ExpectedOutput, Interpreter},
+        render_program, Features, Generator,
+    };
+
+    // Seeds are tried until every rewrite has applied, or until this many have been
+    const MAX_SEEDS: u64 = 40;
+
+    // Small programs that the interpreter finishes within the steps, generated as they are needed
+    fn interpreted_programs() -> impl Iterator<Item = (u64, Program, ExpectedOutput)> {
+        (0..MAX_SEEDS).filter_map(|seed| {
+            let program = Generator::small(seed).generate().into_program();
+            let expected = Interpreter::new(&program)
+                .with_max_steps(100_000)
+                .run()
+                .ok()?;
+            Some((seed, program, expected))
+        })
+    }
+
+    // Number of variants that differ from the program, all of which have to print the same
+    // Variants may take a few more steps than the program, for their extra statements
+    fn assert_same_output(
+        seed: u64,
+        program: &Program,
+        expected: &ExpectedOutput,
+        rewrites: Vec<Rewrite>,
+    ) -> usize {
+        let mut compared = 0;
+
+        let transformer =
+            MetamorphicTransformer::new(program, seed).with_rewrites(rewrites.clone());
+        for variant in transformer.variants(2) {
+            if render_program(&variant, Features::default())
+                != render_program(program, Features::default())
+            {
+                compared += 1;
+            }
+            assert_eq!(
+                Interpreter::new(&variant)
+                    .with_max_steps(1_000_000)
+                    .run()
+                    .as_ref(),
+                Ok(expected),
+                "seed {} with {:?}",
+                seed,
+                rewrites
+            );
+        }
+
+        compared
+    }
+
+    #[test]
+    fn variants_print_the_same() {
+        let compared: usize = interpreted_programs()
+            .take(6)
+            .map(|(seed, program, expected)| {
+                assert_same_output(seed, &program, &expected, Rewrite::iter().collect())
+            })
+            .sum();
+
+        assert!(compared > 0);
+    }
+
+    // Which seeds a rewrite applies to changes along with the generator, so none are picked by hand
+    #[test]
+    fn every_rewrite_changes_some_program() {
+        let mut unapplied: Vec<Rewrite> = Rewrite::iter().collect();
+
+        for (seed, program, expected) in interpreted_programs() {
+            unapplied.retain(|rewrite| {
+                assert_same_output(seed, &program, &expected, vec![*rewrite]) == 0
+            });
+            if unapplied.is_empty() {
+                break;
+            }
+        }
+
+        assert!(unapplied.is_empty(), "{:?} changed no program", unapplied);
+    }
+
+    #[test]
+    fn count_leaves_program_as_it_is() {
+        let program = Generator::new(3).generate().into_program();
+        let mut copy = program.clone();
+
+        for rewrite in Rewrite::iter() {
+            rewrite.count(&mut copy);
+        }
+
+        assert_eq!(copy.to_json(), program.to_json());
+    }
+
+    #[test]
+    fn same_seed_gives_same_variants() {
+        let program = Generator::new(5).generate().into_program();
+
+        let first = MetamorphicTransformer::new(&program, 1).variants(2);
+        let second = MetamorphicTransformer::new(&program, 1).variants(2);
+
+        for (a, b) in first.iter().zip(second.iter()) {
+            assert_eq!(a.to_json(), b.to_json());
+        }
+    }
+}
@@ -0,0 +1,87 @@
+/// Attributes that change inlining and layout decisions without changing semantics
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum FunctionAttribute {
+    Inline,
+    InlineAlways,
+    InlineNever,
+    Cold,
+    // Not allowed on main or async functions
+    TrackCaller,
+}
+
+impl FunctionAttribute {
+    // A function can only have a single inline attribute
+    pub fn is_inline(&self) -> bool {
+        matches!(self, Self::Inline | Self::InlineAlways | Self::InlineNever)
+    }
+
+    // Whether both attributes can be placed on the same function
+    pub fn conflicts_with(&self, other: &FunctionAttribute) -> bool {
+        self == other || (self.is_inline() && other.is_inline())
+    }
+}
+
+impl ToString for FunctionAttribute {
+    fn to_string(&self) -> String {
+        match self {
+            Self::Inline => String::from("#[inline]"),
+            Self::InlineAlways => String::from("#[inline(always)]"),
+            Self::InlineNever => String::from("#[inline(never)]"),
+            Self::Cold => String::from("#[cold]"),
+            Self::TrackCaller => String::from("#[track_caller]"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum StructAttribute {
+    ReprC,
+    // Fields of a packed struct may be unaligned, so the generator never borrows them
+    ReprPacked,
+    // Power of two alignment in bytes
+    ReprAlign(u32),
+}
+
+impl StructAttribute {
+    // Packed and align hints conflict, and each hint can only be given once
+    pub fn conflicts_with(&self, other: &StructAttribute) -> bool {
+        match (self, other) {
+            (Self::ReprC, Self::ReprC) => true,
+            (Self::ReprC, _) | (_, Self::ReprC) => false,
+            _ => true,
+        }
+    }
+}
+
+impl ToString for StructAttribute {
+    fn to_string(&self) -> String {
+        match self {
+            Self::ReprC => String::from("#[repr(C)]"),
+            Self::ReprPacked => String::from("#[repr(packed)]"),
+            Self::ReprAlign(alignment) => format!("#[repr(align({}))]", alignment),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn inline_attributes_conflict() {
+        assert!(FunctionAttribute::InlineAlways.conflicts_with(&FunctionAttribute::InlineNever));
+        assert!(FunctionAttribute::Cold.conflicts_with(&FunctionAttribute::Cold));
+        assert!(!FunctionAttribute::Cold.conflicts_with(&FunctionAttribute::Inline));
+    }
+
+    #[test]
+    fn packed_conflicts_with_align() {
+        assert!(StructAttribute::ReprPacked.conflicts_with(&StructAttribute::ReprAlign(8)));
+        assert!(!StructAttribute::ReprC.conflicts_with(&StructAttribute::ReprPacked));
+        assert_eq!(
+            StructAttribute::ReprAlign(16).to_string(),
+            "#[repr(align(16))]"
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use super::{
+    expr::expr::Expr,
+    types::{IntTypeID, TypeID},
+};
+
+// Names used inside the body of every const generic item
+pub const ELEMS_NAME: &str = "elems";
+pub const ACC_NAME: &str = "acc";
+pub const IDX_NAME: &str = "idx";
+
+// What the update expression can read, each one is a local of the element type
+// All but the accumulator are bound at the start of every iteration
+#[derive(Clone, Copy, Debug, PartialEq, EnumIter)]
+pub enum BodyVar {
+    Front, // The element at the index
+    Back,  // The element as far from the end as the index is from the start
+    Index, // The index, cast to the element type
+    Acc,
+}
+
+impl BodyVar {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            BodyVar::Front => "front",
+            BodyVar::Back => "back",
+            BodyVar::Index => "index",
+            BodyVar::Acc => ACC_NAME,
+        }
+    }
+
+    fn init(&self, elem_type: IntTypeID) -> Option<String> {
+        match self {
+            BodyVar::Front => Some(format!("{}[{}]", ELEMS_NAME, IDX_NAME)),
+            BodyVar::Back => Some(format!("{}[N - 1 - {}]", ELEMS_NAME, IDX_NAME)),
+            BodyVar::Index => Some(format!("{} as {}", IDX_NAME, elem_type.to_string())),
+            BodyVar::Acc => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum ConstGenericKind {
+    // fn name<const N: usize>(elems: [T; N], init: T) -> T
+    Function,
+    // struct Name<const N: usize> { elems: [T; N] } with a fold(&self, init: T) -> T method
+    Struct,
+}
+
+// Everything needed to instantiate a const generic item at some length
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ConstGenericTemplate {
+    name: String,
+    kind: ConstGenericKind,
+    elem_type: IntTypeID,
+}
+
+impl ConstGenericTemplate {
+    pub fn new(name: String, kind: ConstGenericKind, elem_type: IntTypeID) -> Self {
+        ConstGenericTemplate {
+            name,
+            kind,
+            elem_type,
+        }
+    }
+
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn get_kind(&self) -> ConstGenericKind {
+        self.kind
+    }
+
+    pub fn get_type(&self) -> TypeID {
+        self.elem_type.as_type()
+    }
+
+    pub fn get_elem_type(&self) -> IntTypeID {
+        self.elem_type
+    }
+}
+
+impl fmt::Debug for ConstGenericTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConstGenericTemplate")
+            .field("name", &self.name)
+            .field("type", &self.elem_type)
+            .finish()
+    }
+}
+
+// Folds an array of length N into an accumulator
+// The loop bound is the const parameter so that small instantiations can be fully unrolled
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ConstGeneric {
+    template: ConstGenericTemplate,
+    update_expr: Expr,
+}
+
+impl ConstGeneric {
+    pub fn new(template: ConstGenericTemplate, update_expr: Expr) -> Self {
+        ConstGeneric {
+            template,
+            update_expr,
+        }
+    }
+
+    pub fn get_template(&self) -> ConstGenericTemplate {
+        self.template.clone()
+    }
+
+    pub fn get_update_expr(&self) -> &Expr {
+        &self.update_expr
+    }
+
+    fn fold_body(&self) -> String {
+        let elem_type = self.template.elem_type;
+        let bindings: String = BodyVar::iter()
+            .filter_map(|body_var| {
+                body_var.init(elem_type).map(|init| {
+                    format!(
+                        "let {}: {} = {};\n",
+                        body_var.get_name(),
+                        elem_type.to_string(),
+                        init
+                    )
+                })
+            })
+            .collect();
+
+        format!(
+            "let mut {acc}: {} = init;\nfor {idx} in 0..N {{\n{}{acc} = {};\n}}\n{acc}",
+            elem_type.to_string(),
+            bindings,
+            self.update_expr.to_string(),
+            acc = ACC_NAME,
+            idx = IDX_NAME,
+        )
+    }
+
+    pub fn to_string(&self) -> String {
+        self.render("")
+    }
+
+    // The struct field and method are also made visible
+    pub fn to_string_pub(&self) -> String {
+        self.render("pub ")
+    }
+
+    fn render(&self, visibility: &str) -> String {
+        let type_string = self.template.elem_type.to_string();
+
+        match self.template.kind {
+            ConstGenericKind::Function => format!(
+                "{vis}fn {}<const N: usize>({}: [{t}; N], init: {t}) -> {t} {{\n{}\n}}\n",
+                self.template.name,
+                ELEMS_NAME,
+                self.fold_body(),
+                vis = visibility,
+                t = type_string,
+            ),
+            ConstGenericKind::Struct => format!(
+                "{vis}struct {name}<const N: usize> {{\n{vis}{elems}: [{t}; N],\n}}\n\n\
+                impl<const N: usize> {name}<N> {{\n\
+                {vis}fn fold(&self, init: {t}) -> {t} {{\nlet {elems} = self.{elems};\n{}\n}}\n}}\n",
+                self.fold_body(),
+                vis = visibility,
+                name = self.template.name,
+                elems = ELEMS_NAME,
+                t = type_string,
+            ),
+        }
+    }
+}
+
+impl ConstGeneric {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_expr(&self.update_expr);
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_expr_mut(&mut self.update_expr);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::var::Var;
+    #[test]
+    fn function_loops_up_to_const_param() {
+        let template = ConstGenericTemplate::new(
+            String::from("const_generic_1"),
+            ConstGenericKind::Function,
+            IntTypeID::U8,
+        );
+        let update_expr = Var::new(
+            IntTypeID::U8.as_type(),
+            BodyVar::Front.get_name().to_string(),
+            false,
+        );
+
+        let string_rep = ConstGeneric::new(template, update_expr.as_expr()).to_string();
+
+        assert!(string_rep
+            .starts_with("fn const_generic_1<const N: usize>(elems: [u8; N], init: u8) -> u8 {"));
+        assert!(string_rep.contains(
+            "for idx in 0..N {\nlet front: u8 = elems[idx];\nlet back: u8 = elems[N - 1 - idx];\n\
+            let index: u8 = idx as u8;\nacc = front;\n}"
+        ));
+    }
+
+    #[test]
+    fn struct_has_fold_method() {
+        let template = ConstGenericTemplate::new(
+            String::from("ConstGeneric1"),
+            ConstGenericKind::Struct,
+            IntTypeID::I64,
+        );
+        let update_expr = Var::new(IntTypeID::I64.as_type(), String::from("acc"), false);
+
+        let string_rep = ConstGeneric::new(template, update_expr.as_expr()).to_string();
+
+        assert!(
+            string_rep.starts_with("struct ConstGeneric1<const N: usize> {\nelems: [i64; N],\n}")
+        );
+        assert!(string_rep.contains("impl<const N: usize> ConstGeneric1<N> {"));
+        assert!(string_rep.contains("fn fold(&self, init: i64) -> i64 {\nlet elems = self.elems;"));
+    }
+}
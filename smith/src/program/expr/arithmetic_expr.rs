@@ -1,15 +1,23 @@
-use super::{expr::Expr, func_call_expr::FunctionCallExpr};
+use super::{
+    const_generic_call_expr::ConstGenericCallExpr, expr::Expr, func_call_expr::FunctionCallExpr,
+    macro_call_expr::MacroCallExpr,
+};
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
 use crate::program::{
     types::{IntTypeID, TypeID},
     var::Var,
 };
+use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub enum ArithmeticExpr {
     Int(IntExpr),
     Binary(Box<BinaryExpr>),
     Var(Var),
     Func(FunctionCallExpr),
+    Macro(MacroCallExpr),
+    ConstGeneric(ConstGenericCallExpr),
 }
 
 impl ArithmeticExpr {
@@ -31,6 +39,8 @@ impl ArithmeticExpr {
             Self::Binary(s) => s.get_type(),
             Self::Var(s) => s.get_type(),
             Self::Func(s) => s.get_type(),
+            Self::Macro(s) => s.get_type(),
+            Self::ConstGeneric(s) => s.get_type(),
         }
     }
 }
@@ -42,6 +52,8 @@ impl ToString for ArithmeticExpr {
             Self::Binary(s) => (*s).to_string_safe(),
             Self::Var(s) => s.to_string(),
             Self::Func(s) => s.to_string(),
+            Self::Macro(s) => s.to_string(),
+            Self::ConstGeneric(s) => s.to_string(),
         }
     }
 }
@@ -62,21 +74,50 @@ impl From<FunctionCallExpr> for ArithmeticExpr {
     }
 }
 
+impl From<MacroCallExpr> for ArithmeticExpr {
+    fn from(expr: MacroCallExpr) -> Self {
+        ArithmeticExpr::Macro(expr)
+    }
+}
+
+impl From<ConstGenericCallExpr> for ArithmeticExpr {
+    fn from(expr: ConstGenericCallExpr) -> Self {
+        ArithmeticExpr::ConstGeneric(expr)
+    }
+}
+
 impl From<Var> for ArithmeticExpr {
     fn from(expr: Var) -> Self {
         ArithmeticExpr::Var(expr)
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct BinaryExpr {
     left: ArithmeticExpr,
     right: ArithmeticExpr,
     op: BinaryOp,
+    is_const: bool,
 }
 
 impl BinaryExpr {
     pub fn new(left: ArithmeticExpr, right: ArithmeticExpr, op: BinaryOp) -> Self {
-        BinaryExpr { left, right, op }
+        BinaryExpr {
+            left,
+            right,
+            op,
+            is_const: false,
+        }
+    }
+
+    // Trait methods cannot be called in a const fn, so the safe arithmetic is written out inline
+    pub fn new_const(left: ArithmeticExpr, right: ArithmeticExpr, op: BinaryOp) -> Self {
+        BinaryExpr {
+            left,
+            right,
+            op,
+            is_const: true,
+        }
     }
 
     pub fn as_arith_expr(self) -> ArithmeticExpr {
@@ -87,12 +128,51 @@ impl BinaryExpr {
         self.left.get_type()
     }
 
+    pub fn get_left(&self) -> &ArithmeticExpr {
+        &self.left
+    }
+
+    pub fn get_right(&self) -> &ArithmeticExpr {
+        &self.right
+    }
+
+    pub fn get_op(&self) -> BinaryOp {
+        self.op
+    }
+
+    pub fn get_left_mut(&mut self) -> &mut ArithmeticExpr {
+        &mut self.left
+    }
+
+    pub fn get_right_mut(&mut self) -> &mut ArithmeticExpr {
+        &mut self.right
+    }
+
+    pub fn swap_operands(&mut self) {
+        std::mem::swap(&mut self.left, &mut self.right);
+    }
+
+    pub fn into_operands(self) -> (ArithmeticExpr, ArithmeticExpr) {
+        (self.left, self.right)
+    }
+
+    pub fn is_const(&self) -> bool {
+        self.is_const
+    }
+
     pub fn to_string_safe(&self) -> String {
         // This has the form of 5.checked_add(6u8) where 5 and 6 are literal u8 expressions
         // The annotation is only required when a literal is provided as argument
         // The checked arithmetic operations require type annotations in the argument
         match self.op {
             BinaryOp::BITAND | BinaryOp::BITOR | BinaryOp::BITXOR => self.to_string(),
+            // Same semantics as the runtime safe arithmetic, the left value is kept on overflow
+            _ if self.is_const => format!(
+                "{{ let lhs = {}; match lhs.{}({}) {{ Some(result) => result, None => lhs }} }}",
+                self.left.to_string(),
+                self.op.to_string_checked(),
+                self.right.to_string(),
+            ),
             _ => format!(
                 "{}.{}({})",
                 self.left.to_string(),
@@ -114,7 +194,7 @@ impl ToString for BinaryExpr {
     }
 }
 
-#[derive(Copy, Clone, EnumIter)]
+#[derive(Copy, Clone, Debug, PartialEq, EnumIter, Serialize, Deserialize)]
 pub enum BinaryOp {
     ADD,
     SUB,
@@ -127,6 +207,19 @@ pub enum BinaryOp {
 }
 
 impl BinaryOp {
+    pub fn to_string_checked(&self) -> String {
+        match self {
+            BinaryOp::ADD => String::from("checked_add"),
+            BinaryOp::SUB => String::from("checked_sub"),
+            BinaryOp::MUL => String::from("checked_mul"),
+            BinaryOp::DIV => String::from("checked_div"),
+            BinaryOp::MOD => String::from("checked_rem"),
+            BinaryOp::BITAND | BinaryOp::BITOR | BinaryOp::BITXOR => {
+                panic!("Bit operations have no checked equivalent")
+            }
+        }
+    }
+
     pub fn to_string_safe(&self) -> String {
         match self {
             BinaryOp::ADD => String::from("safe_add"),
@@ -169,7 +262,8 @@ impl ToString for BinaryOp {
     }
 }
 
-#[derive(Debug)]
+// Values of the same type are ordered by value
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub enum IntValue {
     I8(i8),
     I16(i16),
@@ -199,6 +293,22 @@ impl IntValue {
         }
     }
 
+    // Never a zero divisor, unlike 0
+    pub fn one(int_type_id: IntTypeID) -> Self {
+        match int_type_id {
+            IntTypeID::I8 => Self::I8(1),
+            IntTypeID::I16 => Self::I16(1),
+            IntTypeID::I32 => Self::I32(1),
+            IntTypeID::I64 => Self::I64(1),
+            IntTypeID::I128 => Self::I128(1),
+            IntTypeID::U8 => Self::U8(1),
+            IntTypeID::U16 => Self::U16(1),
+            IntTypeID::U32 => Self::U32(1),
+            IntTypeID::U64 => Self::U64(1),
+            IntTypeID::U128 => Self::U128(1),
+        }
+    }
+
     pub fn get_type(&self) -> TypeID {
         match self {
             Self::I8(_) => TypeID::IntType(IntTypeID::I8),
@@ -215,7 +325,7 @@ impl IntValue {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct IntExpr {
     value: IntValue,
 }
@@ -225,6 +335,10 @@ impl IntExpr {
         self.value.get_type()
     }
 
+    pub fn get_value(&self) -> IntValue {
+        self.value
+    }
+
     pub fn as_expr(self) -> Expr {
         ArithmeticExpr::new_from_int_expr(self).as_expr()
     }
@@ -278,6 +392,42 @@ impl ToString for IntExpr {
     }
 }
 
+impl ArithmeticExpr {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        match self {
+            Self::Int(s) => visitor.visit_int_expr(s),
+            Self::Binary(s) => visitor.visit_binary_expr(s),
+            Self::Var(s) => visitor.visit_var(s),
+            Self::Func(s) => visitor.visit_func_call_expr(s),
+            Self::Macro(s) => visitor.visit_macro_call_expr(s),
+            Self::ConstGeneric(s) => visitor.visit_const_generic_call_expr(s),
+        }
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        match self {
+            Self::Int(s) => visitor.visit_int_expr_mut(s),
+            Self::Binary(s) => visitor.visit_binary_expr_mut(s),
+            Self::Var(s) => visitor.visit_var_mut(s),
+            Self::Func(s) => visitor.visit_func_call_expr_mut(s),
+            Self::Macro(s) => visitor.visit_macro_call_expr_mut(s),
+            Self::ConstGeneric(s) => visitor.visit_const_generic_call_expr_mut(s),
+        }
+    }
+}
+
+impl BinaryExpr {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_arithmetic_expr(&self.left);
+        visitor.visit_arithmetic_expr(&self.right);
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_arithmetic_expr_mut(&mut self.left);
+        visitor.visit_arithmetic_expr_mut(&mut self.right);
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -292,6 +442,19 @@ mod test {
         assert_eq!(binary_expr.to_string(), "30i32 + 5i32");
     }
 
+    #[test]
+    fn const_binary_expr_falls_back_to_left_value() {
+        let left_expr = ArithmeticExpr::new_from_int_expr(IntExpr::new_u8(200));
+        let right_expr = ArithmeticExpr::new_from_int_expr(IntExpr::new_u8(100));
+
+        let binary_expr = BinaryExpr::new_const(left_expr, right_expr, BinaryOp::ADD);
+
+        assert_eq!(
+            binary_expr.to_string_safe(),
+            "{ let lhs = 200u8; match lhs.checked_add(100u8) { Some(result) => result, None => lhs } }"
+        );
+    }
+
     #[test]
     fn int_expr_has_correct_string_representation() {
         let value = IntValue::I32(5);
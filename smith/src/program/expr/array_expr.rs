@@ -1,7 +1,10 @@
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
 use crate::program::{types::TypeID, var::Var};
+use serde::{Deserialize, Serialize};
 
 use super::expr::Expr;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ArrayExpr {
     type_id: TypeID,
     count: u32,
@@ -24,6 +27,10 @@ impl ArrayExpr {
     pub fn len(&self) -> u32 {
         self.count
     }
+
+    pub fn elements_iter(&self) -> std::slice::Iter<'_, Expr> {
+        self.elements.iter()
+    }
 }
 
 impl ToString for ArrayExpr {
@@ -39,6 +46,7 @@ impl ToString for ArrayExpr {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ArrayIndexExpr {
     index: u32,
     var: Var,
@@ -55,3 +63,17 @@ impl ToString for ArrayIndexExpr {
         format!("{}[{}]", self.var.to_string(), self.index)
     }
 }
+
+impl ArrayExpr {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        for expr in self.elements.iter() {
+            visitor.visit_expr(expr);
+        }
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        for expr in self.elements.iter_mut() {
+            visitor.visit_expr_mut(expr);
+        }
+    }
+}
@@ -1,8 +1,11 @@
-use crate::program::var::Var;
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use crate::program::{pattern::Pattern, var::Var};
+use serde::{Deserialize, Serialize};
 
 use super::arithmetic_expr::ArithmeticExpr;
-use super::{expr::Expr, func_call_expr::FunctionCallExpr};
+use super::{expr::Expr, func_call_expr::FunctionCallExpr, macro_call_expr::MacroCallExpr};
 
+#[derive(Clone, Serialize, Deserialize)]
 pub enum BoolExpr {
     Bool(BoolValue),
     Binary(Box<BinBoolExpr>),
@@ -10,6 +13,9 @@ pub enum BoolExpr {
     Negation(Box<NegationExpr>),
     Var(Var),
     Func(FunctionCallExpr),
+    Macro(MacroCallExpr),
+    Matches(Box<MatchesExpr>),
+    Opaque(OpaqueBool),
 }
 
 impl BoolExpr {
@@ -27,6 +33,9 @@ impl ToString for BoolExpr {
             Self::Negation(s) => (*s).to_string(),
             Self::Var(s) => s.to_string(),
             Self::Func(s) => s.to_string(),
+            Self::Macro(s) => s.to_string(),
+            Self::Matches(s) => (*s).to_string(),
+            Self::Opaque(s) => s.to_string(),
         }
     }
 }
@@ -47,12 +56,19 @@ impl From<FunctionCallExpr> for BoolExpr {
     }
 }
 
+impl From<MacroCallExpr> for BoolExpr {
+    fn from(expr: MacroCallExpr) -> Self {
+        BoolExpr::Macro(expr)
+    }
+}
+
 impl From<Var> for BoolExpr {
     fn from(expr: Var) -> Self {
         BoolExpr::Var(expr)
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct BoolValue {
     value: bool,
 }
@@ -65,6 +81,10 @@ impl BoolValue {
     pub fn as_bool_expr(self) -> BoolExpr {
         BoolExpr::Bool(self)
     }
+
+    pub fn get_value(&self) -> bool {
+        self.value
+    }
 }
 
 impl ToString for BoolValue {
@@ -77,6 +97,33 @@ impl ToString for BoolValue {
     }
 }
 
+// A boolean that the compiler cannot see through, such as the guard of an EMI dead block
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OpaqueBool {
+    value: bool,
+}
+
+impl OpaqueBool {
+    pub fn new(value: bool) -> Self {
+        OpaqueBool { value }
+    }
+
+    pub fn as_bool_expr(self) -> BoolExpr {
+        BoolExpr::Opaque(self)
+    }
+
+    pub fn get_value(&self) -> bool {
+        self.value
+    }
+}
+
+impl ToString for OpaqueBool {
+    fn to_string(&self) -> String {
+        format!("std::hint::black_box({})", self.value)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct BinBoolExpr {
     left: BoolExpr,
     right: BoolExpr,
@@ -91,6 +138,18 @@ impl BinBoolExpr {
     pub fn as_bool_expr(self) -> BoolExpr {
         BoolExpr::Binary(Box::new(self))
     }
+
+    pub fn get_left(&self) -> &BoolExpr {
+        &self.left
+    }
+
+    pub fn get_right(&self) -> &BoolExpr {
+        &self.right
+    }
+
+    pub fn get_op(&self) -> BoolOp {
+        self.op
+    }
 }
 
 impl ToString for BinBoolExpr {
@@ -104,6 +163,42 @@ impl ToString for BinBoolExpr {
     }
 }
 
+// The pattern never binds anything, the bindings would only be visible in a guard
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MatchesExpr {
+    expr: Expr,
+    pattern: Pattern,
+}
+
+impl MatchesExpr {
+    pub fn new(expr: Expr, pattern: Pattern) -> Self {
+        MatchesExpr { expr, pattern }
+    }
+
+    pub fn as_bool_expr(self) -> BoolExpr {
+        BoolExpr::Matches(Box::new(self))
+    }
+
+    pub fn get_expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    pub fn get_pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+}
+
+impl ToString for MatchesExpr {
+    fn to_string(&self) -> String {
+        format!(
+            "matches!({}, {})",
+            self.expr.to_string(),
+            self.pattern.to_string()
+        )
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ComparisonExpr {
     left: ArithmeticExpr,
     right: ArithmeticExpr,
@@ -118,6 +213,18 @@ impl ComparisonExpr {
     pub fn as_bool_expr(self) -> BoolExpr {
         BoolExpr::Comparison(Box::new(self))
     }
+
+    pub fn get_left(&self) -> &ArithmeticExpr {
+        &self.left
+    }
+
+    pub fn get_right(&self) -> &ArithmeticExpr {
+        &self.right
+    }
+
+    pub fn get_op(&self) -> ComparisonOp {
+        self.op
+    }
 }
 
 impl ToString for ComparisonExpr {
@@ -131,6 +238,7 @@ impl ToString for ComparisonExpr {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct NegationExpr {
     expr: BoolExpr,
 }
@@ -143,6 +251,10 @@ impl NegationExpr {
     pub fn as_bool_expr(self) -> BoolExpr {
         BoolExpr::Negation(Box::new(self))
     }
+
+    pub fn get_expr(&self) -> &BoolExpr {
+        &self.expr
+    }
 }
 
 impl ToString for NegationExpr {
@@ -151,6 +263,7 @@ impl ToString for NegationExpr {
     }
 }
 
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum ComparisonOp {
     Greater,
     Less,
@@ -173,6 +286,7 @@ impl ToString for ComparisonOp {
     }
 }
 
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum BoolOp {
     OR,
     AND,
@@ -186,3 +300,79 @@ impl ToString for BoolOp {
         }
     }
 }
+
+impl BoolExpr {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        match self {
+            Self::Bool(s) => visitor.visit_bool_value(s),
+            Self::Binary(s) => visitor.visit_bin_bool_expr(s),
+            Self::Comparison(s) => visitor.visit_comparison_expr(s),
+            Self::Negation(s) => visitor.visit_negation_expr(s),
+            Self::Var(s) => visitor.visit_var(s),
+            Self::Func(s) => visitor.visit_func_call_expr(s),
+            Self::Macro(s) => visitor.visit_macro_call_expr(s),
+            Self::Matches(s) => visitor.visit_matches_expr(s),
+            Self::Opaque(s) => visitor.visit_opaque_bool(s),
+        }
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        match self {
+            Self::Bool(s) => visitor.visit_bool_value_mut(s),
+            Self::Binary(s) => visitor.visit_bin_bool_expr_mut(s),
+            Self::Comparison(s) => visitor.visit_comparison_expr_mut(s),
+            Self::Negation(s) => visitor.visit_negation_expr_mut(s),
+            Self::Var(s) => visitor.visit_var_mut(s),
+            Self::Func(s) => visitor.visit_func_call_expr_mut(s),
+            Self::Macro(s) => visitor.visit_macro_call_expr_mut(s),
+            Self::Matches(s) => visitor.visit_matches_expr_mut(s),
+            Self::Opaque(s) => visitor.visit_opaque_bool_mut(s),
+        }
+    }
+}
+
+impl BinBoolExpr {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_bool_expr(&self.left);
+        visitor.visit_bool_expr(&self.right);
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_bool_expr_mut(&mut self.left);
+        visitor.visit_bool_expr_mut(&mut self.right);
+    }
+}
+
+impl MatchesExpr {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_expr(&self.expr);
+        visitor.visit_pattern(&self.pattern);
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_expr_mut(&mut self.expr);
+        visitor.visit_pattern_mut(&mut self.pattern);
+    }
+}
+
+impl ComparisonExpr {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_arithmetic_expr(&self.left);
+        visitor.visit_arithmetic_expr(&self.right);
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_arithmetic_expr_mut(&mut self.left);
+        visitor.visit_arithmetic_expr_mut(&mut self.right);
+    }
+}
+
+impl NegationExpr {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_bool_expr(&self.expr);
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_bool_expr_mut(&mut self.expr);
+    }
+}
@@ -1,9 +1,12 @@
 use crate::program::types::BorrowTypeID;
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use serde::{Deserialize, Serialize};
 
 use super::expr::Expr;
 // The explicit field refers to whether the variable in scope is already a borrow type
 // i.e. if variable &mut a is passed as mutable reference, we don't pass using
 // function(&mut a) and instead directly do function(a)
+#[derive(Clone, Serialize, Deserialize)]
 pub struct BorrowExpr {
     borrow_type_id: BorrowTypeID,
     expr: Expr,
@@ -22,6 +25,18 @@ impl BorrowExpr {
     pub fn as_expr(self) -> Expr {
         Expr::Borrow(Box::new(self))
     }
+
+    pub fn get_borrow_type(&self) -> BorrowTypeID {
+        self.borrow_type_id
+    }
+
+    pub fn get_expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    pub fn is_explicit(&self) -> bool {
+        self.explicit
+    }
 }
 
 impl ToString for BorrowExpr {
@@ -47,3 +62,13 @@ impl std::fmt::Debug for BorrowExpr {
             .finish()
     }
 }
+
+impl BorrowExpr {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_expr(&self.expr);
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_expr_mut(&mut self.expr);
+    }
+}
@@ -0,0 +1,57 @@
+use super::expr::Expr;
+use crate::program::stmt::block_stmt::BlockStmt;
+use crate::program::types::TypeID;
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use serde::{Deserialize, Serialize};
+
+// A closure called where it is made, so that a return in its block only leaves the block
+// Such as the body of an inlined function that returns early
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ClosureExpr {
+    return_type: TypeID,
+    block_stmt: BlockStmt,
+}
+
+impl ClosureExpr {
+    pub fn new(return_type: TypeID, block_stmt: BlockStmt) -> Self {
+        ClosureExpr {
+            return_type,
+            block_stmt,
+        }
+    }
+
+    pub fn get_type(&self) -> TypeID {
+        self.return_type.clone()
+    }
+
+    pub fn get_block_stmt(&self) -> &BlockStmt {
+        &self.block_stmt
+    }
+
+    pub fn as_expr(self) -> Expr {
+        Expr::Closure(Box::new(self))
+    }
+}
+
+impl ToString for ClosureExpr {
+    fn to_string(&self) -> String {
+        match self.return_type {
+            TypeID::NullType => format!("(|| {})()", self.block_stmt.to_string()),
+            _ => format!(
+                "(|| -> {} {})()",
+                self.return_type.to_string(),
+                self.block_stmt.to_string()
+            ),
+        }
+    }
+}
+
+impl ClosureExpr {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_block_stmt(&self.block_stmt);
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_block_stmt_mut(&mut self.block_stmt);
+    }
+}
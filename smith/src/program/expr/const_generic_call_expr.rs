@@ -0,0 +1,106 @@
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use crate::program::{
+    const_generic::{ConstGenericKind, ConstGenericTemplate, ELEMS_NAME},
+    types::TypeID,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{array_expr::ArrayExpr, expr::Expr};
+
+// Instantiation of a const generic item at the length of the array argument
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ConstGenericCallExpr {
+    template: ConstGenericTemplate,
+    elements: ArrayExpr,
+    init: Box<Expr>,
+}
+
+impl ConstGenericCallExpr {
+    pub fn new(template: ConstGenericTemplate, elements: ArrayExpr, init: Expr) -> Self {
+        ConstGenericCallExpr {
+            template,
+            elements,
+            init: Box::new(init),
+        }
+    }
+
+    pub fn get_type(&self) -> TypeID {
+        self.template.get_type()
+    }
+
+    pub fn get_template(&self) -> &ConstGenericTemplate {
+        &self.template
+    }
+
+    pub fn get_elements(&self) -> &ArrayExpr {
+        &self.elements
+    }
+
+    pub fn get_init(&self) -> &Expr {
+        &self.init
+    }
+}
+
+impl ToString for ConstGenericCallExpr {
+    fn to_string(&self) -> String {
+        // The length is always given explicitly, even though it could be inferred from the array
+        // Struct literals are parenthesised since they are not allowed in loop and if heads
+        match self.template.get_kind() {
+            ConstGenericKind::Function => format!(
+                "{}::<{}>({}, {})",
+                self.template.get_name(),
+                self.elements.len(),
+                self.elements.to_string(),
+                self.init.to_string()
+            ),
+            ConstGenericKind::Struct => format!(
+                "({}::<{}> {{ {}: {} }}).fold({})",
+                self.template.get_name(),
+                self.elements.len(),
+                ELEMS_NAME,
+                self.elements.to_string(),
+                self.init.to_string()
+            ),
+        }
+    }
+}
+
+impl ConstGenericCallExpr {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_array_expr(&self.elements);
+        visitor.visit_expr(&self.init);
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_array_expr_mut(&mut self.elements);
+        visitor.visit_expr_mut(&mut self.init);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::program::{expr::arithmetic_expr::IntExpr, types::IntTypeID};
+
+    use super::*;
+    #[test]
+    fn correct_string_repr() {
+        let template = ConstGenericTemplate::new(
+            String::from("ConstGeneric1"),
+            ConstGenericKind::Struct,
+            IntTypeID::U16,
+        );
+        let elements = ArrayExpr::new(
+            IntTypeID::U16.as_type(),
+            2,
+            vec![IntExpr::new_u16(1).as_expr(), IntExpr::new_u16(2).as_expr()],
+        );
+
+        let call_expr =
+            ConstGenericCallExpr::new(template, elements, IntExpr::new_u16(3).as_expr());
+
+        assert_eq!(
+            call_expr.to_string(),
+            "(ConstGeneric1::<2> { elems: [1u16, 2u16] }).fold(3u16)"
+        );
+    }
+}
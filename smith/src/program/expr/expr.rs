@@ -1,12 +1,18 @@
-use crate::program::var::Var;
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use crate::program::{stmt::block_stmt::BlockStmt, var::Var};
+use serde::{Deserialize, Serialize};
 
 use super::arithmetic_expr::ArithmeticExpr;
 use super::bool_expr::BoolExpr;
 use super::borrow_expr::BorrowExpr;
+use super::closure_expr::ClosureExpr;
 use super::func_call_expr::FunctionCallExpr;
+use super::macro_call_expr::MacroCallExpr;
 use super::struct_expr::StructExpr;
+use super::tuple_expr::TupleExpr;
 
 // The top most form of an expression
+#[derive(Clone, Serialize, Deserialize)]
 pub enum Expr {
     Arithmetic(ArithmeticExpr),
     Boolean(BoolExpr),
@@ -15,6 +21,11 @@ pub enum Expr {
     Borrow(Box<BorrowExpr>),
     Raw(RawExpr),
     Func(FunctionCallExpr),
+    Macro(MacroCallExpr),
+    Tuple(TupleExpr),
+    // Evaluates to its final statement, such as the body of an inlined function
+    Block(Box<BlockStmt>),
+    Closure(Box<ClosureExpr>),
 }
 
 impl ToString for Expr {
@@ -27,12 +38,17 @@ impl ToString for Expr {
             Expr::Borrow(s) => (*s).to_string(),
             Expr::Raw(s) => s.to_string(),
             Expr::Func(s) => s.to_string(),
+            Expr::Macro(s) => s.to_string(),
+            Expr::Tuple(s) => s.to_string(),
+            Expr::Block(s) => s.to_string(),
+            Expr::Closure(s) => s.to_string(),
         }
     }
 }
 
 // Using string only to represent the expression
 // This should not be used unless needed as it doesn't reflect the program's AST
+#[derive(Clone, Serialize, Deserialize)]
 pub struct RawExpr {
     expr_string: String,
 }
@@ -45,6 +61,10 @@ impl RawExpr {
     pub fn as_expr(self) -> Expr {
         Expr::Raw(self)
     }
+
+    pub fn get_expr_string(&self) -> &str {
+        &self.expr_string
+    }
 }
 
 impl ToString for RawExpr {
@@ -52,3 +72,37 @@ impl ToString for RawExpr {
         self.expr_string.clone()
     }
 }
+
+impl Expr {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        match self {
+            Expr::Arithmetic(s) => visitor.visit_arithmetic_expr(s),
+            Expr::Boolean(s) => visitor.visit_bool_expr(s),
+            Expr::Struct(s) => visitor.visit_struct_expr(s),
+            Expr::Variable(s) => visitor.visit_var(s),
+            Expr::Borrow(s) => visitor.visit_borrow_expr(s),
+            Expr::Raw(s) => visitor.visit_raw_expr(s),
+            Expr::Func(s) => visitor.visit_func_call_expr(s),
+            Expr::Macro(s) => visitor.visit_macro_call_expr(s),
+            Expr::Tuple(s) => visitor.visit_tuple_expr(s),
+            Expr::Block(s) => visitor.visit_block_stmt(s),
+            Expr::Closure(s) => visitor.visit_closure_expr(s),
+        }
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        match self {
+            Expr::Arithmetic(s) => visitor.visit_arithmetic_expr_mut(s),
+            Expr::Boolean(s) => visitor.visit_bool_expr_mut(s),
+            Expr::Struct(s) => visitor.visit_struct_expr_mut(s),
+            Expr::Variable(s) => visitor.visit_var_mut(s),
+            Expr::Borrow(s) => visitor.visit_borrow_expr_mut(s),
+            Expr::Raw(s) => visitor.visit_raw_expr_mut(s),
+            Expr::Func(s) => visitor.visit_func_call_expr_mut(s),
+            Expr::Macro(s) => visitor.visit_macro_call_expr_mut(s),
+            Expr::Tuple(s) => visitor.visit_tuple_expr_mut(s),
+            Expr::Block(s) => visitor.visit_block_stmt_mut(s),
+            Expr::Closure(s) => visitor.visit_closure_expr_mut(s),
+        }
+    }
+}
@@ -1,10 +1,14 @@
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
 use crate::program::{function::FunctionTemplate, types::TypeID};
+use serde::{Deserialize, Serialize};
 
 use super::expr::Expr;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct FunctionCallExpr {
     function_template: FunctionTemplate,
     arguments: Vec<Expr>,
+    in_async: bool, // Whether the call is made from inside an async function
 }
 
 impl FunctionCallExpr {
@@ -12,6 +16,27 @@ impl FunctionCallExpr {
         FunctionCallExpr {
             function_template,
             arguments,
+            in_async: false,
+        }
+    }
+
+    pub fn new_in_async(function_template: FunctionTemplate, arguments: Vec<Expr>) -> Self {
+        FunctionCallExpr {
+            function_template,
+            arguments,
+            in_async: true,
+        }
+    }
+
+    // Futures from async functions are awaited inside other async functions,
+    // everywhere else they are driven to completion by the runtime executor
+    pub fn complete_call(&self, call: String) -> String {
+        if !self.function_template.is_async() {
+            call
+        } else if self.in_async {
+            format!("{}.await", call)
+        } else {
+            format!("block_on({})", call)
         }
     }
 
@@ -19,6 +44,26 @@ impl FunctionCallExpr {
         self.function_template.get_type()
     }
 
+    pub fn get_template(&self) -> &FunctionTemplate {
+        &self.function_template
+    }
+
+    pub fn get_name(&self) -> String {
+        self.function_template.get_name()
+    }
+
+    pub fn get_name_str(&self) -> &str {
+        self.function_template.get_name_str()
+    }
+
+    pub fn arguments_iter(&self) -> std::slice::Iter<'_, Expr> {
+        self.arguments.iter()
+    }
+
+    pub fn into_arguments(self) -> Vec<Expr> {
+        self.arguments
+    }
+
     pub fn as_expr(self) -> Expr {
         Expr::Func(self)
     }
@@ -35,7 +80,21 @@ impl ToString for FunctionCallExpr {
             result.push(arg_string);
         }
 
-        format!("{}({})", func_name, result.join(", "))
+        self.complete_call(format!("{}({})", func_name, result.join(", ")))
+    }
+}
+
+impl FunctionCallExpr {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        for expr in self.arguments.iter() {
+            visitor.visit_expr(expr);
+        }
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        for expr in self.arguments.iter_mut() {
+            visitor.visit_expr_mut(expr);
+        }
     }
 }
 
@@ -57,4 +116,19 @@ mod test {
 
         assert_eq!(call_expr.to_string(), "test_function(20i32)");
     }
+
+    #[test]
+    fn async_call_is_awaited_only_in_async_function() {
+        let function_template = FunctionTemplate::new_async(
+            String::from("test_function"),
+            vec![],
+            IntTypeID::U8.as_type(),
+        );
+
+        let blocking_call = FunctionCallExpr::new(function_template.clone(), vec![]);
+        let awaited_call = FunctionCallExpr::new_in_async(function_template, vec![]);
+
+        assert_eq!(blocking_call.to_string(), "block_on(test_function())");
+        assert_eq!(awaited_call.to_string(), "test_function().await");
+    }
 }
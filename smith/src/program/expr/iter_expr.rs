@@ -1,7 +1,10 @@
 use crate::program::types::{IntTypeID, TypeID};
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use serde::{Deserialize, Serialize};
 
 use super::arithmetic_expr::ArithmeticExpr;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub enum IterExpr {
     Range(IterRange),
 }
@@ -14,6 +17,7 @@ impl ToString for IterExpr {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct IterRange {
     type_id: TypeID,
     left: ArithmeticExpr,
@@ -36,6 +40,18 @@ impl IterRange {
     pub fn get_type(&self) -> TypeID {
         self.type_id.clone()
     }
+
+    pub fn get_left(&self) -> &ArithmeticExpr {
+        &self.left
+    }
+
+    pub fn get_right(&self) -> &ArithmeticExpr {
+        &self.right
+    }
+
+    pub fn into_bounds(self) -> (ArithmeticExpr, ArithmeticExpr) {
+        (self.left, self.right)
+    }
 }
 
 impl ToString for IterRange {
@@ -43,3 +59,23 @@ impl ToString for IterRange {
         format!("{}..{}", self.left.to_string(), self.right.to_string())
     }
 }
+
+impl IterExpr {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        match self {
+            IterExpr::Range(range) => {
+                visitor.visit_arithmetic_expr(&range.left);
+                visitor.visit_arithmetic_expr(&range.right);
+            }
+        }
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        match self {
+            IterExpr::Range(range) => {
+                visitor.visit_arithmetic_expr_mut(&mut range.left);
+                visitor.visit_arithmetic_expr_mut(&mut range.right);
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use crate::program::{
+    macro_template::{MacroKind, MacroTemplate},
+    types::TypeID,
+};
+use serde::{Deserialize, Serialize};
+
+use super::expr::Expr;
+
+// Invocation of a generated macro, the type is that of the expanded expression
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MacroCallExpr {
+    macro_template: MacroTemplate,
+    arguments: Vec<Expr>,
+    type_id: TypeID,
+}
+
+impl MacroCallExpr {
+    pub fn new(macro_template: MacroTemplate, arguments: Vec<Expr>, type_id: TypeID) -> Self {
+        MacroCallExpr {
+            macro_template,
+            arguments,
+            type_id,
+        }
+    }
+
+    pub fn get_type(&self) -> TypeID {
+        self.type_id.clone()
+    }
+
+    pub fn as_expr(self) -> Expr {
+        Expr::Macro(self)
+    }
+
+    pub fn get_kind(&self) -> MacroKind {
+        self.macro_template.get_kind()
+    }
+
+    pub fn arguments_iter(&self) -> std::slice::Iter<'_, Expr> {
+        self.arguments.iter()
+    }
+}
+
+impl ToString for MacroCallExpr {
+    fn to_string(&self) -> String {
+        let arguments: Vec<String> = self.arguments.iter().map(|x| x.to_string()).collect();
+
+        format!(
+            "{}!({})",
+            self.macro_template.get_name(),
+            arguments.join(", ")
+        )
+    }
+}
+
+impl MacroCallExpr {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        for expr in self.arguments.iter() {
+            visitor.visit_expr(expr);
+        }
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        for expr in self.arguments.iter_mut() {
+            visitor.visit_expr_mut(expr);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::program::{
+        expr::{arithmetic_expr::BinaryOp, arithmetic_expr::IntExpr},
+        macro_template::MacroKind,
+        types::IntTypeID,
+    };
+
+    use super::*;
+    #[test]
+    fn correct_string_repr() {
+        let template =
+            MacroTemplate::new(String::from("macro_1"), MacroKind::ArithFold(BinaryOp::MUL));
+        let arguments = vec![IntExpr::new_u8(2).as_expr(), IntExpr::new_u8(3).as_expr()];
+
+        let call_expr = MacroCallExpr::new(template, arguments, IntTypeID::U8.as_type());
+
+        assert_eq!(call_expr.to_string(), "macro_1!(2u8, 3u8)");
+    }
+}
@@ -2,9 +2,13 @@ pub mod arithmetic_expr;
 pub mod array_expr;
 pub mod bool_expr;
 pub mod borrow_expr;
+pub mod closure_expr;
+pub mod const_generic_call_expr;
 pub mod expr;
 pub mod func_call_expr;
 pub mod iter_expr;
+pub mod macro_call_expr;
 pub mod rc_expr;
 pub mod refcell_expr;
 pub mod struct_expr;
+pub mod tuple_expr;
@@ -1,8 +1,10 @@
 #![allow(warnings)]
 use crate::program::{types::TypeID, var::Var};
+use serde::{Deserialize, Serialize};
 
 use super::expr::Expr;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct RcExpr {
     expr: Expr,
     type_id: TypeID,
@@ -1,7 +1,9 @@
 #![allow(warnings)]
 use super::expr::Expr;
 use crate::program::types::TypeID;
+use serde::{Deserialize, Serialize};
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct RefCellExpr {
     expr: Expr,
     type_id: TypeID,
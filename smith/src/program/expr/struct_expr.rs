@@ -1,7 +1,10 @@
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
 use crate::program::{struct_template::StructTemplate, var::Var};
+use serde::{Deserialize, Serialize};
 
 use super::{expr::Expr, func_call_expr::FunctionCallExpr};
 
+#[derive(Clone, Serialize, Deserialize)]
 pub enum StructExpr {
     Literal(StructLiteral),
     Var(Var),
@@ -25,6 +28,7 @@ impl ToString for StructExpr {
 }
 
 // Expression for instantiation of a struct
+#[derive(Clone, Serialize, Deserialize)]
 pub struct StructLiteral {
     struct_template: StructTemplate,
     field_values: Vec<Expr>,
@@ -41,6 +45,43 @@ impl StructLiteral {
     pub fn as_struct_expr(self) -> StructExpr {
         StructExpr::Literal(self)
     }
+
+    pub fn get_template(&self) -> &StructTemplate {
+        &self.struct_template
+    }
+
+    pub fn get_struct_name(&self) -> String {
+        self.struct_template.get_name()
+    }
+
+    // Each field name with its value, in the order they are written and evaluated
+    pub fn fields_iter(&self) -> impl Iterator<Item = (&String, &Expr)> {
+        self.struct_template
+            .fields_iter()
+            .map(|(name, _)| name)
+            .zip(self.field_values.iter())
+    }
+
+    pub fn remove_field(&mut self, name: &str) {
+        if let Some(index) = self.struct_template.remove_field(name) {
+            if index < self.field_values.len() {
+                self.field_values.remove(index);
+            }
+        }
+    }
+}
+
+impl StructLiteral {
+    // The values of the fields from the given position on become a literal of the inner struct,
+    // written last so that the values are still evaluated in the same order
+    pub fn split_off(&mut self, at: usize, outer: StructTemplate, inner: StructTemplate) {
+        let inner_values = self.field_values.split_off(at.min(self.field_values.len()));
+        let inner_literal = StructLiteral::new(inner, inner_values);
+
+        self.struct_template = outer;
+        self.field_values
+            .push(inner_literal.as_struct_expr().as_expr());
+    }
 }
 
 impl ToString for StructLiteral {
@@ -64,3 +105,35 @@ impl ToString for StructLiteral {
         format!("{} {{{}}}", struct_name, field_args)
     }
 }
+
+impl StructExpr {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        match self {
+            StructExpr::Literal(s) => visitor.visit_struct_literal(s),
+            StructExpr::Var(s) => visitor.visit_var(s),
+            StructExpr::Func(s) => visitor.visit_func_call_expr(s),
+        }
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        match self {
+            StructExpr::Literal(s) => visitor.visit_struct_literal_mut(s),
+            StructExpr::Var(s) => visitor.visit_var_mut(s),
+            StructExpr::Func(s) => visitor.visit_func_call_expr_mut(s),
+        }
+    }
+}
+
+impl StructLiteral {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        for expr in self.field_values.iter() {
+            visitor.visit_expr(expr);
+        }
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        for expr in self.field_values.iter_mut() {
+            visitor.visit_expr_mut(expr);
+        }
+    }
+}
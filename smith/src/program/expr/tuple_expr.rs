@@ -0,0 +1,53 @@
+use super::expr::Expr;
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use serde::{Deserialize, Serialize};
+
+// Only built as the scrutinee of a pattern
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TupleExpr {
+    elements: Vec<Expr>,
+}
+
+impl TupleExpr {
+    pub fn new(elements: Vec<Expr>) -> Self {
+        TupleExpr { elements }
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn as_expr(self) -> Expr {
+        Expr::Tuple(self)
+    }
+
+    pub fn elements_iter(&self) -> std::slice::Iter<'_, Expr> {
+        self.elements.iter()
+    }
+}
+
+impl ToString for TupleExpr {
+    fn to_string(&self) -> String {
+        let elements: Vec<String> = self
+            .elements
+            .iter()
+            .map(|element| element.to_string())
+            .collect();
+
+        format!("({},)", elements.join(", "))
+    }
+}
+
+impl TupleExpr {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        for expr in self.elements.iter() {
+            visitor.visit_expr(expr);
+        }
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        for expr in self.elements.iter_mut() {
+            visitor.visit_expr_mut(expr);
+        }
+    }
+}
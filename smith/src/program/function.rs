@@ -1,14 +1,22 @@
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-use super::types::{BorrowTypeID, TypeID};
-use crate::program::stmt::block_stmt::BlockStmt;
+use super::{
+    attribute::FunctionAttribute,
+    types::{BorrowTypeID, TypeID},
+};
+use crate::program::stmt::{block_stmt::BlockStmt, stmt::Stmt};
 
 // Contains required information to generate and invoke a function call
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct FunctionTemplate {
     name: String,
     params: Vec<Param>,
     return_type: TypeID,
+    is_const: bool,
+    is_async: bool,
+    attributes: Vec<FunctionAttribute>,
 }
 
 impl FunctionTemplate {
@@ -17,6 +25,31 @@ impl FunctionTemplate {
             name,
             params,
             return_type,
+            is_const: false,
+            is_async: false,
+            attributes: Vec::new(),
+        }
+    }
+
+    pub fn new_const(name: String, params: Vec<Param>, return_type: TypeID) -> Self {
+        FunctionTemplate {
+            name,
+            params,
+            return_type,
+            is_const: true,
+            is_async: false,
+            attributes: Vec::new(),
+        }
+    }
+
+    pub fn new_async(name: String, params: Vec<Param>, return_type: TypeID) -> Self {
+        FunctionTemplate {
+            name,
+            params,
+            return_type,
+            is_const: false,
+            is_async: true,
+            attributes: Vec::new(),
         }
     }
 
@@ -24,10 +57,26 @@ impl FunctionTemplate {
         self.name.clone()
     }
 
+    pub fn get_name_str(&self) -> &str {
+        &self.name
+    }
+
     pub fn get_type(&self) -> TypeID {
         self.return_type.clone()
     }
 
+    pub fn is_const(&self) -> bool {
+        self.is_const
+    }
+
+    pub fn is_async(&self) -> bool {
+        self.is_async
+    }
+
+    pub fn insert_attribute(&mut self, attribute: FunctionAttribute) {
+        self.attributes.push(attribute);
+    }
+
     pub fn params_iter(&self) -> std::slice::Iter<Param> {
         self.params.iter()
     }
@@ -42,6 +91,7 @@ impl FunctionTemplate {
 }
 
 // Main AST representation for a function
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Function {
     block_stmt: BlockStmt,
     function_template: FunctionTemplate,
@@ -60,6 +110,40 @@ impl Function {
         }
     }
 
+    // Const functions can be evaluated at compile time, so their bodies are restricted
+    pub fn new_const(
+        name: String,
+        params: Vec<Param>,
+        return_type: TypeID,
+        block_stmt: BlockStmt,
+    ) -> Self {
+        Function {
+            block_stmt,
+            function_template: FunctionTemplate::new_const(name, params, return_type),
+        }
+    }
+
+    // Async functions return a future, which has to be awaited or driven by the runtime executor
+    pub fn new_async(
+        name: String,
+        params: Vec<Param>,
+        return_type: TypeID,
+        block_stmt: BlockStmt,
+    ) -> Self {
+        Function {
+            block_stmt,
+            function_template: FunctionTemplate::new_async(name, params, return_type),
+        }
+    }
+
+    pub fn insert_attribute(&mut self, attribute: FunctionAttribute) {
+        self.function_template.insert_attribute(attribute);
+    }
+
+    pub fn push_stmt(&mut self, stmt: Stmt) {
+        self.block_stmt.push(stmt);
+    }
+
     pub fn get_template(&self) -> FunctionTemplate {
         self.function_template.clone()
     }
@@ -72,18 +156,61 @@ impl Function {
         self.function_template.return_type.clone()
     }
 
+    pub fn get_block_stmt(&self) -> &BlockStmt {
+        &self.block_stmt
+    }
+
+    pub fn get_block_stmt_mut(&mut self) -> &mut BlockStmt {
+        &mut self.block_stmt
+    }
+
+    pub fn into_block_stmt(self) -> BlockStmt {
+        self.block_stmt
+    }
+
     pub fn get_params(&self) -> Vec<Param> {
         self.function_template.params.to_vec()
     }
 
+    pub fn params_iter(&self) -> std::slice::Iter<'_, Param> {
+        self.function_template.params.iter()
+    }
+
     pub fn to_string(&self) -> String {
+        self.render("")
+    }
+
+    // Visible outside of the module it is declared in
+    pub fn to_string_pub(&self) -> String {
+        self.render("pub ")
+    }
+
+    fn render(&self, visibility: &str) -> String {
         let mut return_string = format!("-> {} ", self.function_template.return_type.to_string());
         if self.function_template.return_type == TypeID::NullType {
             return_string = String::from("");
         }
 
+        let qualifier = if self.function_template.is_const {
+            "const "
+        } else if self.function_template.is_async {
+            "async "
+        } else {
+            ""
+        };
+
+        let attribute_string: String = self
+            .function_template
+            .attributes
+            .iter()
+            .map(|attribute| format!("{}\n", attribute.to_string()))
+            .collect();
+
         format!(
-            "fn {}({}) {}{}",
+            "{}{}{}fn {}({}) {}{}",
+            attribute_string,
+            visibility,
+            qualifier,
             self.get_name(),
             self.function_template.param_list_to_string(),
             return_string,
@@ -98,11 +225,14 @@ impl fmt::Debug for FunctionTemplate {
             .field("Name", &self.name)
             .field("Return Type", &self.return_type)
             .field("Params", &self.param_list_to_string())
+            .field("Const", &self.is_const)
+            .field("Async", &self.is_async)
+            .field("Attributes", &self.attributes)
             .finish()
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Param {
     name: String,
     type_id: TypeID,
@@ -146,6 +276,10 @@ impl Param {
         self.name.clone()
     }
 
+    pub fn get_name_str(&self) -> &str {
+        &self.name
+    }
+
     pub fn get_type(&self) -> TypeID {
         self.type_id.clone()
     }
@@ -166,6 +300,16 @@ impl ToString for Param {
     }
 }
 
+impl Function {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_block_stmt(&self.block_stmt);
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_block_stmt_mut(&mut self.block_stmt);
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -203,6 +347,38 @@ mod test {
         )
     }
 
+    #[test]
+    fn const_function_has_qualifier() {
+        let mut block_stmt = BlockStmt::new();
+        block_stmt.push(basic_let_statement());
+
+        let func = Function::new_const(
+            String::from("function_1"),
+            basic_param_list(),
+            TypeID::NullType,
+            block_stmt,
+        );
+
+        assert!(func.get_template().is_const());
+        assert!(func.to_string().starts_with("const fn function_1("));
+    }
+
+    #[test]
+    fn attributes_precede_visibility() {
+        let mut func = Function::new(
+            String::from("function_1"),
+            Vec::new(),
+            TypeID::NullType,
+            BlockStmt::new(),
+        );
+        func.insert_attribute(FunctionAttribute::InlineNever);
+        func.insert_attribute(FunctionAttribute::Cold);
+
+        assert!(func
+            .to_string_pub()
+            .starts_with("#[inline(never)]\n#[cold]\npub fn function_1()"));
+    }
+
     #[test]
     fn param_string() {
         let param = Param::new(String::from("a"), IntTypeID::I8.as_type());
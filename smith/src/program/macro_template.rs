@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::{expr::arithmetic_expr::BinaryOp, expr::bool_expr::BoolOp, types::TypeID};
+
+// The shape of a generated macro_rules! definition
+// Each kind has several arms, and most of them use repetitions
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum MacroKind {
+    // Right fold of one or more integer expressions with a binary op, recursive
+    ArithFold(BinaryOp),
+    // Binds its arguments to locals named like the generated variables, to exercise hygiene
+    ArithShadow(BinaryOp),
+    // Fold of zero or more boolean expressions with || or &&
+    BoolFold(BoolOp),
+    // Applies the op assign of each argument to the first argument in turn, expands to statements
+    OpAssignEach(BinaryOp),
+}
+
+impl MacroKind {
+    pub fn is_arith(&self) -> bool {
+        matches!(self, Self::ArithFold(_) | Self::ArithShadow(_))
+    }
+
+    pub fn is_bool(&self) -> bool {
+        matches!(self, Self::BoolFold(_))
+    }
+
+    pub fn is_stmt(&self) -> bool {
+        matches!(self, Self::OpAssignEach(_))
+    }
+
+    // The number of arguments a call can take, inclusive
+    pub fn arg_bounds(&self) -> (usize, usize) {
+        match self {
+            Self::ArithFold(_) => (1, 4),
+            Self::ArithShadow(_) => (1, 2),
+            Self::BoolFold(_) => (0, 4),
+            // Includes the assigned variable
+            Self::OpAssignEach(_) => (2, 4),
+        }
+    }
+}
+
+// Representation of a macro's name and the arms it expands to
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MacroTemplate {
+    name: String,
+    kind: MacroKind,
+}
+
+impl MacroTemplate {
+    pub fn new(name: String, kind: MacroKind) -> Self {
+        MacroTemplate { name, kind }
+    }
+
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn get_kind(&self) -> MacroKind {
+        self.kind
+    }
+
+    // Macros are generic over the types of their arguments, so the result type is that of the call
+    pub fn can_produce(&self, type_id: &TypeID) -> bool {
+        match type_id {
+            TypeID::IntType(_) => self.kind.is_arith(),
+            TypeID::BoolType => self.kind.is_bool(),
+            TypeID::NullType => self.kind.is_stmt(),
+            TypeID::StructType(_) => false,
+        }
+    }
+
+    fn arms(&self) -> Vec<(String, String)> {
+        match self.kind {
+            MacroKind::ArithFold(op) => vec![
+                (String::from("$x:expr"), String::from("$x")),
+                (
+                    String::from("$x:expr, $($rest:expr),+"),
+                    apply_binary_op(op, "$x", &format!("{}!($($rest),+)", self.name)),
+                ),
+            ],
+            MacroKind::ArithShadow(op) => vec![
+                (
+                    String::from("$x:expr"),
+                    format!(
+                        "{{\nlet var_1 = $x;\n{}\n}}",
+                        apply_binary_op(op, "var_1", "var_1")
+                    ),
+                ),
+                (
+                    String::from("$x:expr, $y:expr"),
+                    format!(
+                        "{{\nlet var_1 = $x;\nlet var_2 = $y;\n{}\n}}",
+                        apply_binary_op(op, "var_1", "var_2")
+                    ),
+                ),
+            ],
+            MacroKind::BoolFold(op) => {
+                let identity = match op {
+                    BoolOp::AND => "true",
+                    BoolOp::OR => "false",
+                };
+                vec![
+                    (String::new(), identity.to_string()),
+                    (
+                        String::from("$($b:expr),+"),
+                        format!("{} $({} $b)+", identity, op.to_string()),
+                    ),
+                ]
+            }
+            MacroKind::OpAssignEach(op) => vec![(
+                String::from("$lhs:expr, $($rhs:expr),+"),
+                format!("$($lhs.{}($rhs);)+", op.to_string_self_safe()),
+            )],
+        }
+    }
+
+    pub fn to_string(&self) -> String {
+        let arms: String = self
+            .arms()
+            .iter()
+            .map(|(matcher, transcriber)| format!("({}) => {{\n{}\n}};\n", matcher, transcriber))
+            .collect();
+
+        format!("macro_rules! {} {{\n{}}}", self.name, arms)
+    }
+}
+
+// Bit operations are written infix, the rest use the safe arithmetic methods
+fn apply_binary_op(op: BinaryOp, left: &str, right: &str) -> String {
+    match op {
+        BinaryOp::BITAND | BinaryOp::BITOR | BinaryOp::BITXOR => {
+            format!("{} {} {}", left, op.to_string(), right)
+        }
+        _ => format!("{}.{}({})", left, op.to_string_safe(), right),
+    }
+}
+
+impl fmt::Debug for MacroTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MacroTemplate")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn fold_macro_recurses_on_rest() {
+        let template =
+            MacroTemplate::new(String::from("macro_1"), MacroKind::ArithFold(BinaryOp::ADD));
+
+        let string_rep = template.to_string();
+
+        assert!(string_rep.starts_with("macro_rules! macro_1 {"));
+        assert!(string_rep.contains("($x:expr) => {\n$x\n};"));
+        assert!(string_rep.contains("$x.safe_add(macro_1!($($rest),+))"));
+    }
+
+    #[test]
+    fn bool_fold_macro_accepts_no_arguments() {
+        let template =
+            MacroTemplate::new(String::from("macro_2"), MacroKind::BoolFold(BoolOp::AND));
+
+        let string_rep = template.to_string();
+
+        assert!(string_rep.contains("() => {\ntrue\n};"));
+        assert!(string_rep.contains("true $(&& $b)+"));
+    }
+}
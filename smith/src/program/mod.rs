@@ -1,7 +1,12 @@
+pub mod attribute;
+pub mod const_generic;
 pub mod expr;
 pub mod function;
+pub mod macro_template;
+pub mod pattern;
 pub mod program;
 pub mod stmt;
 pub mod struct_template;
 pub mod types;
 pub mod var;
+pub mod visitor;
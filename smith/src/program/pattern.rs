@@ -0,0 +1,192 @@
+use super::{expr::arithmetic_expr::IntExpr, var::Var};
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+/// Patterns used by let destructuring, if let, let else and matches!
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Pattern {
+    Wildcard,
+    Binding(Var),
+    // name @ subpattern, the subpattern never binds anything itself
+    At(Var, Box<Pattern>),
+    // Inclusive range, the start is never greater than the end
+    Range(IntExpr, IntExpr),
+    Bool(bool),
+    Tuple(Vec<Pattern>),
+    // Struct name, the matched fields and whether the remaining fields are skipped with ..
+    Struct(String, Vec<(String, Pattern)>, bool),
+}
+
+impl Pattern {
+    pub fn new_struct(name: String, fields: Vec<(String, Pattern)>, has_rest: bool) -> Self {
+        Pattern::Struct(name, fields, has_rest)
+    }
+
+    pub fn new_at(var: Var, pattern: Pattern) -> Self {
+        Pattern::At(var, Box::new(pattern))
+    }
+
+    // Whether there are values of the type that this pattern does not match
+    pub fn is_refutable(&self) -> bool {
+        match self {
+            Pattern::Wildcard | Pattern::Binding(_) => false,
+            Pattern::At(_, pattern) => pattern.is_refutable(),
+            Pattern::Range(_, _) | Pattern::Bool(_) => true,
+            Pattern::Tuple(patterns) => patterns.iter().any(|pattern| pattern.is_refutable()),
+            Pattern::Struct(_, fields, _) => {
+                fields.iter().any(|(_, pattern)| pattern.is_refutable())
+            }
+        }
+    }
+
+    fn binding_to_string(var: &Var) -> String {
+        if var.is_mut() {
+            format!("mut {}", var.get_name())
+        } else {
+            var.get_name()
+        }
+    }
+}
+
+impl ToString for Pattern {
+    fn to_string(&self) -> String {
+        match self {
+            Pattern::Wildcard => String::from("_"),
+            Pattern::Binding(var) => Pattern::binding_to_string(var),
+            Pattern::At(var, pattern) => format!(
+                "{} @ {}",
+                Pattern::binding_to_string(var),
+                pattern.to_string()
+            ),
+            Pattern::Range(start, end) => format!("{}..={}", start.to_string(), end.to_string()),
+            Pattern::Bool(value) => value.to_string(),
+            Pattern::Tuple(patterns) => {
+                let patterns: Vec<String> =
+                    patterns.iter().map(|pattern| pattern.to_string()).collect();
+                format!("({},)", patterns.join(", "))
+            }
+            Pattern::Struct(name, fields, has_rest) => {
+                let mut fields: Vec<String> = fields
+                    .iter()
+                    .map(|(field_name, pattern)| match pattern {
+                        // Shorthand when the binding has the name of the field
+                        Pattern::Binding(var) if var.get_name() == *field_name => {
+                            Pattern::binding_to_string(var)
+                        }
+                        _ => format!("{}: {}", field_name, pattern.to_string()),
+                    })
+                    .collect();
+
+                if *has_rest {
+                    fields.push(String::from(".."));
+                }
+
+                format!("{} {{ {} }}", name, fields.join(", "))
+            }
+        }
+    }
+}
+
+impl Pattern {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        match self {
+            Pattern::Binding(var) => visitor.visit_binding(var),
+            Pattern::At(var, pattern) => {
+                visitor.visit_binding(var);
+                visitor.visit_pattern(pattern);
+            }
+            Pattern::Range(start, end) => {
+                visitor.visit_int_expr(start);
+                visitor.visit_int_expr(end);
+            }
+            Pattern::Tuple(patterns) => {
+                for pattern in patterns.iter() {
+                    visitor.visit_pattern(pattern);
+                }
+            }
+            Pattern::Struct(_, fields, _) => {
+                for (_, pattern) in fields.iter() {
+                    visitor.visit_pattern(pattern);
+                }
+            }
+            Pattern::Wildcard | Pattern::Bool(_) => {}
+        }
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        match self {
+            Pattern::Binding(var) => visitor.visit_binding_mut(var),
+            Pattern::At(var, pattern) => {
+                visitor.visit_binding_mut(var);
+                visitor.visit_pattern_mut(pattern);
+            }
+            Pattern::Range(start, end) => {
+                visitor.visit_int_expr_mut(start);
+                visitor.visit_int_expr_mut(end);
+            }
+            Pattern::Tuple(patterns) => {
+                for pattern in patterns.iter_mut() {
+                    visitor.visit_pattern_mut(pattern);
+                }
+            }
+            Pattern::Struct(_, fields, _) => {
+                for (_, pattern) in fields.iter_mut() {
+                    visitor.visit_pattern_mut(pattern);
+                }
+            }
+            Pattern::Wildcard | Pattern::Bool(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::types::IntTypeID;
+    #[test]
+    fn struct_pattern_uses_shorthand_and_rest() {
+        let pattern = Pattern::new_struct(
+            String::from("Struct1"),
+            vec![
+                (
+                    String::from("field_1"),
+                    Pattern::Binding(Var::new(
+                        IntTypeID::U8.as_type(),
+                        String::from("field_1"),
+                        false,
+                    )),
+                ),
+                (
+                    String::from("field_2"),
+                    Pattern::new_at(
+                        Var::new(IntTypeID::I8.as_type(), String::from("var_1"), true),
+                        Pattern::Range(IntExpr::new_i8(-3), IntExpr::new_i8(9)),
+                    ),
+                ),
+            ],
+            true,
+        );
+
+        assert_eq!(
+            pattern.to_string(),
+            "Struct1 { field_1, field_2: mut var_1 @ -3i8..=9i8, .. }"
+        );
+        assert!(pattern.is_refutable());
+    }
+
+    #[test]
+    fn tuple_of_bindings_is_irrefutable() {
+        let pattern = Pattern::Tuple(vec![
+            Pattern::Wildcard,
+            Pattern::Binding(Var::new(
+                IntTypeID::U32.as_type(),
+                String::from("var_2"),
+                false,
+            )),
+        ]);
+
+        assert_eq!(pattern.to_string(), "(_, var_2,)");
+        assert!(!pattern.is_refutable());
+        assert!(Pattern::Tuple(vec![Pattern::Bool(true), Pattern::Wildcard]).is_refutable());
+    }
+}
@@ -1,24 +1,74 @@
-use super::{function::Function, stmt::static_stmt::StaticStmt, struct_template::StructTemplate};
+use super::{
+    const_generic::ConstGeneric,
+    function::Function,
+    macro_template::MacroTemplate,
+    stmt::{const_stmt::ConstStmt, static_stmt::StaticStmt, stmt::Stmt},
+    struct_template::StructTemplate,
+};
+use crate::program::visitor::{passes::TracedStmt, visit::Visit, visit_mut::VisitMut};
+use serde::{Deserialize, Serialize};
+
 // Top level AST node representing an entire program
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Program {
+    macros: MacroList,
     statics: StaticList,
+    consts: ConstList,
     functions: FunctionList,
     structs: StructList,
+    const_generics: ConstGenericList,
+    modules: Vec<Module>,
+    header_comment: String, // Goes before everything else in the generated file
 }
 
 impl Program {
     pub fn new() -> Self {
         Program {
+            macros: MacroList::new(),
             statics: StaticList::new(),
+            consts: ConstList::new(),
             functions: FunctionList::new(),
             structs: StructList::new(),
+            const_generics: ConstGenericList::new(),
+            modules: Vec::new(),
+            header_comment: String::new(),
         }
     }
 
+    // Every node of the AST, so that the program can be stored and rendered again later
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    // The statement that the trace with this id follows, in a program generated in trace mode
+    pub fn traced_stmt(&self, stmt_id: u32) -> Option<Stmt> {
+        TracedStmt::of_program(self, stmt_id)
+    }
+
+    pub fn set_header_comment(&mut self, header_comment: String) {
+        self.header_comment = header_comment;
+    }
+
+    pub fn get_header_comment(&self) -> &str {
+        &self.header_comment
+    }
+
+    pub fn push_macro_template(&mut self, macro_template: MacroTemplate) {
+        self.macros.push(macro_template);
+    }
+
     pub fn push_static_stmt(&mut self, stmt: StaticStmt) {
         self.statics.push(stmt);
     }
 
+    pub fn push_const_stmt(&mut self, stmt: ConstStmt) {
+        self.consts.push(stmt);
+    }
+
     pub fn push_function(&mut self, function: Function) {
         self.functions.push(function);
     }
@@ -27,16 +77,201 @@ impl Program {
         self.structs.push(struct_template);
     }
 
+    pub fn push_const_generic(&mut self, const_generic: ConstGeneric) {
+        self.const_generics.push(const_generic);
+    }
+
+    pub fn push_module(&mut self, module: Module) {
+        self.modules.push(module);
+    }
+
+    // Top level functions, without those inside modules
+    pub fn functions_iter(&self) -> std::slice::Iter<'_, Function> {
+        self.functions.list.iter()
+    }
+
+    pub fn macros_iter(&self) -> std::slice::Iter<'_, MacroTemplate> {
+        self.macros.list.iter()
+    }
+
+    pub fn consts_iter(&self) -> std::slice::Iter<'_, ConstStmt> {
+        self.consts.list.iter()
+    }
+
+    pub fn structs_iter(&self) -> std::slice::Iter<'_, StructTemplate> {
+        self.structs.list.iter()
+    }
+
+    pub fn const_generics_iter(&self) -> std::slice::Iter<'_, ConstGeneric> {
+        self.const_generics.list.iter()
+    }
+
+    pub fn modules_iter(&self) -> std::slice::Iter<'_, Module> {
+        self.modules.iter()
+    }
+
+    pub fn remove_function(&mut self, name: &str) -> Option<Function> {
+        let index = self
+            .functions
+            .list
+            .iter()
+            .position(|function| function.get_name() == name)?;
+
+        Some(self.functions.list.remove(index))
+    }
+
+    pub fn module_count(&self) -> usize {
+        self.modules.len()
+    }
+
+    pub fn get_module_mut(&mut self, index: usize) -> &mut Module {
+        &mut self.modules[index]
+    }
+
+    // Includes the structs inside modules
+    pub fn struct_count(&self) -> usize {
+        self.structs.list.len()
+            + self
+                .modules
+                .iter()
+                .map(|module| module.structs.list.len())
+                .sum::<usize>()
+    }
+
+    // Every module is glob imported so that the rest of the program can name its items directly
+    fn module_imports(&self, visibility: &str) -> String {
+        self.modules
+            .iter()
+            .map(|module| format!("{}use {}::*;\n", visibility, module.name))
+            .collect()
+    }
+
+    fn modules_to_string(&self) -> String {
+        self.modules
+            .iter()
+            .map(|module| module.to_string())
+            .collect()
+    }
+
     pub fn to_string(&self) -> String {
+        // Macros are textually scoped so they have to come before any use
+        format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            self.macros.to_string(),
+            self.statics.to_string(),
+            self.consts.to_string(),
+            self.modules_to_string(),
+            self.module_imports(""),
+            self.structs.to_string(),
+            self.const_generics.to_string(),
+            self.functions.to_string(),
+        )
+    }
+
+    // The modules as a library crate, macros are not exported so they are repeated in both crates
+    pub fn lib_to_string(&self) -> String {
         format!(
             "{}\n{}\n{}",
+            self.macros.to_string(),
+            self.modules_to_string(),
+            self.module_imports("pub "),
+        )
+    }
+
+    // Everything outside of the modules as a binary crate that depends on the library crate
+    pub fn bin_to_string(&self, lib_name: &str) -> String {
+        format!(
+            "use {}::*;\n{}\n{}\n{}\n{}\n{}\n{}",
+            lib_name,
+            self.macros.to_string(),
             self.statics.to_string(),
+            self.consts.to_string(),
             self.structs.to_string(),
+            self.const_generics.to_string(),
             self.functions.to_string(),
         )
     }
 }
 
+// A mod block, its items are public and it can see everything in its parent
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Module {
+    name: String,
+    structs: StructList,
+    const_generics: ConstGenericList,
+    functions: FunctionList,
+}
+
+impl Module {
+    pub fn new(name: String) -> Self {
+        Module {
+            name,
+            structs: StructList::new(),
+            const_generics: ConstGenericList::new(),
+            functions: FunctionList::new(),
+        }
+    }
+
+    pub fn push_function(&mut self, function: Function) {
+        self.functions.push(function);
+    }
+
+    pub fn push_struct_template(&mut self, struct_template: StructTemplate) {
+        self.structs.push(struct_template);
+    }
+
+    pub fn push_const_generic(&mut self, const_generic: ConstGeneric) {
+        self.const_generics.push(const_generic);
+    }
+
+    pub fn functions_iter(&self) -> std::slice::Iter<'_, Function> {
+        self.functions.list.iter()
+    }
+
+    pub fn structs_iter(&self) -> std::slice::Iter<'_, StructTemplate> {
+        self.structs.list.iter()
+    }
+
+    pub fn const_generics_iter(&self) -> std::slice::Iter<'_, ConstGeneric> {
+        self.const_generics.list.iter()
+    }
+
+    pub fn to_string(&self) -> String {
+        format!(
+            "mod {} {{\nuse super::*;\n\n{}\n{}\n{}}}\n",
+            self.name,
+            self.structs.to_string_pub(),
+            self.const_generics.to_string_pub(),
+            self.functions.to_string_pub(),
+        )
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct MacroList {
+    list: Vec<MacroTemplate>,
+}
+
+impl MacroList {
+    pub fn new() -> Self {
+        MacroList { list: Vec::new() }
+    }
+
+    pub fn push(&mut self, macro_template: MacroTemplate) {
+        self.list.push(macro_template);
+    }
+
+    pub fn to_string(&self) -> String {
+        let mut string = String::new();
+        for template in self.list.iter() {
+            string.push_str(template.to_string().as_str());
+            string.push('\n');
+        }
+        string
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 struct StaticList {
     list: Vec<StaticStmt>,
 }
@@ -60,6 +295,31 @@ impl StaticList {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+struct ConstList {
+    list: Vec<ConstStmt>,
+}
+
+impl ConstList {
+    pub fn new() -> Self {
+        ConstList { list: Vec::new() }
+    }
+
+    pub fn push(&mut self, stmt: ConstStmt) {
+        self.list.push(stmt);
+    }
+
+    pub fn to_string(&self) -> String {
+        let mut string = String::new();
+        for stmt in self.list.iter() {
+            string.push_str(stmt.to_string().as_str());
+            string.push('\n');
+        }
+        string
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 struct FunctionList {
     list: Vec<Function>,
 }
@@ -81,8 +341,18 @@ impl FunctionList {
         }
         string
     }
+
+    pub fn to_string_pub(&self) -> String {
+        let mut string = String::new();
+        for function in self.list.iter() {
+            string.push_str(function.to_string_pub().as_str());
+            string.push('\n');
+        }
+        string
+    }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 struct StructList {
     list: Vec<StructTemplate>,
 }
@@ -104,4 +374,150 @@ impl StructList {
         }
         string
     }
+
+    pub fn to_string_pub(&self) -> String {
+        let mut string = String::new();
+        for template in self.list.iter() {
+            string.push_str(template.to_string_pub().as_str());
+            string.push('\n');
+        }
+        string
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct ConstGenericList {
+    list: Vec<ConstGeneric>,
+}
+
+impl ConstGenericList {
+    pub fn new() -> Self {
+        ConstGenericList { list: Vec::new() }
+    }
+
+    pub fn push(&mut self, const_generic: ConstGeneric) {
+        self.list.push(const_generic);
+    }
+
+    pub fn to_string(&self) -> String {
+        let mut string = String::new();
+        for const_generic in self.list.iter() {
+            string.push_str(const_generic.to_string().as_str());
+            string.push('\n');
+        }
+        string
+    }
+
+    pub fn to_string_pub(&self) -> String {
+        let mut string = String::new();
+        for const_generic in self.list.iter() {
+            string.push_str(const_generic.to_string_pub().as_str());
+            string.push('\n');
+        }
+        string
+    }
+}
+
+impl Program {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        for macro_template in self.macros.list.iter() {
+            visitor.visit_macro_template(macro_template);
+        }
+        for stmt in self.statics.list.iter() {
+            visitor.visit_static_stmt(stmt);
+        }
+        for stmt in self.consts.list.iter() {
+            visitor.visit_const_stmt(stmt);
+        }
+        for module in self.modules.iter() {
+            visitor.visit_module(module);
+        }
+        for struct_template in self.structs.list.iter() {
+            visitor.visit_struct_template(struct_template);
+        }
+        for const_generic in self.const_generics.list.iter() {
+            visitor.visit_const_generic(const_generic);
+        }
+        for function in self.functions.list.iter() {
+            visitor.visit_function(function);
+        }
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        for macro_template in self.macros.list.iter_mut() {
+            visitor.visit_macro_template_mut(macro_template);
+        }
+        for stmt in self.statics.list.iter_mut() {
+            visitor.visit_static_stmt_mut(stmt);
+        }
+        for stmt in self.consts.list.iter_mut() {
+            visitor.visit_const_stmt_mut(stmt);
+        }
+        for module in self.modules.iter_mut() {
+            visitor.visit_module_mut(module);
+        }
+        for struct_template in self.structs.list.iter_mut() {
+            visitor.visit_struct_template_mut(struct_template);
+        }
+        for const_generic in self.const_generics.list.iter_mut() {
+            visitor.visit_const_generic_mut(const_generic);
+        }
+        for function in self.functions.list.iter_mut() {
+            visitor.visit_function_mut(function);
+        }
+    }
+}
+
+impl Module {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        for struct_template in self.structs.list.iter() {
+            visitor.visit_struct_template(struct_template);
+        }
+        for const_generic in self.const_generics.list.iter() {
+            visitor.visit_const_generic(const_generic);
+        }
+        for function in self.functions.list.iter() {
+            visitor.visit_function(function);
+        }
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        for struct_template in self.structs.list.iter_mut() {
+            visitor.visit_struct_template_mut(struct_template);
+        }
+        for const_generic in self.const_generics.list.iter_mut() {
+            visitor.visit_const_generic_mut(const_generic);
+        }
+        for function in self.functions.list.iter_mut() {
+            visitor.visit_function_mut(function);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::{stmt::block_stmt::BlockStmt, types::TypeID};
+    #[test]
+    fn module_items_are_public_and_imported() {
+        let mut program = Program::new();
+        let mut module = Module::new(String::from("module_1"));
+        module.push_function(Function::new(
+            String::from("function_1"),
+            Vec::new(),
+            TypeID::NullType,
+            BlockStmt::new(),
+        ));
+        program.push_module(module);
+
+        let string_rep = program.to_string();
+
+        assert!(string_rep.contains("mod module_1 {\nuse super::*;\n"));
+        assert!(string_rep.contains("pub fn function_1()"));
+        assert!(string_rep.contains("use module_1::*;"));
+        assert!(program.lib_to_string().contains("pub use module_1::*;"));
+        assert!(program
+            .bin_to_string("generated_lib")
+            .starts_with("use generated_lib::*;"));
+    }
 }
@@ -1,7 +1,10 @@
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
 use crate::program::{expr::expr::Expr, var::Var};
+use serde::{Deserialize, Serialize};
 
 use super::stmt::Stmt;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct AssignStmt {
     left_var: Var,
     right_expr: Expr,
@@ -17,6 +20,26 @@ impl AssignStmt {
         }
     }
 
+    pub fn get_name(&self) -> String {
+        self.left_var.get_name()
+    }
+
+    pub fn get_var(&self) -> &Var {
+        &self.left_var
+    }
+
+    pub fn get_expr(&self) -> &Expr {
+        &self.right_expr
+    }
+
+    pub fn get_expr_mut(&mut self) -> &mut Expr {
+        &mut self.right_expr
+    }
+
+    pub fn is_deref(&self) -> bool {
+        self.deref
+    }
+
     pub fn as_stmt(self) -> Stmt {
         Stmt::AssignStatement(self)
     }
@@ -32,3 +55,15 @@ impl ToString for AssignStmt {
         )
     }
 }
+
+impl AssignStmt {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_var(&self.left_var);
+        visitor.visit_expr(&self.right_expr);
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_var_mut(&mut self.left_var);
+        visitor.visit_expr_mut(&mut self.right_expr);
+    }
+}
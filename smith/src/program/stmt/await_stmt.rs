@@ -0,0 +1,58 @@
+use super::stmt::Stmt;
+use serde::{Deserialize, Serialize};
+
+// Suspends an async function at an await point
+// References that are still alive are read afterwards, so they are held across the suspension
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AwaitStmt {
+    held_borrows: Vec<String>,
+}
+
+impl AwaitStmt {
+    pub fn new(held_borrows: Vec<String>) -> Self {
+        AwaitStmt { held_borrows }
+    }
+}
+
+impl AwaitStmt {
+    pub fn to_string(&self) -> String {
+        if self.held_borrows.is_empty() {
+            return String::from("yield_now().await;");
+        }
+
+        let uses: Vec<String> = self
+            .held_borrows
+            .iter()
+            .map(|borrow| format!("&{}", borrow))
+            .collect();
+
+        format!("yield_now().await;\nlet _ = ({},);", uses.join(", "))
+    }
+
+    pub fn held_borrows_iter(&self) -> std::slice::Iter<'_, String> {
+        self.held_borrows.iter()
+    }
+
+    pub fn held_borrows_iter_mut(&mut self) -> std::slice::IterMut<'_, String> {
+        self.held_borrows.iter_mut()
+    }
+
+    pub fn as_stmt(self) -> Stmt {
+        Stmt::AwaitStatement(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn reads_held_borrows_after_await() {
+        let await_stmt = AwaitStmt::new(vec![String::from("var_1"), String::from("var_3")]);
+
+        assert_eq!(
+            await_stmt.to_string(),
+            "yield_now().await;\nlet _ = (&var_1, &var_3,);"
+        );
+        assert_eq!(AwaitStmt::new(vec![]).to_string(), "yield_now().await;");
+    }
+}
@@ -1,7 +1,10 @@
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 
 use super::stmt::Stmt;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct BlockStmt {
     stmts: VecDeque<Stmt>,
 }
@@ -31,6 +34,22 @@ impl BlockStmt {
         self.stmts.push_front(stmt);
     }
 
+    pub fn insert(&mut self, index: usize, stmt: Stmt) {
+        self.stmts.insert(index, stmt);
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<Stmt> {
+        self.stmts.remove(index)
+    }
+
+    pub fn stmts_iter(&self) -> std::collections::vec_deque::Iter<'_, Stmt> {
+        self.stmts.iter()
+    }
+
+    pub fn last_mut(&mut self) -> Option<&mut Stmt> {
+        self.stmts.back_mut()
+    }
+
     pub fn to_string(&self) -> String {
         let mut string = String::new();
 
@@ -46,3 +65,17 @@ impl BlockStmt {
         string
     }
 }
+
+impl BlockStmt {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        for stmt in self.stmts.iter() {
+            visitor.visit_stmt(stmt);
+        }
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        for stmt in self.stmts.iter_mut() {
+            visitor.visit_stmt_mut(stmt);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use super::stmt::Stmt;
+use serde::{Deserialize, Serialize};
+
+// Folds the values of places into the runtime checksum of a function
+// Places are only ever integers or bools
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ChecksumStmt {
+    function_name: String,
+    places: Vec<String>,
+}
+
+impl ChecksumStmt {
+    pub fn new(function_name: String, places: Vec<String>) -> Self {
+        ChecksumStmt {
+            function_name,
+            places,
+        }
+    }
+
+    pub fn get_function_name(&self) -> &str {
+        &self.function_name
+    }
+
+    pub fn places_iter(&self) -> std::slice::Iter<'_, String> {
+        self.places.iter()
+    }
+
+    pub fn places_iter_mut(&mut self) -> std::slice::IterMut<'_, String> {
+        self.places.iter_mut()
+    }
+
+    pub fn as_stmt(self) -> Stmt {
+        Stmt::ChecksumStatement(self)
+    }
+}
+
+impl ToString for ChecksumStmt {
+    fn to_string(&self) -> String {
+        let values: Vec<String> = self
+            .places
+            .iter()
+            .map(|place| format!("{}.to_checksum()", place))
+            .collect();
+
+        format!(
+            "checksum::fold(\"{}\", &[{}]);",
+            self.function_name,
+            values.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn folds_every_place() {
+        let stmt = ChecksumStmt::new(
+            String::from("function_1"),
+            vec![String::from("var_1"), String::from("var_2.field_1")],
+        );
+
+        assert_eq!(
+            stmt.to_string(),
+            "checksum::fold(\"function_1\", &[var_1.to_checksum(), var_2.field_1.to_checksum()]);"
+        );
+    }
+}
@@ -1,9 +1,12 @@
 use crate::program::expr::bool_expr::BoolExpr;
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use serde::{Deserialize, Serialize};
 
 use super::{block_stmt::BlockStmt, stmt::Stmt};
 
 // Contains the branches of conditions and block statements corresponding to if, else if...
 // Optional else branch
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ConditionalStmt {
     conditional_blocks: Vec<(BoolExpr, BlockStmt)>,
     else_body: Option<BlockStmt>,
@@ -38,6 +41,39 @@ impl ConditionalStmt {
     pub fn as_stmt(self) -> Stmt {
         Stmt::ConditionalStatement(self)
     }
+
+    pub fn branches_iter(&self) -> std::slice::Iter<'_, (BoolExpr, BlockStmt)> {
+        self.conditional_blocks.iter()
+    }
+
+    pub fn branches_iter_mut(&mut self) -> std::slice::IterMut<'_, (BoolExpr, BlockStmt)> {
+        self.conditional_blocks.iter_mut()
+    }
+
+    pub fn branch_count(&self) -> usize {
+        self.conditional_blocks.len()
+    }
+
+    // The next branch becomes the if when the first one is removed
+    pub fn remove_branch(&mut self, index: usize) {
+        self.conditional_blocks.remove(index);
+    }
+
+    pub fn get_else_body(&self) -> Option<&BlockStmt> {
+        self.else_body.as_ref()
+    }
+
+    pub fn get_else_body_mut(&mut self) -> Option<&mut BlockStmt> {
+        self.else_body.as_mut()
+    }
+
+    pub fn has_else_body(&self) -> bool {
+        self.else_body.is_some()
+    }
+
+    pub fn remove_else_body(&mut self) {
+        self.else_body = None;
+    }
 }
 
 impl ToString for ConditionalStmt {
@@ -65,6 +101,28 @@ impl ToString for ConditionalStmt {
     }
 }
 
+impl ConditionalStmt {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        for (condition, block_stmt) in self.conditional_blocks.iter() {
+            visitor.visit_bool_expr(condition);
+            visitor.visit_block_stmt(block_stmt);
+        }
+        if let Some(else_body) = &self.else_body {
+            visitor.visit_block_stmt(else_body);
+        }
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        for (condition, block_stmt) in self.conditional_blocks.iter_mut() {
+            visitor.visit_bool_expr_mut(condition);
+            visitor.visit_block_stmt_mut(block_stmt);
+        }
+        if let Some(else_body) = &mut self.else_body {
+            visitor.visit_block_stmt_mut(else_body);
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::program::{
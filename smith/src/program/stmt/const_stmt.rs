@@ -0,0 +1,81 @@
+use super::stmt::Stmt;
+use crate::program::expr::expr::Expr;
+use crate::program::types::TypeID;
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use serde::{Deserialize, Serialize};
+
+// A const item, the expression is evaluated by the compiler
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ConstStmt {
+    var_name: String,
+    var_type: TypeID,
+    expr: Expr,
+}
+
+impl ConstStmt {
+    pub fn new(var_name: String, var_type: TypeID, expr: Expr) -> Self {
+        ConstStmt {
+            var_name,
+            var_type,
+            expr,
+        }
+    }
+
+    pub fn get_name(&self) -> String {
+        self.var_name.clone()
+    }
+
+    pub fn get_name_str(&self) -> &str {
+        &self.var_name
+    }
+
+    pub fn get_type(&self) -> TypeID {
+        self.var_type.clone()
+    }
+
+    pub fn get_expr(&self) -> &Expr {
+        &self.expr
+    }
+}
+
+impl ConstStmt {
+    pub fn to_string(&self) -> String {
+        format!(
+            "const {}: {} = {};",
+            self.var_name,
+            self.var_type.to_string(),
+            self.expr.to_string()
+        )
+    }
+
+    pub fn as_stmt(self) -> Stmt {
+        Stmt::ConstStatement(self)
+    }
+}
+
+impl ConstStmt {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_expr(&self.expr);
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_expr_mut(&mut self.expr);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::expr::arithmetic_expr::IntExpr;
+    use crate::program::types::IntTypeID;
+    #[test]
+    fn correct_string_repr() {
+        let const_stmt = ConstStmt::new(
+            String::from("CONST_1"),
+            IntTypeID::I16.as_type(),
+            IntExpr::new_i16(-3).as_expr(),
+        );
+
+        assert_eq!(const_stmt.to_string(), "const CONST_1: i16 = -3i16;");
+    }
+}
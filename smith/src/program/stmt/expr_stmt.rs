@@ -1,8 +1,11 @@
 use crate::program::expr::expr::Expr;
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use serde::{Deserialize, Serialize};
 
 use super::stmt::Stmt;
 
 // An expression where its value or return value isn't used
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ExprStmt {
     expr: Expr,
 }
@@ -12,6 +15,10 @@ impl ExprStmt {
         ExprStmt { expr }
     }
 
+    pub fn get_expr(&self) -> &Expr {
+        &self.expr
+    }
+
     pub fn as_stmt(self) -> Stmt {
         Stmt::ExprStatement(self)
     }
@@ -22,3 +29,13 @@ impl ToString for ExprStmt {
         format!("{};", self.expr.to_string())
     }
 }
+
+impl ExprStmt {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_expr(&self.expr);
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_expr_mut(&mut self.expr);
+    }
+}
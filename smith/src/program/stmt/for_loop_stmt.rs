@@ -1,11 +1,14 @@
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
 use crate::program::{
     expr::iter_expr::IterExpr,
     stmt::{block_stmt::BlockStmt, stmt::Stmt},
     types::TypeID,
     var::Var,
 };
+use serde::{Deserialize, Serialize};
 
 // TypeID is for the loop's iter expression
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ForLoopStmt {
     type_id: TypeID,
     iter_var: Var,
@@ -27,6 +30,22 @@ impl ForLoopStmt {
         self.type_id.clone()
     }
 
+    pub fn get_iter_var(&self) -> &Var {
+        &self.iter_var
+    }
+
+    pub fn get_iterable(&self) -> &IterExpr {
+        &self.iterable
+    }
+
+    pub fn get_block_stmt(&self) -> &BlockStmt {
+        &self.block_stmt
+    }
+
+    pub fn into_parts(self) -> (Var, IterExpr, BlockStmt) {
+        (self.iter_var, self.iterable, self.block_stmt)
+    }
+
     pub fn as_stmt(self) -> Stmt {
         Stmt::LoopStatement(self)
     }
@@ -47,6 +66,20 @@ impl ToString for ForLoopStmt {
     }
 }
 
+impl ForLoopStmt {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_binding(&self.iter_var);
+        visitor.visit_iter_expr(&self.iterable);
+        visitor.visit_block_stmt(&self.block_stmt);
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_binding_mut(&mut self.iter_var);
+        visitor.visit_iter_expr_mut(&mut self.iterable);
+        visitor.visit_block_stmt_mut(&mut self.block_stmt);
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::program::{
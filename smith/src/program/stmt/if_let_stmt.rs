@@ -0,0 +1,133 @@
+use super::{block_stmt::BlockStmt, stmt::Stmt};
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use crate::program::{expr::expr::Expr, pattern::Pattern};
+use serde::{Deserialize, Serialize};
+
+// The bindings of the pattern are only in scope in the body
+#[derive(Clone, Serialize, Deserialize)]
+pub struct IfLetStmt {
+    pattern: Pattern,
+    expr: Expr,
+    body: BlockStmt,
+    else_body: Option<BlockStmt>,
+}
+
+impl IfLetStmt {
+    pub fn new(
+        pattern: Pattern,
+        expr: Expr,
+        body: BlockStmt,
+        else_body: Option<BlockStmt>,
+    ) -> Self {
+        IfLetStmt {
+            pattern,
+            expr,
+            body,
+            else_body,
+        }
+    }
+
+    pub fn get_pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
+    pub fn get_expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    pub fn get_expr_mut(&mut self) -> &mut Expr {
+        &mut self.expr
+    }
+
+    pub fn get_body(&self) -> &BlockStmt {
+        &self.body
+    }
+
+    pub fn get_body_mut(&mut self) -> &mut BlockStmt {
+        &mut self.body
+    }
+
+    pub fn get_else_body(&self) -> Option<&BlockStmt> {
+        self.else_body.as_ref()
+    }
+
+    pub fn get_else_body_mut(&mut self) -> Option<&mut BlockStmt> {
+        self.else_body.as_mut()
+    }
+}
+
+impl IfLetStmt {
+    pub fn to_string(&self) -> String {
+        // Struct literals are not allowed unparenthesised before the body
+        let mut result = format!(
+            "if let {} = ({}) {}",
+            self.pattern.to_string(),
+            self.expr.to_string(),
+            self.body.to_string()
+        );
+
+        if let Some(else_body) = &self.else_body {
+            result.push_str(&format!(" else {}", else_body.to_string()));
+        }
+
+        result
+    }
+
+    pub fn as_stmt(self) -> Stmt {
+        Stmt::IfLetStatement(self)
+    }
+}
+
+impl IfLetStmt {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_pattern(&self.pattern);
+        visitor.visit_expr(&self.expr);
+        visitor.visit_block_stmt(&self.body);
+        if let Some(else_body) = &self.else_body {
+            visitor.visit_block_stmt(else_body);
+        }
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_pattern_mut(&mut self.pattern);
+        visitor.visit_expr_mut(&mut self.expr);
+        visitor.visit_block_stmt_mut(&mut self.body);
+        if let Some(else_body) = &mut self.else_body {
+            visitor.visit_block_stmt_mut(else_body);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::{
+        expr::{bool_expr::BoolValue, struct_expr::StructExpr, tuple_expr::TupleExpr},
+        types::TypeID,
+        var::Var,
+    };
+    #[test]
+    fn matches_tuple_of_struct_and_bool() {
+        let var = Var::new(
+            TypeID::StructType(String::from("Struct2")),
+            String::from("var_1"),
+            false,
+        );
+        let expr = TupleExpr::new(vec![
+            StructExpr::Var(var).as_expr(),
+            BoolValue::new(false).as_bool_expr().as_expr(),
+        ])
+        .as_expr();
+        let pattern = Pattern::Tuple(vec![
+            Pattern::new_struct(String::from("Struct2"), vec![], true),
+            Pattern::Bool(true),
+        ]);
+
+        let stmt = IfLetStmt::new(pattern, expr, BlockStmt::new(), Some(BlockStmt::new()));
+
+        assert_eq!(
+            stmt.to_string(),
+            "if let (Struct2 { .. }, true,) = ((var_1, false,)) {\n} else {\n}"
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use super::{block_stmt::BlockStmt, stmt::Stmt};
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use crate::program::{expr::expr::Expr, pattern::Pattern};
+use serde::{Deserialize, Serialize};
+
+// Destructures a value with a pattern, let else diverges when a refutable pattern does not match
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LetPatternStmt {
+    pattern: Pattern,
+    expr: Expr,
+    else_body: Option<BlockStmt>,
+}
+
+impl LetPatternStmt {
+    pub fn new(pattern: Pattern, expr: Expr) -> Self {
+        LetPatternStmt {
+            pattern,
+            expr,
+            else_body: None,
+        }
+    }
+
+    pub fn new_else(pattern: Pattern, expr: Expr, else_body: BlockStmt) -> Self {
+        LetPatternStmt {
+            pattern,
+            expr,
+            else_body: Some(else_body),
+        }
+    }
+
+    pub fn get_pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
+    pub fn get_expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    pub fn get_else_body(&self) -> Option<&BlockStmt> {
+        self.else_body.as_ref()
+    }
+}
+
+impl LetPatternStmt {
+    pub fn to_string(&self) -> String {
+        match &self.else_body {
+            // An expression ending in } is not allowed before else
+            Some(else_body) => format!(
+                "let {} = ({}) else {};",
+                self.pattern.to_string(),
+                self.expr.to_string(),
+                else_body.to_string()
+            ),
+            None => format!(
+                "let {} = {};",
+                self.pattern.to_string(),
+                self.expr.to_string()
+            ),
+        }
+    }
+
+    pub fn as_stmt(self) -> Stmt {
+        Stmt::LetPatternStatement(self)
+    }
+}
+
+impl LetPatternStmt {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_pattern(&self.pattern);
+        visitor.visit_expr(&self.expr);
+        if let Some(else_body) = &self.else_body {
+            visitor.visit_block_stmt(else_body);
+        }
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_pattern_mut(&mut self.pattern);
+        visitor.visit_expr_mut(&mut self.expr);
+        if let Some(else_body) = &mut self.else_body {
+            visitor.visit_block_stmt_mut(else_body);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::{
+        expr::{arithmetic_expr::IntExpr, expr::RawExpr},
+        stmt::expr_stmt::ExprStmt,
+        types::IntTypeID,
+        var::Var,
+    };
+    #[test]
+    fn let_else_diverges() {
+        let pattern = Pattern::new_at(
+            Var::new(IntTypeID::U16.as_type(), String::from("var_1"), false),
+            Pattern::Range(IntExpr::new_u16(1), IntExpr::new_u16(9)),
+        );
+        let else_body = BlockStmt::new_from_vec(vec![ExprStmt::new(
+            RawExpr::new(String::from("break")).as_expr(),
+        )
+        .as_stmt()]);
+
+        let stmt = LetPatternStmt::new_else(pattern, IntExpr::new_u16(4).as_expr(), else_body);
+
+        assert_eq!(
+            stmt.to_string(),
+            "let var_1 @ 1u16..=9u16 = (4u16) else {\nbreak;\n};"
+        );
+    }
+}
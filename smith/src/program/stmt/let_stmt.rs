@@ -1,6 +1,9 @@
 use super::stmt::Stmt;
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
 use crate::program::{expr::expr::Expr, var::Var};
+use serde::{Deserialize, Serialize};
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct LetStmt {
     var: Var,
     expr: Expr,
@@ -11,9 +14,29 @@ impl LetStmt {
         LetStmt { var, expr }
     }
 
+    pub fn get_name(&self) -> String {
+        self.var.get_name()
+    }
+
     pub fn is_mut(&self) -> bool {
         self.var.is_mut()
     }
+
+    pub fn get_var(&self) -> &Var {
+        &self.var
+    }
+
+    pub fn get_expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    pub fn get_expr_mut(&mut self) -> &mut Expr {
+        &mut self.expr
+    }
+
+    pub fn into_parts(self) -> (Var, Expr) {
+        (self.var, self.expr)
+    }
 }
 
 impl LetStmt {
@@ -39,6 +62,18 @@ impl LetStmt {
     }
 }
 
+impl LetStmt {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_binding(&self.var);
+        visitor.visit_expr(&self.expr);
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_binding_mut(&mut self.var);
+        visitor.visit_expr_mut(&mut self.expr);
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::program::{
@@ -1,10 +1,19 @@
 pub mod assign_stmt;
+pub mod await_stmt;
 pub mod block_stmt;
+pub mod checksum_stmt;
 pub mod conditional_stmt;
+pub mod const_stmt;
 pub mod expr_stmt;
 pub mod for_loop_stmt;
+pub mod if_let_stmt;
+pub mod let_pattern_stmt;
 pub mod let_stmt;
 pub mod op_assign_stmt;
 pub mod return_stmt;
 pub mod static_stmt;
 pub mod stmt;
+pub mod thread_scope_stmt;
+pub mod trace_stmt;
+pub mod unsafe_stmt;
+pub mod while_stmt;
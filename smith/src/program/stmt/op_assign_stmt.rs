@@ -1,11 +1,14 @@
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
 use crate::program::{
     expr::arithmetic_expr::{ArithmeticExpr, BinaryOp},
     types::TypeID,
     var::Var,
 };
+use serde::{Deserialize, Serialize};
 
 use super::stmt::Stmt;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct OpAssignStmt {
     left: Var,
     right: ArithmeticExpr,
@@ -17,10 +20,26 @@ impl OpAssignStmt {
         OpAssignStmt { left, right, op }
     }
 
+    pub fn get_name(&self) -> String {
+        self.left.get_name()
+    }
+
+    pub fn get_var(&self) -> &Var {
+        &self.left
+    }
+
     pub fn get_type(&self) -> TypeID {
         self.left.get_type()
     }
 
+    pub fn get_expr(&self) -> &ArithmeticExpr {
+        &self.right
+    }
+
+    pub fn get_op(&self) -> BinaryOp {
+        self.op
+    }
+
     pub fn as_stmt(self) -> Stmt {
         Stmt::OpAssignStatement(self)
     }
@@ -40,3 +59,15 @@ impl ToString for OpAssignStmt {
         self.to_string_safe()
     }
 }
+
+impl OpAssignStmt {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_var(&self.left);
+        visitor.visit_arithmetic_expr(&self.right);
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_var_mut(&mut self.left);
+        visitor.visit_arithmetic_expr_mut(&mut self.right);
+    }
+}
@@ -1,7 +1,10 @@
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
 use crate::program::{expr::expr::Expr, types::TypeID};
+use serde::{Deserialize, Serialize};
 
 use super::stmt::Stmt;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ReturnStmt {
     return_type: TypeID,
     expr: Expr,
@@ -21,6 +24,23 @@ impl ReturnStmt {
         self.return_type.clone()
     }
 
+    pub fn get_expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    pub fn get_expr_mut(&mut self) -> &mut Expr {
+        &mut self.expr
+    }
+
+    // Renders as the tail expression of its block instead
+    pub fn is_explicit(&self) -> bool {
+        self.explicit_return
+    }
+
+    pub fn set_implicit(&mut self) {
+        self.explicit_return = false;
+    }
+
     pub fn as_stmt(self) -> Stmt {
         Stmt::ReturnStatement(self)
     }
@@ -35,3 +55,13 @@ impl ToString for ReturnStmt {
         }
     }
 }
+
+impl ReturnStmt {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_expr(&self.expr);
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_expr_mut(&mut self.expr);
+    }
+}
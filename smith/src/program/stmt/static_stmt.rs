@@ -1,7 +1,10 @@
 use super::stmt::Stmt;
 use crate::program::expr::expr::Expr;
 use crate::program::types::TypeID;
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use serde::{Deserialize, Serialize};
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct StaticStmt {
     var_name: String,
     var_type: TypeID,
@@ -16,6 +19,22 @@ impl StaticStmt {
             expr,
         }
     }
+
+    pub fn get_name(&self) -> String {
+        self.var_name.clone()
+    }
+
+    pub fn get_name_str(&self) -> &str {
+        &self.var_name
+    }
+
+    pub fn get_type(&self) -> TypeID {
+        self.var_type.clone()
+    }
+
+    pub fn get_expr(&self) -> &Expr {
+        &self.expr
+    }
 }
 
 impl StaticStmt {
@@ -32,3 +51,13 @@ impl StaticStmt {
         Stmt::StaticStatement(self)
     }
 }
+
+impl StaticStmt {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_expr(&self.expr);
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_expr_mut(&mut self.expr);
+    }
+}
@@ -1,17 +1,45 @@
 use super::{
-    assign_stmt::AssignStmt, conditional_stmt::ConditionalStmt, expr_stmt::ExprStmt,
-    for_loop_stmt::ForLoopStmt, let_stmt::LetStmt, op_assign_stmt::OpAssignStmt,
-    return_stmt::ReturnStmt, static_stmt::StaticStmt,
+    assign_stmt::AssignStmt, await_stmt::AwaitStmt, checksum_stmt::ChecksumStmt,
+    conditional_stmt::ConditionalStmt, const_stmt::ConstStmt, expr_stmt::ExprStmt,
+    for_loop_stmt::ForLoopStmt, if_let_stmt::IfLetStmt, let_pattern_stmt::LetPatternStmt,
+    let_stmt::LetStmt, op_assign_stmt::OpAssignStmt, return_stmt::ReturnStmt,
+    static_stmt::StaticStmt, thread_scope_stmt::ThreadScopeStmt, trace_stmt::TraceStmt,
+    unsafe_stmt::UnsafeStmt, while_stmt::WhileStmt,
 };
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
 pub enum Stmt {
     LetStatement(LetStmt),
     StaticStatement(StaticStmt),
+    ConstStatement(ConstStmt),
     ConditionalStatement(ConditionalStmt),
     AssignStatement(AssignStmt),
     ReturnStatement(ReturnStmt),
     ExprStatement(ExprStmt),
     LoopStatement(ForLoopStmt),
     OpAssignStatement(OpAssignStmt),
+    ThreadScopeStatement(ThreadScopeStmt),
+    AwaitStatement(AwaitStmt),
+    UnsafeStatement(UnsafeStmt),
+    LetPatternStatement(LetPatternStmt),
+    IfLetStatement(IfLetStmt),
+    ChecksumStatement(ChecksumStmt),
+    TraceStatement(TraceStmt),
+    WhileStatement(WhileStmt),
+}
+
+impl Stmt {
+    // Name of the variable or field the statement writes a new value to, if any
+    pub fn assigned_name(&self) -> Option<String> {
+        match self {
+            Self::LetStatement(s) => Some(s.get_name()),
+            Self::AssignStatement(s) => Some(s.get_name()),
+            Self::OpAssignStatement(s) => Some(s.get_name()),
+            _ => None,
+        }
+    }
 }
 
 impl ToString for Stmt {
@@ -19,12 +47,67 @@ impl ToString for Stmt {
         match self {
             Self::LetStatement(s) => s.to_string(),
             Self::StaticStatement(s) => s.to_string(),
+            Self::ConstStatement(s) => s.to_string(),
             Self::ConditionalStatement(s) => s.to_string(),
             Self::AssignStatement(s) => s.to_string(),
             Self::ReturnStatement(s) => s.to_string(),
             Self::ExprStatement(s) => s.to_string(),
             Self::LoopStatement(s) => s.to_string(),
             Self::OpAssignStatement(s) => s.to_string(),
+            Self::ThreadScopeStatement(s) => s.to_string(),
+            Self::AwaitStatement(s) => s.to_string(),
+            Self::UnsafeStatement(s) => s.to_string(),
+            Self::LetPatternStatement(s) => s.to_string(),
+            Self::IfLetStatement(s) => s.to_string(),
+            Self::ChecksumStatement(s) => s.to_string(),
+            Self::TraceStatement(s) => s.to_string(),
+            Self::WhileStatement(s) => s.to_string(),
+        }
+    }
+}
+
+impl Stmt {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        match self {
+            Self::LetStatement(s) => visitor.visit_let_stmt(s),
+            Self::StaticStatement(s) => visitor.visit_static_stmt(s),
+            Self::ConstStatement(s) => visitor.visit_const_stmt(s),
+            Self::ConditionalStatement(s) => visitor.visit_conditional_stmt(s),
+            Self::AssignStatement(s) => visitor.visit_assign_stmt(s),
+            Self::ReturnStatement(s) => visitor.visit_return_stmt(s),
+            Self::ExprStatement(s) => visitor.visit_expr_stmt(s),
+            Self::LoopStatement(s) => visitor.visit_for_loop_stmt(s),
+            Self::OpAssignStatement(s) => visitor.visit_op_assign_stmt(s),
+            Self::ThreadScopeStatement(s) => visitor.visit_thread_scope_stmt(s),
+            Self::AwaitStatement(s) => visitor.visit_await_stmt(s),
+            Self::UnsafeStatement(s) => visitor.visit_unsafe_stmt(s),
+            Self::LetPatternStatement(s) => visitor.visit_let_pattern_stmt(s),
+            Self::IfLetStatement(s) => visitor.visit_if_let_stmt(s),
+            Self::ChecksumStatement(s) => visitor.visit_checksum_stmt(s),
+            Self::TraceStatement(s) => visitor.visit_trace_stmt(s),
+            Self::WhileStatement(s) => visitor.visit_while_stmt(s),
+        }
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        match self {
+            Self::LetStatement(s) => visitor.visit_let_stmt_mut(s),
+            Self::StaticStatement(s) => visitor.visit_static_stmt_mut(s),
+            Self::ConstStatement(s) => visitor.visit_const_stmt_mut(s),
+            Self::ConditionalStatement(s) => visitor.visit_conditional_stmt_mut(s),
+            Self::AssignStatement(s) => visitor.visit_assign_stmt_mut(s),
+            Self::ReturnStatement(s) => visitor.visit_return_stmt_mut(s),
+            Self::ExprStatement(s) => visitor.visit_expr_stmt_mut(s),
+            Self::LoopStatement(s) => visitor.visit_for_loop_stmt_mut(s),
+            Self::OpAssignStatement(s) => visitor.visit_op_assign_stmt_mut(s),
+            Self::ThreadScopeStatement(s) => visitor.visit_thread_scope_stmt_mut(s),
+            Self::AwaitStatement(s) => visitor.visit_await_stmt_mut(s),
+            Self::UnsafeStatement(s) => visitor.visit_unsafe_stmt_mut(s),
+            Self::LetPatternStatement(s) => visitor.visit_let_pattern_stmt_mut(s),
+            Self::IfLetStatement(s) => visitor.visit_if_let_stmt_mut(s),
+            Self::ChecksumStatement(s) => visitor.visit_checksum_stmt_mut(s),
+            Self::TraceStatement(s) => visitor.visit_trace_stmt_mut(s),
+            Self::WhileStatement(s) => visitor.visit_while_stmt_mut(s),
         }
     }
 }
@@ -0,0 +1,253 @@
+use super::stmt::Stmt;
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use crate::program::{expr::func_call_expr::FunctionCallExpr, var::Var};
+use serde::{Deserialize, Serialize};
+
+// Names used inside every thread scope
+pub const SCOPE_NAME: &str = "scope";
+pub const COUNTER_NAME: &str = "thread_counter";
+pub const COUNTER_REF_NAME: &str = "counter";
+
+// One scoped thread running a single function call
+// The result is bound to a variable, or discarded if there is none
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ThreadSpawn {
+    call: FunctionCallExpr,
+    result: Option<Var>,
+    increment: u64,
+}
+
+impl ThreadSpawn {
+    pub fn new(call: FunctionCallExpr, result: Option<Var>, increment: u64) -> Self {
+        ThreadSpawn {
+            call,
+            result,
+            increment,
+        }
+    }
+
+    pub fn get_call(&self) -> &FunctionCallExpr {
+        &self.call
+    }
+
+    pub fn get_result(&self) -> Option<&Var> {
+        self.result.as_ref()
+    }
+
+    pub fn get_increment(&self) -> u64 {
+        self.increment
+    }
+}
+
+// Runs function calls on scoped threads and joins them in spawn order
+// Arguments are evaluated up front in the spawning thread, so each thread only sees the
+// values and borrows handed to it and no two threads share mutable data
+// Threads may also add to a shared atomic counter, which only depends on the sum of the increments
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ThreadScopeStmt {
+    spawns: Vec<ThreadSpawn>,
+    counter: Option<Var>,
+}
+
+impl ThreadScopeStmt {
+    pub fn new(spawns: Vec<ThreadSpawn>, counter: Option<Var>) -> Self {
+        ThreadScopeStmt { spawns, counter }
+    }
+
+    pub fn spawns_iter(&self) -> std::slice::Iter<'_, ThreadSpawn> {
+        self.spawns.iter()
+    }
+
+    pub fn get_counter(&self) -> Option<&Var> {
+        self.counter.as_ref()
+    }
+
+    fn args_name(index: usize) -> String {
+        format!("thread_args_{}", index)
+    }
+
+    // Empty argument lists need no tuple
+    fn args_to_string(index: usize, spawn: &ThreadSpawn) -> String {
+        let args: Vec<String> = spawn
+            .call
+            .arguments_iter()
+            .map(|arg| arg.to_string())
+            .collect();
+
+        if args.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "let {} = ({},);\n",
+                ThreadScopeStmt::args_name(index),
+                args.join(", ")
+            )
+        }
+    }
+
+    fn spawn_to_string(&self, index: usize, spawn: &ThreadSpawn) -> String {
+        let arg_names: Vec<String> = (0..spawn.call.arguments_iter().len())
+            .map(|i| format!("{}.{}", ThreadScopeStmt::args_name(index), i))
+            .collect();
+
+        let call = spawn.call.complete_call(format!(
+            "{}({})",
+            spawn.call.get_name(),
+            arg_names.join(", ")
+        ));
+
+        let body = if self.counter.is_some() {
+            format!(
+                "{{ {}.fetch_add({}u64, std::sync::atomic::Ordering::SeqCst); {} }}",
+                COUNTER_REF_NAME, spawn.increment, call
+            )
+        } else {
+            call
+        };
+
+        format!(
+            "let handle_{} = {}.spawn(move || {});\n",
+            index, SCOPE_NAME, body
+        )
+    }
+}
+
+impl ThreadScopeStmt {
+    pub fn to_string(&self) -> String {
+        let mut result = String::new();
+
+        if self.counter.is_some() {
+            result.push_str(&format!(
+                "let {} = std::sync::atomic::AtomicU64::new(0);\n",
+                COUNTER_NAME
+            ));
+        }
+
+        // Declared outside of the scope so that borrowed temporaries outlive every thread
+        for (i, spawn) in self.spawns.iter().enumerate() {
+            result.push_str(&ThreadScopeStmt::args_to_string(i + 1, spawn));
+        }
+
+        let pattern: Vec<String> = self
+            .spawns
+            .iter()
+            .map(|spawn| match &spawn.result {
+                Some(var) if var.is_mut() => format!("mut {}", var.get_name()),
+                Some(var) => var.get_name(),
+                None => String::from("_"),
+            })
+            .collect();
+
+        result.push_str(&format!(
+            "let ({},) = std::thread::scope(|{}| {{\n",
+            pattern.join(", "),
+            SCOPE_NAME
+        ));
+
+        if self.counter.is_some() {
+            result.push_str(&format!("let {} = &{};\n", COUNTER_REF_NAME, COUNTER_NAME));
+        }
+
+        for (i, spawn) in self.spawns.iter().enumerate() {
+            result.push_str(&self.spawn_to_string(i + 1, spawn));
+        }
+
+        let joins: Vec<String> = (1..=self.spawns.len())
+            .map(|i| format!("handle_{}.join().unwrap()", i))
+            .collect();
+        result.push_str(&format!("({},)\n}});", joins.join(", ")));
+
+        if let Some(counter) = &self.counter {
+            result.push_str(&format!(
+                "\nlet {}: u64 = {}.into_inner();",
+                counter.get_name(),
+                COUNTER_NAME
+            ));
+        }
+
+        result
+    }
+
+    pub fn as_stmt(self) -> Stmt {
+        Stmt::ThreadScopeStatement(self)
+    }
+}
+
+impl ThreadScopeStmt {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        for spawn in self.spawns.iter() {
+            visitor.visit_func_call_expr(&spawn.call);
+            if let Some(result) = &spawn.result {
+                visitor.visit_binding(result);
+            }
+        }
+        if let Some(counter) = &self.counter {
+            visitor.visit_binding(counter);
+        }
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        for spawn in self.spawns.iter_mut() {
+            visitor.visit_func_call_expr_mut(&mut spawn.call);
+            if let Some(result) = &mut spawn.result {
+                visitor.visit_binding_mut(result);
+            }
+        }
+        if let Some(counter) = &mut self.counter {
+            visitor.visit_binding_mut(counter);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::{
+        expr::arithmetic_expr::IntExpr,
+        function::{FunctionTemplate, Param},
+        types::{IntTypeID, TypeID},
+    };
+    #[test]
+    fn joins_in_spawn_order() {
+        let param = Param::new(String::from("param_a"), IntTypeID::U8.as_type());
+        let template_a =
+            FunctionTemplate::new(String::from("function_1"), vec![param], TypeID::NullType);
+        let template_b =
+            FunctionTemplate::new(String::from("function_2"), vec![], IntTypeID::I32.as_type());
+
+        let spawns = vec![
+            ThreadSpawn::new(
+                FunctionCallExpr::new(template_a, vec![IntExpr::new_u8(3).as_expr()]),
+                None,
+                5,
+            ),
+            ThreadSpawn::new(
+                FunctionCallExpr::new(template_b, vec![]),
+                Some(Var::new(
+                    IntTypeID::I32.as_type(),
+                    String::from("var_1"),
+                    false,
+                )),
+                7,
+            ),
+        ];
+        let counter = Var::new(IntTypeID::U64.as_type(), String::from("var_2"), false);
+
+        let string_rep = ThreadScopeStmt::new(spawns, Some(counter)).to_string();
+
+        assert!(string_rep.starts_with(
+            "let thread_counter = std::sync::atomic::AtomicU64::new(0);\n\
+            let thread_args_1 = (3u8,);\n\
+            let (_, var_1,) = std::thread::scope(|scope| {\nlet counter = &thread_counter;\n"
+        ));
+        assert!(string_rep.contains(
+            "let handle_1 = scope.spawn(move || \
+            { counter.fetch_add(5u64, std::sync::atomic::Ordering::SeqCst); function_1(thread_args_1.0) });"
+        ));
+        assert!(string_rep.contains("let handle_2 = scope.spawn(move || { counter.fetch_add(7u64, std::sync::atomic::Ordering::SeqCst); function_2() });"));
+        assert!(string_rep.ends_with(
+            "(handle_1.join().unwrap(), handle_2.join().unwrap(),)\n});\n\
+            let var_2: u64 = thread_counter.into_inner();"
+        ));
+    }
+}
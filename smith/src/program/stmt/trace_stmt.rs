@@ -0,0 +1,65 @@
+use super::stmt::Stmt;
+use serde::{Deserialize, Serialize};
+
+// Prints the values of the places the previous statement assigned, under the id of that statement
+// Places are only ever integers or bools
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TraceStmt {
+    stmt_id: u32,
+    places: Vec<String>,
+}
+
+impl TraceStmt {
+    pub fn new(stmt_id: u32, places: Vec<String>) -> Self {
+        TraceStmt { stmt_id, places }
+    }
+
+    pub fn get_stmt_id(&self) -> u32 {
+        self.stmt_id
+    }
+
+    pub fn places_iter(&self) -> std::slice::Iter<'_, String> {
+        self.places.iter()
+    }
+
+    pub fn places_iter_mut(&mut self) -> std::slice::IterMut<'_, String> {
+        self.places.iter_mut()
+    }
+
+    pub fn as_stmt(self) -> Stmt {
+        Stmt::TraceStatement(self)
+    }
+}
+
+impl ToString for TraceStmt {
+    fn to_string(&self) -> String {
+        let values: Vec<String> = self
+            .places
+            .iter()
+            .map(|place| format!("(\"{}\", {}.to_checksum())", place, place))
+            .collect();
+
+        format!("trace::record({}, &[{}]);", self.stmt_id, values.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn records_every_place() {
+        let stmt = TraceStmt::new(
+            7,
+            vec![String::from("var_1"), String::from("var_2.field_1")],
+        );
+
+        assert_eq!(
+            stmt.to_string(),
+            "trace::record(7, &[(\"var_1\", var_1.to_checksum()), (\"var_2.field_1\", var_2.field_1.to_checksum())]);"
+        );
+        assert_eq!(
+            TraceStmt::new(8, Vec::new()).to_string(),
+            "trace::record(8, &[]);"
+        );
+    }
+}
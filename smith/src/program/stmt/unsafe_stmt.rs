@@ -0,0 +1,320 @@
+use super::stmt::Stmt;
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use crate::program::{
+    expr::{arithmetic_expr::BinaryOp, array_expr::ArrayExpr, expr::Expr},
+    var::Var,
+};
+use serde::{Deserialize, Serialize};
+
+// Names used inside every unsafe block
+pub const PTR_NAME: &str = "ptr";
+pub const VALUE_NAME: &str = "value";
+pub const ARRAY_NAME: &str = "array";
+pub const UNINIT_NAME: &str = "uninit";
+
+// Raw pointers never outlive the unsafe block they are created in
+// and the place they point to is not accessed any other way inside it
+// Places and struct fields may be in packed structs, so they are read and written unaligned
+#[derive(Clone, Serialize, Deserialize)]
+pub enum UnsafeStmt {
+    // Updates a place through a *mut taken from it, the value is computed before the pointer exists
+    PtrUpdate {
+        place: Var,
+        op: BinaryOp,
+        value: Expr,
+    },
+    // Copies a place through a *const taken from it
+    PtrRead {
+        result: Var,
+        place: Var,
+    },
+    // Combines array elements in place with pointer arithmetic, then reads one element back
+    // Each write is (dest, lhs, op, rhs) with every index in bounds
+    ArrayPtr {
+        result: Var,
+        elements: ArrayExpr,
+        writes: Vec<(u32, u32, BinaryOp, u32)>,
+        read: u32,
+    },
+    // Initialises a value through MaybeUninit, a struct is written one field at a time
+    MaybeUninit {
+        result: Var,
+        fields: Vec<(String, Expr)>,
+        value: Option<Expr>,
+    },
+}
+
+impl UnsafeStmt {
+    pub fn new_ptr_update(place: Var, op: BinaryOp, value: Expr) -> Self {
+        UnsafeStmt::PtrUpdate { place, op, value }
+    }
+
+    pub fn new_ptr_read(result: Var, place: Var) -> Self {
+        UnsafeStmt::PtrRead { result, place }
+    }
+
+    pub fn new_array_ptr(
+        result: Var,
+        elements: ArrayExpr,
+        writes: Vec<(u32, u32, BinaryOp, u32)>,
+        read: u32,
+    ) -> Self {
+        UnsafeStmt::ArrayPtr {
+            result,
+            elements,
+            writes,
+            read,
+        }
+    }
+
+    pub fn new_maybe_uninit(result: Var, value: Expr) -> Self {
+        UnsafeStmt::MaybeUninit {
+            result,
+            fields: Vec::new(),
+            value: Some(value),
+        }
+    }
+
+    pub fn new_maybe_uninit_struct(result: Var, fields: Vec<(String, Expr)>) -> Self {
+        UnsafeStmt::MaybeUninit {
+            result,
+            fields,
+            value: None,
+        }
+    }
+
+    fn let_result(result: &Var, body: String) -> String {
+        let mut_qualifier = if result.is_mut() { "mut " } else { "" };
+
+        format!(
+            "let {}{}: {} = unsafe {{\n{}\n}};",
+            mut_qualifier,
+            result.get_name(),
+            result.get_type().to_string(),
+            body
+        )
+    }
+}
+
+impl UnsafeStmt {
+    pub fn to_string(&self) -> String {
+        match self {
+            UnsafeStmt::PtrUpdate { place, op, value } => format!(
+                "{{\nlet {value}: {t} = {};\nunsafe {{\nlet {ptr}: *mut {t} = std::ptr::addr_of_mut!({});\n\
+                {ptr}.write_unaligned({ptr}.read_unaligned().{}({value}));\n}}\n}}",
+                value.to_string(),
+                place.get_name(),
+                op.to_string_safe(),
+                t = place.get_type().to_string(),
+                ptr = PTR_NAME,
+                value = VALUE_NAME,
+            ),
+            UnsafeStmt::PtrRead { result, place } => UnsafeStmt::let_result(
+                result,
+                format!("std::ptr::addr_of!({}).read_unaligned()", place.get_name()),
+            ),
+            UnsafeStmt::ArrayPtr {
+                result,
+                elements,
+                writes,
+                read,
+            } => {
+                let writes: String = writes
+                    .iter()
+                    .map(|(dest, lhs, op, rhs)| {
+                        format!(
+                            "{ptr}.add({}).write({ptr}.add({}).read().{}({ptr}.add({}).read()));\n",
+                            dest,
+                            lhs,
+                            op.to_string_safe(),
+                            rhs,
+                            ptr = PTR_NAME
+                        )
+                    })
+                    .collect();
+
+                UnsafeStmt::let_result(
+                    result,
+                    format!(
+                        "let mut {array}: [{t}; {}] = {};\nlet {ptr}: *mut {t} = {array}.as_mut_ptr();\n{}{ptr}.add({}).read()",
+                        elements.len(),
+                        elements.to_string(),
+                        writes,
+                        read,
+                        t = elements.get_type().to_string(),
+                        array = ARRAY_NAME,
+                        ptr = PTR_NAME,
+                    ),
+                )
+            }
+            UnsafeStmt::MaybeUninit {
+                result,
+                fields,
+                value,
+            } => {
+                let init = match value {
+                    Some(value) => format!(
+                        "{}.as_mut_ptr().write({});\n",
+                        UNINIT_NAME,
+                        value.to_string()
+                    ),
+                    None => {
+                        let field_writes: String = fields
+                            .iter()
+                            .map(|(field_name, expr)| {
+                                format!(
+                                    "std::ptr::addr_of_mut!((*{}).{}).write_unaligned({});\n",
+                                    PTR_NAME,
+                                    field_name,
+                                    expr.to_string()
+                                )
+                            })
+                            .collect();
+                        format!(
+                            "let {}: *mut {} = {}.as_mut_ptr();\n{}",
+                            PTR_NAME,
+                            result.get_type().to_string(),
+                            UNINIT_NAME,
+                            field_writes
+                        )
+                    }
+                };
+
+                UnsafeStmt::let_result(
+                    result,
+                    format!(
+                        "let mut {uninit} = std::mem::MaybeUninit::<{}>::uninit();\n{}{uninit}.assume_init()",
+                        result.get_type().to_string(),
+                        init,
+                        uninit = UNINIT_NAME,
+                    ),
+                )
+            }
+        }
+    }
+
+    pub fn as_stmt(self) -> Stmt {
+        Stmt::UnsafeStatement(self)
+    }
+}
+
+impl UnsafeStmt {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        match self {
+            Self::PtrUpdate { place, value, .. } => {
+                visitor.visit_var(place);
+                visitor.visit_expr(value);
+            }
+            Self::PtrRead { result, place } => {
+                visitor.visit_binding(result);
+                visitor.visit_var(place);
+            }
+            Self::ArrayPtr {
+                result, elements, ..
+            } => {
+                visitor.visit_binding(result);
+                visitor.visit_array_expr(elements);
+            }
+            Self::MaybeUninit {
+                result,
+                fields,
+                value,
+            } => {
+                visitor.visit_binding(result);
+                for (_, expr) in fields.iter() {
+                    visitor.visit_expr(expr);
+                }
+                if let Some(value) = value {
+                    visitor.visit_expr(value);
+                }
+            }
+        }
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        match self {
+            Self::PtrUpdate { place, value, .. } => {
+                visitor.visit_var_mut(place);
+                visitor.visit_expr_mut(value);
+            }
+            Self::PtrRead { result, place } => {
+                visitor.visit_binding_mut(result);
+                visitor.visit_var_mut(place);
+            }
+            Self::ArrayPtr {
+                result, elements, ..
+            } => {
+                visitor.visit_binding_mut(result);
+                visitor.visit_array_expr_mut(elements);
+            }
+            Self::MaybeUninit {
+                result,
+                fields,
+                value,
+            } => {
+                visitor.visit_binding_mut(result);
+                for (_, expr) in fields.iter_mut() {
+                    visitor.visit_expr_mut(expr);
+                }
+                if let Some(value) = value {
+                    visitor.visit_expr_mut(value);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::{
+        expr::arithmetic_expr::IntExpr,
+        types::{IntTypeID, TypeID},
+    };
+    #[test]
+    fn ptr_update_computes_value_first() {
+        let place = Var::new(IntTypeID::U8.as_type(), String::from("var_1.field_2"), true);
+        let stmt = UnsafeStmt::new_ptr_update(place, BinaryOp::ADD, IntExpr::new_u8(3).as_expr());
+
+        assert_eq!(
+            stmt.to_string(),
+            "{\nlet value: u8 = 3u8;\nunsafe {\nlet ptr: *mut u8 = std::ptr::addr_of_mut!(var_1.field_2);\n\
+            ptr.write_unaligned(ptr.read_unaligned().safe_add(value));\n}\n}"
+        );
+    }
+
+    #[test]
+    fn array_ptr_stays_in_bounds() {
+        let result = Var::new(IntTypeID::I16.as_type(), String::from("var_2"), false);
+        let elements = ArrayExpr::new(
+            IntTypeID::I16.as_type(),
+            2,
+            vec![IntExpr::new_i16(1).as_expr(), IntExpr::new_i16(2).as_expr()],
+        );
+        let stmt = UnsafeStmt::new_array_ptr(result, elements, vec![(1, 0, BinaryOp::MUL, 1)], 1);
+
+        assert_eq!(
+            stmt.to_string(),
+            "let var_2: i16 = unsafe {\nlet mut array: [i16; 2] = [1i16, 2i16];\n\
+            let ptr: *mut i16 = array.as_mut_ptr();\n\
+            ptr.add(1).write(ptr.add(0).read().safe_mul(ptr.add(1).read()));\nptr.add(1).read()\n};"
+        );
+    }
+
+    #[test]
+    fn maybe_uninit_struct_writes_every_field() {
+        let result = Var::new(
+            TypeID::StructType(String::from("Struct1")),
+            String::from("var_3"),
+            true,
+        );
+        let fields = vec![
+            (String::from("field_1"), IntExpr::new_u32(5).as_expr()),
+            (String::from("field_2"), IntExpr::new_i8(-1).as_expr()),
+        ];
+        let string_rep = UnsafeStmt::new_maybe_uninit_struct(result, fields).to_string();
+
+        assert!(string_rep.starts_with("let mut var_3: Struct1 = unsafe {\nlet mut uninit = std::mem::MaybeUninit::<Struct1>::uninit();\n"));
+        assert!(string_rep.contains("std::ptr::addr_of_mut!((*ptr).field_1).write_unaligned(5u32);\nstd::ptr::addr_of_mut!((*ptr).field_2).write_unaligned(-1i8);\nuninit.assume_init()"));
+    }
+}
@@ -0,0 +1,83 @@
+use crate::program::expr::bool_expr::BoolExpr;
+use crate::program::visitor::{visit::Visit, visit_mut::VisitMut};
+use serde::{Deserialize, Serialize};
+
+use super::{block_stmt::BlockStmt, stmt::Stmt};
+
+// Runs its block for as long as the condition holds, the generator itself only writes for loops
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WhileStmt {
+    condition: BoolExpr,
+    block_stmt: BlockStmt,
+}
+
+impl WhileStmt {
+    pub fn new(condition: BoolExpr, block_stmt: BlockStmt) -> Self {
+        WhileStmt {
+            condition,
+            block_stmt,
+        }
+    }
+
+    pub fn get_condition(&self) -> &BoolExpr {
+        &self.condition
+    }
+
+    pub fn get_block_stmt(&self) -> &BlockStmt {
+        &self.block_stmt
+    }
+
+    pub fn as_stmt(self) -> Stmt {
+        Stmt::WhileStatement(self)
+    }
+}
+
+impl ToString for WhileStmt {
+    fn to_string(&self) -> String {
+        format!(
+            "while ({}) {}",
+            self.condition.to_string(),
+            self.block_stmt.to_string()
+        )
+    }
+}
+
+impl WhileStmt {
+    pub fn walk<V: Visit + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_bool_expr(&self.condition);
+        visitor.visit_block_stmt(&self.block_stmt);
+    }
+
+    pub fn walk_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_bool_expr_mut(&mut self.condition);
+        visitor.visit_block_stmt_mut(&mut self.block_stmt);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::{
+        expr::{
+            arithmetic_expr::IntExpr,
+            bool_expr::{ComparisonExpr, ComparisonOp},
+        },
+        types::IntTypeID,
+        var::Var,
+    };
+
+    #[test]
+    fn while_correct_string_representation() {
+        let var = Var::new(IntTypeID::U8.as_type(), String::from("var_1"), true);
+        let condition = ComparisonExpr::new(
+            var.into(),
+            IntExpr::new_u8(4).as_arith_expr(),
+            ComparisonOp::Less,
+        )
+        .as_bool_expr();
+
+        let while_stmt = WhileStmt::new(condition, BlockStmt::new());
+
+        assert_eq!(while_stmt.to_string(), "while ((var_1 < 4u8)) {\n}");
+    }
+}
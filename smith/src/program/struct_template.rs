@@ -1,13 +1,16 @@
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-use super::types::TypeID;
+use super::{attribute::StructAttribute, types::TypeID};
 
-// Representation of a struct's name, fields (and their types) and any derive macro attributes
-#[derive(Clone)]
+// Representation of a struct's name, fields (and their types), any derive macro attributes
+// and any repr attributes
+#[derive(Clone, Serialize, Deserialize)]
 pub struct StructTemplate {
     fields: Vec<(String, TypeID)>,
     name: String,
     derive: Vec<String>,
+    attributes: Vec<StructAttribute>,
 }
 
 impl StructTemplate {
@@ -16,6 +19,7 @@ impl StructTemplate {
             fields: Vec::new(),
             name,
             derive: Vec::new(),
+            attributes: Vec::new(),
         }
     }
 
@@ -24,6 +28,7 @@ impl StructTemplate {
             fields,
             name,
             derive: Vec::new(),
+            attributes: Vec::new(),
         }
     }
 
@@ -43,18 +48,70 @@ impl StructTemplate {
         self.fields.push((name, type_id));
     }
 
+    // Position of the removed field, if the struct had it
+    pub fn remove_field(&mut self, name: &str) -> Option<usize> {
+        let index = self
+            .fields
+            .iter()
+            .position(|(field_name, _)| field_name == name)?;
+        self.fields.remove(index);
+
+        Some(index)
+    }
+
+    // Moves the fields from the given position on into a new struct with the same derives
+    pub fn split_off(&mut self, at: usize, name: String) -> StructTemplate {
+        StructTemplate {
+            fields: self.fields.split_off(at),
+            name,
+            derive: self.derive.clone(),
+            attributes: Vec::new(),
+        }
+    }
+
     pub fn insert_derive_attribute(&mut self, name: String) {
         self.derive.push(name);
     }
 
+    pub fn insert_attribute(&mut self, attribute: StructAttribute) {
+        self.attributes.push(attribute);
+    }
+
+    pub fn is_packed(&self) -> bool {
+        self.attributes.contains(&StructAttribute::ReprPacked)
+    }
+
+    pub fn has_align(&self) -> bool {
+        self.attributes
+            .iter()
+            .any(|attribute| matches!(attribute, StructAttribute::ReprAlign(_)))
+    }
+
     pub fn fields_iter(&self) -> std::slice::Iter<(String, TypeID)> {
         self.fields.iter()
     }
 
     pub fn to_string(&self) -> String {
+        self.render("")
+    }
+
+    // Both the struct and all of its fields are visible outside of the module
+    pub fn to_string_pub(&self) -> String {
+        self.render("pub ")
+    }
+
+    fn render(&self, visibility: &str) -> String {
         let mut field_list = String::new();
         for (field_name, field_type) in self.fields.iter() {
-            field_list.push_str(format!("{}: {},\n", field_name, field_type.to_string()).as_str());
+            field_list.push_str(
+                format!(
+                    "{}{}: {},\n",
+                    visibility,
+                    field_name,
+                    field_type.to_string()
+                )
+                .as_str(),
+            );
         }
         let derive_string = if !self.derive.is_empty() {
             format!("#[derive({})]\n", self.derive.join(", "))
@@ -62,9 +119,15 @@ impl StructTemplate {
             String::new()
         };
 
-        let struct_string = format!("struct {} {{\n{}}}", self.name, field_list);
+        let attribute_string: String = self
+            .attributes
+            .iter()
+            .map(|attribute| format!("{}\n", attribute.to_string()))
+            .collect();
+
+        let struct_string = format!("{}struct {} {{\n{}}}", visibility, self.name, field_list);
 
-        format!("{}{}", derive_string, struct_string)
+        format!("{}{}{}", derive_string, attribute_string, struct_string)
     }
 }
 
@@ -73,6 +136,7 @@ impl fmt::Debug for StructTemplate {
         f.debug_struct("StructTemplate")
             .field("name", &self.name)
             .field("fields", &self.fields)
+            .field("attributes", &self.attributes)
             .finish()
     }
 }
@@ -1,6 +1,7 @@
+use serde::{Deserialize, Serialize};
 use strum_macros::{EnumCount, EnumDiscriminants, EnumIter};
 
-#[derive(Debug, Copy, Clone, PartialEq, EnumIter)]
+#[derive(Debug, Copy, Clone, PartialEq, EnumIter, Serialize, Deserialize)]
 pub enum BorrowTypeID {
     None,
     Ref,
@@ -27,14 +28,14 @@ impl ToString for BorrowTypeID {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum BorrowStatus {
     Borrowed,
     MutBorrowed,
     None,
 }
 
-#[derive(PartialEq, Clone, Hash, Eq, EnumDiscriminants, Debug)]
+#[derive(PartialEq, Clone, Hash, Eq, EnumDiscriminants, Debug, Serialize, Deserialize)]
 #[strum_discriminants(vis(pub))]
 #[strum_discriminants(name(TypeIDVariants))]
 #[strum_discriminants(derive(EnumCount, EnumIter))]
@@ -46,6 +47,10 @@ pub enum TypeID {
 }
 
 impl TypeID {
+    pub fn is_primitive(&self) -> bool {
+        matches!(self, Self::IntType(_) | Self::BoolType)
+    }
+
     pub fn to_string(&self) -> String {
         match self {
             Self::IntType(int_type_id) => int_type_id.to_string(),
@@ -56,7 +61,7 @@ impl TypeID {
     }
 }
 
-#[derive(PartialEq, Clone, Hash, Eq, Copy, Debug)]
+#[derive(PartialEq, Clone, Hash, Eq, Copy, Debug, Serialize, Deserialize)]
 pub enum IntTypeID {
     I8,
     I16,
@@ -3,11 +3,13 @@ use super::{
     function::Param,
     types::{BorrowTypeID, TypeID},
 };
+use serde::{Deserialize, Serialize};
+
 // Variable representation - just it's name
 // Acts as both LHS and RHS in assignment/let statement
 // Can be part of an expression or an expression itself
 // Used for variable scope entry
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Var {
     type_id: TypeID,
     borrow_type: BorrowTypeID,
@@ -85,6 +87,16 @@ impl Var {
     pub fn get_name(&self) -> String {
         self.name.clone()
     }
+
+    // A place such as var_1.field_2 is renamed when the fields of its struct move
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    // Borrowed, for lookups that happen too often to clone the name
+    pub fn get_name_str(&self) -> &str {
+        &self.name
+    }
 }
 
 impl ToString for Var {
@@ -0,0 +1,3 @@
+pub mod passes;
+pub mod visit;
+pub mod visit_mut;
@@ -0,0 +1,311 @@
+/// Analysis passes built on Visit, usable on a whole program or any single node
+use std::collections::BTreeSet;
+
+use crate::program::{
+    expr::{
+        arithmetic_expr::ArithmeticExpr, bool_expr::BoolExpr, expr::Expr, struct_expr::StructExpr,
+    },
+    function::Function,
+    program::Program,
+    stmt::{block_stmt::BlockStmt, stmt::Stmt},
+    var::Var,
+};
+
+use super::visit::Visit;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodeCount {
+    pub functions: usize,
+    pub blocks: usize,
+    pub stmts: usize, // Includes statements in nested blocks
+    pub exprs: usize, // Includes operands, an Expr wrapping another expression counts once
+}
+
+impl NodeCount {
+    pub fn of_program(program: &Program) -> Self {
+        let mut count = NodeCount::default();
+        count.visit_program(program);
+        count
+    }
+
+    pub fn of_stmt(stmt: &Stmt) -> Self {
+        let mut count = NodeCount::default();
+        count.visit_stmt(stmt);
+        count
+    }
+
+    // Size that the generator is given a budget of, see GeneratorConfig::target_size
+    pub fn size(&self) -> usize {
+        self.stmts + self.exprs
+    }
+}
+
+impl Visit for NodeCount {
+    fn visit_function(&mut self, node: &Function) {
+        self.functions += 1;
+        node.walk(self);
+    }
+
+    fn visit_block_stmt(&mut self, node: &BlockStmt) {
+        self.blocks += 1;
+        node.walk(self);
+    }
+
+    fn visit_stmt(&mut self, node: &Stmt) {
+        self.stmts += 1;
+        node.walk(self);
+    }
+
+    fn visit_expr(&mut self, node: &Expr) {
+        if !matches!(
+            node,
+            Expr::Arithmetic(_) | Expr::Boolean(_) | Expr::Struct(_)
+        ) {
+            self.exprs += 1;
+        }
+        node.walk(self);
+    }
+
+    fn visit_arithmetic_expr(&mut self, node: &ArithmeticExpr) {
+        self.exprs += 1;
+        node.walk(self);
+    }
+
+    fn visit_bool_expr(&mut self, node: &BoolExpr) {
+        self.exprs += 1;
+        node.walk(self);
+    }
+
+    fn visit_struct_expr(&mut self, node: &StructExpr) {
+        self.exprs += 1;
+        node.walk(self);
+    }
+}
+
+// Deepest nesting of blocks, with a function body at depth 1
+// and of arithmetic and boolean expressions, with a single literal or variable at depth 1
+// These are the depths bounded by the max_*_depth options of the generator config
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MaxDepth {
+    block_depth: usize,
+    expr_depth: usize,
+    current_block_depth: usize,
+    current_expr_depth: usize,
+}
+
+impl MaxDepth {
+    pub fn of_program(program: &Program) -> Self {
+        let mut depth = MaxDepth::default();
+        depth.visit_program(program);
+        depth
+    }
+
+    pub fn get_block_depth(&self) -> usize {
+        self.block_depth
+    }
+
+    pub fn get_expr_depth(&self) -> usize {
+        self.expr_depth
+    }
+
+    fn enter_expr(&mut self) {
+        self.current_expr_depth += 1;
+        self.expr_depth = self.expr_depth.max(self.current_expr_depth);
+    }
+}
+
+impl Visit for MaxDepth {
+    fn visit_block_stmt(&mut self, node: &BlockStmt) {
+        self.current_block_depth += 1;
+        self.block_depth = self.block_depth.max(self.current_block_depth);
+        node.walk(self);
+        self.current_block_depth -= 1;
+    }
+
+    fn visit_arithmetic_expr(&mut self, node: &ArithmeticExpr) {
+        self.enter_expr();
+        node.walk(self);
+        self.current_expr_depth -= 1;
+    }
+
+    fn visit_bool_expr(&mut self, node: &BoolExpr) {
+        self.enter_expr();
+        node.walk(self);
+        self.current_expr_depth -= 1;
+    }
+}
+
+// Names of the variables that are read or written anywhere, declarations alone do not count
+// A struct field or dereference counts as a use of the variable it belongs to
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UsedVars {
+    names: BTreeSet<String>,
+}
+
+impl UsedVars {
+    pub fn of_program(program: &Program) -> Self {
+        let mut used_vars = UsedVars::default();
+        used_vars.visit_program(program);
+        used_vars
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+
+    pub fn names_iter(&self) -> std::collections::btree_set::Iter<'_, String> {
+        self.names.iter()
+    }
+}
+
+impl Visit for UsedVars {
+    fn visit_var(&mut self, node: &Var) {
+        let name = node.get_name();
+        let root = name.trim_start_matches('*').split('.').next().unwrap();
+        self.names.insert(root.to_string());
+    }
+}
+
+// The statement that the trace statement with the given id follows, see StmtGenerator::trace_stmt
+pub struct TracedStmt {
+    stmt_id: u32,
+    stmt: Option<Stmt>,
+}
+
+impl TracedStmt {
+    pub fn of_program(program: &Program, stmt_id: u32) -> Option<Stmt> {
+        let mut traced_stmt = TracedStmt {
+            stmt_id,
+            stmt: None,
+        };
+        traced_stmt.visit_program(program);
+        traced_stmt.stmt
+    }
+}
+
+impl Visit for TracedStmt {
+    fn visit_block_stmt(&mut self, node: &BlockStmt) {
+        if self.stmt.is_some() {
+            return;
+        }
+
+        for (stmt, next) in node.stmts_iter().zip(node.stmts_iter().skip(1)) {
+            if matches!(next, Stmt::TraceStatement(trace) if trace.get_stmt_id() == self.stmt_id) {
+                self.stmt = Some(stmt.clone());
+                return;
+            }
+        }
+
+        node.walk(self);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        builder::Generator,
+        program::{
+            expr::{
+                arithmetic_expr::{BinaryExpr, BinaryOp, IntExpr},
+                bool_expr::{ComparisonExpr, ComparisonOp},
+            },
+            stmt::{assign_stmt::AssignStmt, conditional_stmt::ConditionalStmt, let_stmt::LetStmt},
+            types::IntTypeID,
+        },
+        Features,
+    };
+
+    fn int_var(name: &str) -> Var {
+        Var::new(IntTypeID::I32.as_type(), String::from(name), true)
+    }
+
+    // let a = b + 1;
+    // if a < 2 { c.field = a; }
+    fn sample_block() -> BlockStmt {
+        let sum = BinaryExpr::new(
+            ArithmeticExpr::Var(int_var("b")),
+            ArithmeticExpr::Int(IntExpr::new_i32(1)),
+            BinaryOp::ADD,
+        );
+        let let_stmt = LetStmt::new(
+            int_var("a"),
+            Expr::Arithmetic(ArithmeticExpr::new_from_bin_expr(sum)),
+        );
+
+        let condition = BoolExpr::Comparison(Box::new(ComparisonExpr::new(
+            ArithmeticExpr::Var(int_var("a")),
+            ArithmeticExpr::Int(IntExpr::new_i32(2)),
+            ComparisonOp::Less,
+        )));
+        let body = BlockStmt::new_from_vec(vec![Stmt::AssignStatement(AssignStmt::new(
+            int_var("c.field"),
+            Expr::Variable(int_var("a")),
+            false,
+        ))]);
+        let conditional = ConditionalStmt::new_from_vec(vec![(condition, body)], None);
+
+        BlockStmt::new_from_vec(vec![
+            Stmt::LetStatement(let_stmt),
+            Stmt::ConditionalStatement(conditional),
+        ])
+    }
+
+    #[test]
+    fn counts_nested_nodes() {
+        let mut count = NodeCount::default();
+        count.visit_block_stmt(&sample_block());
+
+        assert_eq!(count.blocks, 2);
+        assert_eq!(count.stmts, 3);
+        // b + 1, b, 1, a < 2, a, 2 and a
+        assert_eq!(count.exprs, 7);
+    }
+
+    #[test]
+    fn measures_block_and_expr_depth() {
+        let mut depth = MaxDepth::default();
+        depth.visit_block_stmt(&sample_block());
+
+        assert_eq!(depth.get_block_depth(), 2);
+        assert_eq!(depth.get_expr_depth(), 2);
+    }
+
+    #[test]
+    fn collects_used_vars_without_declarations() {
+        let mut used_vars = UsedVars::default();
+        used_vars.visit_block_stmt(&sample_block());
+
+        let names: Vec<&String> = used_vars.names_iter().collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn finds_stmt_before_its_trace() {
+        let trace = Features {
+            trace: true,
+            ..Default::default()
+        };
+        let generated = Generator::small(0).with_features(trace).generate();
+        let trace_count = generated.get_source().matches("trace::record(").count() as u32;
+
+        for stmt_id in 1..=trace_count {
+            let stmt = TracedStmt::of_program(generated.get_program(), stmt_id).unwrap();
+            let traced = format!("{}\ntrace::record({}, ", stmt.to_string(), stmt_id);
+
+            assert!(generated.get_source().contains(&traced), "{}", stmt_id);
+        }
+        assert!(TracedStmt::of_program(generated.get_program(), trace_count + 1).is_none());
+    }
+
+    #[test]
+    fn stmt_count_matches_metadata() {
+        for seed in 0..4 {
+            let generated = Generator::new(seed).generate();
+            let count = NodeCount::of_program(generated.get_program());
+
+            assert_eq!(count.stmts, generated.get_metadata().stmt_count);
+            assert_eq!(count.functions, generated.get_metadata().function_count);
+        }
+    }
+}
@@ -0,0 +1,219 @@
+/// Read only traversal of the AST
+/// Every method visits the children of its node by default, so a pass only overrides the nodes it cares about
+/// and calls the node's walk method where it still wants to go deeper
+use crate::program::{
+    const_generic::ConstGeneric,
+    expr::{
+        arithmetic_expr::{ArithmeticExpr, BinaryExpr, IntExpr},
+        array_expr::ArrayExpr,
+        bool_expr::{
+            BinBoolExpr, BoolExpr, BoolValue, ComparisonExpr, MatchesExpr, NegationExpr, OpaqueBool,
+        },
+        borrow_expr::BorrowExpr,
+        closure_expr::ClosureExpr,
+        const_generic_call_expr::ConstGenericCallExpr,
+        expr::{Expr, RawExpr},
+        func_call_expr::FunctionCallExpr,
+        iter_expr::IterExpr,
+        macro_call_expr::MacroCallExpr,
+        struct_expr::{StructExpr, StructLiteral},
+        tuple_expr::TupleExpr,
+    },
+    function::Function,
+    macro_template::MacroTemplate,
+    pattern::Pattern,
+    program::{Module, Program},
+    stmt::{
+        assign_stmt::AssignStmt, await_stmt::AwaitStmt, block_stmt::BlockStmt,
+        checksum_stmt::ChecksumStmt, conditional_stmt::ConditionalStmt, const_stmt::ConstStmt,
+        expr_stmt::ExprStmt, for_loop_stmt::ForLoopStmt, if_let_stmt::IfLetStmt,
+        let_pattern_stmt::LetPatternStmt, let_stmt::LetStmt, op_assign_stmt::OpAssignStmt,
+        return_stmt::ReturnStmt, static_stmt::StaticStmt, stmt::Stmt,
+        thread_scope_stmt::ThreadScopeStmt, trace_stmt::TraceStmt, unsafe_stmt::UnsafeStmt,
+        while_stmt::WhileStmt,
+    },
+    struct_template::StructTemplate,
+    var::Var,
+};
+
+pub trait Visit {
+    fn visit_program(&mut self, node: &Program) {
+        node.walk(self);
+    }
+
+    fn visit_module(&mut self, node: &Module) {
+        node.walk(self);
+    }
+
+    fn visit_macro_template(&mut self, _node: &MacroTemplate) {}
+
+    fn visit_struct_template(&mut self, _node: &StructTemplate) {}
+
+    fn visit_const_generic(&mut self, node: &ConstGeneric) {
+        node.walk(self);
+    }
+
+    fn visit_function(&mut self, node: &Function) {
+        node.walk(self);
+    }
+
+    fn visit_block_stmt(&mut self, node: &BlockStmt) {
+        node.walk(self);
+    }
+
+    fn visit_stmt(&mut self, node: &Stmt) {
+        node.walk(self);
+    }
+
+    fn visit_let_stmt(&mut self, node: &LetStmt) {
+        node.walk(self);
+    }
+
+    fn visit_static_stmt(&mut self, node: &StaticStmt) {
+        node.walk(self);
+    }
+
+    fn visit_const_stmt(&mut self, node: &ConstStmt) {
+        node.walk(self);
+    }
+
+    fn visit_conditional_stmt(&mut self, node: &ConditionalStmt) {
+        node.walk(self);
+    }
+
+    fn visit_assign_stmt(&mut self, node: &AssignStmt) {
+        node.walk(self);
+    }
+
+    fn visit_return_stmt(&mut self, node: &ReturnStmt) {
+        node.walk(self);
+    }
+
+    fn visit_expr_stmt(&mut self, node: &ExprStmt) {
+        node.walk(self);
+    }
+
+    fn visit_for_loop_stmt(&mut self, node: &ForLoopStmt) {
+        node.walk(self);
+    }
+
+    fn visit_while_stmt(&mut self, node: &WhileStmt) {
+        node.walk(self);
+    }
+
+    fn visit_op_assign_stmt(&mut self, node: &OpAssignStmt) {
+        node.walk(self);
+    }
+
+    fn visit_thread_scope_stmt(&mut self, node: &ThreadScopeStmt) {
+        node.walk(self);
+    }
+
+    fn visit_await_stmt(&mut self, _node: &AwaitStmt) {}
+
+    fn visit_unsafe_stmt(&mut self, node: &UnsafeStmt) {
+        node.walk(self);
+    }
+
+    fn visit_let_pattern_stmt(&mut self, node: &LetPatternStmt) {
+        node.walk(self);
+    }
+
+    fn visit_if_let_stmt(&mut self, node: &IfLetStmt) {
+        node.walk(self);
+    }
+
+    fn visit_checksum_stmt(&mut self, _node: &ChecksumStmt) {}
+
+    fn visit_trace_stmt(&mut self, _node: &TraceStmt) {}
+
+    fn visit_expr(&mut self, node: &Expr) {
+        node.walk(self);
+    }
+
+    fn visit_arithmetic_expr(&mut self, node: &ArithmeticExpr) {
+        node.walk(self);
+    }
+
+    fn visit_binary_expr(&mut self, node: &BinaryExpr) {
+        node.walk(self);
+    }
+
+    fn visit_int_expr(&mut self, _node: &IntExpr) {}
+
+    fn visit_bool_expr(&mut self, node: &BoolExpr) {
+        node.walk(self);
+    }
+
+    fn visit_bool_value(&mut self, _node: &BoolValue) {}
+
+    fn visit_opaque_bool(&mut self, _node: &OpaqueBool) {}
+
+    fn visit_bin_bool_expr(&mut self, node: &BinBoolExpr) {
+        node.walk(self);
+    }
+
+    fn visit_comparison_expr(&mut self, node: &ComparisonExpr) {
+        node.walk(self);
+    }
+
+    fn visit_negation_expr(&mut self, node: &NegationExpr) {
+        node.walk(self);
+    }
+
+    fn visit_matches_expr(&mut self, node: &MatchesExpr) {
+        node.walk(self);
+    }
+
+    fn visit_struct_expr(&mut self, node: &StructExpr) {
+        node.walk(self);
+    }
+
+    fn visit_struct_literal(&mut self, node: &StructLiteral) {
+        node.walk(self);
+    }
+
+    fn visit_borrow_expr(&mut self, node: &BorrowExpr) {
+        node.walk(self);
+    }
+
+    fn visit_raw_expr(&mut self, _node: &RawExpr) {}
+
+    fn visit_func_call_expr(&mut self, node: &FunctionCallExpr) {
+        node.walk(self);
+    }
+
+    fn visit_macro_call_expr(&mut self, node: &MacroCallExpr) {
+        node.walk(self);
+    }
+
+    fn visit_tuple_expr(&mut self, node: &TupleExpr) {
+        node.walk(self);
+    }
+
+    fn visit_closure_expr(&mut self, node: &ClosureExpr) {
+        node.walk(self);
+    }
+
+    fn visit_const_generic_call_expr(&mut self, node: &ConstGenericCallExpr) {
+        node.walk(self);
+    }
+
+    fn visit_array_expr(&mut self, node: &ArrayExpr) {
+        node.walk(self);
+    }
+
+    fn visit_iter_expr(&mut self, node: &IterExpr) {
+        node.walk(self);
+    }
+
+    fn visit_pattern(&mut self, node: &Pattern) {
+        node.walk(self);
+    }
+
+    // A variable that is read or written
+    fn visit_var(&mut self, _node: &Var) {}
+
+    // A variable that is declared, by a let, a pattern, a loop or the result of a thread or unsafe block
+    fn visit_binding(&mut self, _node: &Var) {}
+}
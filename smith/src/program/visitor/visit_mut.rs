@@ -0,0 +1,218 @@
+/// Mutable traversal of the AST, with the same default traversal as Visit
+/// Used by passes that rewrite nodes in place
+use crate::program::{
+    const_generic::ConstGeneric,
+    expr::{
+        arithmetic_expr::{ArithmeticExpr, BinaryExpr, IntExpr},
+        array_expr::ArrayExpr,
+        bool_expr::{
+            BinBoolExpr, BoolExpr, BoolValue, ComparisonExpr, MatchesExpr, NegationExpr, OpaqueBool,
+        },
+        borrow_expr::BorrowExpr,
+        closure_expr::ClosureExpr,
+        const_generic_call_expr::ConstGenericCallExpr,
+        expr::{Expr, RawExpr},
+        func_call_expr::FunctionCallExpr,
+        iter_expr::IterExpr,
+        macro_call_expr::MacroCallExpr,
+        struct_expr::{StructExpr, StructLiteral},
+        tuple_expr::TupleExpr,
+    },
+    function::Function,
+    macro_template::MacroTemplate,
+    pattern::Pattern,
+    program::{Module, Program},
+    stmt::{
+        assign_stmt::AssignStmt, await_stmt::AwaitStmt, block_stmt::BlockStmt,
+        checksum_stmt::ChecksumStmt, conditional_stmt::ConditionalStmt, const_stmt::ConstStmt,
+        expr_stmt::ExprStmt, for_loop_stmt::ForLoopStmt, if_let_stmt::IfLetStmt,
+        let_pattern_stmt::LetPatternStmt, let_stmt::LetStmt, op_assign_stmt::OpAssignStmt,
+        return_stmt::ReturnStmt, static_stmt::StaticStmt, stmt::Stmt,
+        thread_scope_stmt::ThreadScopeStmt, trace_stmt::TraceStmt, unsafe_stmt::UnsafeStmt,
+        while_stmt::WhileStmt,
+    },
+    struct_template::StructTemplate,
+    var::Var,
+};
+
+pub trait VisitMut {
+    fn visit_program_mut(&mut self, node: &mut Program) {
+        node.walk_mut(self);
+    }
+
+    fn visit_module_mut(&mut self, node: &mut Module) {
+        node.walk_mut(self);
+    }
+
+    fn visit_macro_template_mut(&mut self, _node: &mut MacroTemplate) {}
+
+    fn visit_struct_template_mut(&mut self, _node: &mut StructTemplate) {}
+
+    fn visit_const_generic_mut(&mut self, node: &mut ConstGeneric) {
+        node.walk_mut(self);
+    }
+
+    fn visit_function_mut(&mut self, node: &mut Function) {
+        node.walk_mut(self);
+    }
+
+    fn visit_block_stmt_mut(&mut self, node: &mut BlockStmt) {
+        node.walk_mut(self);
+    }
+
+    fn visit_stmt_mut(&mut self, node: &mut Stmt) {
+        node.walk_mut(self);
+    }
+
+    fn visit_let_stmt_mut(&mut self, node: &mut LetStmt) {
+        node.walk_mut(self);
+    }
+
+    fn visit_static_stmt_mut(&mut self, node: &mut StaticStmt) {
+        node.walk_mut(self);
+    }
+
+    fn visit_const_stmt_mut(&mut self, node: &mut ConstStmt) {
+        node.walk_mut(self);
+    }
+
+    fn visit_conditional_stmt_mut(&mut self, node: &mut ConditionalStmt) {
+        node.walk_mut(self);
+    }
+
+    fn visit_assign_stmt_mut(&mut self, node: &mut AssignStmt) {
+        node.walk_mut(self);
+    }
+
+    fn visit_return_stmt_mut(&mut self, node: &mut ReturnStmt) {
+        node.walk_mut(self);
+    }
+
+    fn visit_expr_stmt_mut(&mut self, node: &mut ExprStmt) {
+        node.walk_mut(self);
+    }
+
+    fn visit_for_loop_stmt_mut(&mut self, node: &mut ForLoopStmt) {
+        node.walk_mut(self);
+    }
+
+    fn visit_while_stmt_mut(&mut self, node: &mut WhileStmt) {
+        node.walk_mut(self);
+    }
+
+    fn visit_op_assign_stmt_mut(&mut self, node: &mut OpAssignStmt) {
+        node.walk_mut(self);
+    }
+
+    fn visit_thread_scope_stmt_mut(&mut self, node: &mut ThreadScopeStmt) {
+        node.walk_mut(self);
+    }
+
+    fn visit_await_stmt_mut(&mut self, _node: &mut AwaitStmt) {}
+
+    fn visit_unsafe_stmt_mut(&mut self, node: &mut UnsafeStmt) {
+        node.walk_mut(self);
+    }
+
+    fn visit_let_pattern_stmt_mut(&mut self, node: &mut LetPatternStmt) {
+        node.walk_mut(self);
+    }
+
+    fn visit_if_let_stmt_mut(&mut self, node: &mut IfLetStmt) {
+        node.walk_mut(self);
+    }
+
+    fn visit_checksum_stmt_mut(&mut self, _node: &mut ChecksumStmt) {}
+
+    fn visit_trace_stmt_mut(&mut self, _node: &mut TraceStmt) {}
+
+    fn visit_expr_mut(&mut self, node: &mut Expr) {
+        node.walk_mut(self);
+    }
+
+    fn visit_arithmetic_expr_mut(&mut self, node: &mut ArithmeticExpr) {
+        node.walk_mut(self);
+    }
+
+    fn visit_binary_expr_mut(&mut self, node: &mut BinaryExpr) {
+        node.walk_mut(self);
+    }
+
+    fn visit_int_expr_mut(&mut self, _node: &mut IntExpr) {}
+
+    fn visit_bool_expr_mut(&mut self, node: &mut BoolExpr) {
+        node.walk_mut(self);
+    }
+
+    fn visit_bool_value_mut(&mut self, _node: &mut BoolValue) {}
+
+    fn visit_opaque_bool_mut(&mut self, _node: &mut OpaqueBool) {}
+
+    fn visit_bin_bool_expr_mut(&mut self, node: &mut BinBoolExpr) {
+        node.walk_mut(self);
+    }
+
+    fn visit_comparison_expr_mut(&mut self, node: &mut ComparisonExpr) {
+        node.walk_mut(self);
+    }
+
+    fn visit_negation_expr_mut(&mut self, node: &mut NegationExpr) {
+        node.walk_mut(self);
+    }
+
+    fn visit_matches_expr_mut(&mut self, node: &mut MatchesExpr) {
+        node.walk_mut(self);
+    }
+
+    fn visit_struct_expr_mut(&mut self, node: &mut StructExpr) {
+        node.walk_mut(self);
+    }
+
+    fn visit_struct_literal_mut(&mut self, node: &mut StructLiteral) {
+        node.walk_mut(self);
+    }
+
+    fn visit_borrow_expr_mut(&mut self, node: &mut BorrowExpr) {
+        node.walk_mut(self);
+    }
+
+    fn visit_raw_expr_mut(&mut self, _node: &mut RawExpr) {}
+
+    fn visit_func_call_expr_mut(&mut self, node: &mut FunctionCallExpr) {
+        node.walk_mut(self);
+    }
+
+    fn visit_macro_call_expr_mut(&mut self, node: &mut MacroCallExpr) {
+        node.walk_mut(self);
+    }
+
+    fn visit_tuple_expr_mut(&mut self, node: &mut TupleExpr) {
+        node.walk_mut(self);
+    }
+
+    fn visit_closure_expr_mut(&mut self, node: &mut ClosureExpr) {
+        node.walk_mut(self);
+    }
+
+    fn visit_const_generic_call_expr_mut(&mut self, node: &mut ConstGenericCallExpr) {
+        node.walk_mut(self);
+    }
+
+    fn visit_array_expr_mut(&mut self, node: &mut ArrayExpr) {
+        node.walk_mut(self);
+    }
+
+    fn visit_iter_expr_mut(&mut self, node: &mut IterExpr) {
+        node.walk_mut(self);
+    }
+
+    fn visit_pattern_mut(&mut self, node: &mut Pattern) {
+        node.walk_mut(self);
+    }
+
+    // A variable that is read or written
+    fn visit_var_mut(&mut self, _node: &mut Var) {}
+
+    // A variable that is declared, by a let, a pattern, a loop or the result of a thread or unsafe block
+    fn visit_binding_mut(&mut self, _node: &mut Var) {}
+}
@@ -0,0 +1,770 @@
+/// Edits that the reducer tries, each of which keeps the program well typed and borrow checked
+/// An edit has a number of candidates in a program, counted in traversal order
+use std::collections::{BTreeMap, BTreeSet};
+use strum_macros::EnumIter;
+
+use crate::program::{
+    expr::{
+        arithmetic_expr::{ArithmeticExpr, IntExpr, IntValue},
+        bool_expr::{BoolExpr, BoolValue},
+        expr::{Expr, RawExpr},
+        func_call_expr::FunctionCallExpr,
+        struct_expr::{StructExpr, StructLiteral},
+        tuple_expr::TupleExpr,
+    },
+    function::{Function, Param},
+    pattern::Pattern,
+    program::Program,
+    stmt::{
+        await_stmt::AwaitStmt, block_stmt::BlockStmt, checksum_stmt::ChecksumStmt,
+        conditional_stmt::ConditionalStmt, let_pattern_stmt::LetPatternStmt,
+        return_stmt::ReturnStmt, stmt::Stmt, trace_stmt::TraceStmt, unsafe_stmt::UnsafeStmt,
+    },
+    struct_template::StructTemplate,
+    types::{BorrowTypeID, TypeID},
+    var::Var,
+    visitor::{visit::Visit, visit_mut::VisitMut},
+};
+
+const MAIN_NAME: &str = "main";
+
+// In the order the reducer tries them, larger reductions first
+#[derive(Clone, Copy, Debug, PartialEq, EnumIter)]
+pub enum Edit {
+    DropFunction,     // A function that is never called
+    InlineFunction,   // The only call of a function is replaced by its body
+    DropStmt,         // A statement whose declarations are not used outside of it
+    RemoveBranch,     // A branch, or the else body, of a conditional
+    ReplaceArithExpr, // An arithmetic expression becomes a literal of its type
+    ReplaceBoolExpr,  // A boolean expression becomes false
+    DropStructField,  // A struct field that is never read or written
+}
+
+impl Edit {
+    // Applies the candidate with the given index, or returns false if there are not that many
+    pub fn apply(&self, program: &mut Program, index: usize) -> bool {
+        match self {
+            Edit::DropFunction => drop_function(program, index),
+            Edit::InlineFunction => inline_function(program, index),
+            Edit::DropStmt => {
+                let mut edit = DropStmt::new(index);
+                edit.visit_program_mut(program);
+                edit.target.applied
+            }
+            Edit::RemoveBranch => {
+                let mut edit = RemoveBranch::new(index);
+                edit.visit_program_mut(program);
+                edit.target.applied
+            }
+            Edit::ReplaceArithExpr => {
+                let mut edit = ReplaceArithExpr::new(index);
+                edit.visit_program_mut(program);
+                edit.target.applied
+            }
+            Edit::ReplaceBoolExpr => {
+                let mut edit = ReplaceBoolExpr::new(index);
+                edit.visit_program_mut(program);
+                edit.target.applied
+            }
+            Edit::DropStructField => drop_struct_field(program, index),
+        }
+    }
+}
+
+// Counts candidates in traversal order until it reaches the one to apply
+pub(crate) struct Target {
+    pub(crate) index: usize,
+    pub(crate) seen: usize,
+    pub(crate) applied: bool,
+}
+
+impl Target {
+    pub(crate) fn new(index: usize) -> Self {
+        Target {
+            index,
+            seen: 0,
+            applied: false,
+        }
+    }
+
+    // Whether the next candidate is the one to apply
+    pub(crate) fn hit(&mut self) -> bool {
+        if self.applied {
+            return false;
+        }
+
+        self.applied = self.seen == self.index;
+        self.seen += 1;
+        self.applied
+    }
+}
+
+// How often each name is mentioned, by variables, struct fields, function calls, checksum and
+// trace places and raw expressions, so that whatever an edit removes can be checked to be unused
+// Names are not scoped, which only ever makes edits more conservative
+#[derive(Default)]
+pub(crate) struct Names {
+    counts: BTreeMap<String, usize>,
+}
+
+impl Names {
+    pub(crate) fn of_program(program: &Program) -> Self {
+        let mut names = Names::default();
+        names.visit_program(program);
+        names
+    }
+
+    pub(crate) fn of_function(function: &Function) -> Self {
+        let mut names = Names::default();
+        names.visit_function(function);
+        names
+    }
+
+    fn of_stmt(stmt: &Stmt) -> Self {
+        let mut names = Names::default();
+        names.visit_stmt(stmt);
+        names
+    }
+
+    pub(crate) fn count(&self, name: &str) -> usize {
+        self.counts.get(name).copied().unwrap_or(0)
+    }
+
+    // Every identifier in a place such as var_1.field_2, or in a raw expression
+    fn insert(&mut self, text: &str) {
+        for name in text
+            .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .filter(|name| !name.is_empty())
+        {
+            *self.counts.entry(name.to_string()).or_insert(0) += 1;
+        }
+    }
+}
+
+impl Visit for Names {
+    fn visit_var(&mut self, node: &Var) {
+        self.insert(&node.get_name());
+    }
+
+    fn visit_func_call_expr(&mut self, node: &FunctionCallExpr) {
+        self.insert(&node.get_name());
+        node.walk(self);
+    }
+
+    fn visit_raw_expr(&mut self, node: &RawExpr) {
+        self.insert(node.get_expr_string());
+    }
+
+    fn visit_checksum_stmt(&mut self, node: &ChecksumStmt) {
+        for place in node.places_iter() {
+            self.insert(place);
+        }
+    }
+
+    fn visit_trace_stmt(&mut self, node: &TraceStmt) {
+        for place in node.places_iter() {
+            self.insert(place);
+        }
+    }
+
+    fn visit_await_stmt(&mut self, node: &AwaitStmt) {
+        for borrow in node.held_borrows_iter() {
+            self.insert(borrow);
+        }
+    }
+
+    fn visit_pattern(&mut self, node: &Pattern) {
+        if let Pattern::Struct(_, fields, _) = node {
+            for (field_name, _) in fields.iter() {
+                self.insert(field_name);
+            }
+        }
+        node.walk(self);
+    }
+
+    fn visit_unsafe_stmt(&mut self, node: &UnsafeStmt) {
+        if let UnsafeStmt::MaybeUninit { fields, .. } = node {
+            for (field_name, _) in fields.iter() {
+                self.insert(field_name);
+            }
+        }
+        node.walk(self);
+    }
+}
+
+// Names of the variables a statement declares, including in its nested blocks
+#[derive(Default)]
+struct Bindings {
+    names: Vec<String>,
+}
+
+impl Visit for Bindings {
+    fn visit_binding(&mut self, node: &Var) {
+        self.names.push(node.get_name());
+    }
+}
+
+// Counters compared against by loop stoppers
+#[derive(Default)]
+struct LoopCounters {
+    names: BTreeSet<String>,
+}
+
+impl Visit for LoopCounters {
+    fn visit_conditional_stmt(&mut self, node: &ConditionalStmt) {
+        if is_loop_stopper(node) {
+            let mut names = Names::default();
+            for (condition, _) in node.branches_iter() {
+                names.visit_bool_expr(condition);
+            }
+            self.names.extend(names.counts.into_keys());
+        }
+        node.walk(self);
+    }
+}
+
+#[derive(Default)]
+struct Returns {
+    count: usize,
+}
+
+impl Visit for Returns {
+    fn visit_return_stmt(&mut self, node: &ReturnStmt) {
+        self.count += 1;
+        node.walk(self);
+    }
+}
+
+// The break or return that ends a let else or leaves a loop early
+fn is_diverging(stmt: &Stmt) -> bool {
+    match stmt {
+        Stmt::ExprStatement(s) => matches!(
+            s.get_expr(),
+            Expr::Raw(raw) if matches!(raw.get_expr_string(), "break" | "return")
+        ),
+        _ => false,
+    }
+}
+
+// The conditional break that bounds the iterations of a loop
+// Loops without one may run for up to the full range of their iteration variable
+fn is_loop_stopper(stmt: &ConditionalStmt) -> bool {
+    stmt.branches_iter()
+        .any(|(_, block_stmt)| block_stmt.stmts_iter().any(is_diverging))
+}
+
+// A call that makes up a whole expression, so that it can be replaced by a block
+pub(crate) fn whole_call(expr: &Expr) -> Option<&FunctionCallExpr> {
+    match expr {
+        Expr::Func(call)
+        | Expr::Arithmetic(ArithmeticExpr::Func(call))
+        | Expr::Boolean(BoolExpr::Func(call))
+        | Expr::Struct(StructExpr::Func(call)) => Some(call),
+        _ => None,
+    }
+}
+
+pub(crate) fn into_call(expr: Expr) -> Option<FunctionCallExpr> {
+    match expr {
+        Expr::Func(call)
+        | Expr::Arithmetic(ArithmeticExpr::Func(call))
+        | Expr::Boolean(BoolExpr::Func(call))
+        | Expr::Struct(StructExpr::Func(call)) => Some(call),
+        _ => None,
+    }
+}
+
+fn drop_function(program: &mut Program, index: usize) -> bool {
+    let names = Names::of_program(program);
+    let unused: Vec<String> = program
+        .functions_iter()
+        .map(|function| function.get_name())
+        .filter(|name| name != MAIN_NAME && names.count(name) == 0)
+        .collect();
+
+    match unused.get(index) {
+        Some(name) => program.remove_function(name).is_some(),
+        None => false,
+    }
+}
+
+// Number of whole expression calls of each function that could take its body
+#[derive(Default)]
+struct WholeCalls {
+    counts: BTreeMap<String, usize>,
+}
+
+impl Visit for WholeCalls {
+    fn visit_expr(&mut self, node: &Expr) {
+        if let Some(call) = whole_call(node).filter(|call| !passes_mut_ref_on(call)) {
+            *self.counts.entry(call.get_name()).or_insert(0) += 1;
+        }
+        node.walk(self);
+    }
+}
+
+// Async functions are left alone, since their calls are awaited or driven by the executor
+// The body may only return at its end, where the return becomes the value of the block
+fn can_inline(function: &Function, names: &Names, whole_calls: &WholeCalls) -> bool {
+    let name = function.get_name();
+    if name == MAIN_NAME || function.get_template().is_async() {
+        return false;
+    }
+
+    // Exactly one call, which is not inside the function itself
+    let body_names = Names::of_function(function);
+    if names.count(&name) != 1
+        || whole_calls.counts.get(&name) != Some(&1)
+        || body_names.count(&name) != 0
+    {
+        return false;
+    }
+
+    body_names.count("return") == 0 && returns_only_at_end(function)
+}
+
+// Either the last statement of the body is its only return, or the body never returns
+pub(crate) fn returns_only_at_end(function: &Function) -> bool {
+    let mut returns = Returns::default();
+    returns.visit_function(function);
+    let returns_at_end = matches!(
+        function.get_block_stmt().stmts_iter().last(),
+        Some(Stmt::ReturnStatement(_))
+    );
+
+    returns.count == usize::from(returns_at_end)
+}
+
+// The body of a function as a block that evaluates to what the function returns
+// The arguments are all evaluated before any parameter is bound,
+// since a parameter may have the same name as a variable an argument uses
+pub(crate) fn inlined_body(
+    params: &[Param],
+    mut block_stmt: BlockStmt,
+    arguments: Vec<Expr>,
+) -> Expr {
+    if let Some(Stmt::ReturnStatement(return_stmt)) = block_stmt.last_mut() {
+        return_stmt.set_implicit();
+    }
+
+    if !params.is_empty() {
+        let pattern = Pattern::Tuple(
+            params
+                .iter()
+                .map(|param| Pattern::Binding(Var::from_param(param)))
+                .collect(),
+        );
+        let let_stmt = LetPatternStmt::new(pattern, TupleExpr::new(arguments).as_expr());
+        block_stmt.push_front(let_stmt.as_stmt());
+    }
+
+    Expr::Block(Box::new(block_stmt))
+}
+
+// A mutable reference passed on as it is, which the call reborrows
+// but the let of an inlined body would move, such as on the first iteration of a loop
+pub(crate) fn passes_mut_ref_on(call: &FunctionCallExpr) -> bool {
+    call.arguments_iter().any(|argument| match argument {
+        Expr::Borrow(borrow_expr) => {
+            borrow_expr.get_borrow_type() == BorrowTypeID::MutRef && !borrow_expr.is_explicit()
+        }
+        _ => false,
+    })
+}
+
+fn inline_function(program: &mut Program, index: usize) -> bool {
+    let names = Names::of_program(program);
+    let mut whole_calls = WholeCalls::default();
+    whole_calls.visit_program(program);
+
+    let inlinable: Vec<String> = program
+        .functions_iter()
+        .filter(|function| can_inline(function, &names, &whole_calls))
+        .map(|function| function.get_name())
+        .collect();
+    let name = match inlinable.get(index) {
+        Some(name) => name.clone(),
+        None => return false,
+    };
+
+    let function = program.remove_function(&name).unwrap();
+    let params = function.get_params();
+    let block_stmt = function.into_block_stmt();
+
+    let mut edit = InlineCall {
+        name,
+        params,
+        block_stmt: Some(block_stmt),
+    };
+    edit.visit_program_mut(program);
+
+    edit.block_stmt.is_none()
+}
+
+struct InlineCall {
+    name: String,
+    params: Vec<Param>,
+    block_stmt: Option<BlockStmt>, // Taken once the call is replaced
+}
+
+impl VisitMut for InlineCall {
+    fn visit_expr_mut(&mut self, node: &mut Expr) {
+        if self.block_stmt.is_none() {
+            return;
+        }
+
+        let is_call = whole_call(node).is_some_and(|call| call.get_name() == self.name);
+        if !is_call {
+            node.walk_mut(self);
+            return;
+        }
+
+        let call = std::mem::replace(node, RawExpr::new(String::new()).as_expr());
+        let arguments = into_call(call).unwrap().into_arguments();
+        let block_stmt = self.block_stmt.take().unwrap();
+
+        *node = inlined_body(&self.params, block_stmt, arguments);
+    }
+}
+
+// Drops a chunk of the droppable statements of a block, split into that many chunks
+// Only blocks with more droppable statements than chunks have candidates, single statements
+// are left to Edit::DropStmt
+pub(crate) fn drop_stmt_chunk(program: &mut Program, chunks: usize, index: usize) -> bool {
+    let mut edit = DropStmt::new(index).with_chunks(chunks);
+    edit.visit_program_mut(program);
+    edit.target.applied
+}
+
+// Most droppable statements in any one block
+pub(crate) fn max_droppable_stmts(program: &Program) -> usize {
+    let mut edit = DropStmt::new(usize::MAX);
+    edit.visit_program_mut(&mut program.clone());
+    edit.max_candidates
+}
+
+struct DropStmt {
+    target: Target,
+    chunks: Option<usize>, // Candidates are chunks of statements rather than single ones
+    max_candidates: usize, // Droppable statements in any one block visited
+    names: Names,          // Of the function being visited
+    loop_counters: BTreeSet<String>, // Of the function being visited
+}
+
+impl DropStmt {
+    fn new(index: usize) -> Self {
+        DropStmt {
+            target: Target::new(index),
+            chunks: None,
+            max_candidates: 0,
+            names: Names::default(),
+            loop_counters: BTreeSet::new(),
+        }
+    }
+
+    fn with_chunks(mut self, chunks: usize) -> Self {
+        self.chunks = Some(chunks);
+        self
+    }
+
+    // Dropping several of them at once is fine, as none declares anything used outside of it
+    fn candidate_chunks(&self, candidates: Vec<usize>) -> Vec<Vec<usize>> {
+        match self.chunks {
+            None => candidates.into_iter().map(|index| vec![index]).collect(),
+            Some(chunks) if candidates.len() > chunks => (0..chunks)
+                .map(|chunk| {
+                    let start = chunk * candidates.len() / chunks;
+                    let end = (chunk + 1) * candidates.len() / chunks;
+                    candidates[start..end].to_vec()
+                })
+                .collect(),
+            Some(_) => Vec::new(),
+        }
+    }
+
+    fn can_drop(&self, stmt: &Stmt) -> bool {
+        let is_protected = match stmt {
+            Stmt::ReturnStatement(_) => true,
+            Stmt::ConditionalStatement(s) => is_loop_stopper(s),
+            _ => is_diverging(stmt),
+        };
+        if is_protected {
+            return false;
+        }
+
+        if let Some(name) = stmt.assigned_name() {
+            if self.loop_counters.contains(&name) {
+                return false;
+            }
+        }
+
+        let mut bindings = Bindings::default();
+        bindings.visit_stmt(stmt);
+        let stmt_names = Names::of_stmt(stmt);
+
+        bindings
+            .names
+            .iter()
+            .all(|name| self.names.count(name) == stmt_names.count(name))
+    }
+}
+
+impl VisitMut for DropStmt {
+    fn visit_function_mut(&mut self, node: &mut Function) {
+        if self.target.applied {
+            return;
+        }
+
+        self.names = Names::of_function(node);
+        let mut loop_counters = LoopCounters::default();
+        loop_counters.visit_function(node);
+        self.loop_counters = loop_counters.names;
+
+        node.walk_mut(self);
+    }
+
+    fn visit_block_stmt_mut(&mut self, node: &mut BlockStmt) {
+        if self.target.applied {
+            return;
+        }
+
+        let candidates: Vec<usize> = node
+            .stmts_iter()
+            .enumerate()
+            .filter(|(_, stmt)| self.can_drop(stmt))
+            .map(|(i, _)| i)
+            .collect();
+        self.max_candidates = self.max_candidates.max(candidates.len());
+        for chunk in self.candidate_chunks(candidates) {
+            if self.target.hit() {
+                for &index in chunk.iter().rev() {
+                    node.remove(index);
+                }
+                return;
+            }
+        }
+
+        node.walk_mut(self);
+    }
+}
+
+struct RemoveBranch {
+    target: Target,
+}
+
+impl RemoveBranch {
+    fn new(index: usize) -> Self {
+        RemoveBranch {
+            target: Target::new(index),
+        }
+    }
+}
+
+impl VisitMut for RemoveBranch {
+    fn visit_conditional_stmt_mut(&mut self, node: &mut ConditionalStmt) {
+        if self.target.applied || is_loop_stopper(node) {
+            return;
+        }
+
+        // The only branch goes with the whole statement instead
+        if node.branch_count() > 1 {
+            for index in 0..node.branch_count() {
+                if self.target.hit() {
+                    node.remove_branch(index);
+                    return;
+                }
+            }
+        }
+        if node.has_else_body() && self.target.hit() {
+            node.remove_else_body();
+            return;
+        }
+
+        node.walk_mut(self);
+    }
+}
+
+// Variables holding references are left alone, since the place they borrow may be written through them
+struct ReplaceArithExpr {
+    target: Target,
+}
+
+impl ReplaceArithExpr {
+    fn new(index: usize) -> Self {
+        ReplaceArithExpr {
+            target: Target::new(index),
+        }
+    }
+
+    fn literal(expr: &ArithmeticExpr) -> Option<ArithmeticExpr> {
+        match expr {
+            ArithmeticExpr::Int(_) => return None,
+            ArithmeticExpr::Var(var) if var.get_borrow_type() != BorrowTypeID::None => return None,
+            _ => (),
+        }
+
+        match expr.get_type() {
+            TypeID::IntType(int_type) => {
+                Some(IntExpr::new(IntValue::one(int_type)).as_arith_expr())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl VisitMut for ReplaceArithExpr {
+    fn visit_conditional_stmt_mut(&mut self, node: &mut ConditionalStmt) {
+        if !self.target.applied && !is_loop_stopper(node) {
+            node.walk_mut(self);
+        }
+    }
+
+    fn visit_arithmetic_expr_mut(&mut self, node: &mut ArithmeticExpr) {
+        if self.target.applied {
+            return;
+        }
+
+        if let Some(literal) = ReplaceArithExpr::literal(node) {
+            if self.target.hit() {
+                *node = literal;
+                return;
+            }
+        }
+
+        node.walk_mut(self);
+    }
+}
+
+struct ReplaceBoolExpr {
+    target: Target,
+}
+
+impl ReplaceBoolExpr {
+    fn new(index: usize) -> Self {
+        ReplaceBoolExpr {
+            target: Target::new(index),
+        }
+    }
+
+    fn can_replace(expr: &BoolExpr) -> bool {
+        match expr {
+            BoolExpr::Bool(_) => false,
+            BoolExpr::Var(var) => var.get_borrow_type() == BorrowTypeID::None,
+            _ => true,
+        }
+    }
+}
+
+impl VisitMut for ReplaceBoolExpr {
+    fn visit_conditional_stmt_mut(&mut self, node: &mut ConditionalStmt) {
+        if !self.target.applied && !is_loop_stopper(node) {
+            node.walk_mut(self);
+        }
+    }
+
+    fn visit_bool_expr_mut(&mut self, node: &mut BoolExpr) {
+        if self.target.applied {
+            return;
+        }
+
+        if ReplaceBoolExpr::can_replace(node) && self.target.hit() {
+            *node = BoolValue::new(false).as_bool_expr();
+            return;
+        }
+
+        node.walk_mut(self);
+    }
+}
+
+fn drop_struct_field(program: &mut Program, index: usize) -> bool {
+    let mut edit = DropStructField {
+        target: Target::new(index),
+        names: Names::of_program(program),
+        dropped: None,
+    };
+    edit.visit_program_mut(program);
+
+    match edit.dropped {
+        Some((struct_name, field_name)) => {
+            let mut literals = DropLiteralField {
+                struct_name,
+                field_name,
+            };
+            literals.visit_program_mut(program);
+            true
+        }
+        None => false,
+    }
+}
+
+struct DropStructField {
+    target: Target,
+    names: Names,
+    dropped: Option<(String, String)>, // Struct and field name
+}
+
+impl VisitMut for DropStructField {
+    fn visit_struct_template_mut(&mut self, node: &mut StructTemplate) {
+        let unused: Vec<String> = node
+            .fields_iter()
+            .map(|(field_name, _)| field_name.clone())
+            .filter(|field_name| self.names.count(field_name) == 0)
+            .collect();
+
+        for field_name in unused {
+            if self.target.hit() {
+                node.remove_field(&field_name);
+                self.dropped = Some((node.get_name(), field_name));
+                return;
+            }
+        }
+    }
+}
+
+// Struct literals carry a copy of their struct
+struct DropLiteralField {
+    struct_name: String,
+    field_name: String,
+}
+
+impl VisitMut for DropLiteralField {
+    fn visit_struct_literal_mut(&mut self, node: &mut StructLiteral) {
+        if node.get_struct_name() == self.struct_name {
+            node.remove_field(&self.field_name);
+        }
+        node.walk_mut(self);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+    use crate::{program::visitor::passes::NodeCount, Generator};
+
+    // The reducer retries an index after applying it, which only ends if the candidate goes away
+    #[test]
+    fn applied_candidates_go_away() {
+        for edit in Edit::iter() {
+            let mut program = Generator::new(1).generate().into_program();
+
+            let applied = (0..10_000)
+                .take_while(|_| edit.apply(&mut program, 0))
+                .count();
+
+            assert!(applied < 10_000, "{:?} keeps applying", edit);
+        }
+    }
+
+    #[test]
+    fn chunks_only_come_from_blocks_with_more_stmts() {
+        let mut program = Generator::new(1).generate().into_program();
+        let max_stmts = max_droppable_stmts(&program);
+        let stmt_count = NodeCount::of_program(&program).stmts;
+
+        assert!(!drop_stmt_chunk(&mut program.clone(), max_stmts, 0));
+        assert!(drop_stmt_chunk(&mut program, 2, 0));
+        assert!(NodeCount::of_program(&program).stmts < stmt_count);
+    }
+}
@@ -0,0 +1,185 @@
+/// Reduces a program while it stays interesting, for example while it still shows a miscompilation
+/// Greedily applies every edit that keeps the program interesting until none does
+/// Statements are first dropped in chunks, in the style of ddmin, so that a large program takes
+/// far fewer tests than it has statements
+/// A traced statement can be kept as it is, such as the one where traces first diverged
+use strum::IntoEnumIterator;
+
+use crate::program::program::Program;
+
+use super::edits::{self, Edit};
+
+pub struct Reducer<F: FnMut(&Program) -> bool> {
+    is_interesting: F,
+    test_count: usize,
+    kept_stmt_id: Option<u32>,
+    kept_stmt: Option<String>, // Source of the kept statement in the program being reduced
+}
+
+impl<F: FnMut(&Program) -> bool> Reducer<F> {
+    pub fn new(is_interesting: F) -> Self {
+        Reducer {
+            is_interesting,
+            test_count: 0,
+            kept_stmt_id: None,
+            kept_stmt: None,
+        }
+    }
+
+    // Candidates that change or remove the statement with this trace id are not tested
+    pub fn with_kept_stmt(mut self, stmt_id: u32) -> Self {
+        self.kept_stmt_id = Some(stmt_id);
+        self
+    }
+
+    // None if the program is not interesting to begin with, or has no statement to keep
+    pub fn reduce(&mut self, program: Program) -> Option<Program> {
+        if let Some(stmt_id) = self.kept_stmt_id {
+            self.kept_stmt = Some(program.traced_stmt(stmt_id)?.to_string());
+        }
+        if !self.test(&program) {
+            return None;
+        }
+
+        let mut smallest = program;
+        loop {
+            let mut reduced = false;
+
+            // Halves, then quarters and so on, until chunks would be single statements
+            let mut chunks = 2;
+            while chunks < edits::max_droppable_stmts(&smallest) {
+                let mut index = 0;
+                loop {
+                    let mut candidate = smallest.clone();
+                    if !edits::drop_stmt_chunk(&mut candidate, chunks, index) {
+                        break;
+                    }
+
+                    if self.test(&candidate) {
+                        smallest = candidate;
+                        reduced = true;
+                    } else {
+                        index += 1;
+                    }
+                }
+                chunks *= 2;
+            }
+
+            for edit in Edit::iter() {
+                // An applied edit removes its candidate, so the same index is tried again
+                let mut index = 0;
+                loop {
+                    let mut candidate = smallest.clone();
+                    if !edit.apply(&mut candidate, index) {
+                        break;
+                    }
+
+                    if self.test(&candidate) {
+                        smallest = candidate;
+                        reduced = true;
+                    } else {
+                        index += 1;
+                    }
+                }
+            }
+
+            if !reduced {
+                return Some(smallest);
+            }
+        }
+    }
+
+    // Number of times the predicate has been run
+    pub fn get_test_count(&self) -> usize {
+        self.test_count
+    }
+
+    fn test(&mut self, program: &Program) -> bool {
+        if let (Some(stmt_id), Some(kept_stmt)) = (self.kept_stmt_id, &self.kept_stmt) {
+            let stmt = program.traced_stmt(stmt_id).map(|stmt| stmt.to_string());
+            if stmt.as_ref() != Some(kept_stmt) {
+                return false;
+            }
+        }
+
+        self.test_count += 1;
+        (self.is_interesting)(program)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{program::visitor::passes::NodeCount, render_program, Features, Generator};
+
+    #[test]
+    fn reduces_while_interesting() {
+        let program = Generator::new(1).generate().into_program();
+        let stmt_count = NodeCount::of_program(&program).stmts;
+
+        let mut reducer = Reducer::new(|program| {
+            render_program(program, Features::default()).contains("fn main")
+        });
+        let reduced = reducer.reduce(program).unwrap();
+        let count = NodeCount::of_program(&reduced);
+
+        assert!(count.stmts < stmt_count / 2);
+        assert!(reducer.get_test_count() > 1);
+    }
+
+    // Single edits alone would take at least one test per statement
+    #[test]
+    fn chunks_take_fewer_tests_than_stmts() {
+        let program = Generator::sized(2, 10_000).generate().into_program();
+        let stmt_count = NodeCount::of_program(&program).stmts;
+
+        let mut reducer = Reducer::new(|program| {
+            render_program(program, Features::default()).contains("fn main")
+        });
+        reducer.reduce(program).unwrap();
+
+        assert!(reducer.get_test_count() < stmt_count / 2);
+    }
+
+    #[test]
+    fn keeps_traced_stmt() {
+        let trace = Features {
+            trace: true,
+            ..Default::default()
+        };
+        let program = Generator::small(1)
+            .with_features(trace)
+            .generate()
+            .into_program();
+        let stmt_count = NodeCount::of_program(&program).stmts;
+        let kept_stmt = program.traced_stmt(3).unwrap().to_string();
+
+        let mut reducer =
+            Reducer::new(|program| render_program(program, trace).contains("fn main"))
+                .with_kept_stmt(3);
+        let reduced = reducer.reduce(program).unwrap();
+
+        assert_eq!(reduced.traced_stmt(3).unwrap().to_string(), kept_stmt);
+        assert!(NodeCount::of_program(&reduced).stmts < stmt_count);
+    }
+
+    #[test]
+    fn rejects_program_without_kept_stmt() {
+        let program = Generator::small(1).generate().into_program();
+
+        let mut reducer = Reducer::new(|_| true).with_kept_stmt(1);
+
+        assert!(reducer.reduce(program).is_none());
+        assert_eq!(reducer.get_test_count(), 0);
+    }
+
+    #[test]
+    fn rejects_uninteresting_program() {
+        let program = Generator::new(1).generate().into_program();
+
+        let mut reducer = Reducer::new(|_| false);
+
+        assert!(reducer.reduce(program).is_none());
+        assert_eq!(reducer.get_test_count(), 1);
+    }
+}
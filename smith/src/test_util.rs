@@ -0,0 +1,26 @@
+/// Programs for the unit tests, small enough to generate, interpret and check in a few milliseconds
+use crate::{builder::Generator, generator::config::GeneratorConfig};
+
+// About the size of a few dozen statements, see GeneratorConfig::target_size
+const SMALL_TARGET_SIZE: u32 = 600;
+
+pub(crate) fn sized_config(target_size: u32) -> GeneratorConfig {
+    GeneratorConfig {
+        target_size: Some(target_size),
+        ..GeneratorConfig::default()
+    }
+}
+
+pub(crate) fn small_config() -> GeneratorConfig {
+    sized_config(SMALL_TARGET_SIZE)
+}
+
+impl Generator {
+    pub(crate) fn sized(seed: u64, target_size: u32) -> Self {
+        Generator::new(seed).with_config(sized_config(target_size))
+    }
+
+    pub(crate) fn small(seed: u64) -> Self {
+        Generator::sized(seed, SMALL_TARGET_SIZE)
+    }
+}
@@ -0,0 +1,76 @@
+/// Pins the generated output for a set of seeds, so that any change to what a seed generates is caught
+/// Run with UPDATE_GOLDEN=1 to rewrite the golden file after an intended change
+use std::{env, fs};
+
+use smith::{Features, Generator};
+
+const GOLDEN_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/seeds.txt");
+const SEED_COUNT: u64 = 8;
+
+fn feature_sets() -> Vec<(&'static str, Features)> {
+    vec![
+        ("default", Features::default()),
+        (
+            "threads+unsafe",
+            Features {
+                threads: true,
+                unsafe_code: true,
+                ..Default::default()
+            },
+        ),
+        (
+            "standalone+trace+swarm",
+            Features {
+                standalone: true,
+                trace: true,
+                swarm: true,
+                ..Default::default()
+            },
+        ),
+    ]
+}
+
+// One line per seed and feature set: content hash, then function, struct and statement counts
+fn golden_lines() -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for (name, features) in feature_sets() {
+        for seed in 0..SEED_COUNT {
+            let generated = Generator::new(seed).with_features(features).generate();
+            let metadata = generated.get_metadata();
+
+            lines.push(format!(
+                "{} {} {:016x} {} {} {}",
+                name,
+                seed,
+                generated.get_hash(),
+                metadata.function_count,
+                metadata.struct_count,
+                metadata.stmt_count
+            ));
+        }
+    }
+
+    lines
+}
+
+#[test]
+fn output_matches_golden_file() {
+    let lines = golden_lines();
+
+    if env::var("UPDATE_GOLDEN").is_ok() {
+        fs::write(GOLDEN_PATH, lines.join("\n") + "\n").expect("Failed to write golden file");
+        return;
+    }
+
+    let golden = fs::read_to_string(GOLDEN_PATH).expect("Failed to read golden file");
+    let golden_lines: Vec<&str> = golden.lines().collect();
+
+    assert_eq!(golden_lines.len(), lines.len());
+    for (expected, actual) in golden_lines.iter().zip(lines.iter()) {
+        assert_eq!(
+            expected, actual,
+            "Generated output changed, rerun with UPDATE_GOLDEN=1 if this is intended"
+        );
+    }
+}